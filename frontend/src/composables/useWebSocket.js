@@ -1,31 +1,80 @@
-import { ref, onUnmounted } from 'vue';
+import { ref, onUnmounted, getCurrentInstance } from 'vue';
 
-export function useWebSocket(url) {
-  const data = ref(null);
-  const ws = new WebSocket(url);
+const RECONNECT_DELAY_MS = 3000;
 
-  ws.onmessage = (event) => {
-    data.value = event.data;
-  };
+/**
+ * Connects to the server's live events and subscribes to `topics`, e.g. `store:*:inventory`.
+ * `getToken` is called on every (re)connect, so a renewed access token is picked up when the
+ * server closes the connection because the old one expired.
+ *
+ * `event` holds the last event received: `{ type, seq, topic, data }`. After a reconnect the
+ * events missed in between are replayed first; if the server no longer has them, `onResync`
+ * is called and the caller should reload its data.
+ */
+export function useWebSocket(url, { getToken, topics = [], onResync = () => {} }) {
+  const event = ref(null);
+  const error = ref(null);
+  let lastSeq = null;
+  let ws = null;
+  let reconnectTimer = null;
+  let closed = false;
 
-  ws.onopen = () => {
-    console.log(`WebSocket connected to ${url}`);
-  };
+  const connect = () => {
+    const token = getToken();
+    if (!token || closed) return;
+
+    ws = new WebSocket(`${url}?token=${encodeURIComponent(token)}`);
 
-  ws.onerror = (error) => {
-    console.error('WebSocket Error:', error);
+    ws.onopen = () => {
+      if (topics.length) {
+        ws.send(JSON.stringify({ action: 'subscribe', topics, since: lastSeq }));
+      }
+    };
+
+    ws.onmessage = (message) => {
+      let payload;
+      try {
+        payload = JSON.parse(message.data);
+      } catch {
+        return;
+      }
+      if (payload.type === 'error') {
+        error.value = payload.message;
+        console.error('WebSocket error:', payload.message);
+      } else if (payload.type === 'resync_required') {
+        lastSeq = payload.seq;
+        onResync();
+      } else if (payload.type === 'subscribed') {
+        lastSeq = Math.max(lastSeq ?? 0, payload.seq);
+      } else if (payload.topic) {
+        if (payload.seq != null) lastSeq = payload.seq;
+        event.value = payload;
+      }
+    };
+
+    ws.onclose = () => {
+      ws = null;
+      if (!closed) {
+        reconnectTimer = setTimeout(connect, RECONNECT_DELAY_MS);
+      }
+    };
   };
 
   const close = () => {
-    ws.close();
+    closed = true;
+    clearTimeout(reconnectTimer);
+    if (ws) ws.close();
   };
 
-  onUnmounted(() => {
-    close();
-  });
+  connect();
+
+  if (getCurrentInstance()) {
+    onUnmounted(close);
+  }
 
   return {
-    data,
+    event,
+    error,
     close,
   };
 }
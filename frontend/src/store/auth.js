@@ -17,7 +17,8 @@ export const useAuthStore = defineStore('auth', {
         try {
             const response = await axios.post('/api/auth/login', credentials);
             console.log('Received response from backend:', response.data); // Log the response
-            const { user, token, permissions } = response.data.data;
+            const { user: account, role, token, permissions } = response.data.data;
+            const user = { ...account, role };
             this.user = user;
             this.token = token;
             this.permissions = permissions || [];
@@ -29,6 +30,16 @@ export const useAuthStore = defineStore('auth', {
             throw error;
         }
     },
+    async switchStore(storeId) {
+      const response = await axios.post('/api/auth/switch-store', { store_id: storeId }, {
+        headers: { Authorization: `Bearer ${this.token}` },
+      });
+      const { token } = response.data.data;
+      this.token = token;
+      this.user = { ...this.user, store_id: storeId };
+      localStorage.setItem('token', token);
+      localStorage.setItem('user', JSON.stringify(this.user));
+    },
     logout() {
       this.user = null;
       this.token = null;
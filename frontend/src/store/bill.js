@@ -6,22 +6,25 @@ import { useAuthStore } from './auth'; // Needed for token
 export const useBillStore = defineStore('bill', () => {
   // STATE
   const bills = ref([]);
+  // Where the loaded page sits in the whole list: { page, size, total, total_pages }
+  const pagination = ref(null);
   const isLoading = ref(false);
   const error = ref(null);
 
   // ACTIONS
   const authStore = useAuthStore();
 
-  async function fetchBills(filters = {}) {
+  // `page` holds the list params for the page to load: { page, size, sort }
+  async function fetchBills(filters = {}, page = {}) {
     isLoading.value = true;
     error.value = null;
     try {
-      const params = new URLSearchParams();
+      const params = { ...page };
       if (filters.date) {
-        params.append('date', filters.date);
+        params.date = filters.date;
       }
       if (filters.status) {
-        params.append('status', filters.status);
+        params.status = filters.status;
       }
       // This seems to be an admin-only endpoint.
       // We should ensure the token is included.
@@ -30,6 +33,7 @@ export const useBillStore = defineStore('bill', () => {
         headers: { Authorization: `Bearer ${authStore.token}` }
       });
       bills.value = response.data.data; // FIX: Access data.data
+      pagination.value = response.data.pagination;
     } catch (e) {
       error.value = e;
       console.error('Error fetching bills:', e);
@@ -38,14 +42,16 @@ export const useBillStore = defineStore('bill', () => {
     }
   }
 
-  async function fetchCashierOrders() {
+  async function fetchCashierOrders(page = {}) {
     isLoading.value = true;
     error.value = null;
     try {
       const response = await axios.get('/api/orders', {
+        params: page,
         headers: { Authorization: `Bearer ${authStore.token}` }
       });
       bills.value = response.data.data;
+      pagination.value = response.data.pagination;
     } catch (e) {
       error.value = e;
       console.error('Error fetching cashier orders:', e);
@@ -72,6 +78,7 @@ export const useBillStore = defineStore('bill', () => {
 
   return {
     bills,
+    pagination,
     isLoading,
     error,
     fetchBills,
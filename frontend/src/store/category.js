@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { useAuthStore } from './auth'; // Import auth store
 
 export const useCategoryStore = defineStore('category', {
@@ -20,10 +21,10 @@ export const useCategoryStore = defineStore('category', {
       }
 
       try {
-        const response = await axios.get('/api/categories', { // FIX: URL
+        const rows = await fetchAllPages('/api/categories', { // FIX: URL
           headers: { Authorization: `Bearer ${authStore.token}` } // FIX: Header
         });
-        this.categories = response.data.data; // FIX: Data nesting
+        this.categories = rows; // FIX: Data nesting
       } catch (error) {
         this.error = error;
         console.error('Error fetching categories:', error);
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { useAuthStore } from './auth'; // Import auth store
 
 export const useCustomerStore = defineStore('customer', {
@@ -20,10 +21,10 @@ export const useCustomerStore = defineStore('customer', {
       }
 
       try {
-        const response = await axios.get('/api/customers', {
+        const rows = await fetchAllPages('/api/customers', {
           headers: { Authorization: `Bearer ${authStore.token}` } // Add header
         });
-        this.customers = response.data.data; // Data is nested
+        this.customers = rows; // Data is nested
       } catch (error) {
         this.error = error;
         console.error('Error fetching customers:', error);
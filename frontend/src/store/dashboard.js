@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { useAuthStore } from './auth';
 
 export const useDashboardStore = defineStore('dashboard', {
@@ -43,14 +44,14 @@ export const useDashboardStore = defineStore('dashboard', {
           this.adminSalesChartData = salesRes.data;
         } else if (role === 'inventoryManager') {
           const [productsRes, suppliersRes, categoriesRes, inventoryReportRes] = await Promise.all([
-            axios.get('/api/products', { headers }),
-            axios.get('/api/suppliers', { headers }),
-            axios.get('/api/categories', { headers }),
+            fetchAllPages('/api/products', { headers }),
+            fetchAllPages('/api/suppliers', { headers }),
+            fetchAllPages('/api/categories', { headers }),
             axios.get('/api/inventory/report', { headers }),
           ]);
-          this.products = productsRes.data.data;
-          this.suppliers = suppliersRes.data.data;
-          this.categories = categoriesRes.data.data;
+          this.products = productsRes;
+          this.suppliers = suppliersRes;
+          this.categories = categoriesRes;
           this.inventoryReport = inventoryReportRes.data.data;
         }
       } catch (error) {
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { useAuthStore } from './auth';
 
 export const useEmployeeStore = defineStore('employee', {
@@ -15,25 +16,25 @@ export const useEmployeeStore = defineStore('employee', {
         return;
       }
       try {
-        const params = new URLSearchParams();
+        const params = {};
         if (filters.store_id) {
-            params.append('store_id', filters.store_id);
+            params.store_id = filters.store_id;
         }
         if (filters.roles_to_exclude) {
-            params.append('roles_to_exclude', filters.roles_to_exclude);
+            params.roles_to_exclude = filters.roles_to_exclude;
         }
         if (filters.role) {
-            params.append('role', filters.role);
+            params.role = filters.role;
         }
 
-        const response = await axios.get('/api/employees', {
+        const rows = await fetchAllPages('/api/employees', {
           headers: {
             Authorization: `Bearer ${authStore.token}`,
           },
           params,
         });
-        console.log('API Response data:', response.data.data);
-        this.employees = response.data.data;
+        console.log('API Response data:', rows);
+        this.employees = rows;
         console.log('Store employees after assignment:', this.employees);
       } catch (error) {
         console.error('Error fetching employees:', error);
@@ -46,12 +47,12 @@ export const useEmployeeStore = defineStore('employee', {
             return;
         }
         try {
-            const response = await axios.get('/api/stores', {
+            const rows = await fetchAllPages('/api/stores', {
                 headers: {
                     Authorization: `Bearer ${authStore.token}`,
                 },
             });
-            this.stores = response.data.data.map(s => ({ label: s.name, value: s.id }));
+            this.stores = rows.map(s => ({ label: s.name, value: s.id }));
         } catch (error) {
             console.error('Error fetching stores:', error);
         }
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { ref, watch } from 'vue';
 import { useAuthStore } from './auth';
 import { useWebSocket } from '../composables/useWebSocket';
@@ -11,22 +12,30 @@ export const useInventoryStore = defineStore('inventory', () => {
   const inventoryReport = ref(null);
   const loading = ref(false);
 
+  const authStore = useAuthStore();
+
   // WEBSOCKET
   // Assuming the backend runs on port 8000
   const wsUrl = `ws://${window.location.hostname}:8000/ws`;
-  const { data: wsData } = useWebSocket(wsUrl);
+  const { event: wsEvent } = useWebSocket(wsUrl, {
+    getToken: () => authStore.token,
+    topics: ['store:*:inventory'],
+    onResync: () => {
+      fetchInventory();
+      fetchInventoryReport();
+    },
+  });
 
   // ACTIONS
-  const authStore = useAuthStore();
 
   async function fetchInventory() {
     if (!authStore.token) return;
     loading.value = true;
     try {
-      const response = await axios.get('/api/inventory', {
+      const rows = await fetchAllPages('/api/inventory', {
         headers: { Authorization: `Bearer ${authStore.token}` },
       });
-      inventory.value = response.data.data;
+      inventory.value = rows;
     } catch (error) {
       console.error('Error fetching inventory:', error);
     } finally {
@@ -37,10 +46,10 @@ export const useInventoryStore = defineStore('inventory', () => {
   async function fetchStores() {
     if (!authStore.token) return;
     try {
-      const response = await axios.get('/api/stores', {
+      const rows = await fetchAllPages('/api/stores', {
         headers: { Authorization: `Bearer ${authStore.token}` },
       });
-      stores.value = ['All Stores', ...response.data.data.map(s => s.name)];
+      stores.value = ['All Stores', ...rows.map(s => s.name)];
     } catch (error) {
       console.error('Error fetching stores:', error);
     }
@@ -93,9 +102,8 @@ export const useInventoryStore = defineStore('inventory', () => {
   }
 
   // WATCHER for WebSocket messages
-  watch(wsData, (newMessage) => {
-    if (newMessage === 'inventory_updated') {
-      console.log('Inventory update received via WebSocket. Refetching data...');
+  watch(wsEvent, (event) => {
+    if (event?.type === 'inventory_changed') {
       fetchInventory();
       fetchInventoryReport();
     }
@@ -1,6 +1,7 @@
 
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { ref, computed } from 'vue';
 import { useAuthStore } from './auth';
 import { useInventoryStore } from './inventory';
@@ -19,10 +20,10 @@ export const useProductStore = defineStore('product', () => {
   async function fetchProducts() {
     if (!authStore.token) return;
     try {
-      const response = await axios.get('/api/products', {
+      const rows = await fetchAllPages('/api/products', {
         headers: { Authorization: `Bearer ${authStore.token}` },
       });
-      products.value = response.data.data;
+      products.value = rows;
     } catch (error) {
       console.error('Error fetching products:', error);
     }
@@ -31,10 +32,10 @@ export const useProductStore = defineStore('product', () => {
   async function fetchCategories() {
     if (!authStore.token) return;
     try {
-      const response = await axios.get('/api/categories', {
+      const rows = await fetchAllPages('/api/categories', {
         headers: { Authorization: `Bearer ${authStore.token}` },
       });
-      categories.value = response.data.data.map(c => ({ label: c.name, value: c.id }));
+      categories.value = rows.map(c => ({ label: c.name, value: c.id }));
     } catch (error) {
       console.error('Error fetching categories:', error);
     }
@@ -43,15 +44,27 @@ export const useProductStore = defineStore('product', () => {
   async function fetchSuppliers() {
     if (!authStore.token) return;
     try {
-      const response = await axios.get('/api/suppliers', {
+      const rows = await fetchAllPages('/api/suppliers', {
         headers: { Authorization: `Bearer ${authStore.token}` },
       });
-      suppliers.value = response.data.data.map(s => ({ label: s.name, value: s.id }));
+      suppliers.value = rows.map(s => ({ label: s.name, value: s.id }));
     } catch (error) {
       console.error('Error fetching suppliers:', error);
     }
   }
 
+  // Ranked server-side search, annotated with the stock of the current store
+  async function searchProducts(query, limit = 50) {
+    if (!authStore.token) return [];
+    const params = { q: query, limit };
+    if (authStore.storeId) params.store_id = authStore.storeId;
+    const response = await axios.get('/api/search/products', {
+      headers: { Authorization: `Bearer ${authStore.token}` },
+      params,
+    });
+    return response.data.data.map(product => ({ ...product, stock: product.stock ?? 0 }));
+  }
+
   async function saveProduct(product) {
     if (!authStore.token) throw new Error('Not authenticated');
     const isUpdate = !!product.id;
@@ -151,6 +164,7 @@ export const useProductStore = defineStore('product', () => {
     fetchProducts,
     fetchCategories,
     fetchSuppliers,
+    searchProducts,
     saveProduct,
     deleteProduct,
     removePhoto, // Expose the new action
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 
 export const usePromotionStore = defineStore('promotion', {
   state: () => ({
@@ -12,8 +13,8 @@ export const usePromotionStore = defineStore('promotion', {
       this.isLoading = true;
       this.error = null;
       try {
-        const response = await axios.get('/api/admin/promotions');
-        this.promotions = response.data.data;
+        const rows = await fetchAllPages('/api/admin/promotions');
+        this.promotions = rows;
       } catch (error) {
         this.error = error;
         console.error('Error fetching promotions:', error);
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { useAuthStore } from './auth';
 
 // Set the base URL for all axios requests
@@ -17,10 +18,10 @@ export const usePurchaseOrderStore = defineStore('purchaseOrder', {
       if (!authStore.token) return;
       this.loading = true;
       try {
-        const response = await axios.get('/api/purchase-orders', {
+        const rows = await fetchAllPages('/api/purchase-orders', {
           headers: { Authorization: `Bearer ${authStore.token}` },
         });
-        this.purchaseOrders = response.data.data;
+        this.purchaseOrders = rows;
       } catch (error) {
         console.error('Error fetching purchase orders:', error);
         this.purchaseOrders = []; // Clear on error
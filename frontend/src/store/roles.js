@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 
 export const useRolesStore = defineStore('roles', {
   state: () => ({
@@ -10,8 +11,8 @@ export const useRolesStore = defineStore('roles', {
   actions: {
     async fetchAllRoles() {
       try {
-        const response = await axios.get('/api/roles');
-        this.roles = response.data.data.filter(role => role.name !== 'Owner' && role.name !== 'Admin');
+        const rows = await fetchAllPages('/api/roles');
+        this.roles = rows.filter(role => role.name !== 'Owner' && role.name !== 'Admin');
       } catch (error) {
         console.error('Error fetching roles:', error);
         // Consider adding a toast/notification here if needed
@@ -20,8 +21,8 @@ export const useRolesStore = defineStore('roles', {
     },
     async fetchAllPermissions() {
       try {
-        const response = await axios.get('/api/permissions');
-        this.permissions = response.data.data;
+        const rows = await fetchAllPages('/api/permissions');
+        this.permissions = rows;
       } catch (error) {
         console.error('Error fetching permissions:', error);
         throw error;
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { useAuthStore } from './auth';
 
 export const useStoreStore = defineStore('store', {
@@ -14,12 +15,12 @@ export const useStoreStore = defineStore('store', {
         return;
       }
       try {
-        const response = await axios.get('/api/stores', {
+        const rows = await fetchAllPages('/api/stores', {
           headers: {
             Authorization: `Bearer ${authStore.token}`,
           },
         });
-        this.stores = response.data.data;
+        this.stores = rows;
       } catch (error) {
         console.error('Error fetching stores:', error);
       }
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 
 export const useStoreManagerPromotionStore = defineStore('storeManagerPromotion', {
   state: () => ({
@@ -12,8 +13,8 @@ export const useStoreManagerPromotionStore = defineStore('storeManagerPromotion'
       this.isLoading = true;
       this.error = null;
       try {
-        const response = await axios.get('/api/store-manager/promotions');
-        this.promotions = response.data.data;
+        const rows = await fetchAllPages('/api/store-manager/promotions');
+        this.promotions = rows;
       } catch (error) {
         this.error = error;
         console.error('Error fetching promotions:', error);
@@ -1,5 +1,5 @@
 import { defineStore } from 'pinia';
-import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 
 export const useStoreManagerRefundStore = defineStore('storeManagerRefund', {
   state: () => ({
@@ -12,8 +12,8 @@ export const useStoreManagerRefundStore = defineStore('storeManagerRefund', {
       this.isLoading = true;
       this.error = null;
       try {
-        const response = await axios.get('/api/refunds');
-        this.refunds = response.data.data;
+        const rows = await fetchAllPages('/api/refunds');
+        this.refunds = rows;
       } catch (error) {
         this.error = error;
         console.error('Error fetching refunds:', error);
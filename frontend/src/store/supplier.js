@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia';
 import axios from 'axios';
+import { fetchAllPages } from '../utils/pagination';
 import { useAuthStore } from './auth';
 
 export const useSupplierStore = defineStore('supplier', {
@@ -14,12 +15,12 @@ export const useSupplierStore = defineStore('supplier', {
         return;
       }
       try {
-        const response = await axios.get('/api/suppliers', {
+        const rows = await fetchAllPages('/api/suppliers', {
           headers: {
             Authorization: `Bearer ${authStore.token}`,
           },
         });
-        this.suppliers = response.data.data;
+        this.suppliers = rows;
       } catch (error) {
         console.error('Error fetching suppliers:', error);
       }
@@ -0,0 +1,36 @@
+import axios from 'axios';
+
+// The most rows the server returns per page
+export const MAX_PAGE_SIZE = 200;
+
+/**
+ * Loads every row of a list endpoint, page by page. Only for lists that stay small, such as
+ * stores, categories or roles; large lists like orders should be paged in the table instead.
+ * `config` is passed to axios, and its `params` are sent with every page (filters, sort).
+ */
+export async function fetchAllPages(url, config = {}) {
+  const rows = [];
+  for (let page = 1; ; page++) {
+    const response = await axios.get(url, {
+      ...config,
+      params: { ...config.params, page, size: MAX_PAGE_SIZE },
+    });
+    const { data, pagination } = response.data;
+    rows.push(...data);
+    if (!pagination || page >= (pagination.total_pages ?? 0)) {
+      return rows;
+    }
+  }
+}
+
+/**
+ * Turns a PrimeVue lazy DataTable event (`page`, `sort`) into list query params. Sortable
+ * columns must be named after fields the server can sort on.
+ */
+export function lazyTableParams(event) {
+  const params = { page: event.page + 1, size: event.rows };
+  if (event.sortField) {
+    params.sort = event.sortOrder === -1 ? `-${event.sortField}` : event.sortField;
+  }
+  return params;
+}
@@ -1,28 +1,41 @@
-use actix_web::{web, HttpResponse, Responder};
-use sea_orm::DatabaseConnection;
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
 use crate::helper::response::{ApiResponse, ApiError};
 use crate::entities::employees;
 use crate::auth::auth_service;
 use crate::repository::employees_repository::EmployeeRepository;
 use crate::repository::settings_repository;
-use crate::helper::email;
+use crate::helper::mailer;
+use crate::auth::two_factor_handler;
+use crate::auth::session_handler;
+use crate::entities::settings_model::Settings;
 
 use serde::{Serialize, Deserialize};
 
-use crate::repository::permissions_repository::PermissionsRepository;
+use crate::entities::sessions::{TokenPair, SCOPE_FULL};
+use crate::repository::roles_repository::RoleRepository;
 use crate::repository::password_reset_tokens_repository::PasswordResetTokenRepository;
+use crate::repository::sessions_repository::SessionRepository;
+
+const PASSWORD_RESET_LINK_HOURS: i64 = 1;
 
 #[derive(Deserialize, Debug)]
 pub struct LoginPayload {
     pub email: String,
     pub password: String,
-    pub role: String,
+    /// Store to work in; defaults to the employee's primary store.
+    pub store_id: Option<i32>,
 }
 
 #[derive(Serialize)]
 pub struct LoginSuccessResponse {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
     pub user: employees::Model,
+    /// Name of the employee's role.
+    pub role: String,
     pub permissions: Vec<String>,
 }
 
@@ -37,35 +50,98 @@ pub struct ForgotPasswordPayload {
     pub email: String,
 }
 
-pub async fn login(db: web::Data<DatabaseConnection>, payload: web::Json<LoginPayload>) -> impl Responder {
+/// 423 response telling a locked-out user when they may try again.
+pub(crate) fn account_locked(locked_until: DateTime<Utc>) -> HttpResponse {
+    let retry_after = (locked_until - Utc::now()).num_seconds().max(1);
+    HttpResponse::Locked()
+        .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+        .json(ApiError::new(format!(
+            "Account is locked due to too many failed login attempts. Try again after {}.",
+            locked_until.to_rfc3339()
+        )))
+}
+
+/// Counts a failed password or code against the lockout policy and builds the response.
+pub(crate) async fn reject_attempt(db: &DatabaseConnection, user: employees::Model, settings: &Settings, message: &str) -> HttpResponse {
+    let lockout = &settings.security.account_lockout;
+    match EmployeeRepository::record_failed_login(db, user, lockout.max_failed_attempts, lockout.lockout_duration).await {
+        Ok(user) => match user.locked_until.filter(|until| *until > Utc::now()) {
+            Some(locked_until) => account_locked(locked_until),
+            None => HttpResponse::Unauthorized().json(ApiError::new(message.to_string())),
+        },
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to record login attempt".to_string())),
+    }
+}
+
+/// Finishes a successful login: resets the failed attempt counter and opens a session
+/// in the requested store.
+pub(crate) async fn complete_login(db: &DatabaseConnection, user: employees::Model, store_id: Option<i32>, req: &HttpRequest) -> Result<LoginSuccessResponse, HttpResponse> {
+    let active_store_id = session_handler::choose_active_store(db, &user, store_id).await?;
+    let user = EmployeeRepository::clear_failed_logins(db, user)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to update employee".to_string())))?;
+    let tokens = session_handler::start_session(db, &user, None, active_store_id, req)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to create session".to_string())))?;
+    login_response(db, user, tokens, SCOPE_FULL).await
+}
+
+/// Wraps a new session's tokens with the employee, their role and what the session may do.
+pub(crate) async fn login_response(db: &DatabaseConnection, user: employees::Model, tokens: TokenPair, scope: &str) -> Result<LoginSuccessResponse, HttpResponse> {
+    let permissions = auth_service::session_permissions(db, &user, scope)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch permissions".to_string())))?;
+    let role = RoleRepository::find_by_id(db, user.role_id)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch role".to_string())))?
+        .map(|r| r.name)
+        .unwrap_or_default();
+    Ok(LoginSuccessResponse {
+        token: tokens.token,
+        refresh_token: tokens.refresh_token,
+        expires_in: tokens.expires_in,
+        user,
+        role,
+        permissions,
+    })
+}
+
+pub async fn login(req: HttpRequest, db: web::Data<DatabaseConnection>, payload: web::Json<LoginPayload>) -> impl Responder {
     println!("Received login attempt: {:?}", payload);
+    let settings = match settings_repository::get_settings(&db).await {
+        Ok(settings) => settings,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch settings".to_string())),
+    };
+
     match EmployeeRepository::find_by_email(&db, payload.email.clone()).await {
         Ok(Some(user)) => {
+            if let Some(locked_until) = user.locked_until.filter(|until| *until > Utc::now()) {
+                return account_locked(locked_until);
+            }
+
             // First, verify the password
             match auth_service::verify_password(&payload.password, &user.password_hash) {
                 Ok(true) => {
-                    // After password is ok, check the role
-                    if user.role != payload.role {
-                        return HttpResponse::Unauthorized().json(ApiError::new("Invalid credentials for this role!".to_string()));
+                    let role = match RoleRepository::find_by_id(db.get_ref(), user.role_id).await {
+                        Ok(role) => role.map(|r| r.name).unwrap_or_default(),
+                        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch role".to_string())),
+                    };
+
+                    // With 2FA the token is only issued once the code is verified; the
+                    // failed attempt counter keeps running until then
+                    if user.totp_enabled || settings.security.requires_2fa(&role) {
+                        return two_factor_handler::challenge(&user);
                     }
 
-                    // If role is also ok, create token
-                    match auth_service::create_jwt(&db, &user).await {
-                        Ok(token) => {
-                            match PermissionsRepository::find_permissions_for_role(&db, user.role_id).await {
-                                Ok(permissions) => {
-                                    let permissions: Vec<String> = permissions.into_iter().map(|p| p.name).collect();
-                                    let response_data = LoginSuccessResponse { token, user, permissions };
-                                    println!("Sending success response");
-                                    HttpResponse::Ok().json(ApiResponse::new(response_data))
-                                }
-                                Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch permissions".to_string())),
-                            }
+                    match complete_login(&db, user, payload.store_id, &req).await {
+                        Ok(response_data) => {
+                            println!("Sending success response");
+                            HttpResponse::Ok().json(ApiResponse::new(response_data))
                         }
-                        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create token".to_string())),
+                        Err(response) => response,
                     }
                 }
-                Ok(false) => HttpResponse::Unauthorized().json(ApiError::new("Invalid credentials".to_string())),
+                Ok(false) => reject_attempt(&db, user, &settings, "Invalid credentials").await,
                 Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Password verification failed".to_string())),
             }
         }
@@ -86,7 +162,15 @@ pub async fn set_password(db: web::Data<DatabaseConnection>, payload: web::Json<
                 return HttpResponse::BadRequest().json(ApiError::new("Token has expired.".to_string()));
             }
 
-            // 3. Hash the new password
+            // 3. Check it against the password policy, then hash it
+            match settings_repository::get_settings(db).await {
+                Ok(settings) => {
+                    if let Err(message) = settings.security.password_policy.validate(&payload.new_password) {
+                        return HttpResponse::BadRequest().json(ApiError::new(message));
+                    }
+                }
+                Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch settings.".to_string())),
+            }
             let hashed_password = match auth_service::hash_password(&payload.new_password) {
                 Ok(hash) => hash,
                 Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to hash password.".to_string())),
@@ -99,9 +183,12 @@ pub async fn set_password(db: web::Data<DatabaseConnection>, payload: web::Json<
             };
 
             match EmployeeRepository::update(db, token_model.employee_id, employee_update).await {
-                Ok(Some(_)) => {
-                    // 5. Delete the token so it can't be reused
+                Ok(Some(employee)) => {
+                    // 5. Delete the token so it can't be reused, and lift any lockout
                     let _ = PasswordResetTokenRepository::delete(db, token_model.id).await;
+                    // and sign out any session opened with the old password
+                    let _ = SessionRepository::revoke_all_for_employee(db, employee.id).await;
+                    let _ = EmployeeRepository::clear_failed_logins(db, employee).await;
                     HttpResponse::Ok().json(ApiResponse::new("Password has been set successfully.".to_string()))
                 }
                 Ok(None) => HttpResponse::NotFound().json(ApiError::new("Employee not found.".to_string())),
@@ -122,51 +209,24 @@ pub async fn set_password(db: web::Data<DatabaseConnection>, payload: web::Json<
 pub async fn forgot_password(db: web::Data<DatabaseConnection>, payload: web::Json<ForgotPasswordPayload>) -> impl Responder {
     let db = db.get_ref();
 
-    // Attempt to find the user, but don't let the outcome affect the response time or result.
+    // Attempt to find the user, but don't let the outcome affect the result.
     if let Ok(Some(employee)) = EmployeeRepository::find_by_email(db, payload.email.clone()).await {
-        // User found, so we spawn a background task to do the work.
-        let db_clone = db.clone();
-        tokio::spawn(async move {
-            
-            // 1. Generate Token
+        // Store the token and queue the email together; the outbox worker sends it, so the
+        // response time does not depend on the mail server.
+        let result: Result<(), DbErr> = async {
+            let settings = settings_repository::get_settings(db).await?;
             let token = ::uuid::Uuid::new_v4().to_string();
-            let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::hours(1); // Shorter expiry for password resets
-
-            // 2. Store Token
-            if let Err(e) = PasswordResetTokenRepository::create(&db_clone, employee.id, token.clone(), expires_at).await {
-                eprintln!("Failed to create password reset token for forgot password: {}", e);
-                return;
-            }
+            let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::hours(PASSWORD_RESET_LINK_HOURS); // Shorter expiry for password resets
+            let txn = db.begin().await?;
+            PasswordResetTokenRepository::create(&txn, employee.id, token.clone(), expires_at).await?;
+            mailer::queue_password_link(&txn, mailer::PASSWORD_RESET, &employee, &token, PASSWORD_RESET_LINK_HOURS, &settings).await?;
+            txn.commit().await
+        }
+        .await;
 
-            // 3. Fetch Email Settings
-            match settings_repository::get_settings(&db_clone).await {
-                Ok(settings) => {
-                    // 4. Send Email
-                    let frontend_url = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
-                    let set_password_url = format!("{}/set-password?token={}", frontend_url, token);
-
-                    let subject = "Reset Your Password";
-                    let body = format!(
-                        "Hello {},\n\nYou requested to reset your password. Please click the link below to set a new one:\n\n{}\n\nThe link will expire in 1 hour.",
-                        employee.first_name,
-                        set_password_url
-                    );
-
-                    if let Err(e) = email::send_email(
-                        &settings.email,
-                        &employee.email,
-                        Some(&employee.first_name),
-                        subject,
-                        &body,
-                    ).await {
-                        eprintln!("Failed to send forgot-password email: {}", e);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to fetch settings for sending forgot-password email: {}", e);
-                }
-            }
-        });
+        if let Err(e) = result {
+            log::error!("Failed to queue forgot-password email: {}", e);
+        }
     }
 
     // Always return a success response to prevent email enumeration.
@@ -1,17 +1,22 @@
 use argon2::{self, Argon2, PasswordHash, PasswordVerifier};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
 use lazy_static::lazy_static;
 use password_hash::{PasswordHasher, SaltString};
-use rand::rngs::OsRng;
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
 use sea_orm::{DbErr, DatabaseConnection};
 use serde::{Deserialize, Serialize};
-use actix_web::{FromRequest, HttpRequest, dev::Payload, http, error::ErrorUnauthorized};
+use actix_web::{web, FromRequest, HttpRequest, dev::Payload, http, error::{ErrorInternalServerError, ErrorUnauthorized}};
+use sha2::{Digest, Sha256};
 use std::future::{Future};
 use std::pin::Pin;
 
-use crate::entities::employees;
+use crate::entities::{employees, sessions};
+use crate::repository::employee_stores_repository::EmployeeStoreRepository;
 use crate::repository::permissions_repository::PermissionsRepository;
+use crate::repository::roles_repository::RoleRepository;
+use crate::repository::sessions_repository::SessionRepository;
+use crate::repository::settings_repository;
 
 lazy_static! {
     static ref JWT_SECRET: String = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
@@ -21,12 +26,65 @@ lazy_static! {
 pub struct Claims {
     pub sub: i32, // Employee ID
     pub email: String,
-    pub role: String,
-    pub store_id: Option<i32>,
+    pub role: String, // Role name, for display; authorization goes by permissions
+    pub store_id: Option<i32>, // Store the employee is working in (the session's active store)
+    #[serde(default)]
+    pub store_ids: Vec<i32>, // Every store the employee is assigned to
     pub permissions: Vec<String>, // User's permissions
+    pub sid: i32, // Session the token was issued for
+    pub scope: String, // FULL, or POS for PIN logins
+    #[serde(default)]
+    pub ver: i32, // The session's access version when the token was issued
+    pub iat: usize, // Issued at
     pub exp: usize, // Expiration time
 }
 
+/// Sees the data of every store.
+pub const ACCESS_ALL_STORES: &str = "stores:access_all";
+/// Sees all data of their own store; without either access permission an employee
+/// only sees their own records.
+pub const ACCESS_OWN_STORE: &str = "stores:access_own";
+
+impl Claims {
+    pub fn is_pos_session(&self) -> bool {
+        self.scope == sessions::SCOPE_POS
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    pub fn can_access_all_stores(&self) -> bool {
+        self.has_permission(ACCESS_ALL_STORES)
+    }
+
+    pub fn can_access_own_store(&self) -> bool {
+        self.has_permission(ACCESS_OWN_STORE)
+    }
+}
+
+/// Access tokens are short-lived; clients renew them with their refresh token.
+pub const ACCESS_TOKEN_MINUTES: i64 = 15;
+/// A session ends this long after login no matter how often it is refreshed.
+pub const MAX_SESSION_DAYS: i64 = 30;
+
+/// The most a POS-scoped (PIN login) token can carry, whatever the employee's role allows.
+pub const POS_PERMISSIONS: &[&str] = &[
+    "orders:create",
+    "orders:read",
+    "products:read",
+    "categories:read",
+    "customers:read",
+    "customers:create",
+    "promotions:read",
+    "inventory:read",
+    "refunds:create",
+    "refunds:read",
+    "shifts:read",
+    "shifts:manage",
+    "timeclock:use",
+];
+
 pub fn hash_password(password: &str) -> Result<String, DbErr> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
@@ -40,20 +98,96 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, DbErr> {
     Ok(Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok())
 }
 
-pub async fn create_jwt(db: &DatabaseConnection, employee: &employees::Model) -> Result<String, DbErr> {
-    let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(24)) // Token valid for 24 hours
+/// Issues an access token for a session. Returns the token and its lifetime in seconds.
+pub async fn create_jwt(db: &DatabaseConnection, employee: &employees::Model, session: &sessions::Model) -> Result<(String, i64), DbErr> {
+    // Never outlive the session timeout setting (minutes)
+    let session_timeout = settings_repository::get_settings(db).await?.security.session_timeout.max(1) as i64;
+    let lifetime = Duration::minutes(ACCESS_TOKEN_MINUTES.min(session_timeout));
+    let now = Utc::now();
+    let expiration = now
+        .checked_add_signed(lifetime)
         .expect("valid timestamp")
         .timestamp() as usize;
 
-    let permissions = PermissionsRepository::find_permissions_for_role(db, employee.role_id).await?.into_iter().map(|p| p.name).collect();
+    let permissions = session_permissions(db, employee, &session.scope).await?;
+    let role = RoleRepository::find_by_id(db, employee.role_id).await?.map(|r| r.name).unwrap_or_default();
+    let store_ids = EmployeeStoreRepository::get_store_ids(db, employee.id).await?;
+    // If the employee lost the assignment since picking the store, fall back to their primary one
+    let all_stores = permissions.iter().any(|p| p == ACCESS_ALL_STORES);
+    let store_id = session.active_store_id
+        .filter(|id| all_stores || store_ids.contains(id))
+        .or(employee.store_id);
 
     let claims = Claims {
         sub: employee.id,
         email: employee.email.clone(),
-        role: employee.role.clone(),
-        store_id: employee.store_id,
+        role,
+        store_id,
+        store_ids,
         permissions,
+        sid: session.id,
+        scope: session.scope.clone(),
+        ver: session.access_version,
+        iat: now.timestamp() as usize,
+        exp: expiration,
+    };
+
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes()))
+        .map_err(|e| DbErr::Custom(e.to_string()))?;
+    Ok((token, lifetime.num_seconds()))
+}
+
+/// The employee's role permissions, narrowed to `POS_PERMISSIONS` for POS sessions.
+pub async fn session_permissions(db: &DatabaseConnection, employee: &employees::Model, scope: &str) -> Result<Vec<String>, DbErr> {
+    Ok(PermissionsRepository::find_permissions_for_role(db, employee.role_id)
+        .await?
+        .into_iter()
+        .map(|p| p.name)
+        .filter(|name| scope != sessions::SCOPE_POS || POS_PERMISSIONS.contains(&name.as_str()))
+        .collect())
+}
+
+/// A new opaque refresh token. Only its hash is stored.
+pub fn generate_refresh_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect()
+}
+
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// When a session lapses if it is not refreshed again: the idle timeout from the
+/// security settings, capped by the absolute session lifetime.
+pub fn session_expiry(session_timeout_minutes: i32, created_at: DateTime<Utc>) -> DateTime<Utc> {
+    let idle_expiry = Utc::now() + Duration::minutes(session_timeout_minutes.max(1) as i64);
+    idle_expiry.min(created_at + Duration::days(MAX_SESSION_DAYS))
+}
+
+/// Short-lived token proving the password step of a two-factor login succeeded.
+/// It lacks the access token's fields, so it cannot be used as one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorChallengeClaims {
+    pub sub: i32,
+    pub purpose: String,
+    pub exp: usize,
+}
+
+const TWO_FACTOR_CHALLENGE_PURPOSE: &str = "2fa_challenge";
+pub const TWO_FACTOR_CHALLENGE_MINUTES: i64 = 5;
+
+pub fn create_two_factor_challenge(employee_id: i32) -> Result<String, DbErr> {
+    let expiration = Utc::now()
+        .checked_add_signed(Duration::minutes(TWO_FACTOR_CHALLENGE_MINUTES))
+        .expect("valid timestamp")
+        .timestamp() as usize;
+
+    let claims = TwoFactorChallengeClaims {
+        sub: employee_id,
+        purpose: TWO_FACTOR_CHALLENGE_PURPOSE.to_string(),
         exp: expiration,
     };
 
@@ -61,30 +195,92 @@ pub async fn create_jwt(db: &DatabaseConnection, employee: &employees::Model) ->
         .map_err(|e| DbErr::Custom(e.to_string()))
 }
 
+/// Returns the employee id a challenge token was issued for.
+pub fn decode_two_factor_challenge(token: &str) -> Result<i32, DbErr> {
+    let data = decode::<TwoFactorChallengeClaims>(token, &DecodingKey::from_secret(JWT_SECRET.as_bytes()), &Validation::default())
+        .map_err(|e| DbErr::Custom(e.to_string()))?;
+    if data.claims.purpose != TWO_FACTOR_CHALLENGE_PURPOSE {
+        return Err(DbErr::Custom("Not a two-factor challenge token".to_string()));
+    }
+    Ok(data.claims.sub)
+}
+
 pub fn decode_jwt(token: &str) -> Result<TokenData<Claims>, DbErr> {
     decode::<Claims>(token, &DecodingKey::from_secret(JWT_SECRET.as_bytes()), &Validation::default())
         .map_err(|e| DbErr::Custom(e.to_string()))
 }
 
+pub fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+}
+
+/// Validates the request's bearer token and checks that its session is still active
+/// and has not been told to refresh (after a role or permission change).
+pub async fn authenticate(req: &HttpRequest) -> Result<Claims, actix_web::Error> {
+    let token = bearer_token(req).ok_or_else(|| ErrorUnauthorized("Missing or invalid token"))?;
+    authenticate_token(req, token).await
+}
+
+/// Like `authenticate`, for a token that did not come in the Authorization header.
+pub async fn authenticate_token(req: &HttpRequest, token: &str) -> Result<Claims, actix_web::Error> {
+    let claims = decode_jwt(token).map_err(|_| ErrorUnauthorized("Invalid or expired token"))?.claims;
+
+    let db = req
+        .app_data::<web::Data<DatabaseConnection>>()
+        .ok_or_else(|| ErrorInternalServerError("Database connection not configured"))?;
+    let session = SessionRepository::find_by_id(db.get_ref(), claims.sid)
+        .await
+        .map_err(|_| ErrorInternalServerError("Failed to check session"))?;
+
+    match session {
+        Some(session)
+            if session.employee_id == claims.sub
+                && session.is_active(Utc::now())
+                && session.access_version == claims.ver =>
+        {
+            Ok(claims)
+        }
+        _ => Err(ErrorUnauthorized("Session has expired or been revoked")),
+    }
+}
+
 impl FromRequest for Claims {
     type Error = actix_web::Error;
     type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
         let req = req.clone();
-        Box::pin(async move {
-            if let Some(auth_header) = req.headers().get(http::header::AUTHORIZATION) {
-                if let Ok(auth_str) = auth_header.to_str() {
-                    if auth_str.starts_with("Bearer ") {
-                        let token = &auth_str["Bearer ".len()..];
-                        return match decode_jwt(token) {
-                            Ok(token_data) => Ok(token_data.claims),
-                            Err(_) => Err(ErrorUnauthorized("Invalid token")),
-                        };
-                    }
-                }
-            }
-            Err(ErrorUnauthorized("Missing or invalid token"))
+        Box::pin(async move { authenticate(&req).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::test_database;
+    use actix_web::test::TestRequest;
+
+    async fn authenticate_against(session: sessions::Model) -> Result<Claims, actix_web::Error> {
+        let token = test_database::access_token(&[]);
+        let (db, _) = test_database::connect(move |statement| {
+            if test_database::is_session_lookup(statement) { vec![test_database::row(&session)] } else { vec![] }
         })
+        .await;
+        let req = TestRequest::default().app_data(web::Data::new(db)).to_http_request();
+        authenticate_token(&req, &token).await
+    }
+
+    #[actix_web::test]
+    async fn accepts_a_token_of_the_current_access_version() {
+        assert!(authenticate_against(test_database::session_model()).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn rejects_a_token_issued_in_the_same_second_as_a_revocation() {
+        let session = sessions::Model { access_version: 1, ..test_database::session_model() };
+        assert!(authenticate_against(session).await.is_err());
     }
 }
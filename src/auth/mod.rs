@@ -1,2 +1,7 @@
 pub mod auth_service;
 pub mod auth_handler;
+pub mod totp;
+pub mod two_factor_handler;
+pub mod session_handler;
+pub mod pin_handler;
+pub mod store_scope;
@@ -0,0 +1,213 @@
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::auth_handler::{account_locked, login_response};
+use crate::auth::{auth_service, session_handler};
+use crate::entities::sessions::SCOPE_POS;
+use crate::entities::terminals;
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::extractor::terminal_extractor::TerminalExtractor;
+use crate::helper::response::{ApiResponse, ApiError};
+use crate::repository::employee_stores_repository::EmployeeStoreRepository;
+use crate::repository::employees_repository::EmployeeRepository;
+use crate::repository::sessions_repository::SessionRepository;
+use crate::repository::settings_repository;
+use crate::repository::time_entries_repository::TimeEntryRepository;
+
+/// Wrong PINs allowed before PIN login is locked for the employee.
+const MAX_PIN_ATTEMPTS: i32 = 5;
+const PIN_LOCKOUT_MINUTES: i64 = 15;
+
+#[derive(Deserialize)]
+pub struct PinLoginPayload {
+    pub employee_id: i32,
+    pub pin: String,
+}
+
+#[derive(Deserialize)]
+pub struct SetPinPayload {
+    pub pin: String,
+    pub current_password: String,
+}
+
+/// An employee shown on the terminal's sign-in screen.
+#[derive(Serialize)]
+pub struct PinUser {
+    pub id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub photo_url: Option<String>,
+}
+
+fn validate_pin(pin: &str) -> Result<(), String> {
+    if !(4..=6).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err("PIN must be 4 to 6 digits".to_string());
+    }
+    Ok(())
+}
+
+fn pin_locked(locked_until: DateTime<Utc>) -> HttpResponse {
+    let retry_after = (locked_until - Utc::now()).num_seconds().max(1);
+    HttpResponse::Locked()
+        .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+        .json(ApiError::new(format!(
+            "PIN login is locked due to too many wrong PINs. Try again after {} or sign in with your password.",
+            locked_until.to_rfc3339()
+        )))
+}
+
+/// Employees of the terminal's store who can sign in with a PIN.
+pub async fn get_pin_users(terminal: TerminalExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let terminal = match terminal.0 {
+        Some(terminal) => terminal,
+        None => return HttpResponse::Unauthorized().json(ApiError::new("PIN login is only available on a registered terminal".to_string())),
+    };
+
+    match EmployeeRepository::get_all_by_store(db.get_ref(), terminal.store_id).await {
+        Ok(employees) => {
+            let users: Vec<PinUser> = employees
+                .into_iter()
+                .filter(|e| e.pin_hash.is_some())
+                .map(|e| PinUser { id: e.id, first_name: e.first_name, last_name: e.last_name, photo_url: e.photo_url })
+                .collect();
+            HttpResponse::Ok().json(ApiResponse::new(users))
+        }
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employees".to_string())),
+    }
+}
+
+/// Clocks the employee in at the terminal's store when the time clock is set to follow PIN
+/// logins and they are not already clocked in.
+async fn clock_in_on_login(db: &DatabaseConnection, employee_id: i32, terminal: &terminals::Model) -> Result<(), DbErr> {
+    if !settings_repository::get_settings(db).await?.time_clock.clock_in_on_pin_login {
+        return Ok(());
+    }
+    if TimeEntryRepository::find_open_for_employee(db, employee_id).await?.is_none() {
+        TimeEntryRepository::clock_in(db, employee_id, terminal.store_id, Some(terminal.id)).await?;
+    }
+    Ok(())
+}
+
+/// Signs an employee in on a shared terminal with their PIN. The session is limited to POS
+/// actions and replaces whoever was signed in on the terminal before, so cashiers can switch
+/// without logging out.
+pub async fn pin_login(
+    req: HttpRequest,
+    terminal: TerminalExtractor,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<PinLoginPayload>,
+) -> impl Responder {
+    let db = db.get_ref();
+    let terminal = match terminal.0 {
+        Some(terminal) => terminal,
+        None => return HttpResponse::Unauthorized().json(ApiError::new("PIN login is only available on a registered terminal".to_string())),
+    };
+
+    let user = match EmployeeRepository::find_by_id(db, payload.employee_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::Unauthorized().json(ApiError::new("Invalid PIN".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee".to_string())),
+    };
+    match EmployeeStoreRepository::is_assigned(db, user.id, terminal.store_id).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::Unauthorized().json(ApiError::new("Invalid PIN".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch store assignments".to_string())),
+    }
+
+    let now = Utc::now();
+    if let Some(locked_until) = user.locked_until.filter(|until| *until > now) {
+        return account_locked(locked_until);
+    }
+    if let Some(locked_until) = user.pin_locked_until.filter(|until| *until > now) {
+        return pin_locked(locked_until);
+    }
+
+    let pin_hash = match &user.pin_hash {
+        Some(hash) => hash.clone(),
+        None => return HttpResponse::Unauthorized().json(ApiError::new("Invalid PIN".to_string())),
+    };
+    match auth_service::verify_password(&payload.pin, &pin_hash) {
+        Ok(true) => {}
+        Ok(false) => {
+            return match EmployeeRepository::record_failed_pin(db, user, MAX_PIN_ATTEMPTS, PIN_LOCKOUT_MINUTES).await {
+                Ok(user) => match user.pin_locked_until.filter(|until| *until > Utc::now()) {
+                    Some(locked_until) => pin_locked(locked_until),
+                    None => HttpResponse::Unauthorized().json(ApiError::new("Invalid PIN".to_string())),
+                },
+                Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to record login attempt".to_string())),
+            };
+        }
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("PIN verification failed".to_string())),
+    }
+
+    let user = match EmployeeRepository::clear_failed_pins(db, user).await {
+        Ok(user) => user,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to update employee".to_string())),
+    };
+
+    // One cashier per terminal: signing in ends the previous cashier's session on it
+    if SessionRepository::revoke_all_for_terminal(db, terminal.id).await.is_err() {
+        return HttpResponse::InternalServerError().json(ApiError::new("Failed to end the previous session".to_string()));
+    }
+
+    let tokens = match session_handler::start_session(db, &user, Some(&terminal), Some(terminal.store_id), &req).await {
+        Ok(tokens) => tokens,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to create session".to_string())),
+    };
+    if let Err(e) = clock_in_on_login(db, user.id, &terminal).await {
+        log::error!("Failed to clock in employee {} on PIN login: {}", user.id, e);
+    }
+    match login_response(db, user, tokens, SCOPE_POS).await {
+        Ok(response_data) => HttpResponse::Ok().json(ApiResponse::new(response_data)),
+        Err(response) => response,
+    }
+}
+
+/// Sets the logged-in employee's PIN. Needs a full (password) session and the current password.
+pub async fn set_my_pin(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>, payload: web::Json<SetPinPayload>) -> impl Responder {
+    let db = db.get_ref();
+    if claims.0.is_pos_session() {
+        return HttpResponse::Forbidden().json(ApiError::new("Sign in with your password to change your PIN".to_string()));
+    }
+    if let Err(message) = validate_pin(&payload.pin) {
+        return HttpResponse::BadRequest().json(ApiError::new(message));
+    }
+
+    let employee = match EmployeeRepository::find_by_id(db, claims.0.sub).await {
+        Ok(Some(employee)) => employee,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("Employee not found".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee".to_string())),
+    };
+    match auth_service::verify_password(&payload.current_password, &employee.password_hash) {
+        Ok(true) => {}
+        _ => return HttpResponse::Unauthorized().json(ApiError::new("Current password is incorrect".to_string())),
+    }
+
+    let pin_hash = match auth_service::hash_password(&payload.pin) {
+        Ok(hash) => hash,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to hash PIN".to_string())),
+    };
+    match EmployeeRepository::set_pin_hash(db, employee, Some(pin_hash)).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::new("PIN has been set".to_string())),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to set PIN".to_string())),
+    }
+}
+
+pub async fn remove_my_pin(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let db = db.get_ref();
+    if claims.0.is_pos_session() {
+        return HttpResponse::Forbidden().json(ApiError::new("Sign in with your password to change your PIN".to_string()));
+    }
+
+    let employee = match EmployeeRepository::find_by_id(db, claims.0.sub).await {
+        Ok(Some(employee)) => employee,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("Employee not found".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee".to_string())),
+    };
+    match EmployeeRepository::set_pin_hash(db, employee, None).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::new("PIN has been removed".to_string())),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to remove PIN".to_string())),
+    }
+}
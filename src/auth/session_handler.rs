@@ -0,0 +1,215 @@
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::auth::auth_service;
+use crate::entities::{employees, terminals};
+use crate::entities::sessions::{AccessToken, CreateSession, RefreshTokenPayload, SessionResponse, SwitchStorePayload, TokenPair, SCOPE_FULL, SCOPE_POS};
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::helper::response::{ApiResponse, ApiError};
+use crate::repository::employee_stores_repository::EmployeeStoreRepository;
+use crate::repository::employees_repository::EmployeeRepository;
+use crate::repository::sessions_repository::SessionRepository;
+use crate::repository::settings_repository;
+use crate::repository::stores_repository::StoreRepository;
+
+/// The store a new session works in: the one the employee picked at login, which must be
+/// one of their stores unless they may access every store, or else their primary store.
+pub(crate) async fn choose_active_store(db: &DatabaseConnection, user: &employees::Model, requested: Option<i32>) -> Result<Option<i32>, HttpResponse> {
+    let store_id = match requested {
+        Some(store_id) => store_id,
+        None => return Ok(user.store_id),
+    };
+    let assigned = EmployeeStoreRepository::is_assigned(db, user.id, store_id)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch store assignments".to_string())))?;
+    if assigned {
+        return Ok(Some(store_id));
+    }
+
+    let permissions = auth_service::session_permissions(db, user, SCOPE_FULL)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch permissions".to_string())))?;
+    if !permissions.iter().any(|p| p == auth_service::ACCESS_ALL_STORES) {
+        return Err(HttpResponse::Forbidden().json(ApiError::new("You are not assigned to this store".to_string())));
+    }
+    match StoreRepository::find_by_id(db, store_id).await {
+        Ok(Some(_)) => Ok(Some(store_id)),
+        Ok(None) => Err(HttpResponse::NotFound().json(ApiError::new("Store not found".to_string()))),
+        Err(_) => Err(HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch store".to_string()))),
+    }
+}
+
+/// Opens a session for a freshly authenticated employee and issues its first token pair.
+/// Sessions started from a terminal (PIN logins) are limited to POS actions.
+pub async fn start_session(
+    db: &DatabaseConnection,
+    user: &employees::Model,
+    terminal: Option<&terminals::Model>,
+    active_store_id: Option<i32>,
+    req: &HttpRequest,
+) -> Result<TokenPair, DbErr> {
+    let settings = settings_repository::get_settings(db).await?;
+    let refresh_token = auth_service::generate_refresh_token();
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(|ua| ua.chars().take(255).collect());
+    let ip_address = req.connection_info().realip_remote_addr().map(|ip| ip.to_string());
+
+    let session = SessionRepository::create(
+        db,
+        CreateSession {
+            employee_id: user.id,
+            refresh_token_hash: auth_service::hash_token(&refresh_token),
+            user_agent,
+            ip_address,
+            scope: if terminal.is_some() { SCOPE_POS } else { SCOPE_FULL }.to_string(),
+            terminal_id: terminal.map(|t| t.id),
+            active_store_id,
+            expires_at: auth_service::session_expiry(settings.security.session_timeout, Utc::now()),
+        },
+    )
+    .await?;
+
+    let (token, expires_in) = auth_service::create_jwt(db, user, &session).await?;
+    Ok(TokenPair { token, refresh_token, expires_in })
+}
+
+/// Exchanges a refresh token for a new token pair. The refresh token rotates on every
+/// use; presenting an already rotated one revokes the session, since it was likely stolen.
+pub async fn refresh(db: web::Data<DatabaseConnection>, payload: web::Json<RefreshTokenPayload>) -> impl Responder {
+    let db = db.get_ref();
+    let token_hash = auth_service::hash_token(&payload.refresh_token);
+
+    let session = match SessionRepository::find_by_refresh_token_hash(db, &token_hash).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return match SessionRepository::find_by_previous_token_hash(db, &token_hash).await {
+                Ok(Some(session)) => {
+                    log::warn!("Refresh token reuse detected for session {}; revoking it", session.id);
+                    let _ = SessionRepository::revoke(db, session.id).await;
+                    HttpResponse::Unauthorized().json(ApiError::new("Refresh token has already been used; the session was revoked".to_string()))
+                }
+                Ok(None) => HttpResponse::Unauthorized().json(ApiError::new("Invalid refresh token".to_string())),
+                Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch session".to_string())),
+            };
+        }
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch session".to_string())),
+    };
+
+    if !session.is_active(Utc::now()) {
+        return HttpResponse::Unauthorized().json(ApiError::new("Session has expired or been revoked".to_string()));
+    }
+
+    let employee = match EmployeeRepository::find_by_id(db, session.employee_id).await {
+        Ok(Some(employee)) => employee,
+        Ok(None) => return HttpResponse::Unauthorized().json(ApiError::new("Employee no longer exists".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee".to_string())),
+    };
+
+    let settings = match settings_repository::get_settings(db).await {
+        Ok(settings) => settings,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch settings".to_string())),
+    };
+
+    let refresh_token = auth_service::generate_refresh_token();
+    let expires_at = auth_service::session_expiry(settings.security.session_timeout, session.created_at);
+    let session_id = session.id;
+    let session = match SessionRepository::rotate(db, session, auth_service::hash_token(&refresh_token), expires_at).await {
+        Ok(Some(session)) => session,
+        // Another request used the same token in the meantime
+        Ok(None) => {
+            log::warn!("Refresh token reuse detected for session {}; revoking it", session_id);
+            let _ = SessionRepository::revoke(db, session_id).await;
+            return HttpResponse::Unauthorized().json(ApiError::new("Refresh token has already been used; the session was revoked".to_string()));
+        }
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to update session".to_string())),
+    };
+
+    // A fresh access token picks up any role or permission changes
+    match auth_service::create_jwt(db, &employee, &session).await {
+        Ok((token, expires_in)) => HttpResponse::Ok().json(ApiResponse::new(TokenPair { token, refresh_token, expires_in })),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create token".to_string())),
+    }
+}
+
+/// Moves the session to another of the employee's stores and issues an access token for it.
+/// POS sessions stay in their terminal's store.
+pub async fn switch_store(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>, payload: web::Json<SwitchStorePayload>) -> impl Responder {
+    let db = db.get_ref();
+    let claims = claims.0;
+    if claims.is_pos_session() {
+        return HttpResponse::Forbidden().json(ApiError::new("Terminal sessions cannot switch stores".to_string()));
+    }
+
+    let employee = match EmployeeRepository::find_by_id(db, claims.sub).await {
+        Ok(Some(employee)) => employee,
+        Ok(None) => return HttpResponse::Unauthorized().json(ApiError::new("Employee no longer exists".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee".to_string())),
+    };
+    let store_id = match choose_active_store(db, &employee, Some(payload.store_id)).await {
+        Ok(store_id) => store_id.unwrap_or(payload.store_id),
+        Err(response) => return response,
+    };
+
+    let session = match SessionRepository::find_by_id(db, claims.sid).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return HttpResponse::Unauthorized().json(ApiError::new("Session not found".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch session".to_string())),
+    };
+    let session = match SessionRepository::set_active_store(db, session, store_id).await {
+        Ok(session) => session,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to update session".to_string())),
+    };
+
+    match auth_service::create_jwt(db, &employee, &session).await {
+        Ok((token, expires_in)) => HttpResponse::Ok().json(ApiResponse::new(AccessToken { token, expires_in })),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create token".to_string())),
+    }
+}
+
+/// Ends the session the request was made with.
+pub async fn logout(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match SessionRepository::revoke(db.get_ref(), claims.0.sid).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::new("Logged out".to_string())),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to log out".to_string())),
+    }
+}
+
+/// Ends every session of the logged-in employee, including this one.
+pub async fn logout_all(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match SessionRepository::revoke_all_for_employee(db.get_ref(), claims.0.sub).await {
+        Ok(count) => HttpResponse::Ok().json(ApiResponse::new(format!("Logged out of {} session(s)", count))),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to log out".to_string())),
+    }
+}
+
+pub async fn get_my_sessions(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let claims = claims.0;
+    match SessionRepository::get_active_for_employee(db.get_ref(), claims.sub).await {
+        Ok(sessions) => {
+            let sessions: Vec<SessionResponse> = sessions
+                .into_iter()
+                .map(|session| SessionResponse { current: session.id == claims.sid, session })
+                .collect();
+            HttpResponse::Ok().json(ApiResponse::new(sessions))
+        }
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch sessions".to_string())),
+    }
+}
+
+/// Ends one of the logged-in employee's sessions, e.g. on a lost device.
+pub async fn revoke_my_session(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>, id: web::Path<i32>) -> impl Responder {
+    let session = match SessionRepository::find_by_id(db.get_ref(), id.into_inner()).await {
+        Ok(Some(session)) if session.employee_id == claims.0.sub => session,
+        Ok(_) => return HttpResponse::NotFound().json(ApiError::new("Session not found".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch session".to_string())),
+    };
+
+    match SessionRepository::revoke(db.get_ref(), session.id).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::new("Session revoked".to_string())),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to revoke session".to_string())),
+    }
+}
@@ -0,0 +1,192 @@
+use actix_web::HttpResponse;
+use sea_orm::{ColumnTrait, Condition};
+
+use crate::auth::auth_service::Claims;
+use crate::helper::response::ApiError;
+
+/// The stores whose data a caller may see, derived from their permissions:
+/// `stores:access_all` sees every store, `stores:access_own` everything in the stores they are
+/// assigned to, and anyone else only their own records (orders, shifts, ...) there.
+///
+/// Repository list queries take a scope instead of ad hoc store and employee filters, and
+/// access guards check single records against it, so every endpoint is scoped the same way.
+#[derive(Debug, Clone)]
+pub struct StoreScope {
+    pub employee_id: i32,
+    /// The store the caller is working in, used when a per-store endpoint is called without a store.
+    pub home_store_id: Option<i32>,
+    /// `None` means every store.
+    store_ids: Option<Vec<i32>>,
+    own_records_only: bool,
+}
+
+impl StoreScope {
+    pub fn from_claims(claims: &Claims) -> Self {
+        let all_stores = claims.can_access_all_stores();
+        let store_ids = if all_stores {
+            None
+        } else {
+            let mut ids = claims.store_ids.clone();
+            ids.extend(claims.store_id.filter(|id| !ids.contains(id)));
+            Some(ids)
+        };
+        StoreScope {
+            employee_id: claims.sub,
+            home_store_id: claims.store_id,
+            store_ids,
+            own_records_only: !all_stores && !claims.can_access_own_store(),
+        }
+    }
+
+    pub fn is_all_stores(&self) -> bool {
+        self.store_ids.is_none()
+    }
+
+    /// Whether the caller sees everything in their stores rather than only their own records.
+    pub fn is_store_wide(&self) -> bool {
+        !self.own_records_only
+    }
+
+    /// Store-level data such as the store itself, its inventory or terminals.
+    pub fn includes_store(&self, store_id: i32) -> bool {
+        self.store_ids.as_ref().is_none_or(|ids| ids.contains(&store_id))
+    }
+
+    /// A record made by an employee in a store, such as an order or a shift.
+    pub fn includes_record(&self, store_id: i32, employee_id: i32) -> bool {
+        self.includes_store(store_id) && (!self.own_records_only || employee_id == self.employee_id)
+    }
+
+    /// Narrows the scope to a store requested by the client. `None` if it is out of scope.
+    pub fn restrict_to(&self, store_id: Option<i32>) -> Option<StoreScope> {
+        match store_id {
+            None => Some(self.clone()),
+            Some(id) if self.includes_store(id) => Some(StoreScope { store_ids: Some(vec![id]), ..self.clone() }),
+            Some(_) => None,
+        }
+    }
+
+    /// Picks the single store an endpoint acts on: the requested one if it is in scope,
+    /// otherwise the caller's own store.
+    pub fn resolve_store(&self, requested: Option<i32>) -> Result<i32, HttpResponse> {
+        match requested {
+            Some(store_id) if self.includes_store(store_id) => Ok(store_id),
+            Some(_) => Err(HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string()))),
+            None => self.home_store_id
+                .ok_or_else(|| HttpResponse::BadRequest().json(ApiError::new("store_id is required".to_string()))),
+        }
+    }
+
+    /// Limits a query on a store-owned table to the stores in scope.
+    pub fn store_condition<C: ColumnTrait>(&self, store_column: C) -> Condition {
+        match &self.store_ids {
+            None => Condition::all(),
+            Some(ids) => Condition::all().add(store_column.is_in(ids.clone())),
+        }
+    }
+
+    /// Like `store_condition`, and also to the caller's own rows if they lack store-wide access.
+    pub fn record_condition<C: ColumnTrait>(&self, store_column: C, employee_column: C) -> Condition {
+        let condition = self.store_condition(store_column);
+        if self.own_records_only {
+            condition.add(employee_column.eq(self.employee_id))
+        } else {
+            condition
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use sea_orm::{Condition, DbBackend, EntityTrait, QueryFilter, QueryTrait};
+
+    use super::StoreScope;
+    use crate::auth::auth_service::{Claims, ACCESS_ALL_STORES, ACCESS_OWN_STORE};
+    use crate::entities::{orders, sessions};
+
+    /// An employee working in store 1 and assigned to stores 1 and 2.
+    fn claims(permissions: &[&str]) -> Claims {
+        Claims {
+            sub: 7,
+            email: "jane@example.com".to_string(),
+            role: "Cashier".to_string(),
+            store_id: Some(1),
+            store_ids: vec![1, 2],
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            sid: 1,
+            scope: sessions::SCOPE_FULL.to_string(),
+            ver: 0,
+            iat: 0,
+            exp: 0,
+        }
+    }
+
+    /// The WHERE clause of an orders query filtered by `condition`.
+    fn where_clause(condition: Condition) -> String {
+        let sql = orders::Entity::find().filter(condition).build(DbBackend::MySql).to_string();
+        sql.split_once(" WHERE ").map(|(_, clause)| clause.to_string()).unwrap_or_default()
+    }
+
+    fn store_sql(scope: &StoreScope) -> String {
+        where_clause(scope.store_condition(orders::Column::StoreId))
+    }
+
+    fn record_sql(scope: &StoreScope) -> String {
+        where_clause(scope.record_condition(orders::Column::StoreId, orders::Column::EmployeeId))
+    }
+
+    #[test]
+    fn all_stores_sees_everything() {
+        let scope = StoreScope::from_claims(&claims(&[ACCESS_ALL_STORES]));
+
+        assert!(scope.is_all_stores());
+        assert!(scope.is_store_wide());
+        assert!(scope.includes_store(3));
+        assert!(scope.includes_record(3, 99));
+        assert_eq!(store_sql(&scope), "TRUE");
+        assert_eq!(record_sql(&scope), "TRUE");
+        assert_eq!(scope.resolve_store(Some(3)).unwrap(), 3);
+        assert_eq!(scope.resolve_store(None).unwrap(), 1);
+    }
+
+    #[test]
+    fn own_store_sees_everything_in_assigned_stores() {
+        let scope = StoreScope::from_claims(&claims(&[ACCESS_OWN_STORE]));
+
+        assert!(!scope.is_all_stores());
+        assert!(scope.is_store_wide());
+        assert!(scope.includes_store(2));
+        assert!(!scope.includes_store(3));
+        assert!(scope.includes_record(2, 99));
+        assert_eq!(store_sql(&scope), "`orders`.`store_id` IN (1, 2)");
+        assert_eq!(record_sql(&scope), "`orders`.`store_id` IN (1, 2)");
+        assert_eq!(scope.resolve_store(Some(2)).unwrap(), 2);
+        assert_eq!(scope.resolve_store(Some(3)).unwrap_err().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn own_records_only_sees_own_records_in_assigned_stores() {
+        let scope = StoreScope::from_claims(&claims(&[]));
+
+        assert!(!scope.is_store_wide());
+        assert!(scope.includes_store(1));
+        assert!(!scope.includes_store(3));
+        assert!(scope.includes_record(1, 7));
+        assert!(!scope.includes_record(1, 99));
+        assert!(!scope.includes_record(3, 7));
+        assert_eq!(store_sql(&scope), "`orders`.`store_id` IN (1, 2)");
+        assert_eq!(record_sql(&scope), "`orders`.`store_id` IN (1, 2) AND `orders`.`employee_id` = 7");
+        assert_eq!(scope.resolve_store(None).unwrap(), 1);
+        assert_eq!(scope.resolve_store(Some(3)).unwrap_err().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn resolve_store_needs_a_store_without_a_home_store() {
+        let mut claims = claims(&[ACCESS_ALL_STORES]);
+        claims.store_id = None;
+        let scope = StoreScope::from_claims(&claims);
+
+        assert_eq!(scope.resolve_store(None).unwrap_err().status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,199 @@
+//! RFC 6238 time-based one-time passwords (SHA-1, 6 digits, 30 second steps),
+//! which is what Google Authenticator, Authy and 1Password expect by default.
+
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use qrcode::{render::svg, QrCode};
+use rand::{distributions::Alphanumeric, Rng, RngCore};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+/// Accept codes from one step either side of now to allow for clock drift.
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+const SECRET_BYTES: usize = 20;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+const SECRET_ALPHABET: Alphabet = Alphabet::Rfc4648 { padding: false };
+
+/// Generates a random 160-bit secret, base32 encoded.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32::encode(SECRET_ALPHABET, &bytes)
+}
+
+/// The `otpauth://` URI authenticator apps scan to add the account.
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        STEP_SECONDS
+    )
+}
+
+/// Renders the provisioning URI as an SVG QR code.
+pub fn qr_code_svg(uri: &str) -> Result<String, String> {
+    let code = QrCode::new(uri.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(code.render::<svg::Color>().min_dimensions(200, 200).build())
+}
+
+fn hotp(key: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226 section 5.3)
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([digest[offset] & 0x7f, digest[offset + 1], digest[offset + 2], digest[offset + 3]]);
+    binary % 10u32.pow(DIGITS)
+}
+
+/// Checks a code against the secret at `unix_time`. Returns the matching time step,
+/// which must be greater than `last_step` so an accepted code cannot be replayed.
+pub fn verify(secret: &str, code: &str, unix_time: i64, last_step: Option<i64>) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let key = base32::decode(SECRET_ALPHABET, secret)?;
+
+    let current = unix_time / STEP_SECONDS;
+    (current - ALLOWED_DRIFT_STEPS..=current + ALLOWED_DRIFT_STEPS)
+        .filter(|step| *step >= 0 && last_step.is_none_or(|last| *step > last))
+        .find(|step| hotp(&key, *step as u64) == code)
+}
+
+/// Generates recovery codes formatted as `XXXXX-XXXXX`.
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw: String = (&mut rng)
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(|c| char::from(c).to_ascii_uppercase())
+                .collect();
+            format!("{}-{}", &raw[..5], &raw[5..])
+        })
+        .collect()
+}
+
+/// Hashes a recovery code for storage, ignoring case, spaces and dashes.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    Sha256::digest(normalized.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::test_database;
+    use crate::repository::employee_recovery_codes_repository::RecoveryCodeRepository;
+
+    /// The SHA-1 key of the RFC 6238 test vectors, "12345678901234567890" in ASCII.
+    const RFC_KEY: &[u8] = b"12345678901234567890";
+
+    /// RFC 6238 appendix B: unix time and the 8-digit SHA-1 code for it.
+    const RFC_VECTORS: [(i64, u32); 6] = [
+        (59, 94287082),
+        (1111111109, 7081804),
+        (1111111111, 14050471),
+        (1234567890, 89005924),
+        (2000000000, 69279037),
+        (20000000000, 65353130),
+    ];
+
+    fn rfc_secret() -> String {
+        base32::encode(SECRET_ALPHABET, RFC_KEY)
+    }
+
+    /// A 6-digit code is the last six digits of the 8-digit one, as both truncate the same value.
+    fn six_digits(code: u32) -> String {
+        format!("{:06}", code % 1_000_000)
+    }
+
+    #[test]
+    fn matches_the_rfc_6238_vectors() {
+        for (unix_time, expected) in RFC_VECTORS {
+            assert_eq!(hotp(RFC_KEY, (unix_time / STEP_SECONDS) as u64), expected % 1_000_000, "at {}", unix_time);
+            assert_eq!(verify(&rfc_secret(), &six_digits(expected), unix_time, None), Some(unix_time / STEP_SECONDS));
+        }
+    }
+
+    #[test]
+    fn accepts_one_step_of_drift_either_way() {
+        let (unix_time, expected) = RFC_VECTORS[3];
+        let step = unix_time / STEP_SECONDS;
+        let code = six_digits(expected);
+
+        assert_eq!(verify(&rfc_secret(), &code, unix_time - STEP_SECONDS, None), Some(step));
+        assert_eq!(verify(&rfc_secret(), &code, unix_time + STEP_SECONDS, None), Some(step));
+        assert_eq!(verify(&rfc_secret(), &code, unix_time - 2 * STEP_SECONDS, None), None);
+        assert_eq!(verify(&rfc_secret(), &code, unix_time + 2 * STEP_SECONDS, None), None);
+    }
+
+    #[test]
+    fn rejects_a_code_from_an_accepted_step() {
+        let (unix_time, expected) = RFC_VECTORS[3];
+        let step = unix_time / STEP_SECONDS;
+        let code = six_digits(expected);
+
+        assert_eq!(verify(&rfc_secret(), &code, unix_time, Some(step - 1)), Some(step));
+        assert_eq!(verify(&rfc_secret(), &code, unix_time, Some(step)), None);
+        assert_eq!(verify(&rfc_secret(), &code, unix_time, Some(step + 1)), None);
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        let (unix_time, expected) = RFC_VECTORS[3];
+        assert_eq!(verify(&rfc_secret(), &format!("{:08}", expected), unix_time, None), None);
+        assert_eq!(verify(&rfc_secret(), "12a456", unix_time, None), None);
+        assert_eq!(verify("not base32!", &six_digits(expected), unix_time, None), None);
+    }
+
+    #[test]
+    fn recovery_codes_hash_the_same_however_they_are_typed() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        let code = &codes[0];
+        assert!(code.len() == 11 && code.as_bytes()[5] == b'-');
+
+        let typed = code.to_ascii_lowercase().replace('-', " ");
+        assert_eq!(hash_recovery_code(&typed), hash_recovery_code(code));
+        assert_ne!(hash_recovery_code(&codes[1]), hash_recovery_code(code));
+    }
+
+    #[actix_web::test]
+    async fn consuming_a_recovery_code_only_matches_an_unused_one() {
+        let (db, statements) = test_database::connect(|_| vec![]).await;
+        let hash = hash_recovery_code("ABCDE-12345");
+
+        assert!(RecoveryCodeRepository::consume(&db, 7, &hash).await.unwrap());
+
+        let statements = statements.lock().unwrap();
+        let [update] = statements.as_slice() else { panic!("expected one statement, got {:?}", statements) };
+        assert!(update.sql.starts_with("UPDATE `employee_recovery_codes` SET `used_at` = ?"), "{}", update.sql);
+        assert!(update.sql.ends_with("AND `employee_recovery_codes`.`used_at` IS NULL"), "{}", update.sql);
+    }
+}
@@ -0,0 +1,298 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::auth_handler::{account_locked, complete_login, reject_attempt, LoginSuccessResponse};
+use crate::auth::{auth_service, totp};
+use crate::auth::auth_service::Claims;
+use crate::entities::employees;
+use crate::entities::settings_model::Settings;
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::helper::response::{ApiResponse, ApiError};
+use crate::repository::employee_recovery_codes_repository::RecoveryCodeRepository;
+use crate::repository::employees_repository::EmployeeRepository;
+use crate::repository::settings_repository;
+
+/// Returned by `login` instead of a JWT when a second factor is needed.
+#[derive(Serialize)]
+pub struct TwoFactorChallenge {
+    pub two_factor_required: bool,
+    /// The employee must enroll an authenticator before they can log in.
+    pub setup_required: bool,
+    pub challenge_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ChallengePayload {
+    pub challenge_token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ChallengeCodePayload {
+    pub challenge_token: String,
+    /// A 6-digit authenticator code, or a recovery code where accepted.
+    pub code: String,
+    /// Store to work in, as in the password login.
+    pub store_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct CodePayload {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_code_svg: String,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct EnrolledLogin {
+    #[serde(flatten)]
+    pub login: LoginSuccessResponse,
+    pub recovery_codes: Vec<String>,
+}
+
+pub fn challenge(user: &employees::Model) -> HttpResponse {
+    match auth_service::create_two_factor_challenge(user.id) {
+        Ok(challenge_token) => HttpResponse::Ok().json(ApiResponse::new(TwoFactorChallenge {
+            two_factor_required: true,
+            setup_required: !user.totp_enabled,
+            challenge_token,
+            expires_in: auth_service::TWO_FACTOR_CHALLENGE_MINUTES * 60,
+        })),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create token".to_string())),
+    }
+}
+
+async fn load_settings(db: &DatabaseConnection) -> Result<Settings, HttpResponse> {
+    settings_repository::get_settings(db)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch settings".to_string())))
+}
+
+async fn load_employee(db: &DatabaseConnection, employee_id: i32) -> Result<employees::Model, HttpResponse> {
+    match EmployeeRepository::find_by_id(db, employee_id).await {
+        Ok(Some(employee)) => Ok(employee),
+        Ok(None) => Err(HttpResponse::NotFound().json(ApiError::new("Employee not found".to_string()))),
+        Err(_) => Err(HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee".to_string()))),
+    }
+}
+
+/// The logged-in employee, for self-service changes. PIN sessions on shared terminals
+/// cannot change account security.
+async fn load_own_account(db: &DatabaseConnection, claims: &Claims) -> Result<employees::Model, HttpResponse> {
+    if claims.is_pos_session() {
+        return Err(HttpResponse::Forbidden().json(ApiError::new("Sign in with your password to manage two-factor authentication".to_string())));
+    }
+    load_employee(db, claims.sub).await
+}
+
+/// Resolves the employee behind a challenge token, refusing locked accounts.
+async fn employee_from_challenge(db: &DatabaseConnection, token: &str) -> Result<employees::Model, HttpResponse> {
+    let employee_id = auth_service::decode_two_factor_challenge(token)
+        .map_err(|_| HttpResponse::Unauthorized().json(ApiError::new("Invalid or expired challenge token".to_string())))?;
+    let employee = load_employee(db, employee_id).await?;
+    if let Some(locked_until) = employee.locked_until.filter(|until| *until > Utc::now()) {
+        return Err(account_locked(locked_until));
+    }
+    Ok(employee)
+}
+
+/// Generates and stores a pending secret, returning what the authenticator app needs.
+async fn start_enrollment(db: &DatabaseConnection, employee: employees::Model) -> Result<TotpEnrollment, HttpResponse> {
+    let settings = load_settings(db).await?;
+    let secret = totp::generate_secret();
+    let otpauth_uri = totp::provisioning_uri(&settings.general.site_name, &employee.email, &secret);
+    let qr_code_svg = totp::qr_code_svg(&otpauth_uri)
+        .map_err(|e| HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to render QR code: {}", e))))?;
+
+    EmployeeRepository::set_pending_totp_secret(db, employee, secret.clone())
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to save two-factor secret".to_string())))?;
+
+    Ok(TotpEnrollment { secret, otpauth_uri, qr_code_svg })
+}
+
+/// Checks an authenticator code (or, if allowed, an unused recovery code). Wrong
+/// codes count towards the account lockout like wrong passwords do.
+async fn verify_code(db: &DatabaseConnection, employee: employees::Model, code: &str, allow_recovery: bool) -> Result<employees::Model, HttpResponse> {
+    let Some(secret) = employee.totp_secret.clone() else {
+        return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor authentication has not been set up".to_string())));
+    };
+
+    if let Some(step) = totp::verify(&secret, code, Utc::now().timestamp(), employee.totp_last_step) {
+        return EmployeeRepository::accept_totp_step(db, employee, step)
+            .await
+            .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to update employee".to_string())));
+    }
+
+    if allow_recovery && employee.totp_enabled {
+        match RecoveryCodeRepository::consume(db, employee.id, &totp::hash_recovery_code(code)).await {
+            Ok(true) => return Ok(employee),
+            Ok(false) => {}
+            Err(_) => return Err(HttpResponse::InternalServerError().json(ApiError::new("Failed to check recovery code".to_string()))),
+        }
+    }
+
+    let settings = load_settings(db).await?;
+    Err(reject_attempt(db, employee, &settings, "Invalid verification code").await)
+}
+
+async fn issue_recovery_codes(db: &DatabaseConnection, employee_id: i32) -> Result<Vec<String>, HttpResponse> {
+    let codes = totp::generate_recovery_codes();
+    let hashes = codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+    RecoveryCodeRepository::replace_for_employee(db, employee_id, hashes)
+        .await
+        .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to save recovery codes".to_string())))?;
+    Ok(codes)
+}
+
+/// Second login step for enrolled employees.
+pub async fn verify_login(req: HttpRequest, db: web::Data<DatabaseConnection>, payload: web::Json<ChallengeCodePayload>) -> impl Responder {
+    let result = async {
+        let employee = employee_from_challenge(&db, &payload.challenge_token).await?;
+        if !employee.totp_enabled {
+            return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor setup is required".to_string())));
+        }
+        let employee = verify_code(&db, employee, &payload.code, true).await?;
+        complete_login(&db, employee, payload.store_id, &req).await
+    }
+    .await;
+
+    match result {
+        Ok(login) => HttpResponse::Ok().json(ApiResponse::new(login)),
+        Err(response) => response,
+    }
+}
+
+/// Starts enrollment for an employee who must use 2FA but has not set it up yet.
+pub async fn setup_from_challenge(db: web::Data<DatabaseConnection>, payload: web::Json<ChallengePayload>) -> impl Responder {
+    let result = async {
+        let employee = employee_from_challenge(&db, &payload.challenge_token).await?;
+        if employee.totp_enabled {
+            return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor authentication is already enabled".to_string())));
+        }
+        start_enrollment(&db, employee).await
+    }
+    .await;
+
+    match result {
+        Ok(enrollment) => HttpResponse::Ok().json(ApiResponse::new(enrollment)),
+        Err(response) => response,
+    }
+}
+
+/// Confirms enrollment with the first code and completes the login.
+pub async fn confirm_setup_from_challenge(req: HttpRequest, db: web::Data<DatabaseConnection>, payload: web::Json<ChallengeCodePayload>) -> impl Responder {
+    let result = async {
+        let employee = employee_from_challenge(&db, &payload.challenge_token).await?;
+        if employee.totp_enabled {
+            return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor authentication is already enabled".to_string())));
+        }
+        let employee = verify_code(&db, employee, &payload.code, false).await?;
+        let recovery_codes = issue_recovery_codes(&db, employee.id).await?;
+        let login = complete_login(&db, employee, payload.store_id, &req).await?;
+        Ok(EnrolledLogin { login, recovery_codes })
+    }
+    .await;
+
+    match result {
+        Ok(enrolled) => HttpResponse::Ok().json(ApiResponse::new(enrolled)),
+        Err(response) => response,
+    }
+}
+
+/// Starts enrollment for the logged-in employee.
+pub async fn enroll(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let result = async {
+        let employee = load_own_account(&db, &claims.0).await?;
+        if employee.totp_enabled {
+            return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor authentication is already enabled".to_string())));
+        }
+        start_enrollment(&db, employee).await
+    }
+    .await;
+
+    match result {
+        Ok(enrollment) => HttpResponse::Ok().json(ApiResponse::new(enrollment)),
+        Err(response) => response,
+    }
+}
+
+/// Turns 2FA on for the logged-in employee once they enter a first code.
+pub async fn confirm_enrollment(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>, payload: web::Json<CodePayload>) -> impl Responder {
+    let result = async {
+        let employee = load_own_account(&db, &claims.0).await?;
+        if employee.totp_enabled {
+            return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor authentication is already enabled".to_string())));
+        }
+        let employee = verify_code(&db, employee, &payload.code, false).await?;
+        issue_recovery_codes(&db, employee.id).await
+    }
+    .await;
+
+    match result {
+        Ok(recovery_codes) => HttpResponse::Ok().json(ApiResponse::new(RecoveryCodes { recovery_codes })),
+        Err(response) => response,
+    }
+}
+
+/// Replaces the logged-in employee's recovery codes.
+pub async fn regenerate_recovery_codes(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>, payload: web::Json<CodePayload>) -> impl Responder {
+    let result = async {
+        let employee = load_own_account(&db, &claims.0).await?;
+        if !employee.totp_enabled {
+            return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor authentication is not enabled".to_string())));
+        }
+        let employee = verify_code(&db, employee, &payload.code, false).await?;
+        issue_recovery_codes(&db, employee.id).await
+    }
+    .await;
+
+    match result {
+        Ok(recovery_codes) => HttpResponse::Ok().json(ApiResponse::new(RecoveryCodes { recovery_codes })),
+        Err(response) => response,
+    }
+}
+
+/// Turns 2FA off for the logged-in employee, unless the security policy requires it.
+pub async fn disable(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>, payload: web::Json<CodePayload>) -> impl Responder {
+    let result = async {
+        let employee = load_own_account(&db, &claims.0).await?;
+        if !employee.totp_enabled {
+            return Err(HttpResponse::BadRequest().json(ApiError::new("Two-factor authentication is not enabled".to_string())));
+        }
+        if load_settings(&db).await?.security.requires_2fa(&claims.0.role) {
+            return Err(HttpResponse::Forbidden().json(ApiError::new("Two-factor authentication is required for your role".to_string())));
+        }
+        let employee = verify_code(&db, employee, &payload.code, true).await?;
+        reset(&db, employee)
+            .await
+            .map_err(|_| HttpResponse::InternalServerError().json(ApiError::new("Failed to disable two-factor authentication".to_string())))
+    }
+    .await;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::new("Two-factor authentication disabled".to_string())),
+        Err(response) => response,
+    }
+}
+
+/// Removes an employee's secret and recovery codes.
+pub async fn reset(db: &DatabaseConnection, employee: employees::Model) -> Result<employees::Model, sea_orm::DbErr> {
+    let txn = db.begin().await?;
+    RecoveryCodeRepository::delete_for_employee(&txn, employee.id).await?;
+    let employee = EmployeeRepository::clear_totp(&txn, employee).await?;
+    txn.commit().await?;
+    Ok(employee)
+}
@@ -0,0 +1,58 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "cash_movements")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub shift_id: i32,
+    pub employee_id: i32,
+    pub movement_type: String,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub amount: Decimal,
+    pub reason: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::shifts::Entity",
+        from = "Column::ShiftId",
+        to = "super::shifts::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Cascade"
+    )]
+    Shifts,
+    #[sea_orm(
+        belongs_to = "super::employees::Entity",
+        from = "Column::EmployeeId",
+        to = "super::employees::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Employees,
+}
+
+impl Related<super::shifts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Shifts.def()
+    }
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateCashMovement {
+    /// Either "CASH_IN" or "PAY_OUT".
+    pub movement_type: String,
+    pub amount: Decimal,
+    pub reason: String,
+}
@@ -0,0 +1,59 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A rate within a commission plan: for a category on `PER_CATEGORY` plans, or for sales
+/// from `min_sales` up to the next band on `TIERED` plans.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "commission_plan_rules")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub commission_plan_id: i32,
+    pub category_id: Option<i32>,
+    #[sea_orm(column_type = "Decimal(Some((12, 2)))", nullable)]
+    pub min_sales: Option<Decimal>,
+    /// Percentage of sales.
+    #[sea_orm(column_type = "Decimal(Some((5, 2)))")]
+    pub rate: Decimal,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::commission_plans::Entity",
+        from = "Column::CommissionPlanId",
+        to = "super::commission_plans::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    CommissionPlans,
+    #[sea_orm(
+        belongs_to = "super::categories::Entity",
+        from = "Column::CategoryId",
+        to = "super::categories::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Categories,
+}
+
+impl Related<super::commission_plans::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommissionPlans.def()
+    }
+}
+
+impl Related<super::categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Categories.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CommissionRuleInput {
+    pub category_id: Option<i32>,
+    pub min_sales: Option<Decimal>,
+    pub rate: Decimal,
+}
@@ -0,0 +1,152 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::commission_plan_rules::{self, CommissionRuleInput};
+
+/// `rate` percent of all net sales.
+pub const PERCENT_OF_SALES: &str = "PERCENT_OF_SALES";
+/// Each category's net sales at its rule's rate; categories without a rule use `rate`.
+pub const PER_CATEGORY: &str = "PER_CATEGORY";
+/// Net sales split into bands starting at each rule's `min_sales`, each band paid at its own
+/// rate; sales below the lowest band use `rate`.
+pub const TIERED: &str = "TIERED";
+pub const PLAN_TYPES: [&str; 3] = [PERCENT_OF_SALES, PER_CATEGORY, TIERED];
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "commission_plans")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub name: String,
+    pub plan_type: String,
+    /// Percentage of sales.
+    #[sea_orm(column_type = "Decimal(Some((5, 2)))")]
+    pub rate: Decimal,
+    pub is_active: bool,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::commission_plan_rules::Entity")]
+    CommissionPlanRules,
+    #[sea_orm(has_many = "super::employees::Entity")]
+    Employees,
+}
+
+impl Related<super::commission_plan_rules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommissionPlanRules.def()
+    }
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Commission earned on net sales (after refunds) keyed by category. Nothing is earned on
+    /// a period where refunds outweigh sales.
+    pub fn commission_for(&self, rules: &[commission_plan_rules::Model], sales_by_category: &HashMap<i32, Decimal>) -> Decimal {
+        let hundred = Decimal::ONE_HUNDRED;
+        let net_sales: Decimal = sales_by_category.values().copied().sum();
+        let commission = match self.plan_type.as_str() {
+            PER_CATEGORY => sales_by_category
+                .iter()
+                .map(|(category_id, sales)| {
+                    let rate = rules
+                        .iter()
+                        .find(|r| r.category_id == Some(*category_id))
+                        .map(|r| r.rate)
+                        .unwrap_or(self.rate);
+                    sales * rate / hundred
+                })
+                .sum(),
+            TIERED => {
+                let mut bands: Vec<(Decimal, Decimal)> = rules
+                    .iter()
+                    .filter_map(|r| r.min_sales.map(|min| (min, r.rate)))
+                    .collect();
+                bands.sort_by_key(|(min, _)| *min);
+                let mut commission = Decimal::ZERO;
+                let mut band_start = Decimal::ZERO;
+                let mut band_rate = self.rate;
+                for (min, rate) in bands {
+                    if net_sales <= min {
+                        break;
+                    }
+                    commission += (min - band_start).max(Decimal::ZERO) * band_rate / hundred;
+                    band_start = band_start.max(min);
+                    band_rate = rate;
+                }
+                commission + (net_sales - band_start).max(Decimal::ZERO) * band_rate / hundred
+            }
+            _ => net_sales * self.rate / hundred,
+        };
+        commission.max(Decimal::ZERO).round_dp(2)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateCommissionPlan {
+    pub name: String,
+    pub plan_type: String,
+    #[serde(default)]
+    pub rate: Decimal,
+    pub is_active: Option<bool>,
+    #[serde(default)]
+    pub rules: Vec<CommissionRuleInput>,
+}
+
+/// Fields left out are kept; `rules`, when given, replaces all of the plan's rules.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateCommissionPlan {
+    pub name: Option<String>,
+    pub plan_type: Option<String>,
+    pub rate: Option<Decimal>,
+    pub is_active: Option<bool>,
+    pub rules: Option<Vec<CommissionRuleInput>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommissionPlanDetails {
+    #[serde(flatten)]
+    pub plan: Model,
+    pub rules: Vec<commission_plan_rules::Model>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CommissionReportQueryParams {
+    pub start_date: Date,
+    pub end_date: Date,
+    pub store_id: Option<i32>,
+    pub employee_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmployeeCommission {
+    pub employee_id: i32,
+    pub employee_name: String,
+    pub commission_plan_id: Option<i32>,
+    pub commission_plan_name: Option<String>,
+    pub gross_sales: Decimal,
+    pub refunds: Decimal,
+    pub net_sales: Decimal,
+    pub commission: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommissionReport {
+    pub period_start: Date,
+    pub period_end: Date,
+    pub generated_at: DateTimeUtc,
+    pub employees: Vec<EmployeeCommission>,
+    pub total_commission: Decimal,
+}
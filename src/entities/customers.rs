@@ -15,6 +15,8 @@ pub struct Model {
     pub phone: Option<String>,
     #[sea_orm(column_type = "Text", nullable)]
     pub address: Option<String>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub store_credit_balance: Decimal,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
@@ -23,6 +25,8 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_many = "super::orders::Entity")]
     Orders,
+    #[sea_orm(has_many = "super::refunds::Entity")]
+    Refunds,
 }
 
 impl Related<super::orders::Entity> for Entity {
@@ -31,6 +35,12 @@ impl Related<super::orders::Entity> for Entity {
     }
 }
 
+impl Related<super::refunds::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Refunds.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -0,0 +1,70 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::helper::mailer;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "email_outbox")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub recipient_email: String,
+    pub recipient_name: Option<String>,
+    pub subject: String,
+    #[sea_orm(column_type = "Text")]
+    pub text_body: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub html_body: Option<String>,
+    /// What the email is about, e.g. "ORDER_RECEIPT" or "REFUND_CONFIRMATION".
+    pub category: String,
+    /// Id of the order, refund, ... the email belongs to.
+    pub reference_id: Option<i32>,
+    /// "PENDING", "SENT" or "FAILED".
+    pub status: String,
+    pub attempts: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+    /// When the worker should next try a pending email; cleared once it is sent or gives up.
+    pub next_attempt_at: Option<DateTimeUtc>,
+    pub sent_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Clone)]
+pub struct NewEmail {
+    pub recipient_email: String,
+    pub recipient_name: Option<String>,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+    pub category: String,
+    pub reference_id: Option<i32>,
+}
+
+/// Categories whose bodies carry a live password link; the API never returns their bodies.
+const REDACTED_CATEGORIES: &[&str] = &[mailer::ACCOUNT_SETUP, mailer::PASSWORD_RESET];
+
+#[derive(Debug, Serialize)]
+pub struct EmailResponse {
+    #[serde(flatten)]
+    pub email: Model,
+    /// Whether the bodies were withheld because they contain a password link.
+    pub body_redacted: bool,
+}
+
+impl From<Model> for EmailResponse {
+    fn from(mut email: Model) -> Self {
+        let body_redacted = REDACTED_CATEGORIES.contains(&email.category.as_str());
+        if body_redacted {
+            email.text_body = String::new();
+            email.html_body = None;
+        }
+        EmailResponse { email, body_redacted }
+    }
+}
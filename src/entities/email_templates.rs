@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Admin overrides of the built-in email templates in `helper::mailer`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "email_templates")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub template_key: String,
+    pub subject: String,
+    #[sea_orm(column_type = "Text")]
+    pub text_body: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub html_body: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateEmailTemplate {
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+}
+
+/// A template as the mailer will use it, with the variables it may reference.
+#[derive(Debug, Serialize)]
+pub struct EmailTemplateView {
+    pub template_key: String,
+    pub description: String,
+    pub variables: Vec<String>,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+    /// Whether an admin override is stored; false means the built-in default is in use.
+    pub customized: bool,
+    pub updated_at: Option<DateTimeUtc>,
+}
@@ -0,0 +1,53 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A store an employee is assigned to. An employee may work at several stores and picks
+/// the one they are working in at login (the session's active store).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "employee_stores")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub employee_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub store_id: i32,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::employees::Entity",
+        from = "Column::EmployeeId",
+        to = "super::employees::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Employees,
+    #[sea_orm(
+        belongs_to = "super::stores::Entity",
+        from = "Column::StoreId",
+        to = "super::stores::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Stores,
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl Related<super::stores::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Stores.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Deserialize)]
+pub struct SetEmployeeStores {
+    pub store_ids: Vec<i32>,
+}
@@ -14,16 +14,40 @@ pub struct Model {
     pub email: String,
     pub phone: Option<String>,
     pub store_id: Option<i32>,
-    pub role: String,
     pub password_hash: String,
     pub photo_url: Option<String>,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
     pub role_id: i32,
+    #[serde(skip_serializing)]
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTimeUtc>,
+    /// Base32 TOTP secret; set while enrolling and kept once confirmed.
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    /// Time step of the last accepted code, so a code cannot be replayed.
+    #[serde(skip_serializing)]
+    pub totp_last_step: Option<i64>,
+    /// Argon2 hash of the quick-login PIN used on shared terminals.
+    #[serde(skip_serializing)]
+    pub pin_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub failed_pin_attempts: i32,
+    pub pin_locked_until: Option<DateTimeUtc>,
+    pub commission_plan_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::commission_plans::Entity",
+        from = "Column::CommissionPlanId",
+        to = "super::commission_plans::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    CommissionPlans,
     #[sea_orm(has_many = "super::orders::Entity")]
     Orders,
     #[sea_orm(has_many = "super::purchase_orders::Entity")]
@@ -48,6 +72,12 @@ pub enum Relation {
     Stores,
 }
 
+impl Related<super::commission_plans::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommissionPlans.def()
+    }
+}
+
 impl Related<super::orders::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Orders.def()
@@ -113,6 +143,12 @@ pub struct CreateAdminPayload {
     pub photo_url: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SetCommissionPlan {
+    /// `null` takes the employee off commission.
+    pub commission_plan_id: Option<i32>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct EmployeeResponse {
     pub id: i32,
@@ -124,11 +160,16 @@ pub struct EmployeeResponse {
     pub role: String,
     pub role_id: i32,
     pub photo_url: Option<String>,
+    pub locked_until: Option<DateTimeUtc>,
+    pub totp_enabled: bool,
+    pub has_pin: bool,
+    pub commission_plan_id: Option<i32>,
     pub created_at: DateTimeUtc,
 }
 
-impl From<Model> for EmployeeResponse {
-    fn from(employee: Model) -> Self {
+/// Built from an employee and their role (through `role_id`), whose name is shown as `role`.
+impl From<(Model, Option<super::roles::Model>)> for EmployeeResponse {
+    fn from((employee, role): (Model, Option<super::roles::Model>)) -> Self {
         EmployeeResponse {
             id: employee.id,
             first_name: employee.first_name,
@@ -136,9 +177,13 @@ impl From<Model> for EmployeeResponse {
             email: employee.email,
             phone: employee.phone,
             store_id: employee.store_id,
-            role: employee.role,
+            role: role.map(|r| r.name).unwrap_or_default(),
             role_id: employee.role_id,
             photo_url: employee.photo_url,
+            locked_until: employee.locked_until,
+            totp_enabled: employee.totp_enabled,
+            has_pin: employee.pin_hash.is_some(),
+            commission_plan_id: employee.commission_plan_id,
             created_at: employee.created_at,
         }
     }
@@ -154,14 +199,14 @@ pub struct EmployeeReportData {
     pub store_id: Option<i32>,
 }
 
-impl From<Model> for EmployeeReportData {
-    fn from(employee: Model) -> Self {
+impl From<(Model, Option<super::roles::Model>)> for EmployeeReportData {
+    fn from((employee, role): (Model, Option<super::roles::Model>)) -> Self {
         EmployeeReportData {
             id: employee.id,
             first_name: employee.first_name,
             last_name: employee.last_name,
             email: employee.email,
-            role: employee.role,
+            role: role.map(|r| r.name).unwrap_or_default(),
             store_id: employee.store_id,
         }
     }
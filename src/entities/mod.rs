@@ -22,4 +22,20 @@ pub mod stores;
 pub mod suppliers;
 pub mod settings_model;
 pub mod settings;
+pub mod settings_versions;
+pub mod store_settings;
+pub mod event_log;
 pub mod password_reset_tokens;
+pub mod shifts;
+pub mod cash_movements;
+pub mod terminals;
+pub mod email_outbox;
+pub mod email_templates;
+pub mod employee_recovery_codes;
+pub mod sessions;
+pub mod employee_stores;
+pub mod time_entries;
+pub mod time_entry_breaks;
+pub mod time_entry_edits;
+pub mod commission_plans;
+pub mod commission_plan_rules;
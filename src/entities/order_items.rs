@@ -1,6 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, FromQueryResult};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
@@ -10,6 +10,7 @@ pub struct Model {
     pub id: i32,
     pub order_id: i32,
     pub product_id: i32,
+    pub promotion_id: Option<i32>,
     pub quantity: i32,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub unit_price: Decimal,
@@ -68,3 +69,13 @@ pub struct UpdateOrderItem {
     pub quantity: Option<i32>,
     pub unit_price: Option<Decimal>,
 }
+
+/// A line of a bill, with the name of the product sold (`None` if it has been deleted).
+#[derive(Debug, FromQueryResult)]
+pub struct BillItemRow {
+    pub order_id: i32,
+    pub product_name: Option<String>,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    pub discount_amount: Decimal,
+}
@@ -1,6 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, FromQueryResult};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
@@ -11,6 +11,7 @@ pub struct Model {
     pub customer_id: i32,
     pub employee_id: i32,
     pub store_id: i32,
+    pub terminal_id: Option<i32>,
     pub order_date: DateTimeUtc,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub total_amount: Decimal,
@@ -103,6 +104,8 @@ pub struct CreateOrderPayload {
     pub customer_id: i32,
     pub items: Vec<CreateOrderItemPayload>,
     pub payment_method: String,
+    /// Overrides the `emailReceipts` receipt setting for this sale.
+    pub email_receipt: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -115,6 +118,25 @@ pub struct UpdateOrder {
     pub status: Option<String>,
 }
 
+/// An order as the bills list shows it, with the names of its customer, cashier and store.
+/// The names are `None` when the order has no customer or the row is gone.
+#[derive(Debug, FromQueryResult)]
+pub struct BillRow {
+    pub id: i32,
+    pub customer_name: Option<String>,
+    pub employee_name: Option<String>,
+    pub store_name: Option<String>,
+    pub total_amount: Decimal,
+    pub status: String,
+    pub order_date: DateTimeUtc,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReceiptQueryParams {
+    /// "pdf", "text" or "escpos"; defaults to "text".
+    pub format: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SalesReportQueryParams {
     pub start_date: Option<DateTimeUtc>,
@@ -136,6 +158,33 @@ pub struct EmployeeSalesReport {
     pub employee_id: i32,
     pub employee_name: String,
     pub total_sales_handled: Decimal,
+    pub transactions: u64,
+    pub items_sold: i64,
+    pub items_per_transaction: Decimal,
+    pub average_basket: Decimal,
+    /// Discounts given on this employee's sales, and how many of the items sold were discounted.
+    pub discount_total: Decimal,
+    pub discounted_items: i64,
+    pub refunds_issued: i64,
+    pub refunds_total: Decimal,
+}
+
+impl EmployeeSalesReport {
+    pub fn new(employee_id: i32, employee_name: String) -> Self {
+        EmployeeSalesReport {
+            employee_id,
+            employee_name,
+            total_sales_handled: Decimal::ZERO,
+            transactions: 0,
+            items_sold: 0,
+            items_per_transaction: Decimal::ZERO,
+            average_basket: Decimal::ZERO,
+            discount_total: Decimal::ZERO,
+            discounted_items: 0,
+            refunds_issued: 0,
+            refunds_total: Decimal::ZERO,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -9,6 +9,7 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub order_id: i32,
+    pub terminal_id: Option<i32>,
     pub payment_method: String,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub amount: Decimal,
@@ -41,6 +42,9 @@ impl ActiveModelBehavior for ActiveModel {}
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreatePayment {
     pub order_id: i32,
+    /// Set from the device credential of the till that took the payment.
+    #[serde(skip_deserializing)]
+    pub terminal_id: Option<i32>,
     pub payment_method: String,
     pub amount: Decimal,
     pub payment_date: DateTimeUtc,
@@ -20,3 +20,19 @@ pub use super::roles::Entity as Roles;
 pub use super::stores::Entity as Stores;
 pub use super::suppliers::Entity as Suppliers;
 pub use super::password_reset_tokens::Entity as PasswordResetTokens;
+pub use super::shifts::Entity as Shifts;
+pub use super::cash_movements::Entity as CashMovements;
+pub use super::terminals::Entity as Terminals;
+pub use super::email_outbox::Entity as EmailOutbox;
+pub use super::email_templates::Entity as EmailTemplates;
+pub use super::employee_recovery_codes::Entity as EmployeeRecoveryCodes;
+pub use super::sessions::Entity as Sessions;
+pub use super::employee_stores::Entity as EmployeeStores;
+pub use super::time_entries::Entity as TimeEntries;
+pub use super::time_entry_breaks::Entity as TimeEntryBreaks;
+pub use super::time_entry_edits::Entity as TimeEntryEdits;
+pub use super::commission_plans::Entity as CommissionPlans;
+pub use super::commission_plan_rules::Entity as CommissionPlanRules;
+pub use super::settings_versions::Entity as SettingsVersions;
+pub use super::store_settings::Entity as StoreSettings;
+pub use super::event_log::Entity as EventLog;
@@ -9,7 +9,7 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub refund_id: i32,
-    pub order_item_id: i32,
+    pub order_item_id: Option<i32>,
     pub product_id: i32,
     pub quantity: i32,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
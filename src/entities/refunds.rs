@@ -1,6 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, FromQueryResult};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
@@ -8,18 +8,30 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
-    pub order_id: i32,
+    pub order_id: Option<i32>,
     pub employee_id: i32,
     pub store_id: i32,
     pub reason: String,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub total_amount: Decimal,
+    pub customer_id: Option<i32>,
+    pub refund_method: String,
+    pub is_receiptless: bool,
+    pub approved_by: Option<i32>,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::customers::Entity",
+        from = "Column::CustomerId",
+        to = "super::customers::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Customers,
     #[sea_orm(
         belongs_to = "super::employees::Entity",
         from = "Column::EmployeeId",
@@ -48,6 +60,12 @@ pub enum Relation {
     Stores,
 }
 
+impl Related<super::customers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Customers.def()
+    }
+}
+
 impl Related<super::employees::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Employees.def()
@@ -85,4 +103,47 @@ pub struct CreateRefund {
     pub order_id: i32,
     pub reason: String,
     pub items: Vec<CreateRefundItemPayload>,
+    pub manager_override: Option<ManagerOverride>,
+    /// Overrides the `emailReceipts` receipt setting for this refund.
+    pub email_confirmation: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ManagerOverride {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateReceiptlessRefundItemPayload {
+    pub product_id: Option<i32>,
+    pub barcode: Option<String>,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateReceiptlessRefund {
+    pub customer_id: Option<i32>,
+    pub reason: String,
+    pub items: Vec<CreateReceiptlessRefundItemPayload>,
+    pub manager_override: Option<ManagerOverride>,
+    /// Overrides the `emailReceipts` receipt setting for this refund.
+    pub email_confirmation: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReturnsAbuseReportQueryParams {
+    pub start_date: Option<DateTimeUtc>,
+    pub end_date: Option<DateTimeUtc>,
+    pub store_id: Option<i32>,
+    pub min_returns: Option<i64>,
+}
+
+#[derive(Debug, FromQueryResult, Serialize)]
+pub struct ReturnsAbuseReportRow {
+    pub customer_id: Option<i32>,
+    pub customer_name: Option<String>,
+    pub receiptless_returns: i64,
+    pub overridden_returns: i64,
+    pub total_refunded: Decimal,
 }
@@ -0,0 +1,130 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A logged-in device. Holds the hash of its current refresh token; access tokens
+/// carry the session id and are only accepted while the session is active.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "sessions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub employee_id: i32,
+    #[sea_orm(unique)]
+    #[serde(skip_serializing)]
+    pub refresh_token_hash: String,
+    /// Hash of the token this one replaced; seeing it again means a stolen token was replayed.
+    #[serde(skip_serializing)]
+    pub previous_token_hash: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Bumped to reject the access tokens issued so far, e.g. after a role change.
+    /// Tokens carry the version they were issued at as `ver`.
+    #[serde(skip_serializing)]
+    pub access_version: i32,
+    pub created_at: DateTimeUtc,
+    pub last_used_at: DateTimeUtc,
+    pub expires_at: DateTimeUtc,
+    pub revoked_at: Option<DateTimeUtc>,
+    /// `SCOPE_FULL` for password logins, `SCOPE_POS` for PIN logins on a terminal.
+    pub scope: String,
+    pub terminal_id: Option<i32>,
+    /// The store the employee is working in; access tokens carry it as `store_id`.
+    pub active_store_id: Option<i32>,
+}
+
+pub const SCOPE_FULL: &str = "FULL";
+pub const SCOPE_POS: &str = "POS";
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::employees::Entity",
+        from = "Column::EmployeeId",
+        to = "super::employees::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Employees,
+    #[sea_orm(
+        belongs_to = "super::terminals::Entity",
+        from = "Column::TerminalId",
+        to = "super::terminals::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Terminals,
+    #[sea_orm(
+        belongs_to = "super::stores::Entity",
+        from = "Column::ActiveStoreId",
+        to = "super::stores::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Stores,
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl Related<super::terminals::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Terminals.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn is_active(&self, now: DateTimeUtc) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateSession {
+    pub employee_id: i32,
+    pub refresh_token_hash: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub scope: String,
+    pub terminal_id: Option<i32>,
+    pub active_store_id: Option<i32>,
+    pub expires_at: DateTimeUtc,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenPayload {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwitchStorePayload {
+    pub store_id: i32,
+}
+
+/// A new access token for the same session, e.g. after switching stores.
+#[derive(Debug, Serialize)]
+pub struct AccessToken {
+    pub token: String,
+    /// Seconds until the access token expires.
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub token: String,
+    pub refresh_token: String,
+    /// Seconds until the access token expires.
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub session: Model,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
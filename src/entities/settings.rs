@@ -1,8 +1,6 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::entities::settings_model::Settings as SettingsModel;
-
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "settings")]
 pub struct Model {
@@ -15,9 +13,3 @@ pub struct Model {
 pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
-
-impl From<Json> for SettingsModel {
-    fn from(json: Json) -> Self {
-        serde_json::from_value(json).unwrap_or_default()
-    }
-}
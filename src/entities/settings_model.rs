@@ -1,4 +1,6 @@
+use sea_orm::prelude::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Settings {
@@ -7,6 +9,290 @@ pub struct Settings {
     pub security: SecuritySettings,
     pub integrations: IntegrationsSettings,
     pub email: EmailSettings,
+    #[serde(default)]
+    pub returns: ReturnSettings,
+    #[serde(rename = "timeClock", default)]
+    pub time_clock: TimeClockSettings,
+}
+
+/// Shown by the API in place of a stored secret. Sending it back leaves the secret unchanged.
+pub const SECRET_MASK: &str = "********";
+
+impl Settings {
+    /// Secrets are write-only through the API and encrypted in the database.
+    pub fn secrets_mut(&mut self) -> [&mut String; 2] {
+        [&mut self.email.smtp_password, &mut self.integrations.payment_gateway_api_key]
+    }
+
+    /// A copy safe to send to clients, with every secret that is set replaced by `SECRET_MASK`.
+    pub fn masked(mut self) -> Self {
+        for secret in self.secrets_mut() {
+            if !secret.is_empty() {
+                *secret = SECRET_MASK.to_string();
+            }
+        }
+        self
+    }
+
+    /// Restores secrets the client sent back masked from the current settings.
+    pub fn keep_masked_secrets(&mut self, current: &Settings) {
+        let mut current = current.clone();
+        for (secret, current) in self.secrets_mut().into_iter().zip(current.secrets_mut()) {
+            if secret == SECRET_MASK {
+                *secret = std::mem::take(current);
+            }
+        }
+    }
+
+    /// Takes every secret from `current`, whatever this copy holds. Used when rolling back,
+    /// since version history never stores secrets.
+    pub fn keep_secrets(&mut self, current: &Settings) {
+        let mut current = current.clone();
+        for (secret, current) in self.secrets_mut().into_iter().zip(current.secrets_mut()) {
+            *secret = std::mem::take(current);
+        }
+    }
+
+    /// Reads a stored config, filling in fields added since it was saved from the defaults.
+    /// Unlike a plain deserialize this never falls back to the defaults for the whole
+    /// config: a field that is present but invalid is an error.
+    pub fn from_stored(stored: Value) -> Result<Self, String> {
+        serde_json::from_value(upgrade_config(stored)).map_err(|e| e.to_string())
+    }
+
+    /// Checks every field, returning one error per invalid field.
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, field: &str, message: &str| {
+            if !ok {
+                errors.push(FieldError::new(field, message));
+            }
+        };
+
+        let general = &self.general;
+        check(!general.site_name.trim().is_empty(), "general.siteName", "Site name is required");
+        check(
+            (0.0..=100.0).contains(&general.default_tax_rate),
+            "general.defaultTaxRate",
+            "Tax rate must be between 0 and 100",
+        );
+        check(!general.currency_symbol.trim().is_empty(), "general.currencySymbol", "Currency symbol is required");
+        check(
+            general.currency_code.len() == 3 && general.currency_code.chars().all(|c| c.is_ascii_uppercase()),
+            "general.currencyCode",
+            "Currency code must be a three letter ISO 4217 code",
+        );
+        check(!general.default_date_format.trim().is_empty(), "general.defaultDateFormat", "Date format is required");
+        check(
+            date_pattern(&general.default_date_format).is_some(),
+            "general.defaultDateFormat",
+            "Date format may only use YYYY, YY, MM and DD besides punctuation and spaces",
+        );
+
+        let security = &self.security;
+        check(
+            (1..=1440).contains(&security.session_timeout),
+            "security.sessionTimeout",
+            "Session timeout must be between 1 and 1440 minutes",
+        );
+        check(
+            (4..=128).contains(&security.password_policy.min_length),
+            "security.passwordPolicy.minLength",
+            "Minimum password length must be between 4 and 128",
+        );
+        check(
+            security.account_lockout.max_failed_attempts >= 1,
+            "security.accountLockout.maxFailedAttempts",
+            "Failed attempts before lockout must be at least 1",
+        );
+        check(
+            security.account_lockout.lockout_duration >= 1,
+            "security.accountLockout.lockoutDuration",
+            "Lockout duration must be at least 1 minute",
+        );
+
+        let email = &self.email;
+        check(
+            email.from_email.is_empty() || is_email(&email.from_email),
+            "email.fromEmail",
+            "From email must be a valid email address",
+        );
+        check((1..=65535).contains(&email.smtp_port), "email.smtpPort", "SMTP port must be between 1 and 65535");
+        check(
+            ["STARTTLS", "TLS", "NONE"].contains(&email.smtp_security.as_str()),
+            "email.smtpSecurity",
+            "SMTP security must be STARTTLS, TLS or NONE",
+        );
+
+        let returns = &self.returns;
+        check(returns.return_window_days >= 0, "returns.returnWindowDays", "Return window cannot be negative");
+        check(
+            ["LOWEST_RECENT_PRICE", "CURRENT_PRICE"].contains(&returns.receiptless_value_rule.as_str()),
+            "returns.receiptlessValueRule",
+            "Receiptless value rule must be LOWEST_RECENT_PRICE or CURRENT_PRICE",
+        );
+        check(
+            returns.receiptless_price_lookback_days >= 1,
+            "returns.receiptlessPriceLookbackDays",
+            "Price lookback must be at least 1 day",
+        );
+        check(returns.receiptless_max_amount >= Decimal::ZERO, "returns.receiptlessMaxAmount", "Maximum amount cannot be negative");
+        check(
+            returns.receiptless_max_per_customer >= 0,
+            "returns.receiptlessMaxPerCustomer",
+            "Maximum returns per customer cannot be negative",
+        );
+        check(
+            returns.receiptless_abuse_window_days >= 1,
+            "returns.receiptlessAbuseWindowDays",
+            "Abuse window must be at least 1 day",
+        );
+
+        let time_clock = &self.time_clock;
+        check(
+            (0.0..=24.0).contains(&time_clock.daily_overtime_hours),
+            "timeClock.dailyOvertimeHours",
+            "Daily overtime threshold must be between 0 and 24 hours",
+        );
+        check(
+            (0.0..=168.0).contains(&time_clock.weekly_overtime_hours),
+            "timeClock.weeklyOvertimeHours",
+            "Weekly overtime threshold must be between 0 and 168 hours",
+        );
+        check(
+            time_clock.overtime_multiplier >= 1.0,
+            "timeClock.overtimeMultiplier",
+            "Overtime multiplier must be at least 1",
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.'),
+        None => false,
+    }
+}
+
+/// Top level sections of the config, as named in its JSON. Each can be updated on its own.
+pub const SECTIONS: [&str; 7] = ["general", "receipt", "security", "integrations", "email", "returns", "timeClock"];
+
+/// Sections a store can override; everything else is global.
+pub const STORE_SECTIONS: [&str; 3] = ["general", "receipt", "returns"];
+
+impl Settings {
+    /// These settings with a store's overrides applied. The overrides must only name fields of
+    /// `STORE_SECTIONS`, and the result must be valid.
+    pub fn with_store_overrides(&self, overrides: &Value) -> Result<Settings, Vec<FieldError>> {
+        let Some(sections) = overrides.as_object() else {
+            return Err(vec![FieldError::new("overrides", "Overrides must be a JSON object")]);
+        };
+        let mut config = serde_json::to_value(self)
+            .map_err(|e| vec![FieldError::new("overrides", &e.to_string())])?;
+
+        let mut errors = Vec::new();
+        for (section, fields) in sections {
+            if !STORE_SECTIONS.contains(&section.as_str()) {
+                errors.push(FieldError::new(section, "Only general, receipt and returns settings can be overridden per store"));
+                continue;
+            }
+            let (Some(target), Some(fields)) = (config.get_mut(section).and_then(Value::as_object_mut), fields.as_object()) else {
+                errors.push(FieldError::new(section, "Section must be a JSON object"));
+                continue;
+            };
+            for (field, value) in fields {
+                match target.get_mut(field) {
+                    Some(existing) => *existing = value.clone(),
+                    None => errors.push(FieldError::new(&format!("{}.{}", section, field), "Unknown setting")),
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let settings: Settings = serde_json::from_value(config)
+            .map_err(|e| vec![FieldError::new("overrides", &e.to_string())])?;
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self { field: field.to_string(), message: message.to_string() }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SettingChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Brings a stored config up to the current schema by filling in every field it is missing
+/// from the defaults. Fields it already has are kept as they are.
+pub fn upgrade_config(stored: Value) -> Value {
+    let mut config = serde_json::to_value(Settings::default()).unwrap_or(Value::Null);
+    merge_json(&mut config, stored);
+    config
+}
+
+/// Merges `patch` into `target`: objects are merged key by key, anything else replaces.
+pub fn merge_json(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// Every leaf value that differs between two configs, keyed by its dotted path.
+pub fn diff_config(before: &Value, after: &Value) -> Vec<SettingChange> {
+    let mut changes = Vec::new();
+    diff_values("", Some(before), Some(after), &mut changes);
+    changes
+}
+
+fn diff_values(path: &str, before: Option<&Value>, after: Option<&Value>, changes: &mut Vec<SettingChange>) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                diff_values(&field, before.get(key), after.get(key), changes);
+            }
+        }
+        (before, after) if before != after => changes.push(SettingChange {
+            field: path.to_string(),
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => {}
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -27,6 +313,28 @@ pub struct GeneralSettings {
     pub enable_promotions: bool,
 }
 
+/// Translates a settings date format such as "DD/MM/YYYY" to a chrono pattern, with any
+/// other character kept literally. `None` if it has letters other than the supported tokens.
+pub fn date_pattern(format: &str) -> Option<String> {
+    const TOKENS: [(&str, &str); 4] = [("YYYY", "%Y"), ("YY", "%y"), ("MM", "%m"), ("DD", "%d")];
+    let mut pattern = String::new();
+    let mut rest = format;
+    while let Some(c) = rest.chars().next() {
+        if let Some((token, specifier)) = TOKENS.iter().find(|(token, _)| rest.starts_with(token)) {
+            pattern.push_str(specifier);
+            rest = &rest[token.len()..];
+            continue;
+        }
+        match c {
+            '%' => pattern.push_str("%%"),
+            c if c.is_alphabetic() => return None,
+            c => pattern.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    Some(pattern)
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReceiptSettings {
     #[serde(rename = "headerText")]
@@ -35,20 +343,34 @@ pub struct ReceiptSettings {
     pub footer_text: String,
     #[serde(rename = "showStoreAddress")]
     pub show_store_address: bool,
+    /// Email receipts and refund confirmations to customers who have an email address,
+    /// unless the sale or refund request says otherwise.
+    #[serde(rename = "emailReceipts", default)]
+    pub email_receipts: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SecuritySettings {
     #[serde(rename = "sessionTimeout")]
     pub session_timeout: i32,
+    /// Require two-factor authentication for every employee.
     #[serde(rename = "enable2FA")]
     pub enable_2fa: bool,
+    /// Roles that must use two-factor authentication even when it is not required globally.
+    #[serde(rename = "require2FARoles", default)]
+    pub require_2fa_roles: Vec<String>,
     #[serde(rename = "passwordPolicy")]
     pub password_policy: PasswordPolicy,
     #[serde(rename = "accountLockout")]
     pub account_lockout: AccountLockout,
 }
 
+impl SecuritySettings {
+    pub fn requires_2fa(&self, role: &str) -> bool {
+        self.enable_2fa || self.require_2fa_roles.iter().any(|r| r == role)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PasswordPolicy {
     #[serde(rename = "minLength")]
@@ -71,6 +393,34 @@ pub struct AccountLockout {
     pub lockout_duration: i32,
 }
 
+impl PasswordPolicy {
+    /// Checks a new password against the policy, listing every rule it breaks.
+    pub fn validate(&self, password: &str) -> Result<(), String> {
+        let mut problems = Vec::new();
+        if password.chars().count() < self.min_length.max(0) as usize {
+            problems.push(format!("be at least {} characters long", self.min_length));
+        }
+        if self.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            problems.push("contain an uppercase letter".to_string());
+        }
+        if self.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            problems.push("contain a lowercase letter".to_string());
+        }
+        if self.require_numbers && !password.chars().any(|c| c.is_ascii_digit()) {
+            problems.push("contain a number".to_string());
+        }
+        if self.require_symbols && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+            problems.push("contain a symbol".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Password must {}.", problems.join(", ")))
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IntegrationsSettings {
     #[serde(rename = "paymentGatewayApiKey")]
@@ -91,6 +441,76 @@ pub struct EmailSettings {
     pub smtp_username: String,
     #[serde(rename = "smtpPassword")]
     pub smtp_password: String,
+    /// "STARTTLS", "TLS" (implicit TLS, usually port 465) or "NONE" for a local
+    /// relay such as MailHog. Credentials are only sent when a username is set.
+    #[serde(rename = "smtpSecurity", default = "default_smtp_security")]
+    pub smtp_security: String,
+}
+
+fn default_smtp_security() -> String {
+    "STARTTLS".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReturnSettings {
+    #[serde(rename = "returnWindowDays")]
+    pub return_window_days: i32,
+    #[serde(rename = "allowReceiptlessReturns")]
+    pub allow_receiptless_returns: bool,
+    /// Either "LOWEST_RECENT_PRICE" or "CURRENT_PRICE".
+    #[serde(rename = "receiptlessValueRule")]
+    pub receiptless_value_rule: String,
+    #[serde(rename = "receiptlessPriceLookbackDays")]
+    pub receiptless_price_lookback_days: i32,
+    #[serde(rename = "receiptlessStoreCreditOnly")]
+    pub receiptless_store_credit_only: bool,
+    #[serde(rename = "receiptlessMaxAmount")]
+    pub receiptless_max_amount: Decimal,
+    #[serde(rename = "receiptlessMaxPerCustomer")]
+    pub receiptless_max_per_customer: i32,
+    #[serde(rename = "receiptlessAbuseWindowDays")]
+    pub receiptless_abuse_window_days: i32,
+}
+
+impl Default for ReturnSettings {
+    fn default() -> Self {
+        Self {
+            return_window_days: 30,
+            allow_receiptless_returns: true,
+            receiptless_value_rule: "LOWEST_RECENT_PRICE".to_string(),
+            receiptless_price_lookback_days: 90,
+            receiptless_store_credit_only: true,
+            receiptless_max_amount: Decimal::new(5000, 2),
+            receiptless_max_per_customer: 3,
+            receiptless_abuse_window_days: 90,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeClockSettings {
+    /// Hours in a day after which time counts as overtime; 0 disables daily overtime.
+    #[serde(rename = "dailyOvertimeHours")]
+    pub daily_overtime_hours: f64,
+    /// Regular hours in a week after which time counts as overtime; 0 disables weekly overtime.
+    #[serde(rename = "weeklyOvertimeHours")]
+    pub weekly_overtime_hours: f64,
+    #[serde(rename = "overtimeMultiplier")]
+    pub overtime_multiplier: f64,
+    /// Clock employees in when they sign in on a terminal with their PIN.
+    #[serde(rename = "clockInOnPinLogin")]
+    pub clock_in_on_pin_login: bool,
+}
+
+impl Default for TimeClockSettings {
+    fn default() -> Self {
+        Self {
+            daily_overtime_hours: 8.0,
+            weekly_overtime_hours: 40.0,
+            overtime_multiplier: 1.5,
+            clock_in_on_pin_login: false,
+        }
+    }
 }
 
 impl Default for Settings {
@@ -109,10 +529,12 @@ impl Default for Settings {
                 header_text: "Thank you for your purchase!".to_string(),
                 footer_text: "Please come again.".to_string(),
                 show_store_address: true,
+                email_receipts: false,
             },
             security: SecuritySettings {
                 session_timeout: 30,
                 enable_2fa: false,
+                require_2fa_roles: Vec::new(),
                 password_policy: PasswordPolicy {
                     min_length: 8,
                     require_uppercase: true,
@@ -135,7 +557,28 @@ impl Default for Settings {
                 smtp_port: 587,
                 smtp_username: "".to_string(),
                 smtp_password: "".to_string(),
+                smtp_security: default_smtp_security(),
             },
+            returns: ReturnSettings::default(),
+            time_clock: TimeClockSettings::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::date_pattern;
+
+    #[test]
+    fn date_pattern_translates_tokens_and_escapes_percent() {
+        assert_eq!(date_pattern("DD/MM/YYYY").as_deref(), Some("%d/%m/%Y"));
+        assert_eq!(date_pattern("YY-MM-DD").as_deref(), Some("%y-%m-%d"));
+        assert_eq!(date_pattern("DD%MM").as_deref(), Some("%d%%%m"));
+    }
+
+    #[test]
+    fn date_pattern_rejects_unknown_letters() {
+        assert_eq!(date_pattern("DD MMM YYYY"), None);
+        assert_eq!(date_pattern("%A"), None);
+    }
+}
@@ -0,0 +1,66 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::settings_model::SettingChange;
+
+/// A saved revision of the system settings. Secrets are stored masked, never in any form
+/// that could be read back.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "settings_versions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub version: i32,
+    pub config: Json,
+    pub changed_by: Option<i32>,
+    pub summary: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::employees::Entity",
+        from = "Column::ChangedBy",
+        to = "super::employees::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Employees,
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Serialize)]
+pub struct SettingsVersionSummary {
+    pub version: i32,
+    pub changed_by: Option<i32>,
+    pub summary: String,
+    pub created_at: DateTimeUtc,
+}
+
+impl From<Model> for SettingsVersionSummary {
+    fn from(model: Model) -> Self {
+        Self {
+            version: model.version,
+            changed_by: model.changed_by,
+            summary: model.summary,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// A version with what changed since the one before it.
+#[derive(Debug, Serialize)]
+pub struct SettingsVersionDetails {
+    #[serde(flatten)]
+    pub version: Model,
+    pub changes: Vec<SettingChange>,
+}
@@ -0,0 +1,136 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "shifts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub employee_id: i32,
+    pub store_id: i32,
+    pub terminal_id: Option<i32>,
+    pub terminal_name: String,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub opening_float: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub expected_cash: Option<Decimal>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub counted_cash: Option<Decimal>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub over_short: Option<Decimal>,
+    pub status: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub notes: Option<String>,
+    pub opened_at: DateTimeUtc,
+    pub closed_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::cash_movements::Entity")]
+    CashMovements,
+    #[sea_orm(
+        belongs_to = "super::employees::Entity",
+        from = "Column::EmployeeId",
+        to = "super::employees::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Employees,
+    #[sea_orm(
+        belongs_to = "super::stores::Entity",
+        from = "Column::StoreId",
+        to = "super::stores::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Stores,
+}
+
+impl Related<super::cash_movements::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CashMovements.def()
+    }
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl Related<super::stores::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Stores.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OpenShift {
+    /// Free-text till name; ignored when the request comes from a paired terminal.
+    #[serde(default)]
+    pub terminal_name: String,
+    pub opening_float: Decimal,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CloseShift {
+    pub counted_cash: Decimal,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ShiftQueryParams {
+    pub store_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DrawerReportQueryParams {
+    pub store_id: Option<i32>,
+    pub date: Option<Date>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShiftDetails {
+    #[serde(flatten)]
+    pub shift: Model,
+    pub cash_movements: Vec<super::cash_movements::Model>,
+    /// Cash that should be in the drawer right now (or at close, for closed shifts).
+    pub current_expected_cash: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenderTotal {
+    pub payment_method: String,
+    pub transactions: i64,
+    pub total: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SalesSummary {
+    pub transactions: i64,
+    pub gross_sales: Decimal,
+    pub sales_by_tender: Vec<TenderTotal>,
+    pub refunds_count: i64,
+    pub refunds_total: Decimal,
+    pub discounts_total: Decimal,
+    pub tax_rate: f64,
+    pub tax_total: Decimal,
+    pub net_sales: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DrawerReport {
+    pub report_type: String,
+    pub store_id: i32,
+    pub period_start: DateTimeUtc,
+    pub period_end: DateTimeUtc,
+    pub generated_at: DateTimeUtc,
+    pub summary: SalesSummary,
+    pub shifts: Vec<ShiftDetails>,
+    pub total_over_short: Decimal,
+}
@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::settings_model::Settings as SettingsModel;
+
+/// Fields of the global settings a store overrides, as a partial config such as
+/// `{"receipt": {"headerText": "..."}}`. Only the sections in `STORE_SECTIONS` may appear.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "store_settings")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub store_id: i32,
+    pub overrides: Json,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::stores::Entity",
+        from = "Column::StoreId",
+        to = "super::stores::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Stores,
+}
+
+impl Related<super::stores::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Stores.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Serialize)]
+pub struct StoreSettingsDetails {
+    pub store_id: i32,
+    pub overrides: Json,
+    /// The global settings with the store's overrides applied, secrets masked.
+    pub effective: SettingsModel,
+}
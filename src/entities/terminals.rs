@@ -0,0 +1,79 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "terminals")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub store_id: i32,
+    pub name: String,
+    /// "PENDING" until a device pairs, then "ACTIVE"; "DISABLED" once revoked.
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub credential_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub pairing_code: Option<String>,
+    #[serde(skip_serializing)]
+    pub pairing_code_expires_at: Option<DateTimeUtc>,
+    pub paired_at: Option<DateTimeUtc>,
+    pub last_seen_at: Option<DateTimeUtc>,
+    pub disabled_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::stores::Entity",
+        from = "Column::StoreId",
+        to = "super::stores::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Stores,
+}
+
+impl Related<super::stores::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Stores.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateTerminal {
+    pub store_id: Option<i32>,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateTerminal {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TerminalQueryParams {
+    pub store_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PairTerminal {
+    pub pairing_code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TerminalPairingCode {
+    pub terminal: Model,
+    pub pairing_code: String,
+    pub expires_at: DateTimeUtc,
+}
+
+/// Returned once, when a device completes pairing. The token is never shown again.
+#[derive(Debug, Serialize)]
+pub struct TerminalCredential {
+    pub terminal: Model,
+    pub device_token: String,
+}
@@ -0,0 +1,151 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One clock-in to clock-out stretch of an employee's work at a store.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "time_entries")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub employee_id: i32,
+    pub store_id: i32,
+    pub terminal_id: Option<i32>,
+    pub clock_in_at: DateTimeUtc,
+    pub clock_out_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::time_entry_breaks::Entity")]
+    TimeEntryBreaks,
+    #[sea_orm(has_many = "super::time_entry_edits::Entity")]
+    TimeEntryEdits,
+    #[sea_orm(
+        belongs_to = "super::employees::Entity",
+        from = "Column::EmployeeId",
+        to = "super::employees::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Employees,
+    #[sea_orm(
+        belongs_to = "super::stores::Entity",
+        from = "Column::StoreId",
+        to = "super::stores::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Stores,
+}
+
+impl Related<super::time_entry_breaks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TimeEntryBreaks.def()
+    }
+}
+
+impl Related<super::time_entry_edits::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TimeEntryEdits.def()
+    }
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl Related<super::stores::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Stores.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ClockIn {
+    /// Defaults to the session's active store; ignored when the request comes from a paired terminal.
+    pub store_id: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct StartBreak {
+    #[serde(default)]
+    pub paid: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TimeEntryQueryParams {
+    pub store_id: Option<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BreakInput {
+    pub started_at: DateTimeUtc,
+    pub ended_at: DateTimeUtc,
+    #[serde(default)]
+    pub paid: bool,
+}
+
+/// A manager's correction to a time entry. Fields left out are kept; `breaks`, when given,
+/// replaces all of the entry's breaks.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateTimeEntry {
+    pub reason: String,
+    pub clock_in_at: Option<DateTimeUtc>,
+    pub clock_out_at: Option<DateTimeUtc>,
+    pub breaks: Option<Vec<BreakInput>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeEntryDetails {
+    #[serde(flatten)]
+    pub entry: Model,
+    pub breaks: Vec<super::time_entry_breaks::Model>,
+    /// Hours worked so far, net of unpaid breaks.
+    pub worked_hours: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WeeklyTimesheetQueryParams {
+    pub store_id: Option<i32>,
+    /// Defaults to the Monday of the current week.
+    pub week_start: Option<Date>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyHours {
+    pub date: Date,
+    pub hours: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmployeeTimesheet {
+    pub employee_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub days: Vec<DailyHours>,
+    pub regular_hours: f64,
+    pub overtime_hours: f64,
+    pub total_hours: f64,
+    /// Hours worked at every store that week, which overtime is counted against.
+    pub all_stores_hours: f64,
+    /// Entries still clocked in; their hours are counted up to the time of the report.
+    pub open_entries: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeeklyTimesheet {
+    pub store_id: i32,
+    pub week_start: Date,
+    pub week_end: Date,
+    pub daily_overtime_hours: f64,
+    pub weekly_overtime_hours: f64,
+    pub overtime_multiplier: f64,
+    pub generated_at: DateTimeUtc,
+    pub employees: Vec<EmployeeTimesheet>,
+}
@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "time_entry_breaks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub time_entry_id: i32,
+    pub started_at: DateTimeUtc,
+    pub ended_at: Option<DateTimeUtc>,
+    /// Paid breaks count as worked time.
+    pub paid: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::time_entries::Entity",
+        from = "Column::TimeEntryId",
+        to = "super::time_entries::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    TimeEntries,
+}
+
+impl Related<super::time_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TimeEntries.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
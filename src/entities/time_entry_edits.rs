@@ -0,0 +1,54 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Audit record of a manager's correction to a time entry. The values are JSON snapshots
+/// of the entry and its breaks before and after the edit.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "time_entry_edits")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub time_entry_id: i32,
+    pub edited_by: i32,
+    #[sea_orm(column_type = "Text")]
+    pub reason: String,
+    #[sea_orm(column_type = "Text")]
+    pub previous_values: String,
+    #[sea_orm(column_type = "Text")]
+    pub new_values: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::time_entries::Entity",
+        from = "Column::TimeEntryId",
+        to = "super::time_entries::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    TimeEntries,
+    #[sea_orm(
+        belongs_to = "super::employees::Entity",
+        from = "Column::EditedBy",
+        to = "super::employees::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Employees,
+}
+
+impl Related<super::time_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TimeEntries.def()
+    }
+}
+
+impl Related<super::employees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Employees.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,84 @@
+use actix_web::{dev::Payload, error::InternalError, web, FromRequest, HttpRequest, HttpResponse};
+use std::future::{ready, Ready};
+
+use crate::helper::pagination::{ListFields, ListPlan, DEFAULT_PAGE_SIZE, MAX_PAGE, MAX_PAGE_SIZE};
+use crate::helper::response::ApiError;
+
+/// Paging, sorting and filter parameters of a list request; see `helper::pagination`.
+/// Parameters with an empty value are treated as not given.
+#[derive(Debug, Clone)]
+pub struct ListQuery {
+    pub page: u64,
+    pub size: u64,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub filters: Vec<(String, String)>,
+}
+
+impl ListQuery {
+    fn parse(query_string: &str) -> Result<Self, String> {
+        let pairs = web::Query::<Vec<(String, String)>>::from_query(query_string)
+            .map_err(|e| format!("Invalid query string: {}", e))?
+            .into_inner();
+
+        let mut list = ListQuery { page: 1, size: DEFAULT_PAGE_SIZE, cursor: None, sort: None, filters: vec![] };
+        let mut page_given = false;
+        for (name, value) in pairs.into_iter().filter(|(_, value)| !value.trim().is_empty()) {
+            match name.as_str() {
+                "page" => {
+                    list.page = value
+                        .parse()
+                        .ok()
+                        .filter(|page| (1..=MAX_PAGE).contains(page))
+                        .ok_or_else(|| format!("page must be between 1 and {}", MAX_PAGE))?;
+                    page_given = true;
+                }
+                "size" => {
+                    list.size = value
+                        .parse()
+                        .ok()
+                        .filter(|size| (1..=MAX_PAGE_SIZE).contains(size))
+                        .ok_or_else(|| format!("size must be between 1 and {}", MAX_PAGE_SIZE))?;
+                }
+                "cursor" => list.cursor = Some(value),
+                "sort" => list.sort = Some(value),
+                _ => list.filters.push((name, value)),
+            }
+        }
+        if page_given && list.cursor.is_some() {
+            return Err("Use either page or cursor, not both".to_string());
+        }
+        Ok(list)
+    }
+
+    /// Checks the sort and filters against what the list supports.
+    pub fn resolve(&self, fields: ListFields) -> Result<ListPlan, HttpResponse> {
+        fields.plan(self).map_err(|message| HttpResponse::BadRequest().json(ApiError::new(message)))
+    }
+}
+
+impl FromRequest for ListQuery {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(ListQuery::parse(req.query_string()).map_err(|message| {
+            let response = HttpResponse::BadRequest().json(ApiError::new(message.clone()));
+            InternalError::from_response(message, response).into()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListQuery;
+    use crate::helper::pagination::MAX_PAGE;
+
+    #[test]
+    fn page_is_limited() {
+        assert_eq!(ListQuery::parse(&format!("page={}&size=200", MAX_PAGE)).unwrap().page, MAX_PAGE);
+        assert!(ListQuery::parse(&format!("page={}", MAX_PAGE + 1)).is_err());
+        assert!(ListQuery::parse(&format!("page={}", u64::MAX)).is_err());
+        assert!(ListQuery::parse("page=0").is_err());
+    }
+}
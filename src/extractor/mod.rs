@@ -1,2 +1,4 @@
 pub mod claims_extractor;
-pub mod store_employee_claims_extractor;
+pub mod store_scope_extractor;
+pub mod terminal_extractor;
+pub mod list_query_extractor;
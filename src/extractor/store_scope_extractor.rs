@@ -0,0 +1,20 @@
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::auth::auth_service;
+use crate::auth::store_scope::StoreScope;
+
+/// Extracts the caller's store scope from their access token.
+impl FromRequest for StoreScope {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move {
+            let claims = auth_service::authenticate(&req).await?;
+            Ok(StoreScope::from_claims(&claims))
+        })
+    }
+}
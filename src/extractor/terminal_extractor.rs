@@ -0,0 +1,84 @@
+use actix_web::{
+    dev::Payload,
+    error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized},
+    web, FromRequest, HttpRequest,
+};
+use chrono::{Duration, Utc};
+use std::future::Future;
+use std::pin::Pin;
+use sea_orm::DatabaseConnection;
+
+use crate::auth::auth_service;
+use crate::entities::terminals;
+use crate::repository::terminals_repository::TerminalRepository;
+
+pub const TERMINAL_TOKEN_HEADER: &str = "X-Terminal-Token";
+
+/// How stale `last_seen_at` may get before a request refreshes it.
+const LAST_SEEN_RESOLUTION_SECS: i64 = 60;
+
+/// The paired device a request was made from, if it sent a device credential.
+/// Requests without the header are allowed through with `None` (e.g. the back office);
+/// a header that doesn't match an active terminal is rejected.
+pub struct TerminalExtractor(pub Option<terminals::Model>);
+
+impl TerminalExtractor {
+    pub fn id(&self) -> Option<i32> {
+        self.0.as_ref().map(|t| t.id)
+    }
+
+    /// False if the request came from a terminal of another store than `store_id`.
+    pub fn allowed_in(&self, store_id: i32) -> bool {
+        self.0.as_ref().is_none_or(|t| t.store_id == store_id)
+    }
+}
+
+impl FromRequest for TerminalExtractor {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+
+        Box::pin(async move {
+            let token = match req.headers().get(TERMINAL_TOKEN_HEADER) {
+                Some(header) => header.to_str().map_err(|_| ErrorUnauthorized("Invalid terminal credential"))?.to_string(),
+                None => return Ok(TerminalExtractor(None)),
+            };
+
+            // Device tokens are "<terminal id>.<secret>"
+            let (terminal_id, secret) = token
+                .split_once('.')
+                .and_then(|(id, secret)| id.parse::<i32>().ok().map(|id| (id, secret.to_string())))
+                .ok_or_else(|| ErrorUnauthorized("Invalid terminal credential"))?;
+
+            let db = req.app_data::<web::Data<DatabaseConnection>>()
+                .ok_or_else(|| ErrorInternalServerError("Database connection not found"))?;
+
+            let mut terminal = TerminalRepository::find_by_id(db.get_ref(), terminal_id)
+                .await
+                .map_err(|_| ErrorInternalServerError("Error fetching terminal"))?
+                .ok_or_else(|| ErrorUnauthorized("Invalid terminal credential"))?;
+
+            if terminal.status == "DISABLED" {
+                return Err(ErrorForbidden("Terminal has been disabled"));
+            }
+            let credential_hash = match (&terminal.status[..], &terminal.credential_hash) {
+                ("ACTIVE", Some(hash)) => hash.clone(),
+                _ => return Err(ErrorUnauthorized("Terminal is not paired")),
+            };
+            // The secret is random, so a plain SHA-256 is enough
+            if auth_service::hash_token(&secret) != credential_hash {
+                return Err(ErrorUnauthorized("Invalid terminal credential"));
+            }
+
+            if terminal.last_seen_at.is_none_or(|seen| seen < Utc::now() - Duration::seconds(LAST_SEEN_RESOLUTION_SECS)) {
+                terminal = TerminalRepository::touch_last_seen(db.get_ref(), terminal)
+                    .await
+                    .map_err(|_| ErrorInternalServerError("Error updating terminal"))?;
+            }
+
+            Ok(TerminalExtractor(Some(terminal)))
+        })
+    }
+}
@@ -9,6 +9,8 @@ use std::future::Future;
 
 use crate::entities::employees;
 use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
+use crate::repository::employee_stores_repository::EmployeeStoreRepository;
 use crate::repository::employees_repository::EmployeeRepository;
 use sea_orm::DatabaseConnection;
 
@@ -41,12 +43,18 @@ impl FromRequest for EmployeeAccessGuard {
                 .map_err(|_| ErrorForbidden("Error fetching employee"))?
                 .ok_or_else(|| ErrorNotFound("Employee not found"))?;
 
-            let has_access = if claims.role == "Admin" {
+            let scope = StoreScope::from_claims(&claims);
+            let has_access = if employee.id == claims.sub || scope.is_all_stores() {
                 true
-            } else if claims.role == "StoreManager" {
-                claims.store_id == employee.store_id
+            } else if scope.is_store_wide() {
+                // Managers see anyone working at one of their stores
+                EmployeeStoreRepository::get_store_ids(db.get_ref(), employee.id)
+                    .await
+                    .map_err(|_| ErrorForbidden("Error fetching employee stores"))?
+                    .into_iter()
+                    .any(|store_id| scope.includes_store(store_id))
             } else {
-                employee.id == claims.sub
+                false
             };
 
             if has_access {
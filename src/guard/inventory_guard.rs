@@ -8,6 +8,7 @@ use std::future::Future;
 
 use crate::entities::inventory;
 use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
 use crate::repository::inventory_repository::InventoryRepository;
 use sea_orm::DatabaseConnection;
 
@@ -40,11 +41,7 @@ impl FromRequest for InventoryAccessGuard {
                 .map_err(|_| ErrorForbidden("Error fetching inventory"))?
                 .ok_or_else(|| ErrorNotFound("Inventory not found"))?;
 
-            let has_access = if claims.role == "Admin" {
-                true
-            } else {
-                claims.store_id == Some(inventory.store_id)
-            };
+            let has_access = StoreScope::from_claims(&claims).includes_store(inventory.store_id);
 
             if has_access {
                 Ok(InventoryAccessGuard { inventory })
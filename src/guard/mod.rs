@@ -6,3 +6,6 @@ pub mod order_guard;
 pub mod order_item_guard;
 pub mod payment_guard;
 pub mod product_guard;
+pub mod shift_guard;
+pub mod terminal_guard;
+pub mod time_entry_guard;
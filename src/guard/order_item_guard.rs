@@ -7,6 +7,7 @@ use std::pin::Pin;
 use std::future::Future;
 
 use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
 use crate::entities::{order_items, orders};
 use crate::extractor::claims_extractor::ClaimsExtractor;
 use crate::repository::{order_items_repository::OrderItemRepository};
@@ -49,13 +50,7 @@ impl FromRequest for OrderItemAccessGuard {
                 .map_err(|_| ErrorForbidden("Error fetching associated order"))?
                 .ok_or_else(|| ErrorNotFound("Associated order not found"))?;
 
-            let has_access = if claims.role == "Admin" {
-                true
-            } else if claims.role == "StoreManager" {
-                order.store_id == claims.store_id.unwrap_or(-1)
-            } else {
-                order.employee_id == claims.sub
-            };
+            let has_access = StoreScope::from_claims(&claims).includes_record(order.store_id, order.employee_id);
 
             if has_access {
                 Ok(OrderItemAccessGuard { claims, order_item })
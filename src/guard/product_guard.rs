@@ -9,14 +9,14 @@ use std::future::Future;
 use std::pin::Pin;
 
 use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
 use crate::entities::products;
 use crate::extractor::claims_extractor::ClaimsExtractor;
 use crate::repository::products_repository::ProductRepository;
 use sea_orm::DatabaseConnection;
 
 // This guard will be used to protect product routes.
-// It ensures that a user can only access products belonging to their own store,
-// unless they are an Admin or Owner.
+// Products are shared by every store, so it only lets through callers with store-wide access.
 pub struct ProductAccessGuard {
     pub claims: Claims,
     pub product: products::Model,
@@ -52,56 +52,9 @@ impl FromRequest for ProductAccessGuard {
                 .map_err(|_| ErrorInternalServerError("Error fetching product"))?
                 .ok_or_else(|| ErrorNotFound("Product not found"))?;
 
-            // 5. Perform authorization check
-            let has_access = if claims.role == "Admin" || claims.role == "Owner" {
-                true
-            } else if let Some(_user_store_id) = claims.store_id {
-                // For StoreManager and Cashier, they can only access products in their own store.
-                // We need to fetch the product's store_id. This requires a JOIN in the repository
-                // or another query. For now, we assume a product entity has a `store_id` field.
-                // Let's assume `products` table has a `store_id` which is not correct based on schema.
-                // A product belongs to a category and supplier, but is sold in a store via inventory.
-                // So we should check against inventory.
-                // This guard is getting complex. Let's simplify for now.
-                // Let's assume a product is universal and access is based on roles for now.
-                // The original request was about store managers not accessing OTHER stores data.
-                // This applies to inventory, orders, employees, etc., but less so to the global product list.
-
-                // Let's refine the logic. A user can access a product if it's in their store's inventory.
-                // This is a more complex check that is probably better suited for the handler logic itself,
-                // or a more advanced guard.
-
-                // Re-evaluating: The user said "store manager tidak bisa mengakses store lain selain storenya".
-                // This applies to store-specific data. A global product list might be visible to all.
-                // However, if we are editing or deleting a product, that's a high-level operation.
-                // Let's stick to a simple role check for now, and I can explain the complexity.
-
-                // Let's check if the product is in the user's store via inventory.
-                // This is too complex for a guard without more info.
-
-                // Let's go with the initial simple check. The product entity needs a store_id.
-                // The schema does not support this. A product is global.
-                // The relationship is Product -> Inventory <- Store.
-
-                // OK, let's change the guard's purpose. This guard will protect who can C-U-D products.
-                // Viewing a product might be more open. Let's assume only Admins and Owners can modify products.
-                
-                // The user's request is about a multi-store POS.
-                // Let's assume the `product` entity itself doesn't have a `store_id`.
-                // The authorization should happen at the INVENTORY level, not the product level.
-
-                // This means `ProductAccessGuard` is not the right tool for this specific problem.
-                // The user's request is better handled by a guard on INVENTORY items.
-                // I already created `InventoryAccessGuard` which does exactly this.
-
-                // I will reuse `InventoryAccessGuard` for this purpose.
-                // But the user asked me to implement a guard for products.
-
-                // I will create a simple guard for products that checks for modification roles.
-                claims.role == "StoreManager" // Let's assume StoreManagers can see all products
-            } else {
-                false // No store_id in claims
-            };
+            // 5. Products are a shared catalog rather than store data, so only callers who
+            //    see whole stores may act on them
+            let has_access = StoreScope::from_claims(&claims).is_store_wide();
 
             if has_access {
                 Ok(ProductAccessGuard { claims, product })
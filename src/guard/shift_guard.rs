@@ -0,0 +1,55 @@
+use actix_web::{
+    dev::Payload,
+    error::{ErrorForbidden, ErrorNotFound},
+    web, FromRequest, HttpRequest,
+};
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
+use crate::entities::shifts;
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::repository::shifts_repository::ShiftRepository;
+use sea_orm::DatabaseConnection;
+
+pub struct ShiftAccessGuard {
+    pub claims: Claims,
+    pub shift: shifts::Model,
+}
+
+impl FromRequest for ShiftAccessGuard {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            let claims_extractor = ClaimsExtractor::from_request(&req, &mut payload).await?;
+            let claims = claims_extractor.0;
+
+            let target_id: i32 = req.match_info()
+                .get("id")
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| ErrorNotFound("Missing or invalid shift ID in path"))?;
+
+            let db = req.app_data::<web::Data<DatabaseConnection>>()
+                .ok_or_else(|| ErrorForbidden("Database connection not found"))?;
+
+            let shift = ShiftRepository::find_by_id(db.get_ref(), target_id)
+                .await
+                .map_err(|_| ErrorForbidden("Error fetching shift"))?
+                .ok_or_else(|| ErrorNotFound("Shift not found"))?;
+
+            let has_access = StoreScope::from_claims(&claims).includes_record(shift.store_id, shift.employee_id);
+
+            if has_access {
+                Ok(ShiftAccessGuard { claims, shift })
+            } else {
+                Err(ErrorForbidden("Forbidden: Access denied to this shift resource"))
+            }
+        })
+    }
+}
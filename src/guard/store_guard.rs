@@ -8,6 +8,7 @@ use std::future::Future;
 
 use crate::entities::stores;
 use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
 use crate::repository::stores_repository::StoreRepository;
 use sea_orm::DatabaseConnection;
 
@@ -40,12 +41,7 @@ impl FromRequest for StoreAccessGuard {
                 .map_err(|_| ErrorForbidden("Error fetching store"))?
                 .ok_or_else(|| ErrorNotFound("Store not found"))?;
 
-            let has_access = if claims.role == "Admin" || claims.role == "Owner" {
-                true
-            } else {
-                // Ensure both store_id are Some and match
-                claims.store_id == Some(store.id)
-            };
+            let has_access = StoreScope::from_claims(&claims).includes_store(store.id);
 
             if has_access {
                 Ok(StoreAccessGuard { store })
@@ -0,0 +1,55 @@
+use actix_web::{
+    dev::Payload,
+    error::{ErrorForbidden, ErrorNotFound},
+    web, FromRequest, HttpRequest,
+};
+use std::pin::Pin;
+use std::future::Future;
+
+use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
+use crate::entities::time_entries;
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::repository::time_entries_repository::TimeEntryRepository;
+use sea_orm::DatabaseConnection;
+
+pub struct TimeEntryAccessGuard {
+    pub claims: Claims,
+    pub entry: time_entries::Model,
+}
+
+impl FromRequest for TimeEntryAccessGuard {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            let claims_extractor = ClaimsExtractor::from_request(&req, &mut payload).await?;
+            let claims = claims_extractor.0;
+
+            let target_id: i32 = req.match_info()
+                .get("id")
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| ErrorNotFound("Missing or invalid time entry ID in path"))?;
+
+            let db = req.app_data::<web::Data<DatabaseConnection>>()
+                .ok_or_else(|| ErrorForbidden("Database connection not found"))?;
+
+            let entry = TimeEntryRepository::find_by_id(db.get_ref(), target_id)
+                .await
+                .map_err(|_| ErrorForbidden("Error fetching time entry"))?
+                .ok_or_else(|| ErrorNotFound("Time entry not found"))?;
+
+            let has_access = StoreScope::from_claims(&claims).includes_record(entry.store_id, entry.employee_id);
+
+            if has_access {
+                Ok(TimeEntryAccessGuard { claims, entry })
+            } else {
+                Err(ErrorForbidden("Forbidden: Access denied to this time entry"))
+            }
+        })
+    }
+}
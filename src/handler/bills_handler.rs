@@ -1,20 +1,21 @@
 use actix_web::{web, HttpResponse, Responder};
-use sea_orm::{DatabaseConnection, EntityTrait, QuerySelect, ColumnTrait, QueryFilter};
-use crate::entities::{orders, customers, employees, stores, order_items, products};
-use crate::helper::response::{ApiResponse, ApiError};
-use crate::auth::auth_service::Claims;
-use sea_orm::prelude::DateTimeUtc;
+use sea_orm::DatabaseConnection;
+use sea_orm::prelude::{DateTimeUtc, Decimal};
+use crate::helper::response::{ApiPage, ApiError};
+use crate::auth::store_scope::StoreScope;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::repository::orders_repository::OrderRepository;
+use crate::repository::order_items_repository::OrderItemRepository;
 use serde::Serialize;
-use std::str::FromStr;
-use sea_orm::sea_query::Expr;
+use std::collections::HashMap;
 
 #[derive(Serialize)]
 pub struct BillItemResponse {
     pub product_name: String,
     pub quantity: i32,
-    pub unit_price: f64,
-    pub discount_amount: f64,
-    pub total: f64,
+    pub unit_price: Decimal,
+    pub discount_amount: Decimal,
+    pub total: Decimal,
 }
 
 #[derive(Serialize)]
@@ -23,104 +24,127 @@ pub struct BillResponse {
     pub customer_name: String,
     pub employee_name: String,
     pub store_name: String,
-    pub total_amount: f64,
+    pub total_amount: Decimal,
     pub status: String,
     pub order_date: DateTimeUtc,
     pub items: Vec<BillItemResponse>,
 }
 
-pub async fn get_all_bills(db: web::Data<DatabaseConnection>, claims: web::ReqData<Claims>) -> impl Responder {
-    // Only Admin and Owner can access this endpoint
-    if claims.role != "Admin" && claims.role != "Owner" {
-        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
+/// A page of bills takes three queries whatever its size: the count, the orders with their
+/// names, and the items of all of them.
+pub async fn get_all_bills(db: web::Data<DatabaseConnection>, scope: StoreScope, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(OrderRepository::bill_list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    let bills = match OrderRepository::get_bills(db.get_ref(), &scope, &plan).await {
+        Ok(bills) => bills,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch bills".to_string())),
+    };
+
+    let order_ids: Vec<i32> = bills.items.iter().map(|bill| bill.id).collect();
+    let rows = if order_ids.is_empty() {
+        Vec::new()
+    } else {
+        match OrderItemRepository::find_bill_items(db.get_ref(), order_ids).await {
+            Ok(rows) => rows,
+            Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch bill items".to_string())),
+        }
+    };
+    let mut items: HashMap<i32, Vec<BillItemResponse>> = HashMap::new();
+    for row in rows {
+        items.entry(row.order_id).or_default().push(BillItemResponse {
+            product_name: row.product_name.unwrap_or_else(|| "N/A".to_string()),
+            quantity: row.quantity,
+            unit_price: row.unit_price,
+            discount_amount: row.discount_amount,
+            total: row.unit_price * Decimal::from(row.quantity) - row.discount_amount,
+        });
     }
 
-    let orders_result = orders::Entity::find()
-        .left_join(customers::Entity)
-        .left_join(employees::Entity)
-        .left_join(stores::Entity)
-        .select_only()
-        .column(orders::Column::Id)
-        .column(orders::Column::TotalAmount)
-        .column(orders::Column::Status)
-        .column(orders::Column::OrderDate)
-        .column_as(
-            Expr::cust_with_exprs(
-                "CONCAT(?, ' ', ?)",
-                [
-                    Expr::col((customers::Entity, customers::Column::FirstName)).into(),
-                    Expr::col((customers::Entity, customers::Column::LastName)).into(),
-                ],
-            ),
-            "customer_name",
-        )
-        .column_as(
-            Expr::cust_with_exprs(
-                "CONCAT(?, ' ', ?)",
-                [
-                    Expr::col((employees::Entity, employees::Column::FirstName)).into(),
-                    Expr::col((employees::Entity, employees::Column::LastName)).into(),
-                ],
-            ),
-            "employee_name",
-        )
-        .column_as(stores::Column::Name, "store_name")
-        .into_json()
-        .all(db.get_ref())
-        .await;
+    let bills = bills.map(|bill| BillResponse {
+        items: items.remove(&bill.id).unwrap_or_default(),
+        id: bill.id,
+        customer_name: bill.customer_name.unwrap_or_else(|| "N/A".to_string()),
+        employee_name: bill.employee_name.unwrap_or_else(|| "N/A".to_string()),
+        store_name: bill.store_name.unwrap_or_else(|| "N/A".to_string()),
+        total_amount: bill.total_amount,
+        status: bill.status,
+        order_date: bill.order_date,
+    });
+    HttpResponse::Ok().json(ApiPage::new(bills))
+}
 
-    match orders_result {
-        Ok(orders_json) => {
-            let mut bills_response: Vec<BillResponse> = Vec::new();
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
 
-            for order_json in orders_json {
-                let order_id = order_json["id"].as_i64().unwrap() as i32;
+    use actix_web::{test, web, App};
+    use chrono::Utc;
+    use sea_orm::prelude::Decimal;
+    use sea_orm::{ProxyRow, Value};
 
-                let items_result = order_items::Entity::find()
-                    .filter(order_items::Column::OrderId.eq(order_id))
-                    .left_join(products::Entity)
-                    .select_only()
-                    .column_as(products::Column::Name, "product_name")
-                    .column(order_items::Column::Quantity)
-                    .column(order_items::Column::UnitPrice)
-                    .column(order_items::Column::DiscountAmount)
-                    .into_json()
-                    .all(db.get_ref())
-                    .await;
+    use crate::helper::test_database;
+    use crate::routes::bills_routes;
 
-                let items_response = match items_result {
-                    Ok(items_json) => {
-                        items_json.into_iter().map(|item_json| {
-                            let quantity = item_json["quantity"].as_i64().unwrap() as i32;
-                            let unit_price = item_json["unit_price"].as_f64().unwrap();
-                            let discount_amount = item_json["discount_amount"].as_f64().unwrap_or(0.0);
+    const ORDERS: i32 = 5000;
+    const ITEMS_PER_ORDER: usize = 3;
 
-                            BillItemResponse {
-                                product_name: item_json["product_name"].as_str().unwrap().to_string(),
-                                quantity,
-                                unit_price,
-                                discount_amount,
-                                total: (quantity as f64 * unit_price) - discount_amount,
-                            }
-                        }).collect()
-                    },
-                    Err(_) => Vec::new(), // Handle error or return empty items
-                };
+    fn bill(id: i32) -> ProxyRow {
+        ProxyRow::new(BTreeMap::from([
+            ("id".to_string(), Value::from(id)),
+            ("customer_name".to_string(), Value::from("Jane Doe")),
+            ("employee_name".to_string(), Value::from("John Roe")),
+            ("store_name".to_string(), Value::from("Main Store")),
+            ("total_amount".to_string(), Value::from(Decimal::new(1250, 2))),
+            ("status".to_string(), Value::from("COMPLETED")),
+            ("order_date".to_string(), Value::from(Utc::now())),
+        ]))
+    }
 
-                bills_response.push(BillResponse {
-                    id: order_json["id"].as_i64().unwrap() as i32,
-                    customer_name: order_json["customer_name"].as_str().unwrap_or("N/A").to_string(),
-                    employee_name: order_json["employee_name"].as_str().unwrap_or("N/A").to_string(),
-                    store_name: order_json["store_name"].as_str().unwrap_or("N/A").to_string(),
-                    total_amount: order_json["total_amount"].as_f64().unwrap(),
-                    status: order_json["status"].as_str().unwrap().to_string(),
-                    order_date: DateTimeUtc::from_str(order_json["order_date"].as_str().unwrap()).unwrap(),
-                    items: items_response,
-                });
-            }
-            HttpResponse::Ok().json(ApiResponse::new(bills_response))
-        },
-        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch bills".to_string())),
+    fn item(order_id: i32) -> ProxyRow {
+        ProxyRow::new(BTreeMap::from([
+            ("order_id".to_string(), Value::from(order_id)),
+            ("product_name".to_string(), Value::from("Green Tea")),
+            ("quantity".to_string(), Value::from(2)),
+            ("unit_price".to_string(), Value::from(Decimal::new(500, 2))),
+            ("discount_amount".to_string(), Value::from(Decimal::ZERO)),
+        ]))
     }
-}
 
+    /// Loads pages of bills from thousands of orders, each with several items, and checks
+    /// the number of queries does not grow with the page: a count, the orders, their items.
+    #[actix_web::test]
+    async fn a_page_of_bills_takes_three_queries() {
+        for size in [20, 200] {
+            let (db, statements) = test_database::connect(move |statement| {
+                if test_database::is_session_lookup(statement) {
+                    vec![test_database::session()]
+                } else if statement.sql.starts_with("SELECT COUNT(*)") {
+                    vec![test_database::count_row(ORDERS)]
+                } else if statement.sql.contains("FROM `order_items`") {
+                    let order_ids = test_database::int_values(statement);
+                    order_ids.into_iter().flat_map(|id| std::iter::repeat_n(item(id), ITEMS_PER_ORDER)).collect()
+                } else {
+                    (1..=size).map(bill).collect()
+                }
+            })
+            .await;
+            let app = test::init_service(App::new().app_data(web::Data::new(db)).configure(bills_routes::configure_routes)).await;
+
+            let token = test_database::access_token(&["orders:read", "stores:access_all"]);
+            let request = test::TestRequest::get()
+                .uri(&format!("/admin/bills?page=3&size={}", size))
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+
+            let bills = body["data"].as_array().unwrap();
+            assert_eq!(bills.len(), size as usize);
+            assert!(bills.iter().all(|bill| bill["items"].as_array().unwrap().len() == ITEMS_PER_ORDER));
+            assert_eq!(body["pagination"]["total"], ORDERS);
+            let queries = statements.lock().unwrap().iter().filter(|statement| !test_database::is_session_lookup(statement)).count();
+            assert_eq!(queries, 3, "queries for a page of {} bills", size);
+        }
+    }
+}
@@ -1,16 +1,23 @@
 use crate::repository::categories_repository::CategoryRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 use crate::entities::categories::{CreateCategory, UpdateCategory};
-use sea_orm::DatabaseConnection;
+use crate::entities::products;
+use crate::search::SearchIndex;
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection};
 // use crate::guard::role_guard::{Claims, has_role, ErrorResponse as RoleErrorResponse};
 
-pub async fn get_all_categories(db: web::Data<DatabaseConnection>) -> impl Responder {
+pub async fn get_all_categories(db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
     // if !has_role(&claims, &["Admin", "Owner"]) {
     //     return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
     // }
-    match CategoryRepository::get_all(db.get_ref()).await {
-        Ok(categories) => HttpResponse::Ok().json(ApiResponse::new(categories)),
+    let plan = match list.resolve(CategoryRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match CategoryRepository::get_all(db.get_ref(), &plan).await {
+        Ok(categories) => HttpResponse::Ok().json(ApiPage::new(categories)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch categories".to_string())),
     }
 }
@@ -36,12 +43,16 @@ pub async fn get_category_by_id(db: web::Data<DatabaseConnection>, id: web::Path
     }
 }
 
-pub async fn update_category(db: web::Data<DatabaseConnection>, id: web::Path<i32>, update_data: web::Json<UpdateCategory>) -> impl Responder {
+pub async fn update_category(db: web::Data<DatabaseConnection>, search: web::Data<SearchIndex>, id: web::Path<i32>, update_data: web::Json<UpdateCategory>) -> impl Responder {
     // if !has_role(&claims, &["Admin"]) {
     //     return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Only Admin can update categories.".to_string()));
     // }
     match CategoryRepository::update(db.get_ref(), id.into_inner(), update_data.into_inner()).await {
-        Ok(Some(category)) => HttpResponse::Ok().json(ApiResponse::new(category)),
+        Ok(Some(category)) => {
+            // Products are found by their category's name
+            search.products_changed(db.get_ref(), Condition::all().add(products::Column::CategoryId.eq(category.id))).await;
+            HttpResponse::Ok().json(ApiResponse::new(category))
+        }
         Ok(None) => HttpResponse::NotFound().json(ApiError::new("Category not found".to_string())),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update category".to_string())),
     }
@@ -0,0 +1,339 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Duration;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, TransactionTrait};
+use sea_orm::prelude::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{categories, commission_plans, employees};
+use crate::entities::commission_plan_rules::{self, CommissionRuleInput};
+use crate::entities::commission_plans::{
+    CommissionPlanDetails, CommissionReport, CommissionReportQueryParams, CreateCommissionPlan,
+    EmployeeCommission, UpdateCommissionPlan, PER_CATEGORY, PERCENT_OF_SALES, PLAN_TYPES, TIERED,
+};
+use crate::entities::employees::SetCommissionPlan;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::guard::employee_guard::EmployeeAccessGuard;
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::repository::commissions_repository::CommissionRepository;
+
+fn valid_rate(rate: Decimal) -> bool {
+    rate >= Decimal::ZERO && rate <= Decimal::ONE_HUNDRED
+}
+
+/// Checks that the rules fit the plan type; category ids are checked against the database separately.
+fn validate_plan(plan_type: &str, rate: Decimal, rules: &[CommissionRuleInput]) -> Result<(), String> {
+    if !PLAN_TYPES.contains(&plan_type) {
+        return Err(format!("plan_type must be one of {}", PLAN_TYPES.join(", ")));
+    }
+    if !valid_rate(rate) || rules.iter().any(|r| !valid_rate(r.rate)) {
+        return Err("Rates must be percentages between 0 and 100".to_string());
+    }
+    match plan_type {
+        PERCENT_OF_SALES if !rules.is_empty() => Err("PERCENT_OF_SALES plans take no rules".to_string()),
+        PER_CATEGORY => {
+            if rules.iter().any(|r| r.category_id.is_none() || r.min_sales.is_some()) {
+                return Err("PER_CATEGORY rules need a category_id and no min_sales".to_string());
+            }
+            let mut category_ids: Vec<i32> = rules.iter().filter_map(|r| r.category_id).collect();
+            category_ids.sort();
+            category_ids.dedup();
+            if category_ids.len() != rules.len() {
+                return Err("Each category can only have one rule".to_string());
+            }
+            Ok(())
+        }
+        TIERED => {
+            if rules.iter().any(|r| r.category_id.is_some() || r.min_sales.is_none_or(|min| min <= Decimal::ZERO)) {
+                return Err("TIERED rules need a min_sales above zero and no category_id".to_string());
+            }
+            let mut thresholds: Vec<Decimal> = rules.iter().filter_map(|r| r.min_sales).collect();
+            thresholds.sort();
+            thresholds.dedup();
+            if thresholds.len() != rules.len() {
+                return Err("Each min_sales can only have one rule".to_string());
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn categories_exist(db: &DatabaseConnection, rules: &[CommissionRuleInput]) -> Result<bool, DbErr> {
+    let category_ids: Vec<i32> = rules.iter().filter_map(|r| r.category_id).collect();
+    if category_ids.is_empty() {
+        return Ok(true);
+    }
+    let found = categories::Entity::find()
+        .filter(categories::Column::Id.is_in(category_ids.clone()))
+        .count(db)
+        .await?;
+    Ok(found == category_ids.len() as u64)
+}
+
+fn as_input(rules: &[commission_plan_rules::Model]) -> Vec<CommissionRuleInput> {
+    rules
+        .iter()
+        .map(|r| CommissionRuleInput { category_id: r.category_id, min_sales: r.min_sales, rate: r.rate })
+        .collect()
+}
+
+pub async fn get_all_commission_plans(db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
+    let list_plan = match list.resolve(CommissionRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    let result = async {
+        let plans = CommissionRepository::get_all(db.get_ref(), &list_plan).await?;
+        let mut rules_by_plan: HashMap<i32, Vec<commission_plan_rules::Model>> = HashMap::new();
+        for rule in CommissionRepository::get_rules(db.get_ref(), plans.items.iter().map(|p| p.id).collect()).await? {
+            rules_by_plan.entry(rule.commission_plan_id).or_default().push(rule);
+        }
+        Ok::<_, DbErr>(plans.map(|plan| {
+            let rules = rules_by_plan.remove(&plan.id).unwrap_or_default();
+            CommissionPlanDetails { plan, rules }
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(plans) => HttpResponse::Ok().json(ApiPage::new(plans)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch commission plans: {}", e))),
+    }
+}
+
+pub async fn get_commission_plan_by_id(db: web::Data<DatabaseConnection>, path: web::Path<i32>) -> impl Responder {
+    let id = path.into_inner();
+    let plan = match CommissionRepository::find_by_id(db.get_ref(), id).await {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new(format!("Commission plan with ID {} not found", id))),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch commission plan: {}", e))),
+    };
+
+    match CommissionRepository::get_rules(db.get_ref(), vec![plan.id]).await {
+        Ok(rules) => HttpResponse::Ok().json(ApiResponse::new(CommissionPlanDetails { plan, rules })),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch commission plan rules: {}", e))),
+    }
+}
+
+pub async fn create_commission_plan(db: web::Data<DatabaseConnection>, payload: web::Json<CreateCommissionPlan>) -> impl Responder {
+    let payload = payload.into_inner();
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new("name is required".to_string()));
+    }
+    if let Err(message) = validate_plan(&payload.plan_type, payload.rate, &payload.rules) {
+        return HttpResponse::BadRequest().json(ApiError::new(message));
+    }
+    match categories_exist(db.get_ref(), &payload.rules).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::BadRequest().json(ApiError::new("One or more categories do not exist".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check categories: {}", e))),
+    }
+    match CommissionRepository::find_by_name(db.get_ref(), &name).await {
+        Ok(Some(_)) => return HttpResponse::Conflict().json(ApiError::new("A commission plan with this name already exists".to_string())),
+        Ok(None) => {}
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check commission plans: {}", e))),
+    }
+
+    let result = async {
+        let txn = db.begin().await?;
+        let plan = CommissionRepository::create(&txn, name, payload.plan_type, payload.rate, payload.is_active.unwrap_or(true)).await?;
+        let rules = CommissionRepository::replace_rules(&txn, plan.id, payload.rules).await?;
+        txn.commit().await?;
+        Ok::<_, DbErr>(CommissionPlanDetails { plan, rules })
+    }
+    .await;
+
+    match result {
+        Ok(details) => HttpResponse::Created().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create commission plan: {}", e))),
+    }
+}
+
+pub async fn update_commission_plan(
+    db: web::Data<DatabaseConnection>,
+    path: web::Path<i32>,
+    payload: web::Json<UpdateCommissionPlan>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let payload = payload.into_inner();
+    let plan = match CommissionRepository::find_by_id(db.get_ref(), id).await {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new(format!("Commission plan with ID {} not found", id))),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch commission plan: {}", e))),
+    };
+    let current_rules = match CommissionRepository::get_rules(db.get_ref(), vec![plan.id]).await {
+        Ok(rules) => rules,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch commission plan rules: {}", e))),
+    };
+
+    let name = payload.name.map(|n| n.trim().to_string()).unwrap_or_else(|| plan.name.clone());
+    if name.is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new("name cannot be empty".to_string()));
+    }
+    let plan_type = payload.plan_type.unwrap_or_else(|| plan.plan_type.clone());
+    let rate = payload.rate.unwrap_or(plan.rate);
+    let is_active = payload.is_active.unwrap_or(plan.is_active);
+    // Rules are kept when not given, so they must still fit a changed plan type
+    let rules = payload.rules.unwrap_or_else(|| as_input(&current_rules));
+    if let Err(message) = validate_plan(&plan_type, rate, &rules) {
+        return HttpResponse::BadRequest().json(ApiError::new(message));
+    }
+    match categories_exist(db.get_ref(), &rules).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::BadRequest().json(ApiError::new("One or more categories do not exist".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check categories: {}", e))),
+    }
+    if name != plan.name {
+        match CommissionRepository::find_by_name(db.get_ref(), &name).await {
+            Ok(Some(_)) => return HttpResponse::Conflict().json(ApiError::new("A commission plan with this name already exists".to_string())),
+            Ok(None) => {}
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check commission plans: {}", e))),
+        }
+    }
+
+    let result = async {
+        let txn = db.begin().await?;
+        let plan = CommissionRepository::update(&txn, plan, name, plan_type, rate, is_active).await?;
+        let rules = CommissionRepository::replace_rules(&txn, plan.id, rules).await?;
+        txn.commit().await?;
+        Ok::<_, DbErr>(CommissionPlanDetails { plan, rules })
+    }
+    .await;
+
+    match result {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to update commission plan: {}", e))),
+    }
+}
+
+/// Deletes a plan; employees on it are taken off commission.
+pub async fn delete_commission_plan(db: web::Data<DatabaseConnection>, path: web::Path<i32>) -> impl Responder {
+    let id = path.into_inner();
+    match CommissionRepository::delete(db.get_ref(), id).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            HttpResponse::Ok().json(ApiResponse::new(format!("Commission plan with ID {} deleted successfully", id)))
+        }
+        Ok(_) => HttpResponse::NotFound().json(ApiError::new(format!("Commission plan with ID {} not found", id))),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to delete commission plan: {}", e))),
+    }
+}
+
+pub async fn set_employee_commission_plan(
+    guard: EmployeeAccessGuard,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<SetCommissionPlan>,
+) -> impl Responder {
+    if let Some(plan_id) = payload.commission_plan_id {
+        match CommissionRepository::find_by_id(db.get_ref(), plan_id).await {
+            Ok(Some(plan)) if plan.is_active => {}
+            Ok(Some(_)) => return HttpResponse::BadRequest().json(ApiError::new("Commission plan is not active".to_string())),
+            Ok(None) => return HttpResponse::BadRequest().json(ApiError::new(format!("Commission plan with ID {} not found", plan_id))),
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch commission plan: {}", e))),
+        }
+    }
+
+    match CommissionRepository::set_employee_plan(db.get_ref(), guard.employee, payload.commission_plan_id).await {
+        Ok(employee) => HttpResponse::Ok().json(ApiResponse::new(SetCommissionPlan { commission_plan_id: employee.commission_plan_id })),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to set commission plan: {}", e))),
+    }
+}
+
+async fn build_commission_report(
+    db: &DatabaseConnection,
+    scope: &StoreScope,
+    params: &CommissionReportQueryParams,
+) -> Result<CommissionReport, DbErr> {
+    let from = params.start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let to = params.end_date.and_hms_opt(0, 0, 0).unwrap().and_utc() + Duration::days(1) - Duration::seconds(1);
+
+    let sales = CommissionRepository::get_sales_by_employee_and_category(db, scope, from, to).await?;
+    let refunds = CommissionRepository::get_refunds_by_employee_and_category(db, scope, from, to).await?;
+
+    // employee -> (gross sales, refunds, net sales by category)
+    let mut totals: BTreeMap<i32, (Decimal, Decimal, HashMap<i32, Decimal>)> = BTreeMap::new();
+    for (employee_id, category_id, amount) in sales {
+        let (gross, _, by_category) = totals.entry(employee_id).or_default();
+        *gross += amount;
+        *by_category.entry(category_id).or_default() += amount;
+    }
+    for (employee_id, category_id, amount) in refunds {
+        let (_, refunded, by_category) = totals.entry(employee_id).or_default();
+        *refunded += amount;
+        *by_category.entry(category_id).or_default() -= amount;
+    }
+    if let Some(employee_id) = params.employee_id {
+        totals.retain(|id, _| *id == employee_id);
+    }
+
+    let employees = employees::Entity::find()
+        .filter(employees::Column::Id.is_in(totals.keys().copied().collect::<Vec<_>>()))
+        .all(db)
+        .await?;
+    let employees: HashMap<i32, employees::Model> = employees.into_iter().map(|e| (e.id, e)).collect();
+    let plan_ids: Vec<i32> = employees.values().filter_map(|e| e.commission_plan_id).collect();
+    let plans: HashMap<i32, commission_plans::Model> = commission_plans::Entity::find()
+        .filter(commission_plans::Column::Id.is_in(plan_ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+    let rules = CommissionRepository::get_rules(db, plan_ids).await?;
+
+    let rows: Vec<EmployeeCommission> = totals
+        .into_iter()
+        .map(|(employee_id, (gross_sales, refunds, by_category))| {
+            let employee = employees.get(&employee_id);
+            let plan = employee.and_then(|e| e.commission_plan_id).and_then(|id| plans.get(&id));
+            let commission = match plan {
+                Some(plan) if plan.is_active => {
+                    let plan_rules: Vec<_> = rules.iter().filter(|r| r.commission_plan_id == plan.id).cloned().collect();
+                    plan.commission_for(&plan_rules, &by_category)
+                }
+                _ => Decimal::ZERO,
+            };
+            EmployeeCommission {
+                employee_id,
+                employee_name: employee.map(|e| format!("{} {}", e.first_name, e.last_name)).unwrap_or_default(),
+                commission_plan_id: plan.map(|p| p.id),
+                commission_plan_name: plan.map(|p| p.name.clone()),
+                gross_sales: gross_sales.round_dp(2),
+                refunds: refunds.round_dp(2),
+                net_sales: (gross_sales - refunds).round_dp(2),
+                commission,
+            }
+        })
+        .collect();
+
+    Ok(CommissionReport {
+        period_start: params.start_date,
+        period_end: params.end_date,
+        generated_at: chrono::Utc::now(),
+        total_commission: rows.iter().map(|r| r.commission).sum(),
+        employees: rows,
+    })
+}
+
+/// Commission earned per employee over a pay period. Refunds in the period are charged back
+/// to whoever made the original sale; employees without an active plan earn nothing.
+pub async fn get_commission_report(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<CommissionReportQueryParams>,
+) -> impl Responder {
+    let params = query_params.into_inner();
+    if params.end_date < params.start_date {
+        return HttpResponse::BadRequest().json(ApiError::new("end_date must not be before start_date".to_string()));
+    }
+    let scope = match scope.restrict_to(params.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
+    };
+
+    match build_commission_report(db.get_ref(), &scope, &params).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::new(report)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to build commission report: {}", e))),
+    }
+}
@@ -1,19 +1,28 @@
 use crate::repository::customers_repository::CustomerRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 use crate::entities::customers::{CreateCustomer, UpdateCustomer};
+use crate::search::SearchIndex;
 use sea_orm::DatabaseConnection;
 
-pub async fn get_all_customers(db: web::Data<DatabaseConnection>) -> impl Responder {
-    match CustomerRepository::get_all(db.get_ref()).await {
-        Ok(customers) => HttpResponse::Ok().json(ApiResponse::new(customers)),
+pub async fn get_all_customers(db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(CustomerRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match CustomerRepository::get_all(db.get_ref(), &plan).await {
+        Ok(customers) => HttpResponse::Ok().json(ApiPage::new(customers)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch customers".to_string())),
     }
 }
 
-pub async fn create_customer(db: web::Data<DatabaseConnection>, new_customer: web::Json<CreateCustomer>) -> impl Responder {
+pub async fn create_customer(db: web::Data<DatabaseConnection>, search: web::Data<SearchIndex>, new_customer: web::Json<CreateCustomer>) -> impl Responder {
     match CustomerRepository::create(db.get_ref(), new_customer.into_inner()).await {
-        Ok(customer) => HttpResponse::Ok().json(ApiResponse::new(customer)),
+        Ok(customer) => {
+            search.customer_changed(db.get_ref(), customer.id).await;
+            HttpResponse::Ok().json(ApiResponse::new(customer))
+        }
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create customer".to_string())),
     }
 }
@@ -26,17 +35,22 @@ pub async fn get_customer_by_id(db: web::Data<DatabaseConnection>, id: web::Path
     }
 }
 
-pub async fn update_customer(db: web::Data<DatabaseConnection>, id: web::Path<i32>, update_data: web::Json<UpdateCustomer>) -> impl Responder {
+pub async fn update_customer(db: web::Data<DatabaseConnection>, search: web::Data<SearchIndex>, id: web::Path<i32>, update_data: web::Json<UpdateCustomer>) -> impl Responder {
     match CustomerRepository::update(db.get_ref(), id.into_inner(), update_data.into_inner()).await {
-        Ok(Some(customer)) => HttpResponse::Ok().json(ApiResponse::new(customer)),
+        Ok(Some(customer)) => {
+            search.customer_changed(db.get_ref(), customer.id).await;
+            HttpResponse::Ok().json(ApiResponse::new(customer))
+        }
         Ok(None) => HttpResponse::NotFound().json(ApiError::new("Customer not found".to_string())),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update customer".to_string())),
     }
 }
 
-pub async fn delete_customer(db: web::Data<DatabaseConnection>, id: web::Path<i32>) -> impl Responder {
-    match CustomerRepository::delete(db.get_ref(), id.into_inner()).await {
+pub async fn delete_customer(db: web::Data<DatabaseConnection>, search: web::Data<SearchIndex>, id: web::Path<i32>) -> impl Responder {
+    let id = id.into_inner();
+    match CustomerRepository::delete(db.get_ref(), id).await {
         Ok(rows_affected) if rows_affected > 0 => {
+            search.customer_removed(id);
             HttpResponse::Ok().json(ApiResponse::new("Customer deleted successfully".to_string()))
         }
         Ok(_) => HttpResponse::NotFound().json(ApiError::new("Customer not found".to_string())),
@@ -0,0 +1,52 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::DatabaseConnection;
+
+use crate::entities::email_outbox::EmailResponse;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::repository::email_outbox_repository::EmailOutboxRepository;
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+
+pub async fn get_all_emails(
+    db: web::Data<DatabaseConnection>,
+    list: ListQuery,
+) -> impl Responder {
+    let plan = match list.resolve(EmailOutboxRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match EmailOutboxRepository::get_all(db.get_ref(), &plan).await {
+        Ok(emails) => HttpResponse::Ok().json(ApiPage::new(emails.map(EmailResponse::from))),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch emails: {}", e))),
+    }
+}
+
+pub async fn get_email_by_id(
+    db: web::Data<DatabaseConnection>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    match EmailOutboxRepository::find_by_id(db.get_ref(), id.into_inner()).await {
+        Ok(Some(email)) => HttpResponse::Ok().json(ApiResponse::new(EmailResponse::from(email))),
+        Ok(None) => HttpResponse::NotFound().json(ApiError::new("Email not found".to_string())),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch email: {}", e))),
+    }
+}
+
+pub async fn retry_email(
+    db: web::Data<DatabaseConnection>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let email = match EmailOutboxRepository::find_by_id(db.get_ref(), id.into_inner()).await {
+        Ok(Some(email)) => email,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("Email not found".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch email: {}", e))),
+    };
+
+    if email.status != "FAILED" {
+        return HttpResponse::BadRequest().json(ApiError::new("Only failed emails can be retried".to_string()));
+    }
+
+    match EmailOutboxRepository::requeue(db.get_ref(), email).await {
+        Ok(email) => HttpResponse::Ok().json(ApiResponse::new(EmailResponse::from(email))),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to retry email: {}", e))),
+    }
+}
@@ -0,0 +1,98 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::DatabaseConnection;
+
+use crate::entities::email_templates::{self, EmailTemplateView, UpdateEmailTemplate};
+use crate::helper::mailer::{self, BuiltinTemplate};
+use crate::repository::email_templates_repository::EmailTemplateRepository;
+use crate::helper::response::{ApiResponse, ApiError};
+
+fn to_view(builtin: &BuiltinTemplate, custom: Option<email_templates::Model>) -> EmailTemplateView {
+    let variables = builtin.variables.iter().map(|v| v.to_string()).collect();
+    match custom {
+        Some(custom) => EmailTemplateView {
+            template_key: builtin.key.to_string(),
+            description: builtin.description.to_string(),
+            variables,
+            subject: custom.subject,
+            text_body: custom.text_body,
+            html_body: custom.html_body,
+            customized: true,
+            updated_at: Some(custom.updated_at),
+        },
+        None => EmailTemplateView {
+            template_key: builtin.key.to_string(),
+            description: builtin.description.to_string(),
+            variables,
+            subject: builtin.subject.to_string(),
+            text_body: builtin.text_body.to_string(),
+            html_body: Some(builtin.html_body.to_string()),
+            customized: false,
+            updated_at: None,
+        },
+    }
+}
+
+pub async fn get_all_templates(db: web::Data<DatabaseConnection>) -> impl Responder {
+    let mut overrides = match EmailTemplateRepository::get_all(db.get_ref()).await {
+        Ok(overrides) => overrides,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch email templates: {}", e))),
+    };
+
+    let templates: Vec<EmailTemplateView> = mailer::TEMPLATES
+        .iter()
+        .map(|builtin| {
+            let custom = overrides
+                .iter()
+                .position(|t| t.template_key == builtin.key)
+                .map(|index| overrides.swap_remove(index));
+            to_view(builtin, custom)
+        })
+        .collect();
+    HttpResponse::Ok().json(ApiResponse::new(templates))
+}
+
+pub async fn get_template(db: web::Data<DatabaseConnection>, key: web::Path<String>) -> impl Responder {
+    let Some(builtin) = mailer::builtin_template(&key) else {
+        return HttpResponse::NotFound().json(ApiError::new("Email template not found".to_string()));
+    };
+
+    match EmailTemplateRepository::find_by_key(db.get_ref(), builtin.key).await {
+        Ok(custom) => HttpResponse::Ok().json(ApiResponse::new(to_view(builtin, custom))),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch email template: {}", e))),
+    }
+}
+
+pub async fn update_template(
+    db: web::Data<DatabaseConnection>,
+    key: web::Path<String>,
+    payload: web::Json<UpdateEmailTemplate>,
+) -> impl Responder {
+    let Some(builtin) = mailer::builtin_template(&key) else {
+        return HttpResponse::NotFound().json(ApiError::new("Email template not found".to_string()));
+    };
+
+    let mut payload = payload.into_inner();
+    if payload.subject.trim().is_empty() || payload.text_body.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new("subject and text_body are required".to_string()));
+    }
+    if payload.html_body.as_ref().is_some_and(|html| html.trim().is_empty()) {
+        payload.html_body = None;
+    }
+
+    match EmailTemplateRepository::upsert(db.get_ref(), builtin.key, payload).await {
+        Ok(custom) => HttpResponse::Ok().json(ApiResponse::new(to_view(builtin, Some(custom)))),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to save email template: {}", e))),
+    }
+}
+
+/// Drops the override so the built-in template is used again.
+pub async fn reset_template(db: web::Data<DatabaseConnection>, key: web::Path<String>) -> impl Responder {
+    let Some(builtin) = mailer::builtin_template(&key) else {
+        return HttpResponse::NotFound().json(ApiError::new("Email template not found".to_string()));
+    };
+
+    match EmailTemplateRepository::delete_by_key(db.get_ref(), builtin.key).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::new(to_view(builtin, None))),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to reset email template: {}", e))),
+    }
+}
@@ -1,14 +1,23 @@
 use crate::auth::auth_service::{self, Claims};
+use crate::auth::store_scope::StoreScope;
+use crate::auth::two_factor_handler;
+use crate::repository::employee_stores_repository::EmployeeStoreRepository;
 use crate::repository::employees_repository::EmployeeRepository;
+use crate::repository::permissions_repository::PermissionsRepository;
 use crate::repository::roles_repository::RoleRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
 use crate::entities::employees::{CreateEmployee, UpdateEmployee, EmployeeReportData, EmployeeResponse, CreateAdminPayload};
-use sea_orm::DatabaseConnection;
+use crate::entities::employees;
+use crate::entities::employee_stores::SetEmployeeStores;
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
 use crate::guard::employee_guard::EmployeeAccessGuard;
 use crate::repository::password_reset_tokens_repository::PasswordResetTokenRepository;
+use crate::repository::sessions_repository::SessionRepository;
 use crate::repository::settings_repository;
-use crate::helper::email;
+use crate::repository::stores_repository::StoreRepository;
+use crate::helper::mailer;
 
 // use crate::guard::role_guard::{Claims, has_role, ErrorResponse as RoleErrorResponse};
 
@@ -16,100 +25,62 @@ use serde::Deserialize;
 
 #[derive(Deserialize)]
 pub struct EmployeeFilter {
-    pub role: Option<String>,
     pub store_id: Option<i32>,
     #[serde(rename = "roles_to_exclude")]
     pub roles_to_exclude: Option<String>,
 }
 
-pub async fn get_all_employees(db: web::Data<DatabaseConnection>, query: web::Query<EmployeeFilter>, claims: web::ReqData<Claims>) -> impl Responder {
+pub async fn get_all_employees(db: web::Data<DatabaseConnection>, query: web::Query<EmployeeFilter>, scope: StoreScope, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(EmployeeRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
     let roles_to_exclude_vec = query.roles_to_exclude.as_ref().map(|s| {
         s.split(',').map(|role_str| role_str.trim().to_string()).collect()
     });
+    let scope = match scope.restrict_to(query.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
+    };
 
-    let effective_role_filter = query.role.clone();
-    let mut effective_store_id_filter = query.store_id;
-
-    // Apply role-based filtering
-    if claims.role == "StoreManager" {
-        // StoreManager can only see employees in their own store
-        if let Some(store_id) = claims.store_id {
-            effective_store_id_filter = Some(store_id);
-        } else {
-            // If StoreManager has no store_id, they shouldn't see any employees
-            return HttpResponse::Forbidden().json(ApiError::new("StoreManager has no assigned store".to_string()));
-        }
-        // StoreManager can only see roles they manage (e.g., Cashier, InventoryManager)
-        // For now, let's assume they can only see employees in their store, regardless of role filter from query
-        // If you want to restrict roles they can see, add logic here.
-    } else if claims.role == "Admin" || claims.role == "Owner" {
-        // Admin and Owner can see all employees (subject to query filters)
-        // No additional restrictions needed here based on their own store_id
+    match EmployeeRepository::get_all(db.get_ref(), &scope, roles_to_exclude_vec, &plan).await {
+        Ok(employees) => HttpResponse::Ok().json(ApiPage::new(employees.map(EmployeeResponse::from))),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employees".to_string())),
     }
+}
 
-    match EmployeeRepository::get_all(db.get_ref(), effective_role_filter, effective_store_id_filter, roles_to_exclude_vec).await {
-        Ok(employees) => {
-            let employee_responses: Vec<EmployeeResponse> = employees.into_iter().map(EmployeeResponse::from).collect();
-            HttpResponse::Ok().json(ApiResponse::new(employee_responses))
-        },
-        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employees".to_string())),
+/// Responds with the employee as an `EmployeeResponse`, which includes their role name.
+async fn employee_response(db: &DatabaseConnection, employee: employees::Model) -> HttpResponse {
+    match EmployeeRepository::with_role(db, employee).await {
+        Ok(employee) => HttpResponse::Ok().json(ApiResponse::new(EmployeeResponse::from(employee))),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee role".to_string())),
     }
 }
 
-pub async fn create_employee_general(db: web::Data<DatabaseConnection>, new_employee: web::Json<CreateEmployee>, _claims: web::ReqData<Claims>) -> impl Responder {
-    let employee_data = new_employee.into_inner();
+const ACCOUNT_SETUP_LINK_HOURS: i64 = 24;
 
-    match EmployeeRepository::create(db.get_ref(), employee_data).await {
-        Ok(employee) => {
-            // --- Send Set Password Email ---
-            let db_clone = db.get_ref().clone();
-            let created_employee = employee.clone();
-            tokio::spawn(async move {
-                // 1. Generate Token
-                let token = ::uuid::Uuid::new_v4().to_string();
-                let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::hours(24);
-
-                // 2. Store Token
-                if let Err(e) = PasswordResetTokenRepository::create(&db_clone, created_employee.id, token.clone(), expires_at).await {
-                    eprintln!("Failed to create password reset token: {}", e);
-                    return; // Don't send email if token wasn't saved
-                }
+/// Creates the employee together with their set-password token and the queued
+/// email carrying it, so an account is never left without a way to log in.
+async fn create_with_setup_email(db: &DatabaseConnection, employee_data: CreateEmployee) -> Result<employees::Model, DbErr> {
+    let settings = settings_repository::get_settings(db).await?;
+    let txn = db.begin().await?;
 
-                // 3. Fetch Email Settings
-                match settings_repository::get_settings(&db_clone).await {
-                    Ok(settings) => {
-                        // 4. Send Email
-                        let frontend_url = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
-                        let set_password_url = format!("{}/set-password?token={}", frontend_url, token);
-
-                        let subject = "Set Your Account Password";
-                        let body = format!(
-                            "Hello {},\n\nWelcome! Your account has been created. Please set your password by clicking the link below:\n\n{}\n\nThe link will expire in 24 hours.",
-                            created_employee.first_name,
-                            set_password_url
-                        );
-
-                        if let Err(e) = email::send_email(
-                            &settings.email,
-                            &created_employee.email,
-                            Some(&created_employee.first_name),
-                            subject,
-                            &body,
-                        ).await {
-                            eprintln!("Failed to send set-password email: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to fetch settings for sending email: {}", e);
-                    }
-                }
-            });
-            // --- End Email Logic ---
+    let employee = EmployeeRepository::create(&txn, employee_data).await?;
+    let token = ::uuid::Uuid::new_v4().to_string();
+    let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::hours(ACCOUNT_SETUP_LINK_HOURS);
+    PasswordResetTokenRepository::create(&txn, employee.id, token.clone(), expires_at).await?;
+    mailer::queue_password_link(&txn, mailer::ACCOUNT_SETUP, &employee, &token, ACCOUNT_SETUP_LINK_HOURS, &settings).await?;
 
-            let employee_response = EmployeeResponse::from(employee);
-            HttpResponse::Ok().json(ApiResponse::new(employee_response))
-        },
-        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create employee: {}", e.to_string()))),
+    txn.commit().await?;
+    Ok(employee)
+}
+
+pub async fn create_employee_general(db: web::Data<DatabaseConnection>, new_employee: web::Json<CreateEmployee>, _claims: web::ReqData<Claims>) -> impl Responder {
+    let employee_data = new_employee.into_inner();
+
+    match create_with_setup_email(db.get_ref(), employee_data).await {
+        Ok(employee) => employee_response(db.get_ref(), employee).await,
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create employee: {}", e))),
     }
 }
 
@@ -118,7 +89,7 @@ pub async fn create_admin(db: web::Data<DatabaseConnection>, new_admin_payload:
     let admin_role = match RoleRepository::find_by_name(db.get_ref(), "Admin".to_string()).await {
         Ok(Some(role)) => role,
         Ok(None) => return HttpResponse::InternalServerError().json(ApiError::new("Admin role not found in database".to_string())),
-        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to query admin role: {}", e.to_string()))),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to query admin role: {}", e))),
     };
 
     let payload = new_admin_payload.into_inner();
@@ -133,77 +104,36 @@ pub async fn create_admin(db: web::Data<DatabaseConnection>, new_admin_payload:
         photo_url: payload.photo_url,
     };
 
-    match EmployeeRepository::create(db.get_ref(), employee_data).await {
-        Ok(employee) => {
-            // --- Send Set Password Email ---
-            let db_clone = db.get_ref().clone();
-            let created_employee = employee.clone();
-            tokio::spawn(async move {
-                // 1. Generate Token
-                let token = ::uuid::Uuid::new_v4().to_string();
-                let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::hours(24);
-
-                // 2. Store Token
-                if let Err(e) = PasswordResetTokenRepository::create(&db_clone, created_employee.id, token.clone(), expires_at).await {
-                    eprintln!("Failed to create password reset token: {}", e);
-                    return; // Don't send email if token wasn't saved
-                }
-
-                // 3. Fetch Email Settings
-                match settings_repository::get_settings(&db_clone).await {
-                    Ok(settings) => {
-                        // 4. Send Email
-                        let frontend_url = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
-                        let set_password_url = format!("{}/set-password?token={}", frontend_url, token);
-
-                        let subject = "Set Your Account Password";
-                        let body = format!(
-                            "Hello {},\n\nWelcome! Your account has been created. Please set your password by clicking the link below:\n\n{}\n\nThe link will expire in 24 hours.",
-                            created_employee.first_name,
-                            set_password_url
-                        );
-
-                        if let Err(e) = email::send_email(
-                            &settings.email,
-                            &created_employee.email,
-                            Some(&created_employee.first_name),
-                            subject,
-                            &body,
-                        ).await {
-                            eprintln!("Failed to send set-password email: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to fetch settings for sending email: {}", e);
-                    }
-                }
-            });
-            // --- End Email Logic ---
-
-            let employee_response = EmployeeResponse::from(employee);
-            HttpResponse::Ok().json(ApiResponse::new(employee_response))
-        },
-        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create admin: {}", e.to_string()))),
+    match create_with_setup_email(db.get_ref(), employee_data).await {
+        Ok(employee) => employee_response(db.get_ref(), employee).await,
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create admin: {}", e))),
     }
 }
 
-pub async fn get_employee_by_id(guard: EmployeeAccessGuard) -> impl Responder {
-    let employee_response = EmployeeResponse::from(guard.employee);
-    HttpResponse::Ok().json(ApiResponse::new(employee_response))
+pub async fn get_employee_by_id(guard: EmployeeAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    employee_response(db.get_ref(), guard.employee).await
 }
 
-pub async fn get_my_profile(guard: EmployeeAccessGuard) -> impl Responder {
-    let employee_response = EmployeeResponse::from(guard.employee);
-    HttpResponse::Ok().json(ApiResponse::new(employee_response))
+pub async fn get_my_profile(guard: EmployeeAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    employee_response(db.get_ref(), guard.employee).await
 }
 
 pub async fn update_employee(guard: EmployeeAccessGuard, db: web::Data<DatabaseConnection>, update_data: web::Json<UpdateEmployee>) -> impl Responder {
     let employee_id = guard.employee.id;
+    let previous = guard.employee;
     let mut employee_data = update_data.into_inner();
 
     // Hash password if it is provided in the update
     if let Some(password) = employee_data.password.clone() {
         if !password.is_empty() {
+            match settings_repository::get_settings(db.get_ref()).await {
+                Ok(settings) => {
+                    if let Err(message) = settings.security.password_policy.validate(&password) {
+                        return HttpResponse::BadRequest().json(ApiError::new(message));
+                    }
+                }
+                Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch settings".to_string())),
+            }
             let hashed_password = match auth_service::hash_password(&password) {
                 Ok(hash) => hash,
                 Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(e.to_string())),
@@ -215,16 +145,156 @@ pub async fn update_employee(guard: EmployeeAccessGuard, db: web::Data<DatabaseC
         }
     }
 
+    let password_changed = employee_data.password.is_some();
     match EmployeeRepository::update(db.get_ref(), employee_id, employee_data).await {
         Ok(Some(employee)) => {
-            let employee_response = EmployeeResponse::from(employee);
-            HttpResponse::Ok().json(ApiResponse::new(employee_response))
+            // A new password ends every session; role or store changes only force a
+            // token refresh so the new claims take effect immediately
+            let invalidated = if password_changed {
+                SessionRepository::revoke_all_for_employee(db.get_ref(), employee.id).await
+            } else if employee.role_id != previous.role_id || employee.store_id != previous.store_id {
+                SessionRepository::invalidate_access_for_employee(db.get_ref(), employee.id).await
+            } else {
+                Ok(0)
+            };
+            if invalidated.is_err() {
+                return HttpResponse::InternalServerError().json(ApiError::new("Failed to invalidate sessions".to_string()));
+            }
+
+            employee_response(db.get_ref(), employee).await
         },
         Ok(None) => HttpResponse::NotFound().json(ApiError::new("Employee not found".to_string())),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update employee".to_string())),
     }
 }
 
+/// Whether the caller may manage an employee's security: they must hold every permission of
+/// the employee's role. Owners hold `employees:manage_owners`, so only they can manage an Owner.
+async fn outranks(db: &DatabaseConnection, claims: &Claims, employee: &employees::Model) -> Result<bool, DbErr> {
+    if employee.id == claims.sub {
+        return Ok(true);
+    }
+    let permissions = PermissionsRepository::find_permissions_for_role(db, employee.role_id).await?;
+    Ok(permissions.iter().all(|permission| claims.has_permission(&permission.name)))
+}
+
+/// Clears an employee's two-factor enrollment so they can set it up again,
+/// e.g. after losing their phone and recovery codes.
+pub async fn reset_two_factor(db: web::Data<DatabaseConnection>, id: web::Path<i32>, claims: web::ReqData<Claims>) -> impl Responder {
+    if !claims.has_permission("employees:manage_security") {
+        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges to reset two-factor authentication".to_string()));
+    }
+
+    let employee = match EmployeeRepository::find_by_id(db.get_ref(), id.into_inner()).await {
+        Ok(Some(employee)) => employee,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("Employee not found".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee".to_string())),
+    };
+
+    match outranks(db.get_ref(), &claims, &employee).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::Forbidden().json(ApiError::new("Forbidden: You cannot reset two-factor authentication for a higher-privileged employee".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee role".to_string())),
+    }
+
+    match two_factor_handler::reset(db.get_ref(), employee).await {
+        Ok(employee) => employee_response(db.get_ref(), employee).await,
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to reset two-factor authentication".to_string())),
+    }
+}
+
+/// Removes an employee's quick-login PIN, e.g. when it may have been seen by someone else.
+pub async fn reset_pin(guard: EmployeeAccessGuard, db: web::Data<DatabaseConnection>, claims: web::ReqData<Claims>) -> impl Responder {
+    match outranks(db.get_ref(), &claims, &guard.employee).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::Forbidden().json(ApiError::new("Forbidden: You cannot reset the PIN of a higher-privileged employee".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee role".to_string())),
+    }
+
+    match EmployeeRepository::set_pin_hash(db.get_ref(), guard.employee, None).await {
+        Ok(employee) => employee_response(db.get_ref(), employee).await,
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to reset PIN".to_string())),
+    }
+}
+
+pub async fn get_employee_sessions(guard: EmployeeAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match SessionRepository::get_active_for_employee(db.get_ref(), guard.employee.id).await {
+        Ok(sessions) => HttpResponse::Ok().json(ApiResponse::new(sessions)),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch sessions".to_string())),
+    }
+}
+
+/// Signs an employee out everywhere, e.g. when a device is lost or they leave.
+pub async fn revoke_employee_sessions(guard: EmployeeAccessGuard, db: web::Data<DatabaseConnection>, claims: web::ReqData<Claims>) -> impl Responder {
+    match outranks(db.get_ref(), &claims, &guard.employee).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::Forbidden().json(ApiError::new("Forbidden: You cannot sign out a higher-privileged employee".to_string())),
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch employee role".to_string())),
+    }
+
+    match SessionRepository::revoke_all_for_employee(db.get_ref(), guard.employee.id).await {
+        Ok(count) => HttpResponse::Ok().json(ApiResponse::new(format!("Revoked {} session(s)", count))),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to revoke sessions".to_string())),
+    }
+}
+
+pub async fn get_employee_stores(guard: EmployeeAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match EmployeeStoreRepository::get_stores(db.get_ref(), guard.employee.id).await {
+        Ok(stores) => HttpResponse::Ok().json(ApiResponse::new(stores)),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch stores".to_string())),
+    }
+}
+
+/// Replaces the stores an employee works at. Their primary store must stay among them, and
+/// only stores within the caller's own scope can be added or removed.
+pub async fn set_employee_stores(
+    guard: EmployeeAccessGuard,
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<SetEmployeeStores>,
+) -> impl Responder {
+    let employee = guard.employee;
+    let mut store_ids = payload.into_inner().store_ids;
+    store_ids.sort_unstable();
+    store_ids.dedup();
+
+    if employee.store_id.is_some_and(|primary| !store_ids.contains(&primary)) {
+        return HttpResponse::BadRequest().json(ApiError::new("The employee's primary store must be one of their stores".to_string()));
+    }
+    let current = match EmployeeStoreRepository::get_store_ids(db.get_ref(), employee.id).await {
+        Ok(current) => current,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch stores".to_string())),
+    };
+    let changed = store_ids.iter().filter(|id| !current.contains(id)).chain(current.iter().filter(|id| !store_ids.contains(id)));
+    if changed.clone().any(|id| !scope.includes_store(*id)) {
+        return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string()));
+    }
+    for store_id in changed {
+        match StoreRepository::find_by_id(db.get_ref(), *store_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return HttpResponse::BadRequest().json(ApiError::new(format!("Store {} not found", store_id))),
+            Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch store".to_string())),
+        }
+    }
+
+    let result = async {
+        let txn = db.begin().await?;
+        EmployeeStoreRepository::replace_for_employee(&txn, employee.id, &store_ids).await?;
+        // Tokens carry the employee's stores, so make them refresh
+        SessionRepository::invalidate_access_for_employee(&txn, employee.id).await?;
+        txn.commit().await
+    }
+    .await;
+
+    match result {
+        Ok(()) => match EmployeeStoreRepository::get_stores(db.get_ref(), employee.id).await {
+            Ok(stores) => HttpResponse::Ok().json(ApiResponse::new(stores)),
+            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch stores".to_string())),
+        },
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update stores".to_string())),
+    }
+}
+
 pub async fn delete_employee(db: web::Data<DatabaseConnection>, id: web::Path<i32>) -> impl Responder {
     let employee_id = id.into_inner(); // Extract once
     match EmployeeRepository::delete(db.get_ref(), employee_id).await {
@@ -238,22 +308,14 @@ pub async fn delete_employee(db: web::Data<DatabaseConnection>, id: web::Path<i3
 
 pub async fn get_employee_report(
     db: web::Data<DatabaseConnection>,
-    claims: web::ReqData<Claims>,
+    scope: StoreScope,
 ) -> impl Responder {
-    let employees_result = if claims.role == "Admin" || claims.role == "Owner" {
-        EmployeeRepository::get_all(db.get_ref(), None, None, None).await
-    } else if claims.role == "StoreManager" {
-        if let Some(store_id) = claims.store_id {
-            EmployeeRepository::get_all_by_store(db.get_ref(), store_id).await
-        } else {
-            return HttpResponse::Forbidden().json(ApiError::new("StoreManager has no assigned store".to_string()));
-        }
-    } else {
-        // Other roles like Cashier cannot access this report
+    // Employees limited to their own records cannot access this report
+    if !scope.is_store_wide() {
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
-    };
+    }
 
-    match employees_result {
+    match EmployeeRepository::get_all_in_scope(db.get_ref(), &scope).await {
         Ok(employees) => {
             let report_data: Vec<EmployeeReportData> = employees.into_iter().map(EmployeeReportData::from).collect();
             HttpResponse::Ok().json(ApiResponse::new(report_data))
@@ -1,9 +1,10 @@
 use crate::repository::inventory_repository::InventoryRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
-use crate::auth::auth_service::Claims;
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::auth::store_scope::StoreScope;
 use crate::entities::inventory::{CreateInventory, UpdateInventory, InventoryReport, InventoryReportQueryParams, InventoryReportItem};
-use sea_orm::{DatabaseConnection, QuerySelect, ColumnTrait, Condition, EntityTrait, QueryFilter};
+use sea_orm::{DatabaseConnection, QuerySelect, ColumnTrait, EntityTrait, QueryFilter};
 use crate::guard::inventory_guard::InventoryAccessGuard;
 use crate::entities::inventory::Column as InventoryColumn;
 use crate::entities::inventory::Entity as InventoryEntity;
@@ -11,31 +12,28 @@ use crate::entities::{products, stores};
 use sea_orm::prelude::DateTimeUtc;
 use std::str::FromStr;
 use actix::Addr;
-use crate::websocket::broadcaster::Broadcaster;
+use crate::websocket::{self, broadcaster::Broadcaster, events::Event};
 
-pub async fn get_all_inventory(db: web::Data<DatabaseConnection>, claims: web::ReqData<Claims>) -> impl Responder {
-    let result = if claims.role == "Admin" || claims.role == "Owner" {
-        InventoryRepository::get_all(db.get_ref()).await
-    } else {
-        if let Some(store_id) = claims.store_id {
-            InventoryRepository::get_all_by_store(db.get_ref(), store_id).await
-        } else {
-            return HttpResponse::Forbidden().json(ApiError::new("User has no assigned store".to_string()));
-        }
+pub async fn get_all_inventory(db: web::Data<DatabaseConnection>, scope: StoreScope, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(InventoryRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
     };
-
-    match result {
-        Ok(inventory) => HttpResponse::Ok().json(ApiResponse::new(inventory)),
+    match InventoryRepository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(inventory) => HttpResponse::Ok().json(ApiPage::new(inventory)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch inventory".to_string())),
     }
 }
 
-pub async fn create_inventory(db: web::Data<DatabaseConnection>, new_inventory: web::Json<CreateInventory>, broadcaster: web::Data<Addr<Broadcaster>>) -> impl Responder {
+pub async fn create_inventory(scope: StoreScope, db: web::Data<DatabaseConnection>, new_inventory: web::Json<CreateInventory>, broadcaster: web::Data<Addr<Broadcaster>>) -> impl Responder {
     let inventory_data = new_inventory.into_inner();
+    if !scope.includes_store(inventory_data.store_id) {
+        return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string()));
+    }
 
     match InventoryRepository::create(db.get_ref(), inventory_data).await {
         Ok(inventory) => {
-            broadcaster.do_send(crate::websocket::broadcaster::BroadcastMessage("inventory_updated".to_string()));
+            websocket::publish(&broadcaster, Event::stock_changed(&inventory, None));
             HttpResponse::Ok().json(ApiResponse::new(inventory))
         },
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create inventory".to_string())),
@@ -48,8 +46,11 @@ pub async fn get_inventory_by_id(guard: InventoryAccessGuard) -> impl Responder
 
 pub async fn update_inventory(guard: InventoryAccessGuard, db: web::Data<DatabaseConnection>, update_data: web::Json<UpdateInventory>, broadcaster: web::Data<Addr<Broadcaster>>) -> impl Responder {
     let item_id = guard.inventory.id;
-    match InventoryRepository::update(db.get_ref(), item_id, update_data.into_inner(), broadcaster.get_ref()).await {
-        Ok(Some(item)) => HttpResponse::Ok().json(ApiResponse::new(item)),
+    match InventoryRepository::update(db.get_ref(), item_id, update_data.into_inner()).await {
+        Ok(Some(item)) => {
+            websocket::publish(&broadcaster, Event::stock_changed(&item, Some(guard.inventory.quantity)));
+            HttpResponse::Ok().json(ApiResponse::new(item))
+        }
         Ok(None) => HttpResponse::NotFound().json(ApiError::new("Inventory not found".to_string())),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update inventory".to_string())),
     }
@@ -59,7 +60,7 @@ pub async fn delete_inventory(guard: InventoryAccessGuard, db: web::Data<Databas
     let item_id = guard.inventory.id;
     match InventoryRepository::delete(db.get_ref(), item_id).await {
         Ok(rows_affected) if rows_affected > 0 => {
-            broadcaster.do_send(crate::websocket::broadcaster::BroadcastMessage("inventory_updated".to_string()));
+            websocket::publish(&broadcaster, [Event::stock_deleted(&guard.inventory)]);
             HttpResponse::Ok().json(ApiResponse::new("Inventory deleted successfully".to_string()))
         }
         Ok(_) => HttpResponse::NotFound().json(ApiError::new("Inventory not found".to_string())),
@@ -68,15 +69,17 @@ pub async fn delete_inventory(guard: InventoryAccessGuard, db: web::Data<Databas
 }
 
 pub async fn get_inventory_report(
+    scope: StoreScope,
     db: web::Data<DatabaseConnection>,
     query_params: web::Query<InventoryReportQueryParams>,
 ) -> impl Responder {
+    let scope = match scope.restrict_to(query_params.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
+    };
 
-    let mut condition = Condition::all();
+    let mut condition = scope.store_condition(InventoryColumn::StoreId);
 
-    if let Some(store_id) = query_params.store_id {
-        condition = condition.add(InventoryColumn::StoreId.eq(store_id));
-    }
     if let Some(product_id) = query_params.product_id {
         condition = condition.add(InventoryColumn::ProductId.eq(product_id));
     }
@@ -16,3 +16,10 @@ pub mod bills_handler; // New module
 pub mod roles_handler;
 pub mod upload_handler;
 pub mod settings_handler;
+pub mod shifts_handler;
+pub mod terminals_handler;
+pub mod email_outbox_handler;
+pub mod email_templates_handler;
+pub mod timesheets_handler;
+pub mod commissions_handler;
+pub mod search_handler;
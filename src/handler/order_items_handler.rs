@@ -1,32 +1,21 @@
 use crate::repository::order_items_repository::OrderItemRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 // use crate::entities::order_items::{CreateOrderItem, UpdateOrderItem};
 use crate::entities::order_items::UpdateOrderItem;
 use sea_orm::{DatabaseConnection};
-use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::auth::store_scope::StoreScope;
 use crate::guard::order_item_guard::OrderItemAccessGuard;
 
-pub async fn get_all_order_items(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
-    if claims.0.role == "Admin" {
-        match OrderItemRepository::get_all(db.get_ref()).await {
-            Ok(order_items) => HttpResponse::Ok().json(ApiResponse::new(order_items)),
-            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch order items".to_string())),
-        }
-    } else if claims.0.role == "StoreManager" {
-        if let Some(store_id) = claims.0.store_id {
-            match OrderItemRepository::get_all_by_store(db.get_ref(), store_id).await {
-                Ok(order_items) => HttpResponse::Ok().json(ApiResponse::new(order_items)),
-                Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch order items for store".to_string())),
-            }
-        } else {
-            HttpResponse::Forbidden().json(ApiError::new("Store manager is not associated with a store".to_string()))
-        }
-    } else {
-        match OrderItemRepository::get_all_by_employee(db.get_ref(), claims.0.sub).await {
-            Ok(order_items) => HttpResponse::Ok().json(ApiResponse::new(order_items)),
-            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch order items".to_string())),
-        }
+pub async fn get_all_order_items(scope: StoreScope, db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(OrderItemRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match OrderItemRepository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(order_items) => HttpResponse::Ok().json(ApiPage::new(order_items)),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch order items".to_string())),
     }
 }
 
@@ -60,8 +49,8 @@ pub async fn delete_order_item(guard: OrderItemAccessGuard, db: web::Data<Databa
     let order_item_id = guard.order_item.id;
     let claims = guard.claims;
 
-    if claims.role == "Cashier" {
-        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Cashiers cannot delete order items.".to_string()));
+    if !claims.has_permission("orders:delete") {
+        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges to delete order items.".to_string()));
     }
 
     match OrderItemRepository::delete(db.get_ref(), order_item_id).await {
@@ -1,61 +1,61 @@
 use crate::repository::orders_repository::OrderRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
-use crate::entities::orders::{CreateOrderPayload, UpdateOrder};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::entities::orders::{CreateOrderPayload, ReceiptQueryParams, UpdateOrder};
 use sea_orm::{DatabaseConnection, TransactionTrait, prelude::Decimal, ActiveValue, ActiveModelTrait};
+use crate::auth::store_scope::StoreScope;
 use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::extractor::terminal_extractor::TerminalExtractor;
 use crate::guard::order_guard::OrderAccessGuard;
 use crate::repository::products_repository::ProductRepository;
 use crate::repository::promotions_repository::PromotionRepository;
 use crate::repository::inventory_repository::InventoryRepository;
 use crate::repository::payments_repository::PaymentRepository;
+use crate::repository::{refunds_repository, settings_repository};
+use crate::helper::{mailer, receipt};
 use crate::entities::{order_items, promotions, products, employees, payments, orders};
 use std::collections::HashMap;
 use chrono::Utc;
 use crate::entities::orders::{SalesReport, ProductSalesReport, EmployeeSalesReport, SalesReportQueryParams};
 use crate::entities::orders::Column as OrderColumn;
 use crate::entities::orders::Entity as OrderEntity;
-use sea_orm::{QuerySelect, ColumnTrait, Condition, self, EntityTrait, QueryFilter};
+use sea_orm::{QuerySelect, ColumnTrait, self, EntityTrait, QueryFilter};
 use std::str::FromStr;
+use actix::Addr;
+use crate::websocket::{self, broadcaster::Broadcaster, events::Event};
 
-pub async fn get_all_orders(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
-    if claims.0.role == "Admin" {
-        match OrderRepository::get_all(db.get_ref()).await {
-            Ok(orders) => HttpResponse::Ok().json(ApiResponse::new(orders)),
-            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch orders".to_string())),
-        }
-    } else if claims.0.role == "StoreManager" {
-        if let Some(store_id) = claims.0.store_id {
-            match OrderRepository::get_all_by_store(db.get_ref(), store_id).await { 
-                Ok(orders) => HttpResponse::Ok().json(ApiResponse::new(orders)),
-                Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch orders".to_string())),
-            }
-        } else {
-            HttpResponse::Forbidden().json(ApiError::new("StoreManager has no assigned store".to_string()))
-        }
-    } else { // Cashier and other roles
-        if let Some(store_id) = claims.0.store_id {
-            match OrderRepository::get_all_by_employee_and_store(db.get_ref(), claims.0.sub, store_id).await {
-                Ok(orders) => HttpResponse::Ok().json(ApiResponse::new(orders)),
-                Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch orders for cashier in store".to_string())),
-            }
-        } else {
-            // Fallback for users with roles other than Admin/StoreManager who might not have a store_id
-            // Or if a cashier somehow doesn't have a store_id in their token
-            HttpResponse::Forbidden().json(ApiError::new("User is not assigned to a store session".to_string()))
-        }
+pub async fn get_all_orders(scope: StoreScope, db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(OrderRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match OrderRepository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(orders) => HttpResponse::Ok().json(ApiPage::new(orders)),
+        Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch orders".to_string())),
     }
 }
 
 pub async fn create_order(
     claims: ClaimsExtractor,
+    terminal: TerminalExtractor,
     db: web::Data<DatabaseConnection>,
+    broadcaster: web::Data<Addr<Broadcaster>>,
     new_order_payload: web::Json<CreateOrderPayload>,
 ) -> impl Responder {
     let store_id = match claims.0.store_id {
         Some(id) => id,
         None => return HttpResponse::Forbidden().json(ApiError::new("User is not assigned to a store".to_string())),
     };
+    if !terminal.allowed_in(store_id) {
+        return HttpResponse::Forbidden().json(ApiError::new("Terminal belongs to another store".to_string()));
+    }
+    let terminal_id = terminal.id();
+
+    let settings = match settings_repository::get_settings_for_store(db.get_ref(), store_id).await {
+        Ok(settings) => settings,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch settings: {}", e))),
+    };
 
     let txn = match db.begin().await {
         Ok(txn) => txn,
@@ -67,6 +67,7 @@ pub async fn create_order(
 
     let mut total_order_amount = Decimal::new(0, 2);
     let mut order_items_active_models = Vec::new();
+    let mut stock_events = Vec::new();
 
     // Fetch all products and promotions needed in one go to minimize DB calls
     let product_ids: Vec<i32> = new_order_payload.items.iter().map(|item| item.product_id).collect();
@@ -103,6 +104,7 @@ pub async fn create_order(
 
         let mut unit_price = product.price;
         let mut discount_amount = Decimal::new(0, 2);
+        let mut applied_promotion_id = None;
 
         if let Some(promo_id) = item_payload.promotion_id {
             if let Some(promotion) = promotions_map.get(&promo_id) {
@@ -114,10 +116,12 @@ pub async fn create_order(
                             "PERCENTAGE" => {
                                 discount_amount = unit_price * (promotion.value / Decimal::new(100, 0));
                                 unit_price -= discount_amount;
+                                applied_promotion_id = Some(promotion.id);
                             },
                             "FIXED_AMOUNT" => {
                                 discount_amount = promotion.value;
                                 unit_price -= discount_amount;
+                                applied_promotion_id = Some(promotion.id);
                             },
                             _ => { /* Unknown promotion type, ignore */ }
                         }
@@ -130,6 +134,7 @@ pub async fn create_order(
 
         order_items_active_models.push(order_items::ActiveModel {
             product_id: ActiveValue::Set(product.id),
+            promotion_id: ActiveValue::Set(applied_promotion_id),
             quantity: ActiveValue::Set(item_payload.quantity),
             unit_price: ActiveValue::Set(unit_price),
             discount_amount: ActiveValue::Set(discount_amount),
@@ -137,8 +142,9 @@ pub async fn create_order(
         });
 
         // Deduct from inventory
-        if let Err(e) = InventoryRepository::decrease_quantity(&txn, product.id, store_id, item_payload.quantity).await {
-            return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to deduct inventory for product {}: {}", product.name, e)));
+        match InventoryRepository::decrease_quantity(&txn, product.id, store_id, item_payload.quantity).await {
+            Ok(item) => stock_events.extend(Event::stock_changed(&item, Some(inventory_item.quantity))),
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to deduct inventory for product {}: {}", product.name, e))),
         }
     }
 
@@ -148,6 +154,7 @@ pub async fn create_order(
         customer_id,
         employee_id,
         store_id,
+        terminal_id,
         total_order_amount,
         "Pending".to_string(), // Initial status
         order_items_active_models,
@@ -159,6 +166,7 @@ pub async fn create_order(
     // Create the payment
     let payment_to_create = payments::CreatePayment {
         order_id: order.id,
+        terminal_id,
         payment_method: new_order_payload.payment_method.clone(),
         amount: order.total_amount, // Assuming amount paid is the total amount for now
         payment_date: Utc::now(),
@@ -177,11 +185,20 @@ pub async fn create_order(
         Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to update order status: {}", e))),
     };
 
+    // Queue the emailed receipt; the outbox worker sends it once the sale is committed
+    if new_order_payload.email_receipt.unwrap_or(settings.receipt.email_receipts) {
+        if let Err(e) = mailer::queue_order_receipt(&txn, updated_order.clone(), &settings).await {
+            return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to queue receipt email: {}", e)));
+        }
+    }
+
     // Commit transaction
     if let Err(e) = txn.commit().await {
         return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to commit transaction: {}", e)));
     }
 
+    websocket::publish(&broadcaster, std::iter::once(Event::order_created(&updated_order)).chain(stock_events));
+
     HttpResponse::Ok().json(ApiResponse::new(updated_order))
 }
 
@@ -189,6 +206,42 @@ pub async fn get_order_by_id(guard: OrderAccessGuard) -> impl Responder {
     HttpResponse::Ok().json(ApiResponse::new(guard.order))
 }
 
+pub async fn get_order_receipt(
+    guard: OrderAccessGuard,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<ReceiptQueryParams>,
+) -> impl Responder {
+    let format = query_params.format.clone().unwrap_or_else(|| "text".to_string());
+    if !["pdf", "text", "escpos"].contains(&format.as_str()) {
+        return HttpResponse::BadRequest().json(ApiError::new("format must be one of pdf, text or escpos".to_string()));
+    }
+
+    let settings = match settings_repository::get_settings_for_store(db.get_ref(), guard.order.store_id).await {
+        Ok(settings) => settings,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch settings: {}", e))),
+    };
+
+    let order_id = guard.order.id;
+    let receipt = match receipt::build_receipt(db.get_ref(), guard.order, &settings).await {
+        Ok(receipt) => receipt,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to build receipt: {}", e))),
+    };
+
+    match format.as_str() {
+        "pdf" => HttpResponse::Ok()
+            .content_type("application/pdf")
+            .insert_header(("Content-Disposition", format!("inline; filename=\"receipt-{}.pdf\"", order_id)))
+            .body(receipt::render_pdf(&receipt)),
+        "escpos" => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"receipt-{}.bin\"", order_id)))
+            .body(receipt::render_escpos(&receipt)),
+        _ => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(receipt::render_text(&receipt)),
+    }
+}
+
 pub async fn update_order(guard: OrderAccessGuard, db: web::Data<DatabaseConnection>, update_data: web::Json<UpdateOrder>) -> impl Responder {
     let order_id = guard.order.id;
     match OrderRepository::update(db.get_ref(), order_id, update_data.into_inner()).await {
@@ -202,9 +255,9 @@ pub async fn delete_order(guard: OrderAccessGuard, db: web::Data<DatabaseConnect
     let order_id = guard.order.id;
     let claims = guard.claims;
 
-    // Additional check: Cashiers are not allowed to delete orders.
-    if claims.role == "Cashier" {
-        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Cashiers cannot delete orders.".to_string()));
+    // Additional check: being able to see an order does not mean being able to delete it.
+    if !claims.has_permission("orders:delete") {
+        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges to delete orders.".to_string()));
     }
 
     match OrderRepository::delete(db.get_ref(), order_id).await {
@@ -217,15 +270,19 @@ pub async fn delete_order(guard: OrderAccessGuard, db: web::Data<DatabaseConnect
 }
 
 pub async fn get_sales_report(
-    claims: ClaimsExtractor,
+    scope: StoreScope,
     db: web::Data<DatabaseConnection>,
     query_params: web::Query<SalesReportQueryParams>,
 ) -> impl Responder {
-    if claims.0.role != "Admin" && claims.0.role != "StoreManager" {
+    if !scope.is_store_wide() {
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
     }
+    let scope = match scope.restrict_to(query_params.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
+    };
 
-    let mut condition = Condition::all();
+    let mut condition = scope.store_condition(OrderColumn::StoreId);
 
     if let Some(start_date) = query_params.start_date {
         condition = condition.add(OrderColumn::OrderDate.gte(start_date));
@@ -233,15 +290,6 @@ pub async fn get_sales_report(
     if let Some(end_date) = query_params.end_date {
         condition = condition.add(OrderColumn::OrderDate.lte(end_date));
     }
-    if let Some(store_id) = query_params.store_id {
-        condition = condition.add(OrderColumn::StoreId.eq(store_id));
-    }
-    // If StoreManager, filter by their store_id
-    if claims.0.role == "StoreManager" {
-        if let Some(store_id) = claims.0.store_id {
-            condition = condition.add(OrderColumn::StoreId.eq(store_id));
-        }
-    }
     if let Some(employee_id) = query_params.employee_id {
         condition = condition.add(OrderColumn::EmployeeId.eq(employee_id));
     }
@@ -280,24 +328,42 @@ pub async fn get_sales_report(
     let mut product_sales_map: HashMap<i32, ProductSalesReport> = HashMap::new();
     let mut employee_sales_map: HashMap<i32, EmployeeSalesReport> = HashMap::new();
 
+    // One row per order item, so order-level totals are only counted the first time an order appears
     let mut processed_order_ids = std::collections::HashSet::new();
 
     for row in orders_with_items_and_employees {
         let order_id: i32 = row["id"].as_i64().unwrap() as i32;
-        let order_total_amount: Decimal = Decimal::from_str(&row["total_amount"].as_str().unwrap()).unwrap();
+        let order_total_amount: Decimal = Decimal::from_str(row["total_amount"].as_str().unwrap()).unwrap();
         let employee_id: i32 = row["employee_id"].as_i64().unwrap() as i32;
         let employee_first_name: String = row["employees"].as_object().unwrap()["first_name"].as_str().unwrap().to_string();
         let employee_last_name: String = row["employees"].as_object().unwrap()["last_name"].as_str().unwrap().to_string();
-        let product_id: i32 = row["order_items"].as_object().unwrap()["product_id"].as_i64().unwrap() as i32;
-        let quantity: i32 = row["order_items"].as_object().unwrap()["quantity"].as_i64().unwrap() as i32;
-        let unit_price: Decimal = Decimal::from_str(&row["order_items"].as_object().unwrap()["unit_price"].as_str().unwrap()).unwrap();
-        let discount_amount: Decimal = Decimal::from_str(&row["order_items"].as_object().unwrap()["discount_amount"].as_str().unwrap()).unwrap();
-        let product_name: String = products_map.get(&product_id).map(|p| p.name.clone()).unwrap_or_else(|| "Unknown Product".to_string());
+
+        let employee_sales = employee_sales_map
+            .entry(employee_id)
+            .or_insert_with(|| EmployeeSalesReport::new(employee_id, format!("{} {}", employee_first_name, employee_last_name)));
 
         // Aggregate total sales amount and total orders
         if processed_order_ids.insert(order_id) {
             total_sales_amount += order_total_amount;
             total_orders += 1;
+            employee_sales.total_sales_handled += order_total_amount;
+            employee_sales.transactions += 1;
+        }
+
+        // Orders without items still count as transactions above
+        let Some(item) = row["order_items"].as_object().filter(|item| !item["product_id"].is_null()) else {
+            continue;
+        };
+        let product_id: i32 = item["product_id"].as_i64().unwrap() as i32;
+        let quantity: i32 = item["quantity"].as_i64().unwrap() as i32;
+        let unit_price: Decimal = Decimal::from_str(item["unit_price"].as_str().unwrap()).unwrap();
+        let discount_amount: Decimal = Decimal::from_str(item["discount_amount"].as_str().unwrap()).unwrap();
+        let product_name: String = products_map.get(&product_id).map(|p| p.name.clone()).unwrap_or_else(|| "Unknown Product".to_string());
+
+        employee_sales.items_sold += quantity as i64;
+        if discount_amount > Decimal::ZERO {
+            employee_sales.discount_total += discount_amount * Decimal::from(quantity);
+            employee_sales.discounted_items += quantity as i64;
         }
 
         // Aggregate product sales
@@ -313,17 +379,33 @@ pub async fn get_sales_report(
                 quantity_sold: quantity,
                 total_revenue: product_revenue,
             });
+    }
 
-        // Aggregate employee sales
-        employee_sales_map.entry(employee_id)
-            .and_modify(|e| {
-                e.total_sales_handled += order_total_amount;
-            })
-            .or_insert(EmployeeSalesReport {
-                employee_id,
-                employee_name: format!("{} {}", employee_first_name, employee_last_name),
-                total_sales_handled: order_total_amount,
-            });
+    let refund_totals = match refunds_repository::get_totals_by_employee(
+        db.get_ref(),
+        &scope,
+        query_params.start_date,
+        query_params.end_date,
+        query_params.employee_id,
+    ).await {
+        Ok(totals) => totals,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch refunds: {}", e))),
+    };
+    for (employee_id, first_name, last_name, count, total) in refund_totals {
+        let employee_sales = employee_sales_map
+            .entry(employee_id)
+            .or_insert_with(|| EmployeeSalesReport::new(employee_id, format!("{} {}", first_name, last_name)));
+        employee_sales.refunds_issued = count;
+        employee_sales.refunds_total = total.unwrap_or_default();
+    }
+
+    for employee_sales in employee_sales_map.values_mut() {
+        if employee_sales.transactions > 0 {
+            let transactions = Decimal::from(employee_sales.transactions);
+            employee_sales.items_per_transaction = (Decimal::from(employee_sales.items_sold) / transactions).round_dp(2);
+            employee_sales.average_basket = (employee_sales.total_sales_handled / transactions).round_dp(2);
+        }
+        employee_sales.discount_total = employee_sales.discount_total.round_dp(2);
     }
 
     let sales_report = SalesReport {
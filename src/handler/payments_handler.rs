@@ -1,40 +1,36 @@
 use crate::repository::payments_repository::PaymentRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
 use crate::entities::payments::{CreatePayment, UpdatePayment};
 use sea_orm::{DatabaseConnection, EntityTrait};
-use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::auth::store_scope::StoreScope;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::extractor::terminal_extractor::TerminalExtractor;
 use crate::guard::payment_guard::PaymentAccessGuard;
 use crate::entities::orders;
 
-pub async fn get_all_payments(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
-    let db_ref = db.get_ref();
-    let result = if claims.0.role == "Admin" {
-        PaymentRepository::get_all(db_ref).await
-    } else if claims.0.role == "StoreManager" {
-        if let Some(store_id) = claims.0.store_id {
-            PaymentRepository::get_all_by_store(db_ref, store_id).await
-        } else {
-            // If a store manager has no store_id, return an empty list.
-            return HttpResponse::Ok().json(ApiResponse::new(Vec::<crate::entities::payments::Model>::new()));
-        }
-    } else {
-        PaymentRepository::get_all_by_employee(db_ref, claims.0.sub).await
+pub async fn get_all_payments(scope: StoreScope, db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(PaymentRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
     };
-
-    match result {
-        Ok(payments) => HttpResponse::Ok().json(ApiResponse::new(payments)),
+    match PaymentRepository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(payments) => HttpResponse::Ok().json(ApiPage::new(payments)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch payments".to_string())),
     }
 }
 
-pub async fn create_payment(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>, new_payment: web::Json<CreatePayment>) -> impl Responder {
+pub async fn create_payment(scope: StoreScope, terminal: TerminalExtractor, db: web::Data<DatabaseConnection>, new_payment: web::Json<CreatePayment>) -> impl Responder {
     let db_ref = db.get_ref();
-    let payment_data = new_payment.into_inner();
+    let mut payment_data = new_payment.into_inner();
     match orders::Entity::find_by_id(payment_data.order_id).one(db_ref).await {
         Ok(Some(order)) => {
-            if (claims.0.role == "StoreManager" && claims.0.store_id.map_or(false, |id| id == order.store_id)) ||
-               (claims.0.role == "Cashier" && order.employee_id == claims.0.sub) {
+            if !terminal.allowed_in(order.store_id) {
+                return HttpResponse::Forbidden().json(ApiError::new("Terminal belongs to another store".to_string()));
+            }
+            // The till is taken from the device credential, never from the request body
+            payment_data.terminal_id = terminal.id().or(order.terminal_id);
+            if scope.includes_record(order.store_id, order.employee_id) {
                 match PaymentRepository::create(db_ref, payment_data).await {
                     Ok(payment) => HttpResponse::Ok().json(ApiResponse::new(payment)),
                     Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create payment".to_string())),
@@ -65,8 +61,8 @@ pub async fn delete_payment(guard: PaymentAccessGuard, db: web::Data<DatabaseCon
     let payment_id = guard.payment.id;
     let claims = guard.claims;
 
-    if claims.role == "Cashier" {
-        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Cashiers cannot delete payments.".to_string()));
+    if !claims.has_permission("orders:delete") {
+        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges to delete payments.".to_string()));
     }
 
     match PaymentRepository::delete(db.get_ref(), payment_id).await {
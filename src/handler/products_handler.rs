@@ -1,25 +1,35 @@
 use crate::repository::products_repository::ProductRepository;
 use actix_web::{web, HttpResponse, Responder};
 use crate::guard::product_guard::ProductAccessGuard;
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 use crate::entities::products::{CreateProduct, UpdateProduct};
+use crate::search::SearchIndex;
 use sea_orm::DatabaseConnection;
 
-pub async fn get_all_products(db: web::Data<DatabaseConnection>) -> impl Responder {
+pub async fn get_all_products(db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
     // TODO: Re-implement authorization with actix middleware
-    match ProductRepository::get_all(db.get_ref()).await {
-        Ok(products) => HttpResponse::Ok().json(ApiResponse::new(products)),
+    let plan = match list.resolve(ProductRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match ProductRepository::get_all(db.get_ref(), &plan).await {
+        Ok(products) => HttpResponse::Ok().json(ApiPage::new(products)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch products".to_string())),
     }
 }
 
 pub async fn create_product(
     db: web::Data<DatabaseConnection>,
+    search: web::Data<SearchIndex>,
     new_product: web::Json<CreateProduct>,
 ) -> impl Responder {
     // TODO: Re-implement authorization with actix middleware
     match ProductRepository::create(db.get_ref(), new_product.into_inner()).await {
-        Ok(product) => HttpResponse::Ok().json(ApiResponse::new(product)),
+        Ok(product) => {
+            search.product_changed(db.get_ref(), product.id).await;
+            HttpResponse::Ok().json(ApiResponse::new(product))
+        }
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to create product".to_string())),
     }
 }
@@ -38,12 +48,16 @@ pub async fn get_product_by_id(
 
 pub async fn update_product(
     db: web::Data<DatabaseConnection>,
+    search: web::Data<SearchIndex>,
     id: web::Path<i32>,
     update_data: web::Json<UpdateProduct>,
 ) -> impl Responder {
     // TODO: Re-implement authorization with actix middleware
     match ProductRepository::update(db.get_ref(), id.into_inner(), update_data.into_inner()).await {
-        Ok(Some(product)) => HttpResponse::Ok().json(ApiResponse::new(product)),
+        Ok(Some(product)) => {
+            search.product_changed(db.get_ref(), product.id).await;
+            HttpResponse::Ok().json(ApiResponse::new(product))
+        }
         Ok(None) => HttpResponse::NotFound().json(ApiError::new("Product not found".to_string())),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update product".to_string())),
     }
@@ -51,10 +65,11 @@ pub async fn update_product(
 
 pub async fn delete_product(
     db: web::Data<DatabaseConnection>,
+    search: web::Data<SearchIndex>,
     guard: ProductAccessGuard,
 ) -> impl Responder {
-    // Only Admin and Owner should be able to delete products
-    if guard.claims.role != "Admin" && guard.claims.role != "Owner" {
+    // Products are shared by every store, so only those who manage all stores may delete them
+    if !guard.claims.can_access_all_stores() {
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges to delete product".to_string()));
     }
 
@@ -81,6 +96,7 @@ pub async fn delete_product(
     match ProductRepository::delete(db.get_ref(), product_to_delete.id).await {
         Ok(rows_affected) => {
             if rows_affected > 0 {
+                search.product_removed(product_to_delete.id);
                 HttpResponse::Ok().json(ApiResponse::new("Product deleted successfully".to_string()))
             } else {
                 HttpResponse::NotFound().json(ApiError::new("Product not found".to_string()))
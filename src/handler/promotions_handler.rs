@@ -3,7 +3,8 @@ use sea_orm::DatabaseConnection;
 
 use crate::repository::promotions_repository::PromotionRepository;
 use crate::entities::promotions::{CreatePromotion, UpdatePromotion};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 // use crate::guard::role_guard::{Claims, has_role, ErrorResponse as RoleErrorResponse};
 
 pub async fn create_promotion(
@@ -23,12 +24,17 @@ pub async fn create_promotion(
 pub async fn get_all_promotions(
     // claims: Claims,
     db: web::Data<DatabaseConnection>,
+    list: ListQuery,
 ) -> impl Responder {
     // if !has_role(&claims, &["Admin", "Owner"]) {
     //     return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
     // }
-    match PromotionRepository::get_all(db.get_ref()).await {
-        Ok(promotions) => HttpResponse::Ok().json(ApiResponse::new(promotions)),
+    let plan = match list.resolve(PromotionRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match PromotionRepository::get_all(db.get_ref(), &plan).await {
+        Ok(promotions) => HttpResponse::Ok().json(ApiPage::new(promotions)),
         Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch promotions: {}", e))),
     }
 }
@@ -1,9 +1,12 @@
 use actix_web::{web, HttpResponse, Responder};
 use crate::repository::purchase_orders_repository::{PurchaseOrderRepository};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 use sea_orm::{DatabaseConnection, prelude::Decimal};
 use serde::{Deserialize, Serialize};
 use crate::entities::{purchase_orders, purchase_order_items, suppliers, stores, products};
+use crate::websocket::{self, broadcaster::Broadcaster, events::Event};
+use actix::Addr;
 
 #[derive(Deserialize)]
 pub struct CreatePurchaseOrderPayload {
@@ -55,18 +58,19 @@ pub struct ReceiveStockPayload {
     pub items: Vec<ReceiveItem>,
 }
 
-pub async fn get_all_purchase_orders(db: web::Data<DatabaseConnection>) -> impl Responder {
-    match PurchaseOrderRepository::find_all_pos_with_relations(db.get_ref()).await {
+pub async fn get_all_purchase_orders(db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(PurchaseOrderRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match PurchaseOrderRepository::find_all_pos_with_relations(db.get_ref(), &plan).await {
         Ok(pos) => {
-            let response = pos
-                .into_iter()
-                .map(|(purchase_order, supplier, store)| PurchaseOrderListResponse {
-                    purchase_order,
-                    supplier,
-                    store,
-                })
-                .collect::<Vec<_>>();
-            HttpResponse::Ok().json(ApiResponse::new(response))
+            let response = pos.map(|(purchase_order, supplier, store)| PurchaseOrderListResponse {
+                purchase_order,
+                supplier,
+                store,
+            });
+            HttpResponse::Ok().json(ApiPage::new(response))
         }
         Err(e) => {
             log::error!("Failed to get all purchase orders: {:?}", e);
@@ -176,6 +180,7 @@ pub async fn update_purchase_order_status(
 
 pub async fn receive_stock(
     db: web::Data<DatabaseConnection>,
+    broadcaster: web::Data<Addr<Broadcaster>>,
     path: web::Path<i32>,
     payload: web::Json<ReceiveStockPayload>,
 ) -> impl Responder {
@@ -183,10 +188,92 @@ pub async fn receive_stock(
     let items = payload.into_inner().items;
 
     match PurchaseOrderRepository::receive_purchase_order_items(db.get_ref(), po_id, items).await {
-        Ok(_) => HttpResponse::Ok().json(ApiResponse::new("Stock received successfully".to_string())),
+        Ok((purchase_order, stock_changes)) => {
+            let stock_events = stock_changes
+                .iter()
+                .flat_map(|(stock, previous_quantity)| Event::stock_changed(stock, Some(*previous_quantity)));
+            websocket::publish(&broadcaster, std::iter::once(Event::purchase_order_received(&purchase_order)).chain(stock_events));
+            HttpResponse::Ok().json(ApiResponse::new("Stock received successfully".to_string()))
+        }
         Err(e) => {
             log::error!("Failed to receive stock: {:?}", e);
             HttpResponse::InternalServerError().json(ApiError::new(e.to_string()))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App};
+    use chrono::Utc;
+    use sea_orm::prelude::Decimal;
+
+    use crate::entities::{purchase_orders, stores, suppliers};
+    use crate::helper::test_database;
+    use crate::routes::purchase_orders_routes;
+
+    const PURCHASE_ORDERS: i32 = 4000;
+
+    fn purchase_order(id: i32) -> purchase_orders::Model {
+        let now = Utc::now();
+        purchase_orders::Model {
+            id,
+            supplier_id: id % 40 + 1,
+            store_id: id % 5 + 1,
+            employee_id: None,
+            order_date: now,
+            expected_delivery_date: None,
+            status: "ORDERED".to_string(),
+            total_amount: Decimal::new(9900, 2),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn supplier(id: i32) -> suppliers::Model {
+        let now = Utc::now();
+        suppliers::Model { id, name: format!("Supplier {}", id), contact_person: None, email: None, phone: None, address: None, created_at: now, updated_at: now }
+    }
+
+    fn store(id: i32) -> stores::Model {
+        let now = Utc::now();
+        stores::Model { id, name: format!("Store {}", id), address: None, phone: None, created_at: now, updated_at: now }
+    }
+
+    /// Loads pages from thousands of purchase orders and checks the number of queries does
+    /// not grow with the page: a count, the purchase orders, their suppliers, their stores.
+    #[actix_web::test]
+    async fn a_page_of_purchase_orders_takes_four_queries() {
+        for size in [20, 200] {
+            let (db, statements) = test_database::connect(move |statement| {
+                if test_database::is_session_lookup(statement) {
+                    vec![test_database::session()]
+                } else if statement.sql.starts_with("SELECT COUNT(*)") {
+                    vec![test_database::count_row(PURCHASE_ORDERS)]
+                } else if statement.sql.contains("FROM `suppliers`") {
+                    test_database::int_values(statement).into_iter().map(|id| test_database::row(&supplier(id))).collect()
+                } else if statement.sql.contains("FROM `stores`") {
+                    test_database::int_values(statement).into_iter().map(|id| test_database::row(&store(id))).collect()
+                } else {
+                    (1..=size).map(|id| test_database::row(&purchase_order(id))).collect()
+                }
+            })
+            .await;
+            let app = test::init_service(App::new().app_data(web::Data::new(db)).configure(purchase_orders_routes::configure_routes)).await;
+
+            let token = test_database::access_token(&["purchase_orders:read"]);
+            let request = test::TestRequest::get()
+                .uri(&format!("/purchase-orders?page=2&size={}", size))
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+
+            let purchase_orders = body["data"].as_array().unwrap();
+            assert_eq!(purchase_orders.len(), size as usize);
+            assert!(purchase_orders.iter().all(|po| po["supplier"]["id"] == po["supplier_id"] && po["store"]["id"] == po["store_id"]));
+            assert_eq!(body["pagination"]["total"], PURCHASE_ORDERS);
+            let queries = statements.lock().unwrap().iter().filter(|statement| !test_database::is_session_lookup(statement)).count();
+            assert_eq!(queries, 4, "queries for a page of {} purchase orders", size);
+        }
+    }
+}
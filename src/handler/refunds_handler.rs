@@ -1,20 +1,33 @@
 use actix_web::{web, HttpResponse, Responder};
-use sea_orm::{DatabaseConnection, TransactionTrait, Set, ActiveModelTrait};
+use sea_orm::{DatabaseConnection, TransactionTrait, Set, ActiveModelTrait, prelude::Decimal};
 use std::collections::HashMap;
 use serde::Serialize;
-use chrono::Utc;
+use chrono::{Utc, Duration};
 
-use crate::auth::auth_service::Claims;
+use crate::auth::auth_service::{self, Claims};
+use crate::auth::store_scope::StoreScope;
 use crate::entities::{refunds, refund_items, orders, payments};
+use crate::entities::refunds::{ManagerOverride, CreateReceiptlessRefund, ReturnsAbuseReportQueryParams};
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::extractor::terminal_extractor::TerminalExtractor;
 use crate::repository::{
     orders_repository::OrderRepository,
     order_items_repository::OrderItemRepository,
     inventory_repository::InventoryRepository,
     payments_repository::PaymentRepository,
+    products_repository::ProductRepository,
+    customers_repository::CustomerRepository,
+    employees_repository::EmployeeRepository,
+    permissions_repository::PermissionsRepository,
+    settings_repository,
     refunds_repository,
 };
 // use crate::guard::role_guard::{Claims, has_role, ErrorResponse as RoleErrorResponse};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::mailer;
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::websocket::{self, broadcaster::Broadcaster, events::Event};
+use actix::Addr;
 
 #[derive(Serialize)]
 pub struct FullRefund {
@@ -22,15 +35,54 @@ pub struct FullRefund {
     items: Vec<refund_items::Model>,
 }
 
+const OVERRIDE_PERMISSION: &str = "refunds:override";
+
+/// Resolves the employee who approves a refund that is outside the return policy.
+/// The requester approves it themselves if they hold `refunds:override`; otherwise the
+/// payload must carry the credentials of an employee who does.
+async fn resolve_manager_override(
+    db: &DatabaseConnection,
+    claims: &Claims,
+    manager_override: Option<&ManagerOverride>,
+    reason: &str,
+) -> Result<i32, HttpResponse> {
+    if claims.permissions.iter().any(|p| p == OVERRIDE_PERMISSION) {
+        return Ok(claims.sub);
+    }
+
+    let manager_override = match manager_override {
+        Some(o) => o,
+        None => return Err(HttpResponse::Forbidden().json(ApiError::new(format!("Manager override required: {}", reason)))),
+    };
+
+    let manager = match EmployeeRepository::find_by_email(db, manager_override.email.clone()).await {
+        Ok(Some(manager)) => manager,
+        Ok(None) => return Err(HttpResponse::Forbidden().json(ApiError::new("Invalid manager override credentials".to_string()))),
+        Err(_) => return Err(HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch manager".to_string()))),
+    };
+
+    match auth_service::verify_password(&manager_override.password, &manager.password_hash) {
+        Ok(true) => {}
+        Ok(false) => return Err(HttpResponse::Forbidden().json(ApiError::new("Invalid manager override credentials".to_string()))),
+        Err(_) => return Err(HttpResponse::InternalServerError().json(ApiError::new("Password verification failed".to_string()))),
+    }
+
+    match PermissionsRepository::find_permissions_for_role(db, manager.role_id).await {
+        Ok(permissions) if permissions.iter().any(|p| p.name == OVERRIDE_PERMISSION) => Ok(manager.id),
+        Ok(_) => Err(HttpResponse::Forbidden().json(ApiError::new("Manager is not allowed to override refunds".to_string()))),
+        Err(_) => Err(HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch manager permissions".to_string()))),
+    }
+}
+
 pub async fn create_refund(
+    claims: ClaimsExtractor,
+    terminal: TerminalExtractor,
     db: web::Data<DatabaseConnection>,
-    // claims: Claims, // TODO: Re-implement with actix middleware
+    broadcaster: web::Data<Addr<Broadcaster>>,
     payload: web::Json<refunds::CreateRefund>,
 ) -> impl Responder {
-    // if !has_role(&claims, &["Admin"]) {
-    //     return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Only Admin can create refunds.".to_string()));
-    // }
-    
+    let claims = claims.0;
+
     let txn = match db.begin().await {
         Ok(txn) => txn,
         Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to start transaction: {}", e))),
@@ -43,9 +95,31 @@ pub async fn create_refund(
         Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch order: {}", e))),
     };
 
-    // TODO: This needs to be replaced with a proper way to get employee/store ID from middleware
-    let employee_id = 1; // Placeholder
+    if !StoreScope::from_claims(&claims).includes_record(order.store_id, order.employee_id) {
+        return HttpResponse::Forbidden().json(ApiError::new("Forbidden: You can only refund orders in your store or created by you.".to_string()));
+    }
+
+    let employee_id = claims.sub;
     let store_id = order.store_id; // Assuming refund happens in the same store
+    if !terminal.allowed_in(store_id) {
+        return HttpResponse::Forbidden().json(ApiError::new("Terminal belongs to another store".to_string()));
+    }
+
+    let settings = match settings_repository::get_settings_for_store(db.get_ref(), store_id).await {
+        Ok(settings) => settings,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch settings: {}", e))),
+    };
+
+    // Returns outside the store's return window need a manager override
+    let return_window_days = settings.returns.return_window_days;
+    let mut approved_by = None;
+    if order.order_date + Duration::days(return_window_days as i64) < Utc::now() {
+        let reason = format!("order is outside the {}-day return window", return_window_days);
+        match resolve_manager_override(db.get_ref(), &claims, payload.manager_override.as_ref(), &reason).await {
+            Ok(manager_id) => approved_by = Some(manager_id),
+            Err(response) => return response,
+        }
+    }
 
     let original_items = match OrderItemRepository::get_all_by_order_id(&txn, order.id).await {
         Ok(items) => items,
@@ -70,7 +144,7 @@ pub async fn create_refund(
             total_refund_amount += item_amount;
 
             refund_item_models.push(refund_items::ActiveModel {
-                order_item_id: Set(item_to_refund.order_item_id),
+                order_item_id: Set(Some(item_to_refund.order_item_id)),
                 product_id: Set(original_item.product_id),
                 quantity: Set(item_to_refund.quantity),
                 amount: Set(item_amount),
@@ -87,13 +161,24 @@ pub async fn create_refund(
         total_original_quantity += item.quantity;
     }
 
+    // Cash sales are refunded from the drawer, everything else goes back to the original tender
+    let refund_method = match PaymentRepository::find_by_order_id(&txn, order.id).await {
+        Ok(order_payments) if order_payments.iter().any(|p| p.payment_method == "Cash" && p.amount > Decimal::ZERO) => "CASH",
+        Ok(_) => "ORIGINAL_TENDER",
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch order payments: {}", e))),
+    };
+
     // 3. Create the main refund record
     let refund_model = refunds::ActiveModel {
-        order_id: Set(payload.order_id),
+        order_id: Set(Some(payload.order_id)),
         employee_id: Set(employee_id),
         store_id: Set(store_id),
         reason: Set(payload.reason.clone()),
         total_amount: Set(total_refund_amount),
+        customer_id: Set(Some(order.customer_id)),
+        refund_method: Set(refund_method.to_string()),
+        is_receiptless: Set(false),
+        approved_by: Set(approved_by),
         created_at: Set(Utc::now()),
         updated_at: Set(Utc::now()),
         ..Default::default()
@@ -105,9 +190,11 @@ pub async fn create_refund(
     };
 
     // 4. Increase inventory for each refunded item
+    let mut stock_events = Vec::new();
     for item in &refund_items {
-        if let Err(e) = InventoryRepository::increase_quantity(&txn, item.product_id, store_id, item.quantity).await {
-            return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to update inventory for product {}: {}", item.product_id, e)));
+        match InventoryRepository::increase_quantity(&txn, item.product_id, store_id, item.quantity).await {
+            Ok(stock) => stock_events.extend(Event::stock_changed(&stock, Some(stock.quantity - item.quantity))),
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to update inventory for product {}: {}", item.product_id, e))),
         }
     }
 
@@ -122,6 +209,7 @@ pub async fn create_refund(
     // 6. Create a negative payment record for the refund
     let refund_payment = payments::CreatePayment {
         order_id: payload.order_id,
+        terminal_id: terminal.id(),
         payment_method: "REFUND".to_string(),
         amount: -total_refund_amount, // Negative amount
         payment_date: Utc::now(),
@@ -131,11 +219,20 @@ pub async fn create_refund(
         return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create refund payment record: {}", e)));
     }
 
-    // 7. Commit transaction
+    // 7. Queue the customer's confirmation email
+    if payload.email_confirmation.unwrap_or(settings.receipt.email_receipts) {
+        if let Err(e) = mailer::queue_refund_confirmation(&txn, &refund, &refund_items, &settings).await {
+            return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to queue confirmation email: {}", e)));
+        }
+    }
+
+    // 8. Commit transaction
     if let Err(e) = txn.commit().await {
         return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to commit transaction: {}", e)));
     }
 
+    websocket::publish(&broadcaster, std::iter::once(Event::refund_issued(&refund)).chain(stock_events));
+
     let full_refund = FullRefund {
         refund,
         items: refund_items,
@@ -144,43 +241,208 @@ pub async fn create_refund(
     HttpResponse::Ok().json(ApiResponse::new(full_refund))
 }
 
-pub async fn get_all_refunds(db: web::Data<DatabaseConnection>, claims: web::ReqData<Claims>) -> impl Responder {
-    let result = if claims.role == "Admin" || claims.role == "Owner" {
-        refunds_repository::get_all(db.get_ref()).await
-    } else if claims.role == "StoreManager" {
-        if let Some(store_id) = claims.store_id {
-            refunds_repository::get_all_by_store(db.get_ref(), store_id).await
+pub async fn create_receiptless_refund(
+    claims: ClaimsExtractor,
+    db: web::Data<DatabaseConnection>,
+    broadcaster: web::Data<Addr<Broadcaster>>,
+    payload: web::Json<CreateReceiptlessRefund>,
+) -> impl Responder {
+    let claims = claims.0;
+
+    let store_id = match claims.store_id {
+        Some(id) => id,
+        None => return HttpResponse::Forbidden().json(ApiError::new("User is not assigned to a store".to_string())),
+    };
+
+    let settings = match settings_repository::get_settings_for_store(db.get_ref(), store_id).await {
+        Ok(settings) => settings,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch settings: {}", e))),
+    };
+    let policy = &settings.returns;
+
+    if !policy.allow_receiptless_returns {
+        return HttpResponse::Forbidden().json(ApiError::new("Receipt-less returns are disabled".to_string()));
+    }
+    if payload.items.is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new("At least one item must be returned".to_string()));
+    }
+    if policy.receiptless_store_credit_only && payload.customer_id.is_none() {
+        return HttpResponse::BadRequest().json(ApiError::new("A customer is required to issue store credit".to_string()));
+    }
+
+    let txn = match db.begin().await {
+        Ok(txn) => txn,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to start transaction: {}", e))),
+    };
+
+    let now = Utc::now();
+    let price_since = now - Duration::days(policy.receiptless_price_lookback_days as i64);
+    let mut total_refund_amount = Decimal::new(0, 2);
+    let mut refund_item_models = vec![];
+
+    // 1. Resolve each returned product and value it according to the policy
+    for item in &payload.items {
+        if item.quantity <= 0 {
+            return HttpResponse::BadRequest().json(ApiError::new("Returned quantity must be positive".to_string()));
+        }
+
+        let product_result = match (item.product_id, &item.barcode) {
+            (Some(product_id), _) => ProductRepository::find_by_id(&txn, product_id).await,
+            (None, Some(barcode)) => ProductRepository::find_by_sku(&txn, barcode.clone()).await,
+            (None, None) => return HttpResponse::BadRequest().json(ApiError::new("Each item needs a product_id or barcode".to_string())),
+        };
+        let product = match product_result {
+            Ok(Some(product)) => product,
+            Ok(None) => return HttpResponse::NotFound().json(ApiError::new("Returned product not found".to_string())),
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch product: {}", e))),
+        };
+
+        let unit_value = if policy.receiptless_value_rule == "LOWEST_RECENT_PRICE" {
+            match OrderItemRepository::find_lowest_unit_price_since(&txn, product.id, price_since).await {
+                Ok(Some(lowest)) => lowest.min(product.price),
+                Ok(None) => product.price,
+                Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch recent prices: {}", e))),
+            }
         } else {
-            return HttpResponse::Forbidden().json(ApiError::new("StoreManager has no assigned store".to_string()));
+            product.price
+        };
+
+        let item_amount = unit_value * Decimal::from(item.quantity);
+        total_refund_amount += item_amount;
+
+        refund_item_models.push(refund_items::ActiveModel {
+            order_item_id: Set(None),
+            product_id: Set(product.id),
+            quantity: Set(item.quantity),
+            amount: Set(item_amount),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        });
+    }
+
+    // 2. Check the per-refund and per-customer limits
+    let mut exceeded = vec![];
+    let max_amount = policy.receiptless_max_amount.round_dp(2);
+    if total_refund_amount > max_amount {
+        exceeded.push(format!("refund of {} exceeds the receipt-less limit of {}", total_refund_amount, max_amount));
+    }
+    if let Some(customer_id) = payload.customer_id {
+        let since = now - Duration::days(policy.receiptless_abuse_window_days as i64);
+        match refunds_repository::count_receiptless_by_customer_since(&txn, customer_id, since).await {
+            Ok(count) if count >= policy.receiptless_max_per_customer as u64 => {
+                exceeded.push(format!("customer already has {} receipt-less returns in the last {} days", count, policy.receiptless_abuse_window_days));
+            }
+            Ok(_) => {}
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to count previous returns: {}", e))),
         }
+    }
+
+    let approved_by = if exceeded.is_empty() {
+        None
     } else {
+        match resolve_manager_override(db.get_ref(), &claims, payload.manager_override.as_ref(), &exceeded.join("; ")).await {
+            Ok(manager_id) => Some(manager_id),
+            Err(response) => return response,
+        }
+    };
+
+    // 3. Create the refund record
+    let refund_method = if policy.receiptless_store_credit_only { "STORE_CREDIT" } else { "CASH" };
+    let refund_model = refunds::ActiveModel {
+        order_id: Set(None),
+        employee_id: Set(claims.sub),
+        store_id: Set(store_id),
+        reason: Set(payload.reason.clone()),
+        total_amount: Set(total_refund_amount),
+        customer_id: Set(payload.customer_id),
+        refund_method: Set(refund_method.to_string()),
+        is_receiptless: Set(true),
+        approved_by: Set(approved_by),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+
+    let (refund, refund_items) = match refunds_repository::create_refund(&txn, refund_model, refund_item_models).await {
+        Ok(data) => data,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create refund records: {}", e))),
+    };
+
+    // 4. Put the returned items back into stock
+    let mut stock_events = Vec::new();
+    for item in &refund_items {
+        match InventoryRepository::increase_quantity(&txn, item.product_id, store_id, item.quantity).await {
+            Ok(stock) => stock_events.extend(Event::stock_changed(&stock, Some(stock.quantity - item.quantity))),
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to update inventory for product {}: {}", item.product_id, e))),
+        }
+    }
+
+    // 5. Credit the customer's account instead of paying out
+    if let (true, Some(customer_id)) = (policy.receiptless_store_credit_only, payload.customer_id) {
+        if let Err(e) = CustomerRepository::add_store_credit(&txn, customer_id, total_refund_amount).await {
+            return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to issue store credit: {}", e)));
+        }
+    }
+
+    // 6. Queue the customer's confirmation email
+    if payload.email_confirmation.unwrap_or(settings.receipt.email_receipts) {
+        if let Err(e) = mailer::queue_refund_confirmation(&txn, &refund, &refund_items, &settings).await {
+            return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to queue confirmation email: {}", e)));
+        }
+    }
+
+    if let Err(e) = txn.commit().await {
+        return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to commit transaction: {}", e)));
+    }
+
+    websocket::publish(&broadcaster, std::iter::once(Event::refund_issued(&refund)).chain(stock_events));
+
+    HttpResponse::Ok().json(ApiResponse::new(FullRefund { refund, items: refund_items }))
+}
+
+pub async fn get_returns_abuse_report(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<ReturnsAbuseReportQueryParams>,
+) -> impl Responder {
+    if !scope.is_store_wide() {
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
+    }
+    let params = query_params.into_inner();
+    let scope = match scope.restrict_to(params.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
     };
 
-    match result {
-        Ok(refunds) => HttpResponse::Ok().json(ApiResponse::new(refunds)),
+    match refunds_repository::get_receiptless_returns_by_customer(db.get_ref(), &scope, &params).await {
+        Ok(rows) => HttpResponse::Ok().json(ApiResponse::new(rows)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch returns abuse report: {}", e))),
+    }
+}
+
+pub async fn get_all_refunds(db: web::Data<DatabaseConnection>, scope: StoreScope, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(refunds_repository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match refunds_repository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(refunds) => HttpResponse::Ok().json(ApiPage::new(refunds)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch refunds".to_string())),
     }
 }
 
 pub async fn get_refund_by_id(
+    scope: StoreScope,
     db: web::Data<DatabaseConnection>,
-    // claims: Claims, // TODO: Re-implement with actix middleware
     path: web::Path<i32>,
 ) -> impl Responder {
     let id = path.into_inner();
     let result = refunds_repository::find_by_id(db.get_ref(), id).await;
 
     match result {
-        Ok(Some(refund)) => {
-            // Security check: ensure the user can access this refund
-            // TODO: Re-implement with actix middleware
-            // if has_role(&claims, &["Admin", "Owner"]) || (claims.store_id.is_some() && claims.store_id.unwrap() == refund.store_id) {
-                HttpResponse::Ok().json(ApiResponse::new(refund))
-            // } else {
-            //     HttpResponse::Forbidden().json(ApiError::new("Forbidden: You do not have access to this refund".to_string()))
-            // }
-        }
+        Ok(Some(refund)) if scope.includes_record(refund.store_id, refund.employee_id) => HttpResponse::Ok().json(ApiResponse::new(refund)),
+        Ok(Some(_)) => HttpResponse::Forbidden().json(ApiError::new("Forbidden: You do not have access to this refund".to_string())),
         Ok(None) => HttpResponse::NotFound().json(ApiError::new(format!("Refund with ID {} not found", id))),
         Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch refund: {}", e))),
     }
@@ -1,19 +1,25 @@
 use actix_web::{web, HttpResponse, Responder};
 use sea_orm::DatabaseConnection;
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
 use crate::entities::roles::{CreateRole, UpdateRole};
 use crate::entities::permissions::{CreatePermission, UpdatePermission};
 use crate::entities::role_permissions::{AssignPermissionToRole, RemovePermissionFromRole};
 use crate::repository::roles_repository::RoleRepository;
 use crate::repository::permissions_repository::PermissionsRepository;
+use crate::repository::sessions_repository::SessionRepository;
 use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::extractor::list_query_extractor::ListQuery;
 
-pub async fn get_all_roles(db: web::Data<DatabaseConnection>, claims: ClaimsExtractor) -> impl Responder {
+pub async fn get_all_roles(db: web::Data<DatabaseConnection>, claims: ClaimsExtractor, list: ListQuery) -> impl Responder {
     if !claims.0.permissions.contains(&"roles:read".to_string()) {
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
     }
-    match RoleRepository::get_all(db.get_ref()).await {
-        Ok(roles) => HttpResponse::Ok().json(ApiResponse::new(roles)),
+    let plan = match list.resolve(RoleRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match RoleRepository::get_all(db.get_ref(), &plan).await {
+        Ok(roles) => HttpResponse::Ok().json(ApiPage::new(roles)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch roles".to_string())),
     }
 }
@@ -46,7 +52,10 @@ pub async fn update_role(db: web::Data<DatabaseConnection>, path: web::Path<i32>
     }
     let id = path.into_inner();
     match RoleRepository::update(db.get_ref(), id, update_data.into_inner()).await {
-        Ok(Some(role)) => HttpResponse::Ok().json(ApiResponse::new(role)),
+        Ok(Some(role)) => match SessionRepository::invalidate_access_for_role(db.get_ref(), role.id).await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse::new(role)),
+            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to invalidate sessions".to_string())),
+        },
         Ok(None) => HttpResponse::NotFound().json(ApiError::new(format!("Role with ID {} not found", id))),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update role".to_string())),
     }
@@ -80,7 +89,11 @@ pub async fn assign_permission_to_role(db: web::Data<DatabaseConnection>, payloa
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
     }
     match RoleRepository::assign_permission(db.get_ref(), payload.role_id, payload.permission_id).await {
-        Ok(role_permission) => HttpResponse::Ok().json(ApiResponse::new(role_permission)),
+        // Tokens carry the role's permissions, so holders must refresh to see the change
+        Ok(role_permission) => match SessionRepository::invalidate_access_for_role(db.get_ref(), payload.role_id).await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse::new(role_permission)),
+            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to invalidate sessions".to_string())),
+        },
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to assign permission".to_string())),
     }
 }
@@ -90,18 +103,25 @@ pub async fn remove_permission_from_role(db: web::Data<DatabaseConnection>, payl
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
     }
     match RoleRepository::remove_permission(db.get_ref(), payload.role_id, payload.permission_id).await {
-        Ok(rows_affected) if rows_affected > 0 => HttpResponse::Ok().json(ApiResponse::new("Permission removed successfully".to_string())),
+        Ok(rows_affected) if rows_affected > 0 => match SessionRepository::invalidate_access_for_role(db.get_ref(), payload.role_id).await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse::new("Permission removed successfully".to_string())),
+            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to invalidate sessions".to_string())),
+        },
         Ok(_) => HttpResponse::NotFound().json(ApiError::new("Permission not found for role".to_string())),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to remove permission".to_string())),
     }
 }
 
-pub async fn get_all_permissions(db: web::Data<DatabaseConnection>, claims: ClaimsExtractor) -> impl Responder {
+pub async fn get_all_permissions(db: web::Data<DatabaseConnection>, claims: ClaimsExtractor, list: ListQuery) -> impl Responder {
     if !claims.0.permissions.contains(&"permissions:read".to_string()) {
         return HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges".to_string()));
     }
-    match PermissionsRepository::get_all(db.get_ref()).await {
-        Ok(permissions) => HttpResponse::Ok().json(ApiResponse::new(permissions)),
+    let plan = match list.resolve(PermissionsRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match PermissionsRepository::get_all(db.get_ref(), &plan).await {
+        Ok(permissions) => HttpResponse::Ok().json(ApiPage::new(permissions)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch permissions".to_string())),
     }
 }
@@ -122,7 +142,10 @@ pub async fn update_permission(db: web::Data<DatabaseConnection>, path: web::Pat
     }
     let id = path.into_inner();
     match PermissionsRepository::update(db.get_ref(), id, update_data.into_inner()).await {
-        Ok(Some(permission)) => HttpResponse::Ok().json(ApiResponse::new(permission)),
+        Ok(Some(permission)) => match SessionRepository::invalidate_access_for_all(db.get_ref()).await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse::new(permission)),
+            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to invalidate sessions".to_string())),
+        },
         Ok(None) => HttpResponse::NotFound().json(ApiError::new(format!("Permission with ID {} not found", id))),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update permission".to_string())),
     }
@@ -134,7 +157,10 @@ pub async fn delete_permission(db: web::Data<DatabaseConnection>, path: web::Pat
     }
     let id = path.into_inner();
     match PermissionsRepository::delete(db.get_ref(), id).await {
-        Ok(rows_affected) if rows_affected > 0 => HttpResponse::Ok().json(ApiResponse::new("Permission deleted successfully".to_string())),
+        Ok(rows_affected) if rows_affected > 0 => match SessionRepository::invalidate_access_for_all(db.get_ref()).await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse::new("Permission deleted successfully".to_string())),
+            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to invalidate sessions".to_string())),
+        },
         Ok(_) => HttpResponse::NotFound().json(ApiError::new(format!("Permission with ID {} not found", id))),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to delete permission".to_string())),
     }
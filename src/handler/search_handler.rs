@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::store_scope::StoreScope;
+use crate::entities::customers;
+use crate::entities::products::{self, ProductWithDetails};
+use crate::helper::response::{ApiResponse, ApiError};
+use crate::repository::customers_repository::CustomerRepository;
+use crate::repository::inventory_repository::InventoryRepository;
+use crate::repository::products_repository::ProductRepository;
+use crate::search::{SearchIndex, DEFAULT_RESULTS, MAX_RESULTS};
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    /// The store whose stock products are annotated with; defaults to the caller's store.
+    pub store_id: Option<i32>,
+    pub limit: Option<usize>,
+}
+
+impl SearchQuery {
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_RESULTS).clamp(1, MAX_RESULTS)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProductSearchResult {
+    #[serde(flatten)]
+    pub product: ProductWithDetails,
+    pub score: f32,
+    /// Quantity in the store searched from, or `None` when there is no store to look in.
+    pub stock: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct CustomerSearchResult {
+    #[serde(flatten)]
+    pub customer: customers::Model,
+    pub score: f32,
+}
+
+pub async fn search_products(
+    db: web::Data<DatabaseConnection>,
+    search: web::Data<SearchIndex>,
+    scope: StoreScope,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    let store_id = match scope.resolve_store(query.store_id) {
+        Ok(store_id) => Some(store_id),
+        Err(response) if query.store_id.is_some() => return response,
+        Err(_) => None,
+    };
+
+    let hits = search.search_products(&query.q, query.limit());
+    if hits.is_empty() {
+        return HttpResponse::Ok().json(ApiResponse::new(Vec::<ProductSearchResult>::new()));
+    }
+    let ids: Vec<i32> = hits.iter().map(|hit| hit.id).collect();
+
+    let products = match ProductRepository::find_with_details(db.get_ref(), Condition::all().add(products::Column::Id.is_in(ids.clone()))).await {
+        Ok(products) => products,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to search products".to_string())),
+    };
+    let stock = match store_id {
+        Some(store_id) => match InventoryRepository::quantities_in_store(db.get_ref(), store_id, ids).await {
+            Ok(stock) => Some(stock),
+            Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to search products".to_string())),
+        },
+        None => None,
+    };
+
+    // In the index's order; products deleted since they were indexed are left out
+    let mut products: HashMap<i32, ProductWithDetails> = products.into_iter().map(|product| (product.id, product)).collect();
+    let results: Vec<ProductSearchResult> = hits
+        .into_iter()
+        .filter_map(|hit| {
+            products.remove(&hit.id).map(|product| ProductSearchResult {
+                stock: stock.as_ref().map(|stock| stock.get(&hit.id).copied().unwrap_or(0)),
+                product,
+                score: hit.score,
+            })
+        })
+        .collect();
+    HttpResponse::Ok().json(ApiResponse::new(results))
+}
+
+pub async fn search_customers(
+    db: web::Data<DatabaseConnection>,
+    search: web::Data<SearchIndex>,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    let hits = search.search_customers(&query.q, query.limit());
+    if hits.is_empty() {
+        return HttpResponse::Ok().json(ApiResponse::new(Vec::<CustomerSearchResult>::new()));
+    }
+
+    let customers = match CustomerRepository::find_by_ids(db.get_ref(), hits.iter().map(|hit| hit.id).collect()).await {
+        Ok(customers) => customers,
+        Err(_) => return HttpResponse::InternalServerError().json(ApiError::new("Failed to search customers".to_string())),
+    };
+
+    let mut customers: HashMap<i32, customers::Model> = customers.into_iter().map(|customer| (customer.id, customer)).collect();
+    let results: Vec<CustomerSearchResult> = hits
+        .into_iter()
+        .filter_map(|hit| customers.remove(&hit.id).map(|customer| CustomerSearchResult { customer, score: hit.score }))
+        .collect();
+    HttpResponse::Ok().json(ApiResponse::new(results))
+}
@@ -1,20 +1,100 @@
 use actix_web::{
-    get,
-    post,
-    web::{Data, Json},
+    web::{Data, Json, Path},
     HttpResponse,
     Responder,
 };
+use serde_json::Value;
 
-use crate::entities::settings_model::Settings as SettingsModel;
+use crate::entities::settings_model::{
+    diff_config, merge_json, upgrade_config, FieldError, Settings as SettingsModel, SECTIONS,
+};
+use crate::entities::settings_versions::{SettingsVersionDetails, SettingsVersionSummary};
+use crate::entities::store_settings::StoreSettingsDetails;
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::guard::store_guard::StoreAccessGuard;
+use crate::helper::response::{ApiError, ApiPage, ApiResponse, ApiValidationError};
 use crate::repository::settings_repository;
 use crate::AppState;
 
-#[get("/settings")]
+fn invalid(errors: Vec<FieldError>) -> HttpResponse {
+    HttpResponse::UnprocessableEntity().json(ApiValidationError::new("Settings are invalid".to_string(), errors))
+}
+
+/// Top level sections that differ between two configs, for the version summary.
+fn changed_sections(current: &SettingsModel, updated: &SettingsModel) -> Vec<String> {
+    let (Ok(current), Ok(updated)) = (serde_json::to_value(current), serde_json::to_value(updated)) else {
+        return vec![];
+    };
+    let mut sections: Vec<String> = diff_config(&current, &updated)
+        .into_iter()
+        .map(|change| change.field.split('.').next().unwrap_or_default().to_string())
+        .collect();
+    sections.dedup();
+    sections
+}
+
+/// Validates and saves the settings as a new version. Saving unchanged settings does not
+/// create a version.
+async fn save(
+    data: &AppState,
+    current: &SettingsModel,
+    updated: SettingsModel,
+    changed_by: i32,
+    summary: Option<String>,
+) -> HttpResponse {
+    let db = &data.db;
+    if let Err(errors) = updated.validate() {
+        return invalid(errors);
+    }
+
+    let sections = changed_sections(current, &updated);
+    if sections.is_empty() && summary.is_none() {
+        return match settings_repository::current_version(db).await {
+            Ok(version) => HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "message": "Settings are unchanged.",
+                "data": updated.masked(),
+                "version": version
+            })),
+            Err(e) => {
+                eprintln!("Error getting settings version: {}", e);
+                HttpResponse::InternalServerError().json("Failed to save settings")
+            }
+        };
+    }
+    let summary = summary.unwrap_or_else(|| format!("Updated {}", sections.join(", ")));
+
+    match settings_repository::update_settings(db, updated, Some(changed_by), summary).await {
+        Ok((updated_settings, version)) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "message": "Settings saved successfully.",
+                "data": updated_settings.masked(),
+                "version": version
+            }))
+        }
+        Err(e) => {
+            eprintln!("Error saving settings: {}", e);
+            HttpResponse::InternalServerError().json(format!("Failed to save settings: {}", e))
+        }
+    }
+}
+
+/// Secrets come back masked; they can be replaced but never read through the API.
 pub async fn get_settings(data: Data<AppState>) -> impl Responder {
     let db = &data.db;
-    match settings_repository::get_settings(db).await {
-        Ok(settings) => HttpResponse::Ok().json(serde_json::json!({ "data": settings })),
+    let result = async {
+        let settings = settings_repository::get_settings(db).await?;
+        let version = settings_repository::current_version(db).await?;
+        Ok::<_, sea_orm::DbErr>((settings, version))
+    }
+    .await;
+
+    match result {
+        Ok((settings, version)) => {
+            HttpResponse::Ok().json(serde_json::json!({ "data": settings.masked(), "version": version }))
+        }
         Err(e) => {
             eprintln!("Error getting settings: {}", e);
             HttpResponse::InternalServerError().json("Failed to retrieve settings")
@@ -22,25 +102,211 @@ pub async fn get_settings(data: Data<AppState>) -> impl Responder {
     }
 }
 
-#[post("/settings")]
+/// Replaces the whole config. Every section must be present.
 pub async fn save_settings(
     data: Data<AppState>,
-    payload: Json<SettingsModel>,
+    claims: ClaimsExtractor,
+    payload: Json<Value>,
+) -> impl Responder {
+    let mut settings_data: SettingsModel = match serde_json::from_value(payload.into_inner()) {
+        Ok(settings) => settings,
+        Err(e) => return invalid(vec![FieldError::new("settings", &e.to_string())]),
+    };
+
+    let current = match settings_repository::get_settings(&data.db).await {
+        Ok(current) => current,
+        Err(e) => {
+            eprintln!("Error getting settings: {}", e);
+            return HttpResponse::InternalServerError().json("Failed to save settings");
+        }
+    };
+    settings_data.keep_masked_secrets(&current);
+
+    save(&data, &current, settings_data, claims.0.sub, None).await
+}
+
+/// Updates one section, e.g. `email`. Fields left out of the payload keep their current values.
+pub async fn update_settings_section(
+    data: Data<AppState>,
+    claims: ClaimsExtractor,
+    path: Path<String>,
+    payload: Json<Value>,
 ) -> impl Responder {
+    let section = path.into_inner();
+    if !SECTIONS.contains(&section.as_str()) {
+        return HttpResponse::NotFound().json(ApiError::new(format!("Unknown settings section '{}'", section)));
+    }
+    let patch = payload.into_inner();
+    if !patch.is_object() {
+        return invalid(vec![FieldError::new(&section, "Section must be a JSON object")]);
+    }
+
+    let current = match settings_repository::get_settings(&data.db).await {
+        Ok(current) => current,
+        Err(e) => {
+            eprintln!("Error getting settings: {}", e);
+            return HttpResponse::InternalServerError().json("Failed to save settings");
+        }
+    };
+
+    let mut config = match serde_json::to_value(&current) {
+        Ok(config) => config,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Failed to save settings: {}", e)),
+    };
+    if let Some(target) = config.get_mut(&section) {
+        merge_json(target, patch);
+    }
+    let mut updated: SettingsModel = match serde_json::from_value(config) {
+        Ok(settings) => settings,
+        Err(e) => return invalid(vec![FieldError::new(&section, &e.to_string())]),
+    };
+    updated.keep_masked_secrets(&current);
+
+    save(&data, &current, updated, claims.0.sub, None).await
+}
+
+pub async fn get_settings_versions(data: Data<AppState>, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(settings_repository::version_list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match settings_repository::get_versions(&data.db, &plan).await {
+        Ok(versions) => HttpResponse::Ok().json(ApiPage::new(versions.map(SettingsVersionSummary::from))),
+        Err(e) => {
+            eprintln!("Error getting settings versions: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new("Failed to retrieve settings versions".to_string()))
+        }
+    }
+}
+
+/// A version with its config and a diff against the version before it.
+pub async fn get_settings_version(data: Data<AppState>, path: Path<i32>) -> impl Responder {
     let db = &data.db;
-    let settings_data = payload.into_inner();
+    let version = path.into_inner();
+    let result = async {
+        let Some(model) = settings_repository::find_version(db, version).await? else {
+            return Ok(None);
+        };
+        let changes = match settings_repository::find_previous_version(db, version).await? {
+            Some(previous) => diff_config(&upgrade_config(previous.config), &upgrade_config(model.config.clone())),
+            None => vec![],
+        };
+        Ok::<_, sea_orm::DbErr>(Some(SettingsVersionDetails { version: model, changes }))
+    }
+    .await;
 
-    match settings_repository::update_settings(db, settings_data).await {
-        Ok(updated_settings) => {
-            HttpResponse::Ok().json(serde_json::json!({
-                "status": "success",
-                "message": "Settings saved successfully.",
-                "data": updated_settings
-            }))
+    match result {
+        Ok(Some(details)) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Ok(None) => HttpResponse::NotFound().json(ApiError::new("Settings version not found".to_string())),
+        Err(e) => {
+            eprintln!("Error getting settings version: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new("Failed to retrieve settings version".to_string()))
+        }
+    }
+}
+
+/// Restores an earlier version as a new version. Secrets are not part of the history and
+/// keep their current values.
+pub async fn rollback_settings(
+    data: Data<AppState>,
+    claims: ClaimsExtractor,
+    path: Path<i32>,
+) -> impl Responder {
+    let db = &data.db;
+    let version = path.into_inner();
+
+    let target = match settings_repository::find_version(db, version).await {
+        Ok(Some(target)) => target,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("Settings version not found".to_string())),
+        Err(e) => {
+            eprintln!("Error getting settings version: {}", e);
+            return HttpResponse::InternalServerError().json("Failed to roll back settings");
         }
+    };
+    let current = match settings_repository::get_settings(db).await {
+        Ok(current) => current,
         Err(e) => {
-            eprintln!("Error saving settings: {}", e);
-            HttpResponse::InternalServerError().json("Failed to save settings")
+            eprintln!("Error getting settings: {}", e);
+            return HttpResponse::InternalServerError().json("Failed to roll back settings");
+        }
+    };
+
+    let mut restored = match SettingsModel::from_stored(target.config) {
+        Ok(settings) => settings,
+        Err(e) => return invalid(vec![FieldError::new("settings", &e)]),
+    };
+    restored.keep_secrets(&current);
+
+    save(&data, &current, restored, claims.0.sub, Some(format!("Rolled back to version {}", version))).await
+}
+
+async fn store_settings_details(db: &sea_orm::DbConn, store_id: i32) -> Result<StoreSettingsDetails, String> {
+    let settings = settings_repository::get_settings(db).await.map_err(|e| e.to_string())?;
+    let overrides = settings_repository::get_store_overrides(db, store_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|o| o.overrides)
+        .unwrap_or_else(|| serde_json::json!({}));
+    let effective = settings.with_store_overrides(&overrides).map_err(|errors| {
+        let fields: Vec<String> = errors.into_iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+        format!("Settings overrides for store {} are invalid: {}", store_id, fields.join("; "))
+    })?;
+    Ok(StoreSettingsDetails { store_id, overrides, effective: effective.masked() })
+}
+
+/// A store's overrides and the effective settings they produce.
+pub async fn get_store_settings(guard: StoreAccessGuard, data: Data<AppState>) -> impl Responder {
+    match store_settings_details(&data.db, guard.store.id).await {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => {
+            eprintln!("Error getting store settings: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new("Failed to retrieve store settings".to_string()))
+        }
+    }
+}
+
+/// Replaces a store's overrides, e.g. `{"receipt": {"headerText": "..."}}`. Fields left out
+/// follow the global settings.
+pub async fn update_store_settings(
+    guard: StoreAccessGuard,
+    data: Data<AppState>,
+    payload: Json<Value>,
+) -> impl Responder {
+    let db = &data.db;
+    let store_id = guard.store.id;
+    let overrides = payload.into_inner();
+
+    let settings = match settings_repository::get_settings(db).await {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error getting settings: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new("Failed to save store settings".to_string()));
+        }
+    };
+    if let Err(errors) = settings.with_store_overrides(&overrides) {
+        return invalid(errors);
+    }
+
+    if let Err(e) = settings_repository::set_store_overrides(db, store_id, overrides).await {
+        eprintln!("Error saving store settings: {}", e);
+        return HttpResponse::InternalServerError().json(ApiError::new("Failed to save store settings".to_string()));
+    }
+    match store_settings_details(db, store_id).await {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => {
+            eprintln!("Error getting store settings: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new("Failed to retrieve store settings".to_string()))
+        }
+    }
+}
+
+/// Removes every override, so the store follows the global settings again.
+pub async fn delete_store_settings(guard: StoreAccessGuard, data: Data<AppState>) -> impl Responder {
+    match settings_repository::delete_store_overrides(&data.db, guard.store.id).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse::new("Store settings overrides removed".to_string())),
+        Err(e) => {
+            eprintln!("Error deleting store settings: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new("Failed to delete store settings".to_string()))
         }
     }
 }
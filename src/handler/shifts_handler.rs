@@ -0,0 +1,249 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::{DatabaseConnection, DbErr, prelude::Decimal};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{cash_movements, shifts};
+use crate::entities::shifts::{DrawerReport, DrawerReportQueryParams, ShiftDetails, ShiftQueryParams};
+use crate::extractor::claims_extractor::ClaimsExtractor;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::extractor::terminal_extractor::TerminalExtractor;
+use crate::guard::shift_guard::ShiftAccessGuard;
+use crate::repository::{settings_repository, shifts_repository::ShiftRepository};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+
+async fn build_shift_details(db: &DatabaseConnection, shift: shifts::Model) -> Result<ShiftDetails, DbErr> {
+    let cash_movements = ShiftRepository::get_movements_for_shift(db, shift.id).await?;
+    let current_expected_cash = match shift.expected_cash {
+        Some(expected) => expected,
+        None => ShiftRepository::compute_expected_cash(db, &shift, &cash_movements).await?,
+    };
+    Ok(ShiftDetails { shift, cash_movements, current_expected_cash })
+}
+
+/// An X or Z report: the store's sales over the period, and the given shifts with their over/short.
+async fn build_drawer_report(
+    db: &DatabaseConnection,
+    report_type: &str,
+    store_id: i32,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    shifts: Vec<shifts::Model>,
+) -> Result<DrawerReport, DbErr> {
+    let settings = settings_repository::get_settings_for_store(db, store_id).await?;
+    let summary = ShiftRepository::summarize(db, store_id, None, period_start, period_end, settings.general.default_tax_rate).await?;
+
+    let mut shift_details = Vec::with_capacity(shifts.len());
+    for shift in shifts {
+        shift_details.push(build_shift_details(db, shift).await?);
+    }
+    let total_over_short = shift_details.iter().filter_map(|d| d.shift.over_short).sum();
+
+    Ok(DrawerReport {
+        report_type: report_type.to_string(),
+        store_id,
+        period_start,
+        period_end,
+        generated_at: Utc::now(),
+        summary,
+        shifts: shift_details,
+        total_over_short,
+    })
+}
+
+pub async fn open_shift(
+    claims: ClaimsExtractor,
+    terminal: TerminalExtractor,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<shifts::OpenShift>,
+) -> impl Responder {
+    let claims = claims.0;
+    let payload = payload.into_inner();
+
+    let store_id = match claims.store_id {
+        Some(id) => id,
+        None => return HttpResponse::Forbidden().json(ApiError::new("User is not assigned to a store".to_string())),
+    };
+    let terminal_name = match &terminal.0 {
+        Some(t) if t.store_id != store_id => return HttpResponse::Forbidden().json(ApiError::new("Terminal belongs to another store".to_string())),
+        Some(t) => t.name.clone(),
+        None if payload.terminal_name.trim().is_empty() => return HttpResponse::BadRequest().json(ApiError::new("terminal_name is required".to_string())),
+        None => payload.terminal_name.clone(),
+    };
+    if payload.opening_float < Decimal::ZERO {
+        return HttpResponse::BadRequest().json(ApiError::new("opening_float cannot be negative".to_string()));
+    }
+
+    match ShiftRepository::find_open_for_employee(db.get_ref(), claims.sub).await {
+        Ok(Some(_)) => return HttpResponse::Conflict().json(ApiError::new("Employee already has an open shift".to_string())),
+        Ok(None) => {}
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check open shifts: {}", e))),
+    }
+
+    if let Some(terminal_id) = terminal.id() {
+        match ShiftRepository::find_open_for_terminal(db.get_ref(), terminal_id).await {
+            Ok(Some(_)) => return HttpResponse::Conflict().json(ApiError::new("Terminal already has an open shift".to_string())),
+            Ok(None) => {}
+            Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check open shifts: {}", e))),
+        }
+    }
+
+    match ShiftRepository::open(db.get_ref(), claims.sub, store_id, terminal.id(), terminal_name, payload.opening_float).await {
+        Ok(shift) => HttpResponse::Created().json(ApiResponse::new(shift)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to open shift: {}", e))),
+    }
+}
+
+pub async fn get_current_shift(claims: ClaimsExtractor, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let shift = match ShiftRepository::find_open_for_employee(db.get_ref(), claims.0.sub).await {
+        Ok(Some(shift)) => shift,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("No open shift".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch shift: {}", e))),
+    };
+
+    match build_shift_details(db.get_ref(), shift).await {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch shift details: {}", e))),
+    }
+}
+
+pub async fn get_all_shifts(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<ShiftQueryParams>,
+    list: ListQuery,
+) -> impl Responder {
+    let params = query_params.into_inner();
+    let scope = match scope.restrict_to(params.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
+    };
+    let plan = match list.resolve(ShiftRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+
+    match ShiftRepository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(shifts) => HttpResponse::Ok().json(ApiPage::new(shifts)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch shifts: {}", e))),
+    }
+}
+
+pub async fn get_shift_by_id(guard: ShiftAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match build_shift_details(db.get_ref(), guard.shift).await {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch shift details: {}", e))),
+    }
+}
+
+pub async fn add_cash_movement(
+    guard: ShiftAccessGuard,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<cash_movements::CreateCashMovement>,
+) -> impl Responder {
+    let payload = payload.into_inner();
+
+    if guard.shift.status != "OPEN" {
+        return HttpResponse::BadRequest().json(ApiError::new("Shift is already closed".to_string()));
+    }
+    if payload.movement_type != "CASH_IN" && payload.movement_type != "PAY_OUT" {
+        return HttpResponse::BadRequest().json(ApiError::new("movement_type must be CASH_IN or PAY_OUT".to_string()));
+    }
+    if payload.amount <= Decimal::ZERO {
+        return HttpResponse::BadRequest().json(ApiError::new("amount must be greater than zero".to_string()));
+    }
+    if payload.reason.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new("reason is required".to_string()));
+    }
+
+    match ShiftRepository::add_cash_movement(db.get_ref(), guard.shift.id, guard.claims.sub, payload).await {
+        Ok(movement) => HttpResponse::Created().json(ApiResponse::new(movement)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to record cash movement: {}", e))),
+    }
+}
+
+pub async fn close_shift(
+    guard: ShiftAccessGuard,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<shifts::CloseShift>,
+) -> impl Responder {
+    let payload = payload.into_inner();
+    let shift = guard.shift;
+
+    if shift.status != "OPEN" {
+        return HttpResponse::BadRequest().json(ApiError::new("Shift is already closed".to_string()));
+    }
+    if payload.counted_cash < Decimal::ZERO {
+        return HttpResponse::BadRequest().json(ApiError::new("counted_cash cannot be negative".to_string()));
+    }
+
+    let movements = match ShiftRepository::get_movements_for_shift(db.get_ref(), shift.id).await {
+        Ok(movements) => movements,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch cash movements: {}", e))),
+    };
+    let expected_cash = match ShiftRepository::compute_expected_cash(db.get_ref(), &shift, &movements).await {
+        Ok(expected) => expected,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to compute expected cash: {}", e))),
+    };
+
+    match ShiftRepository::close(db.get_ref(), shift, expected_cash, payload.counted_cash, payload.notes).await {
+        Ok(shift) => HttpResponse::Ok().json(ApiResponse::new(ShiftDetails {
+            current_expected_cash: expected_cash,
+            shift,
+            cash_movements: movements,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to close shift: {}", e))),
+    }
+}
+
+/// Mid-day report: today's takings so far and the drawers that are still open.
+pub async fn get_x_report(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<DrawerReportQueryParams>,
+) -> impl Responder {
+    let store_id = match scope.resolve_store(query_params.store_id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let now = Utc::now();
+    let period_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+    let store_scope = scope.restrict_to(Some(store_id)).unwrap_or(scope);
+    let open_shifts = match ShiftRepository::get_open(db.get_ref(), &store_scope).await {
+        Ok(shifts) => shifts,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch shifts: {}", e))),
+    };
+
+    match build_drawer_report(db.get_ref(), "X", store_id, period_start, now, open_shifts).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::new(report)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to build X-report: {}", e))),
+    }
+}
+
+/// End-of-day report: the full day's takings and every shift that ran on that day.
+pub async fn get_z_report(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<DrawerReportQueryParams>,
+) -> impl Responder {
+    let store_id = match scope.resolve_store(query_params.store_id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let date = query_params.date.unwrap_or_else(|| Utc::now().date_naive());
+    let period_start = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let period_end = period_start + Duration::days(1) - Duration::seconds(1);
+
+    let day_shifts = match ShiftRepository::get_for_store_between(db.get_ref(), store_id, period_start, period_end).await {
+        Ok(shifts) => shifts,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch shifts: {}", e))),
+    };
+
+    match build_drawer_report(db.get_ref(), "Z", store_id, period_start, period_end, day_shifts).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::new(report)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to build Z-report: {}", e))),
+    }
+}
@@ -1,33 +1,20 @@
 use crate::repository::stores_repository::StoreRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 use crate::entities::stores::{CreateStore, UpdateStore};
 use sea_orm::DatabaseConnection;
 // use crate::guard::role_guard::{Claims, has_role, ErrorResponse as RoleErrorResponse};
+use crate::auth::store_scope::StoreScope;
 use crate::guard::store_guard::StoreAccessGuard;
 
-pub async fn get_all_stores(db: web::Data<DatabaseConnection>) -> impl Responder {
-    // This logic is now broken because it depends on claims.
-    // You will need to re-implement this using actix-web middleware.
-    /*
-    if has_role(&claims, &["Admin", "Owner"]) {
-        match StoreRepository::get_all(db.get_ref()).await {
-            Ok(stores) => HttpResponse::Ok().json(ApiResponse::new(stores)),
-            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch stores".to_string())),
-        }
-    } else if claims.store_id.is_some() {
-        // Store Managers and other employees can only see their own store
-        match StoreRepository::find_by_id(db.get_ref(), claims.store_id.unwrap()).await {
-            Ok(Some(store)) => HttpResponse::Ok().json(ApiResponse::new(vec![store])), // Return as a vec for consistency
-            Ok(None) => HttpResponse::NotFound().json(ApiError::new("Store not found for this employee".to_string())),
-            Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch store".to_string())),
-        }
-    } else {
-        HttpResponse::Forbidden().json(ApiError::new("Forbidden: Insufficient privileges or missing store ID".to_string()))
-    }
-    */
-    match StoreRepository::get_all(db.get_ref()).await {
-        Ok(stores) => HttpResponse::Ok().json(ApiResponse::new(stores)),
+pub async fn get_all_stores(scope: StoreScope, db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
+    let plan = match list.resolve(StoreRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match StoreRepository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(stores) => HttpResponse::Ok().json(ApiPage::new(stores)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch stores".to_string())),
     }
 }
@@ -1,13 +1,20 @@
 use crate::repository::suppliers_repository::SupplierRepository;
 use actix_web::{web, HttpResponse, Responder};
-use crate::helper::response::{ApiResponse, ApiError};
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::extractor::list_query_extractor::ListQuery;
 use crate::entities::suppliers::{CreateSupplier, UpdateSupplier};
-use sea_orm::DatabaseConnection;
+use crate::entities::products;
+use crate::search::SearchIndex;
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection};
 
-pub async fn get_all_suppliers(db: web::Data<DatabaseConnection>) -> impl Responder {
+pub async fn get_all_suppliers(db: web::Data<DatabaseConnection>, list: ListQuery) -> impl Responder {
     // TODO: Add role check middleware
-    match SupplierRepository::get_all(db.get_ref()).await {
-        Ok(suppliers) => HttpResponse::Ok().json(ApiResponse::new(suppliers)),
+    let plan = match list.resolve(SupplierRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+    match SupplierRepository::get_all(db.get_ref(), &plan).await {
+        Ok(suppliers) => HttpResponse::Ok().json(ApiPage::new(suppliers)),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to fetch suppliers".to_string())),
     }
 }
@@ -29,10 +36,14 @@ pub async fn get_supplier_by_id(db: web::Data<DatabaseConnection>, id: web::Path
     }
 }
 
-pub async fn update_supplier(db: web::Data<DatabaseConnection>, id: web::Path<i32>, update_data: web::Json<UpdateSupplier>) -> impl Responder {
+pub async fn update_supplier(db: web::Data<DatabaseConnection>, search: web::Data<SearchIndex>, id: web::Path<i32>, update_data: web::Json<UpdateSupplier>) -> impl Responder {
     // TODO: Add role check middleware
     match SupplierRepository::update(db.get_ref(), id.into_inner(), update_data.into_inner()).await {
-        Ok(Some(supplier)) => HttpResponse::Ok().json(ApiResponse::new(supplier)),
+        Ok(Some(supplier)) => {
+            // Products are found by their supplier's name
+            search.products_changed(db.get_ref(), Condition::all().add(products::Column::SupplierId.eq(supplier.id))).await;
+            HttpResponse::Ok().json(ApiResponse::new(supplier))
+        }
         Ok(None) => HttpResponse::NotFound().json(ApiError::new("Supplier not found".to_string())),
         Err(_) => HttpResponse::InternalServerError().json(ApiError::new("Failed to update supplier".to_string())),
     }
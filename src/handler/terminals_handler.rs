@@ -0,0 +1,154 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::DatabaseConnection;
+use chrono::{Duration, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+
+use crate::auth::auth_service;
+use crate::entities::terminals::{CreateTerminal, PairTerminal, TerminalCredential, TerminalPairingCode, TerminalQueryParams, UpdateTerminal};
+use crate::auth::store_scope::StoreScope;
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::extractor::terminal_extractor::TerminalExtractor;
+use crate::guard::terminal_guard::TerminalAccessGuard;
+use crate::repository::terminals_repository::TerminalRepository;
+use crate::repository::sessions_repository::SessionRepository;
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+
+const PAIRING_CODE_TTL_MINUTES: i64 = 15;
+
+fn generate_pairing_code() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(8)
+        .map(|c| char::from(c).to_ascii_uppercase())
+        .collect()
+}
+
+pub async fn create_terminal(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<CreateTerminal>,
+) -> impl Responder {
+    let payload = payload.into_inner();
+    let store_id = match scope.resolve_store(payload.store_id) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    if payload.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new("name is required".to_string()));
+    }
+
+    let pairing_code = generate_pairing_code();
+    let expires_at = Utc::now() + Duration::minutes(PAIRING_CODE_TTL_MINUTES);
+
+    match TerminalRepository::create(db.get_ref(), store_id, payload.name, pairing_code.clone(), expires_at).await {
+        Ok(terminal) => HttpResponse::Created().json(ApiResponse::new(TerminalPairingCode { terminal, pairing_code, expires_at })),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to create terminal: {}", e))),
+    }
+}
+
+pub async fn get_all_terminals(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<TerminalQueryParams>,
+    list: ListQuery,
+) -> impl Responder {
+    let scope = match scope.restrict_to(query_params.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
+    };
+    let plan = match list.resolve(TerminalRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+
+    match TerminalRepository::get_all(db.get_ref(), &scope, &plan).await {
+        Ok(terminals) => HttpResponse::Ok().json(ApiPage::new(terminals)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch terminals: {}", e))),
+    }
+}
+
+pub async fn get_terminal_by_id(guard: TerminalAccessGuard) -> impl Responder {
+    HttpResponse::Ok().json(ApiResponse::new(guard.terminal))
+}
+
+pub async fn update_terminal(
+    guard: TerminalAccessGuard,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<UpdateTerminal>,
+) -> impl Responder {
+    let name = match payload.into_inner().name {
+        Some(name) if !name.trim().is_empty() => name,
+        Some(_) => return HttpResponse::BadRequest().json(ApiError::new("name cannot be empty".to_string())),
+        None => return HttpResponse::Ok().json(ApiResponse::new(guard.terminal)),
+    };
+
+    match TerminalRepository::rename(db.get_ref(), guard.terminal, name).await {
+        Ok(terminal) => HttpResponse::Ok().json(ApiResponse::new(terminal)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to update terminal: {}", e))),
+    }
+}
+
+/// Issues a fresh pairing code, e.g. to replace a device. The old device credential stops working.
+pub async fn reset_terminal_pairing(guard: TerminalAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let pairing_code = generate_pairing_code();
+    let expires_at = Utc::now() + Duration::minutes(PAIRING_CODE_TTL_MINUTES);
+
+    match TerminalRepository::reset_pairing(db.get_ref(), guard.terminal, pairing_code.clone(), expires_at).await {
+        Ok(terminal) => HttpResponse::Ok().json(ApiResponse::new(TerminalPairingCode { terminal, pairing_code, expires_at })),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to reset terminal pairing: {}", e))),
+    }
+}
+
+pub async fn disable_terminal(guard: TerminalAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match TerminalRepository::disable(db.get_ref(), guard.terminal).await {
+        // Sign out whoever is using the till with a PIN
+        Ok(terminal) => match SessionRepository::revoke_all_for_terminal(db.get_ref(), terminal.id).await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse::new(terminal)),
+            Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to end terminal sessions: {}", e))),
+        },
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to disable terminal: {}", e))),
+    }
+}
+
+/// Called from the till itself, by a signed-in employee of the terminal's store, to exchange
+/// a pairing code for the device credential.
+pub async fn pair_terminal(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<PairTerminal>,
+) -> impl Responder {
+    let code = payload.pairing_code.trim().to_ascii_uppercase();
+
+    let terminal = match TerminalRepository::find_by_pairing_code(db.get_ref(), &code).await {
+        Ok(Some(terminal)) => terminal,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("Invalid pairing code".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch terminal: {}", e))),
+    };
+
+    if terminal.status != "PENDING" || terminal.pairing_code_expires_at.is_none_or(|expires_at| expires_at < Utc::now()) {
+        return HttpResponse::BadRequest().json(ApiError::new("Pairing code has expired".to_string()));
+    }
+    if !scope.includes_store(terminal.store_id) {
+        return HttpResponse::Forbidden().json(ApiError::new("Terminal belongs to another store".to_string()));
+    }
+
+    let secret = ::uuid::Uuid::new_v4().simple().to_string();
+    let credential_hash = auth_service::hash_token(&secret);
+
+    match TerminalRepository::complete_pairing(db.get_ref(), terminal, credential_hash).await {
+        Ok(terminal) => {
+            let device_token = format!("{}.{}", terminal.id, secret);
+            HttpResponse::Ok().json(ApiResponse::new(TerminalCredential { terminal, device_token }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to pair terminal: {}", e))),
+    }
+}
+
+/// Lets a device check that its credential is still valid.
+pub async fn get_current_terminal(terminal: TerminalExtractor) -> impl Responder {
+    match terminal.0 {
+        Some(terminal) => HttpResponse::Ok().json(ApiResponse::new(terminal)),
+        None => HttpResponse::Unauthorized().json(ApiError::new("Missing terminal credential".to_string())),
+    }
+}
@@ -0,0 +1,546 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{time_entries, time_entry_breaks};
+use crate::entities::time_entries::{
+    ClockIn, DailyHours, EmployeeTimesheet, StartBreak, TimeEntryDetails, TimeEntryQueryParams,
+    UpdateTimeEntry, WeeklyTimesheet, WeeklyTimesheetQueryParams,
+};
+use crate::extractor::list_query_extractor::ListQuery;
+use crate::extractor::terminal_extractor::TerminalExtractor;
+use crate::guard::time_entry_guard::TimeEntryAccessGuard;
+use crate::helper::csv;
+use crate::helper::response::{ApiResponse, ApiPage, ApiError};
+use crate::repository::settings_repository;
+use crate::repository::time_entries_repository::TimeEntryRepository;
+
+fn overlap_seconds(start: DateTime<Utc>, end: DateTime<Utc>, from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    (end.min(to) - start.max(from)).num_seconds().max(0)
+}
+
+/// Seconds worked on an entry between `from` and `to`, net of unpaid breaks. Open entries
+/// and breaks run until `now`.
+fn worked_seconds(
+    entry: &time_entries::Model,
+    breaks: &[&time_entry_breaks::Model],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> i64 {
+    let clock_out = entry.clock_out_at.unwrap_or(now);
+    let unpaid: i64 = breaks
+        .iter()
+        .filter(|b| !b.paid)
+        .map(|b| {
+            let start = b.started_at.max(entry.clock_in_at);
+            let end = b.ended_at.unwrap_or(clock_out).min(clock_out);
+            overlap_seconds(start, end, from, to)
+        })
+        .sum();
+    (overlap_seconds(entry.clock_in_at, clock_out, from, to) - unpaid).max(0)
+}
+
+fn to_hours(seconds: f64) -> f64 {
+    (seconds / 36.0).round() / 100.0
+}
+
+fn details(entry: time_entries::Model, breaks: Vec<time_entry_breaks::Model>) -> TimeEntryDetails {
+    let now = Utc::now();
+    let clock_out = entry.clock_out_at.unwrap_or(now);
+    let seconds = worked_seconds(&entry, &breaks.iter().collect::<Vec<_>>(), entry.clock_in_at, clock_out, now);
+    TimeEntryDetails { entry, breaks, worked_hours: to_hours(seconds as f64) }
+}
+
+async fn load_details(db: &DatabaseConnection, entry: time_entries::Model) -> Result<TimeEntryDetails, DbErr> {
+    let breaks = TimeEntryRepository::get_breaks(db, entry.id).await?;
+    Ok(details(entry, breaks))
+}
+
+fn snapshot(entry: &time_entries::Model, breaks: &[time_entry_breaks::Model]) -> String {
+    serde_json::json!({
+        "clock_in_at": entry.clock_in_at,
+        "clock_out_at": entry.clock_out_at,
+        "breaks": breaks
+            .iter()
+            .map(|b| serde_json::json!({ "started_at": b.started_at, "ended_at": b.ended_at, "paid": b.paid }))
+            .collect::<Vec<_>>(),
+    })
+    .to_string()
+}
+
+pub async fn clock_in(
+    scope: StoreScope,
+    terminal: TerminalExtractor,
+    db: web::Data<DatabaseConnection>,
+    payload: Option<web::Json<ClockIn>>,
+) -> impl Responder {
+    let payload = payload.map(|p| p.into_inner()).unwrap_or_default();
+
+    let store_id = match &terminal.0 {
+        Some(t) if !scope.includes_store(t.store_id) => return HttpResponse::Forbidden().json(ApiError::new("Terminal belongs to another store".to_string())),
+        Some(t) => t.store_id,
+        None => match scope.resolve_store(payload.store_id) {
+            Ok(id) => id,
+            Err(response) => return response,
+        },
+    };
+
+    match TimeEntryRepository::find_open_for_employee(db.get_ref(), scope.employee_id).await {
+        Ok(Some(_)) => return HttpResponse::Conflict().json(ApiError::new("You are already clocked in".to_string())),
+        Ok(None) => {}
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check time entries: {}", e))),
+    }
+
+    match TimeEntryRepository::clock_in(db.get_ref(), scope.employee_id, store_id, terminal.id()).await {
+        Ok(entry) => HttpResponse::Created().json(ApiResponse::new(details(entry, vec![]))),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to clock in: {}", e))),
+    }
+}
+
+pub async fn clock_out(scope: StoreScope, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let entry = match TimeEntryRepository::find_open_for_employee(db.get_ref(), scope.employee_id).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return HttpResponse::BadRequest().json(ApiError::new("You are not clocked in".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entry: {}", e))),
+    };
+
+    let result = async {
+        let txn = db.begin().await?;
+        let entry = TimeEntryRepository::clock_out(&txn, entry).await?;
+        txn.commit().await?;
+        load_details(db.get_ref(), entry).await
+    }
+    .await;
+
+    match result {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to clock out: {}", e))),
+    }
+}
+
+pub async fn start_break(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    payload: Option<web::Json<StartBreak>>,
+) -> impl Responder {
+    let payload = payload.map(|p| p.into_inner()).unwrap_or_default();
+
+    let entry = match TimeEntryRepository::find_open_for_employee(db.get_ref(), scope.employee_id).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return HttpResponse::BadRequest().json(ApiError::new("You are not clocked in".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entry: {}", e))),
+    };
+    match TimeEntryRepository::find_open_break(db.get_ref(), entry.id).await {
+        Ok(Some(_)) => return HttpResponse::Conflict().json(ApiError::new("You are already on a break".to_string())),
+        Ok(None) => {}
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check breaks: {}", e))),
+    }
+
+    match TimeEntryRepository::start_break(db.get_ref(), entry.id, payload.paid).await {
+        Ok(entry_break) => HttpResponse::Created().json(ApiResponse::new(entry_break)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to start break: {}", e))),
+    }
+}
+
+pub async fn end_break(scope: StoreScope, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let entry = match TimeEntryRepository::find_open_for_employee(db.get_ref(), scope.employee_id).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return HttpResponse::BadRequest().json(ApiError::new("You are not clocked in".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entry: {}", e))),
+    };
+    let open_break = match TimeEntryRepository::find_open_break(db.get_ref(), entry.id).await {
+        Ok(Some(open_break)) => open_break,
+        Ok(None) => return HttpResponse::BadRequest().json(ApiError::new("You are not on a break".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch break: {}", e))),
+    };
+
+    match TimeEntryRepository::end_break(db.get_ref(), open_break).await {
+        Ok(entry_break) => HttpResponse::Ok().json(ApiResponse::new(entry_break)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to end break: {}", e))),
+    }
+}
+
+pub async fn get_current_entry(scope: StoreScope, db: web::Data<DatabaseConnection>) -> impl Responder {
+    let entry = match TimeEntryRepository::find_open_for_employee(db.get_ref(), scope.employee_id).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return HttpResponse::NotFound().json(ApiError::new("You are not clocked in".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entry: {}", e))),
+    };
+
+    match load_details(db.get_ref(), entry).await {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entry details: {}", e))),
+    }
+}
+
+pub async fn get_all_time_entries(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<TimeEntryQueryParams>,
+    list: ListQuery,
+) -> impl Responder {
+    let params = query_params.into_inner();
+    let scope = match scope.restrict_to(params.store_id) {
+        Some(scope) => scope,
+        None => return HttpResponse::Forbidden().json(ApiError::new("You do not have access to this store".to_string())),
+    };
+    let plan = match list.resolve(TimeEntryRepository::list_fields()) {
+        Ok(plan) => plan,
+        Err(response) => return response,
+    };
+
+    let result = async {
+        let entries = TimeEntryRepository::get_all(db.get_ref(), &scope, &plan).await?;
+        let ids = entries.items.iter().map(|e| e.id).collect();
+        let mut breaks_by_entry: HashMap<i32, Vec<time_entry_breaks::Model>> = HashMap::new();
+        for entry_break in TimeEntryRepository::get_breaks_for_entries(db.get_ref(), ids).await? {
+            breaks_by_entry.entry(entry_break.time_entry_id).or_default().push(entry_break);
+        }
+        Ok::<_, DbErr>(entries.map(|entry| {
+            let breaks = breaks_by_entry.remove(&entry.id).unwrap_or_default();
+            details(entry, breaks)
+        }))
+    }
+    .await;
+
+    match result {
+        Ok(entries) => HttpResponse::Ok().json(ApiPage::new(entries)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entries: {}", e))),
+    }
+}
+
+pub async fn get_time_entry_by_id(guard: TimeEntryAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match load_details(db.get_ref(), guard.entry).await {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entry details: {}", e))),
+    }
+}
+
+/// Corrects a time entry. Every edit needs a reason and is recorded with the values before and after.
+pub async fn update_time_entry(
+    guard: TimeEntryAccessGuard,
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<UpdateTimeEntry>,
+) -> impl Responder {
+    let payload = payload.into_inner();
+    let entry = guard.entry;
+
+    if entry.employee_id == guard.claims.sub && !guard.claims.can_access_all_stores() {
+        return HttpResponse::Forbidden().json(ApiError::new("You cannot edit your own time entries".to_string()));
+    }
+    let reason = payload.reason.trim().to_string();
+    if reason.is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new("reason is required".to_string()));
+    }
+
+    let now = Utc::now();
+    let clock_in_at = payload.clock_in_at.unwrap_or(entry.clock_in_at);
+    let clock_out_at = payload.clock_out_at.or(entry.clock_out_at);
+    if clock_in_at > now || clock_out_at.is_some_and(|out| out > now) {
+        return HttpResponse::BadRequest().json(ApiError::new("Times cannot be in the future".to_string()));
+    }
+    if clock_out_at.is_some_and(|out| out <= clock_in_at) {
+        return HttpResponse::BadRequest().json(ApiError::new("clock_out_at must be after clock_in_at".to_string()));
+    }
+    let window_end = clock_out_at.unwrap_or(now);
+
+    let previous_breaks = match TimeEntryRepository::get_breaks(db.get_ref(), entry.id).await {
+        Ok(breaks) => breaks,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch breaks: {}", e))),
+    };
+    match &payload.breaks {
+        Some(breaks) => {
+            let mut sorted: Vec<_> = breaks.iter().collect();
+            sorted.sort_by_key(|b| b.started_at);
+            if sorted.iter().any(|b| b.ended_at <= b.started_at || b.started_at < clock_in_at || b.ended_at > window_end) {
+                return HttpResponse::BadRequest().json(ApiError::new("Each break must end after it starts and fall within the entry's times".to_string()));
+            }
+            if sorted.windows(2).any(|pair| pair[1].started_at < pair[0].ended_at) {
+                return HttpResponse::BadRequest().json(ApiError::new("Breaks cannot overlap".to_string()));
+            }
+        }
+        None => {
+            if previous_breaks.iter().any(|b| b.started_at < clock_in_at || b.ended_at.is_some_and(|end| end > window_end)) {
+                return HttpResponse::BadRequest().json(ApiError::new("Existing breaks fall outside the new times; send breaks to replace them".to_string()));
+            }
+        }
+    }
+
+    let previous_values = snapshot(&entry, &previous_breaks);
+    let result = async {
+        let txn = db.begin().await?;
+        let entry = TimeEntryRepository::update_times(&txn, entry, clock_in_at, clock_out_at).await?;
+        let breaks = match payload.breaks {
+            Some(breaks) => TimeEntryRepository::replace_breaks(&txn, entry.id, breaks).await?,
+            None => previous_breaks,
+        };
+        TimeEntryRepository::record_edit(&txn, entry.id, guard.claims.sub, reason, previous_values, snapshot(&entry, &breaks)).await?;
+        txn.commit().await?;
+        Ok::<_, DbErr>(details(entry, breaks))
+    }
+    .await;
+
+    match result {
+        Ok(details) => HttpResponse::Ok().json(ApiResponse::new(details)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to update time entry: {}", e))),
+    }
+}
+
+pub async fn get_time_entry_edits(guard: TimeEntryAccessGuard, db: web::Data<DatabaseConnection>) -> impl Responder {
+    match TimeEntryRepository::get_edits(db.get_ref(), guard.entry.id).await {
+        Ok(edits) => HttpResponse::Ok().json(ApiResponse::new(edits)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to fetch time entry edits: {}", e))),
+    }
+}
+
+/// Splits worked time, given as (day of week, seconds) in the order it was worked, into regular
+/// and overtime seconds. Time past the daily limit is overtime, and so is regular time once the
+/// week's regular time has reached the weekly limit. A limit of zero means none.
+fn split_overtime(worked: &[(usize, f64)], daily_limit: f64, weekly_limit: f64) -> Vec<(f64, f64)> {
+    let mut day_seconds = [0.0; 7];
+    let mut week_regular = 0.0;
+    worked
+        .iter()
+        .map(|(day, seconds)| {
+            let daily_room = if daily_limit > 0.0 { (daily_limit - day_seconds[*day]).max(0.0) } else { f64::INFINITY };
+            let weekly_room = if weekly_limit > 0.0 { (weekly_limit - week_regular).max(0.0) } else { f64::INFINITY };
+            let regular = seconds.min(daily_room).min(weekly_room);
+            day_seconds[*day] += seconds;
+            week_regular += regular;
+            (regular, seconds - regular)
+        })
+        .collect()
+}
+
+/// Time an employee worked on one entry during one day.
+struct WorkedTime {
+    day: usize,
+    clock_in_at: DateTime<Utc>,
+    at_store: bool,
+    seconds: i64,
+}
+
+/// Hours per employee for each day of the week (UTC days) at a store, split into regular and
+/// overtime. Overtime counts everything the employee worked that week, at any store, and is
+/// taken in the order the hours were worked; the store's share is what falls on its entries.
+async fn build_weekly_timesheet(
+    db: &DatabaseConnection,
+    scope: &StoreScope,
+    store_id: i32,
+    week_start: NaiveDate,
+) -> Result<WeeklyTimesheet, DbErr> {
+    let settings = settings_repository::get_settings(db).await?.time_clock;
+    let now = Utc::now();
+    let period_start = week_start.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let period_end = period_start + Duration::days(7);
+    let day_starts: Vec<DateTime<Utc>> = (0..7).map(|d| period_start + Duration::days(d)).collect();
+
+    let store_entries = TimeEntryRepository::get_between(db, scope, period_start, period_end).await?;
+    let mut timesheets: BTreeMap<i32, EmployeeTimesheet> = BTreeMap::new();
+    for (entry, employee) in &store_entries {
+        let timesheet = timesheets.entry(entry.employee_id).or_insert_with(|| {
+            let (first_name, last_name) = employee
+                .as_ref()
+                .map(|e| (e.first_name.clone(), e.last_name.clone()))
+                .unwrap_or_default();
+            EmployeeTimesheet {
+                employee_id: entry.employee_id,
+                first_name,
+                last_name,
+                days: vec![],
+                regular_hours: 0.0,
+                overtime_hours: 0.0,
+                total_hours: 0.0,
+                all_stores_hours: 0.0,
+                open_entries: 0,
+            }
+        });
+        if entry.clock_out_at.is_none() {
+            timesheet.open_entries += 1;
+        }
+    }
+
+    let entries = TimeEntryRepository::get_for_employees_between(db, timesheets.keys().copied().collect(), period_start, period_end).await?;
+    let breaks = TimeEntryRepository::get_breaks_for_entries(db, entries.iter().map(|e| e.id).collect()).await?;
+    let mut breaks_by_entry: HashMap<i32, Vec<&time_entry_breaks::Model>> = HashMap::new();
+    for entry_break in &breaks {
+        breaks_by_entry.entry(entry_break.time_entry_id).or_default().push(entry_break);
+    }
+
+    // What each employee worked, per entry and day, in the order it was worked
+    let mut worked: HashMap<i32, Vec<WorkedTime>> = HashMap::new();
+    for entry in &entries {
+        let entry_breaks = breaks_by_entry.get(&entry.id).map(Vec::as_slice).unwrap_or(&[]);
+        for (day, day_start) in day_starts.iter().enumerate() {
+            let seconds = worked_seconds(entry, entry_breaks, *day_start, *day_start + Duration::days(1), now);
+            if seconds > 0 {
+                worked.entry(entry.employee_id).or_default().push(WorkedTime {
+                    day,
+                    clock_in_at: entry.clock_in_at,
+                    at_store: entry.store_id == store_id,
+                    seconds,
+                });
+            }
+        }
+    }
+
+    let daily_limit = settings.daily_overtime_hours * 3600.0;
+    let weekly_limit = settings.weekly_overtime_hours * 3600.0;
+    let employees = timesheets
+        .into_values()
+        .map(|mut timesheet| {
+            let mut worked = worked.remove(&timesheet.employee_id).unwrap_or_default();
+            worked.sort_by_key(|w| (w.day, w.clock_in_at));
+            let split = split_overtime(
+                &worked.iter().map(|w| (w.day, w.seconds as f64)).collect::<Vec<_>>(),
+                daily_limit,
+                weekly_limit,
+            );
+
+            let mut store_seconds = [0i64; 7];
+            let (mut regular, mut overtime) = (0.0, 0.0);
+            for (w, (regular_seconds, overtime_seconds)) in worked.iter().zip(&split) {
+                if w.at_store {
+                    store_seconds[w.day] += w.seconds;
+                    regular += regular_seconds;
+                    overtime += overtime_seconds;
+                }
+            }
+
+            timesheet.days = day_starts
+                .iter()
+                .zip(&store_seconds)
+                .map(|(day_start, s)| DailyHours { date: day_start.date_naive(), hours: to_hours(*s as f64) })
+                .collect();
+            timesheet.regular_hours = to_hours(regular);
+            timesheet.overtime_hours = to_hours(overtime);
+            timesheet.total_hours = to_hours(store_seconds.iter().sum::<i64>() as f64);
+            timesheet.all_stores_hours = to_hours(worked.iter().map(|w| w.seconds).sum::<i64>() as f64);
+            timesheet
+        })
+        .collect();
+
+    Ok(WeeklyTimesheet {
+        store_id,
+        week_start,
+        week_end: week_start + Duration::days(6),
+        daily_overtime_hours: settings.daily_overtime_hours,
+        weekly_overtime_hours: settings.weekly_overtime_hours,
+        overtime_multiplier: settings.overtime_multiplier,
+        generated_at: now,
+        employees,
+    })
+}
+
+/// Resolves the report's store and week, defaulting to the caller's store and the current week.
+fn weekly_report_period(scope: StoreScope, params: &WeeklyTimesheetQueryParams) -> Result<(StoreScope, i32, NaiveDate), HttpResponse> {
+    let store_id = scope.resolve_store(params.store_id)?;
+    let week_start = params.week_start.unwrap_or_else(|| {
+        let today = Utc::now().date_naive();
+        today - Duration::days(today.weekday().num_days_from_monday() as i64)
+    });
+    let store_scope = scope.restrict_to(Some(store_id)).unwrap_or(scope);
+    Ok((store_scope, store_id, week_start))
+}
+
+pub async fn get_weekly_timesheet(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<WeeklyTimesheetQueryParams>,
+) -> impl Responder {
+    let (scope, store_id, week_start) = match weekly_report_period(scope, &query_params) {
+        Ok(period) => period,
+        Err(response) => return response,
+    };
+
+    match build_weekly_timesheet(db.get_ref(), &scope, store_id, week_start).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::new(report)),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to build timesheet: {}", e))),
+    }
+}
+
+/// The weekly timesheet as CSV for payroll: one row per employee.
+pub async fn export_weekly_timesheet(
+    scope: StoreScope,
+    db: web::Data<DatabaseConnection>,
+    query_params: web::Query<WeeklyTimesheetQueryParams>,
+) -> impl Responder {
+    let (scope, store_id, week_start) = match weekly_report_period(scope, &query_params) {
+        Ok(period) => period,
+        Err(response) => return response,
+    };
+    let report = match build_weekly_timesheet(db.get_ref(), &scope, store_id, week_start).await {
+        Ok(report) => report,
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to build timesheet: {}", e))),
+    };
+
+    let mut header: Vec<String> = ["employee_id", "last_name", "first_name", "store_id", "week_start"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    header.extend((0..7).map(|d| (week_start + Duration::days(d)).to_string()));
+    header.extend(["regular_hours", "overtime_hours", "total_hours", "all_stores_hours", "overtime_multiplier", "open_entries"].iter().map(|h| h.to_string()));
+
+    let rows: Vec<Vec<String>> = report
+        .employees
+        .iter()
+        .map(|e| {
+            let mut row = vec![
+                e.employee_id.to_string(),
+                e.last_name.clone(),
+                e.first_name.clone(),
+                report.store_id.to_string(),
+                report.week_start.to_string(),
+            ];
+            row.extend(e.days.iter().map(|d| format!("{:.2}", d.hours)));
+            row.extend([
+                format!("{:.2}", e.regular_hours),
+                format!("{:.2}", e.overtime_hours),
+                format!("{:.2}", e.total_hours),
+                format!("{:.2}", e.all_stores_hours),
+                report.overtime_multiplier.to_string(),
+                e.open_entries.to_string(),
+            ]);
+            row
+        })
+        .collect();
+
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"timesheet-{}-{}.csv\"", store_id, week_start)))
+        .body(csv::document(&header, &rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: f64 = 3600.0;
+
+    #[test]
+    fn time_past_the_daily_limit_is_overtime() {
+        let split = split_overtime(&[(0, 6.0 * HOUR), (0, 4.0 * HOUR), (1, 5.0 * HOUR)], 8.0 * HOUR, 0.0);
+        assert_eq!(split, vec![(6.0 * HOUR, 0.0), (2.0 * HOUR, 2.0 * HOUR), (5.0 * HOUR, 0.0)]);
+    }
+
+    #[test]
+    fn regular_time_past_the_weekly_limit_is_overtime() {
+        let worked: Vec<(usize, f64)> = (0..5).map(|day| (day, 9.0 * HOUR)).collect();
+        let split = split_overtime(&worked, 8.0 * HOUR, 38.0 * HOUR);
+        let regular: f64 = split.iter().map(|(regular, _)| regular).sum();
+        let overtime: f64 = split.iter().map(|(_, overtime)| overtime).sum();
+        assert_eq!((regular, overtime), (38.0 * HOUR, 7.0 * HOUR));
+        // The limit is reached on the last day, so that day's regular time is what gets cut
+        assert_eq!(split[4], (6.0 * HOUR, 3.0 * HOUR));
+    }
+
+    #[test]
+    fn hours_at_another_store_count_towards_the_weekly_limit() {
+        // 30 hours elsewhere earlier in the week leave 10 regular hours for the last shift
+        let split = split_overtime(&[(0, 10.0 * HOUR), (1, 10.0 * HOUR), (2, 10.0 * HOUR), (3, 12.0 * HOUR)], 0.0, 40.0 * HOUR);
+        assert_eq!(split[3], (10.0 * HOUR, 2.0 * HOUR));
+    }
+}
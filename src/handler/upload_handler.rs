@@ -1,15 +1,23 @@
+use std::collections::BTreeMap;
+
 use actix_multipart::Multipart;
-use actix_web::{web, HttpResponse, Error};
-use actix_web::error::ErrorInternalServerError;
+use actix_web::{web, HttpResponse, Responder};
 use futures_util::stream::{StreamExt, TryStreamExt};
-use std::io::Write;
-use uuid::Uuid;
-use image::io::Reader as ImageReader;
-use webp::{Encoder, WebPMemory};
+use sea_orm::DatabaseConnection;
+
+use crate::helper::response::ApiError;
+use crate::helper::uploads::{self, UploadError};
+use crate::storage::{self, Storage};
+
+/// How long a redirect to a signed URL is valid, and slightly longer than clients may cache it.
+const SIGNED_URL_SECS: u64 = 60 * 60;
 
 #[derive(serde::Serialize)]
 pub struct UploadResponse {
+    /// The full size image, which is what gets stored on the product or employee.
     pub url: String,
+    /// Every generated size (`thumb`, `medium`, `full`) by name.
+    pub sizes: BTreeMap<&'static str, String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -17,75 +25,131 @@ pub struct DeleteFilePayload {
     pub url: String,
 }
 
-pub async fn upload_product_image(payload: Multipart) -> Result<HttpResponse, Error> {
-    upload_to_subfolder(payload, "products").await
+pub async fn upload_product_image(storage: web::Data<dyn Storage>, payload: Multipart) -> impl Responder {
+    upload_to_subfolder(storage.get_ref(), payload, uploads::PRODUCTS).await
 }
 
-pub async fn upload_employee_photo(payload: Multipart) -> Result<HttpResponse, Error> {
-    upload_to_subfolder(payload, "employees").await
+pub async fn upload_employee_photo(storage: web::Data<dyn Storage>, payload: Multipart) -> impl Responder {
+    upload_to_subfolder(storage.get_ref(), payload, uploads::EMPLOYEES).await
 }
 
-async fn upload_to_subfolder(mut payload: Multipart, subfolder: &str) -> Result<HttpResponse, Error> {
-    let mut file_path = String::new();
-    let upload_dir = format!("./uploads/{}", subfolder);
-
-    std::fs::create_dir_all(&upload_dir)
-        .map_err(|e| ErrorInternalServerError(format!("Failed to create upload directory: {}", e)))?;
-
-    while let Ok(Some(mut field)) = payload.try_next().await {
-        let _content_disposition = field.content_disposition();
+fn error_response(error: UploadError) -> HttpResponse {
+    match error {
+        UploadError::TooLarge => HttpResponse::PayloadTooLarge().json(ApiError::new(format!(
+            "Image must be at most {} MB",
+            uploads::MAX_UPLOAD_BYTES / (1024 * 1024)
+        ))),
+        UploadError::Rejected(message) => HttpResponse::BadRequest().json(ApiError::new(message)),
+        UploadError::Failed(message) => {
+            log::error!("Image upload failed: {}", message);
+            HttpResponse::InternalServerError().json(ApiError::new("Failed to store image".to_string()))
+        }
+    }
+}
 
+/// Reads the single file in the request, stopping as soon as it goes over the size limit.
+async fn read_single_file(payload: &mut Multipart) -> Result<Vec<u8>, UploadError> {
+    let mut file = None;
+    while let Some(mut field) = payload
+        .try_next()
+        .await
+        .map_err(|e| UploadError::Rejected(format!("Invalid multipart body: {}", e)))?
+    {
+        if file.is_some() {
+            return Err(UploadError::Rejected("Upload exactly one image per request".to_string()));
+        }
         let mut buffer = Vec::new();
         while let Some(chunk) = field.next().await {
-            let data = chunk?;
-            buffer.write_all(&data)?;
+            let data = chunk.map_err(|e| UploadError::Rejected(format!("Invalid multipart body: {}", e)))?;
+            if buffer.len() + data.len() > uploads::MAX_UPLOAD_BYTES {
+                return Err(UploadError::TooLarge);
+            }
+            buffer.extend_from_slice(&data);
         }
+        file = Some(buffer);
+    }
+    file.ok_or_else(|| UploadError::Rejected("No image was uploaded".to_string()))
+}
 
-        let img = ImageReader::new(std::io::Cursor::new(buffer))
-            .with_guessed_format()? 
-            .decode()
-            .map_err(|e| ErrorInternalServerError(format!("Failed to decode image: {}", e)))?;
-
-        let webp_filename = format!("{}.webp", Uuid::new_v4());
-        let webp_filepath = format!("{}/{}", upload_dir, webp_filename);
-        println!("Attempting to save WebP image to: {}", webp_filepath);
-
-        // Encode the image with a quality of 80%
-        let encoder = Encoder::from_image(&img)
-            .map_err(|e| ErrorInternalServerError(format!("Failed to create WebP encoder: {}", e)))?;
-        let webp_memory: WebPMemory = encoder.encode(80.0);
-
-        // Write the WebP data to a file
-        let mut webp_file = std::fs::File::create(&webp_filepath)
-            .map_err(|e| ErrorInternalServerError(format!("Failed to create WebP file: {}", e)))?;
-        webp_file.write_all(&webp_memory)
-            .map_err(|e| ErrorInternalServerError(format!("Failed to write WebP data: {}", e)))?;
-        println!("Image saved successfully to: {}", webp_filepath);
-
-        file_path = format!("/uploads/{}/{}", subfolder, webp_filename);
-        println!("Returning file_path: {}", file_path);
+async fn upload_to_subfolder(storage: &dyn Storage, mut payload: Multipart, subfolder: &str) -> HttpResponse {
+    let bytes = match read_single_file(&mut payload).await {
+        Ok(bytes) => bytes,
+        Err(e) => return error_response(e),
+    };
+
+    match uploads::store_image(storage, subfolder, bytes).await {
+        Ok(urls) => {
+            let sizes: BTreeMap<&'static str, String> = urls.into_iter().collect();
+            let url = sizes.get("full").cloned().unwrap_or_default();
+            HttpResponse::Ok().json(UploadResponse { url, sizes })
+        }
+        Err(e) => error_response(e),
     }
+}
 
-    Ok(HttpResponse::Ok().json(UploadResponse { url: file_path }))
+pub async fn delete_product_image(
+    db: web::Data<DatabaseConnection>,
+    storage: web::Data<dyn Storage>,
+    payload: web::Json<DeleteFilePayload>,
+) -> impl Responder {
+    delete_from_subfolder(&db, storage.get_ref(), &payload.url, uploads::PRODUCTS).await
 }
 
-pub async fn delete_file(payload: web::Json<DeleteFilePayload>) -> Result<HttpResponse, Error> {
-    let file_url = &payload.url;
+pub async fn delete_employee_photo(
+    db: web::Data<DatabaseConnection>,
+    storage: web::Data<dyn Storage>,
+    payload: web::Json<DeleteFilePayload>,
+) -> impl Responder {
+    delete_from_subfolder(&db, storage.get_ref(), &payload.url, uploads::EMPLOYEES).await
+}
 
-    if !file_url.starts_with("/uploads/") || file_url.contains("..") {
-        return Ok(HttpResponse::BadRequest().body("Invalid file path"));
+/// Deletes an uploaded image with all its sizes. Only images in the route's own folder can be
+/// deleted, and only once no product or employee uses them.
+async fn delete_from_subfolder(db: &DatabaseConnection, storage: &dyn Storage, url: &str, subfolder: &str) -> HttpResponse {
+    let Some(id) = uploads::image_id(subfolder, url) else {
+        return HttpResponse::BadRequest().json(ApiError::new("Invalid file path".to_string()));
+    };
+
+    match uploads::is_referenced(db, subfolder, &id).await {
+        Ok(false) => {}
+        Ok(true) => return HttpResponse::Conflict().json(ApiError::new("Image is still in use".to_string())),
+        Err(e) => return HttpResponse::InternalServerError().json(ApiError::new(format!("Failed to check image references: {}", e))),
     }
 
-    let file_path = format!(".{}", file_url);
-
-    match std::fs::remove_file(&file_path) {
-        Ok(_) => {
-            println!("Deleted file: {}", file_path);
-            Ok(HttpResponse::Ok().finish())
+    match uploads::delete_image(storage, subfolder, &id).await {
+        Ok(true) => HttpResponse::Ok().finish(),
+        Ok(false) => HttpResponse::NotFound().json(ApiError::new("File not found".to_string())),
+        Err(e) => {
+            log::error!("Error deleting image {}: {}", url, e);
+            HttpResponse::InternalServerError().json(ApiError::new("Failed to delete file".to_string()))
         }
+    }
+}
+
+/// Serves `/uploads/<key>`. Local files are sent directly and cached for good, since an
+/// image never changes once stored; other backends redirect to a signed or CDN URL.
+pub async fn serve_upload(storage: web::Data<dyn Storage>, path: web::Path<String>) -> impl Responder {
+    let key = path.into_inner();
+    if !uploads::is_image_key(&key) {
+        return HttpResponse::NotFound().finish();
+    }
+
+    if let Some(url) = storage.signed_url(&key, std::time::Duration::from_secs(SIGNED_URL_SECS)) {
+        return HttpResponse::Found()
+            .insert_header(("Location", url))
+            .insert_header(("Cache-Control", format!("private, max-age={}", SIGNED_URL_SECS - 5 * 60)))
+            .finish();
+    }
+
+    match storage.get(&key).await {
+        Ok(Some(bytes)) => HttpResponse::Ok()
+            .content_type(storage::content_type(&key))
+            .insert_header(("Cache-Control", "public, max-age=31536000, immutable"))
+            .body(bytes),
+        Ok(None) => HttpResponse::NotFound().finish(),
         Err(e) => {
-            eprintln!("Error deleting file {}: {}", file_path, e);
-            Ok(HttpResponse::InternalServerError().body("Failed to delete file"))
+            log::error!("Failed to read upload {}: {}", key, e);
+            HttpResponse::InternalServerError().finish()
         }
     }
 }
@@ -0,0 +1,34 @@
+/// Renders rows as RFC 4180 CSV with CRLF line endings. Fields containing commas, quotes or
+/// line breaks are quoted, and fields that a spreadsheet would read as a formula are
+/// prefixed with `'` so exported names cannot run formulas when opened.
+pub fn document(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    write_row(&mut out, header);
+    for row in rows {
+        write_row(&mut out, row);
+    }
+    out
+}
+
+fn write_row(out: &mut String, fields: &[String]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&escape(field));
+    }
+    out.push_str("\r\n");
+}
+
+fn escape(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) && field.parse::<f64>().is_err() {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
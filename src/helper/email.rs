@@ -1,5 +1,5 @@
 use lettre::{
-    message::{header::ContentType, Mailbox as LettreMailbox, Message},
+    message::{header::ContentType, Mailbox as LettreMailbox, Message, MultiPart},
     transport::smtp::{authentication::Credentials, client::{Tls, TlsParameters}},
     AsyncSmtpTransport, AsyncTransport,
     Tokio1Executor,
@@ -7,16 +7,17 @@ use lettre::{
 
 use crate::entities::settings_model::EmailSettings;
 
-#[allow(dead_code)]
-pub async fn send_email(
+/// Sends a plain-text email, or a text/HTML alternative when `html_body` is given.
+pub async fn send_email_with_html(
     settings: &EmailSettings,
     to_email: &str,
     to_name: Option<&str>,
     subject: &str,
     body: &str,
+    html_body: Option<&str>,
 ) -> Result<(), String> {
     // 1. Validate settings
-    if settings.smtp_server.is_empty() || settings.smtp_username.is_empty() || settings.smtp_password.is_empty() {
+    if settings.smtp_server.is_empty() {
         return Err("SMTP settings are incomplete.".to_string());
     }
 
@@ -27,26 +28,37 @@ pub async fn send_email(
     let to_mailbox = LettreMailbox::new(to_name.map(|s| s.to_string()), to_address);
     let from_mailbox = LettreMailbox::new(Some(settings.from_name.clone()), from_address);
 
-    let email = Message::builder()
+    let builder = Message::builder()
         .from(from_mailbox)
         .to(to_mailbox)
-        .subject(subject)
-        .header(ContentType::TEXT_PLAIN)
-        .body(body.to_string())
-        .map_err(|e| format!("Failed to build email: {}", e))?;
+        .subject(subject);
+    let email = match html_body {
+        Some(html) => builder.multipart(MultiPart::alternative_plain_html(body.to_string(), html.to_string())),
+        None => builder.header(ContentType::TEXT_PLAIN).body(body.to_string()),
+    }
+    .map_err(|e| format!("Failed to build email: {}", e))?;
 
     // 3. Setup SMTP client
-    let creds = Credentials::new(settings.smtp_username.clone(), settings.smtp_password.clone());
-
-    let tls_parameters = TlsParameters::new(settings.smtp_server.clone())
-        .map_err(|e| format!("Failed to build TLS parameters: {}", e))?;
-    let tls = Tls::Required(tls_parameters);
+    let tls = match settings.smtp_security.as_str() {
+        "NONE" => Tls::None,
+        security => {
+            let tls_parameters = TlsParameters::new(settings.smtp_server.clone())
+                .map_err(|e| format!("Failed to build TLS parameters: {}", e))?;
+            if security == "TLS" {
+                Tls::Wrapper(tls_parameters)
+            } else {
+                Tls::Required(tls_parameters)
+            }
+        }
+    };
 
-    let mailer = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(settings.smtp_server.clone())
+    let mut builder = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(settings.smtp_server.clone())
         .port(settings.smtp_port as u16)
-        .tls(tls)
-        .credentials(creds)
-        .build();
+        .tls(tls);
+    if !settings.smtp_username.is_empty() {
+        builder = builder.credentials(Credentials::new(settings.smtp_username.clone(), settings.smtp_password.clone()));
+    }
+    let mailer = builder.build();
 
     // 4. Send email
     match mailer.send(email).await {
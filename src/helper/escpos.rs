@@ -0,0 +1,106 @@
+use image::{imageops::FilterType, DynamicImage};
+
+const ESC: u8 = 0x1B;
+const GS: u8 = 0x1D;
+
+/// Widest raster a 58mm thermal printer can print; 80mm printers centre it.
+const MAX_RASTER_WIDTH: u32 = 384;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Justification {
+    Left,
+    Center,
+}
+
+/// Builds a raw ESC/POS command stream.
+pub struct EscPos {
+    buf: Vec<u8>,
+}
+
+impl EscPos {
+    pub fn new() -> Self {
+        // ESC @ resets the printer to its power-on state
+        EscPos { buf: vec![ESC, b'@'] }
+    }
+
+    pub fn justify(&mut self, justification: Justification) -> &mut Self {
+        let n = match justification {
+            Justification::Left => 0,
+            Justification::Center => 1,
+        };
+        self.buf.extend_from_slice(&[ESC, b'a', n]);
+        self
+    }
+
+    pub fn bold(&mut self, on: bool) -> &mut Self {
+        self.buf.extend_from_slice(&[ESC, b'E', on as u8]);
+        self
+    }
+
+    /// Writes one line of text. Printers default to an ASCII-compatible code page,
+    /// so anything outside ASCII is replaced with `?`.
+    pub fn line(&mut self, text: &str) -> &mut Self {
+        self.buf.extend(text.chars().map(|c| if c.is_ascii() && !c.is_ascii_control() { c as u8 } else { b'?' }));
+        self.buf.push(b'\n');
+        self
+    }
+
+    /// Prints an image as a 1-bit raster (GS v 0), scaled down to the printable width.
+    pub fn image(&mut self, image: &DynamicImage) -> &mut Self {
+        let image = if image.width() > MAX_RASTER_WIDTH {
+            image.resize(MAX_RASTER_WIDTH, u32::MAX, FilterType::Triangle)
+        } else {
+            image.clone()
+        };
+        let rgba = image.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let width_bytes = width.div_ceil(8);
+
+        self.buf.extend_from_slice(&[
+            GS, b'v', b'0', 0,
+            (width_bytes & 0xFF) as u8, (width_bytes >> 8) as u8,
+            (height & 0xFF) as u8, (height >> 8) as u8,
+        ]);
+
+        for y in 0..height {
+            for byte_index in 0..width_bytes {
+                let mut byte = 0u8;
+                for bit in 0..8 {
+                    let x = byte_index * 8 + bit;
+                    if x < width {
+                        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+                        // Composite onto white paper, then threshold
+                        let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+                        let on_paper = 255 - (a as u32 * (255 - luma)) / 255;
+                        if on_paper < 128 {
+                            byte |= 0x80 >> bit;
+                        }
+                    }
+                }
+                self.buf.push(byte);
+            }
+        }
+        self
+    }
+
+    pub fn feed(&mut self, lines: u8) -> &mut Self {
+        self.buf.extend_from_slice(&[ESC, b'd', lines]);
+        self
+    }
+
+    /// Feeds past the cutter and makes a partial cut.
+    pub fn cut(&mut self) -> &mut Self {
+        self.buf.extend_from_slice(&[GS, b'V', 66, 0]);
+        self
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl Default for EscPos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
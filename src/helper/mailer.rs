@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr};
+
+use crate::entities::{email_outbox, employees, orders, refund_items, refunds};
+use crate::entities::email_outbox::NewEmail;
+use crate::entities::settings_model::{EmailSettings, Settings};
+use crate::helper::{email, receipt, template};
+use crate::repository::{
+    customers_repository::CustomerRepository,
+    email_outbox_repository::EmailOutboxRepository,
+    email_templates_repository::EmailTemplateRepository,
+    products_repository::ProductRepository,
+    settings_repository,
+    stores_repository::StoreRepository,
+};
+
+pub const ORDER_RECEIPT: &str = "ORDER_RECEIPT";
+pub const REFUND_CONFIRMATION: &str = "REFUND_CONFIRMATION";
+pub const ACCOUNT_SETUP: &str = "ACCOUNT_SETUP";
+pub const PASSWORD_RESET: &str = "PASSWORD_RESET";
+
+/// How often the worker looks for due emails.
+const WORKER_INTERVAL_SECS: u64 = 5;
+const WORKER_BATCH_SIZE: u64 = 20;
+/// How long a claimed email is hidden from other workers while it is being sent.
+const CLAIM_LEASE_SECS: i64 = 300;
+/// Attempts before an email is marked FAILED and left for an admin to retry.
+const MAX_ATTEMPTS: i32 = 6;
+const BASE_RETRY_DELAY_SECS: i64 = 30;
+const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+/// A built-in template. Admins can override the subject and bodies through the
+/// email templates API; the key, description and variables are fixed.
+pub struct BuiltinTemplate {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub variables: &'static [&'static str],
+    pub subject: &'static str,
+    pub text_body: &'static str,
+    pub html_body: &'static str,
+}
+
+pub const TEMPLATES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        key: ORDER_RECEIPT,
+        description: "Receipt emailed to the customer after a sale.",
+        variables: &["customer_name", "store_name", "order_id", "total", "receipt"],
+        subject: "Your receipt from {{store_name}} (order #{{order_id}})",
+        text_body: "Hello {{customer_name}},\n\nThank you for shopping at {{store_name}}. Your receipt is below.\n\n{{receipt}}",
+        html_body: r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; color: #222;">
+  <p>Hello {{customer_name}},</p>
+  <p>Thank you for shopping at {{store_name}}. Your receipt is below.</p>
+  <pre style="font-family: 'Courier New', monospace; font-size: 13px; background: #f7f7f7; padding: 16px; display: inline-block;">{{receipt}}</pre>
+</body>
+</html>"#,
+    },
+    BuiltinTemplate {
+        key: REFUND_CONFIRMATION,
+        description: "Confirmation emailed to the customer after a refund.",
+        variables: &["customer_name", "store_name", "refund_id", "order_reference", "items", "total", "refund_method", "footer_text"],
+        subject: "Refund confirmation from {{store_name}} (refund #{{refund_id}})",
+        text_body: "Hello {{customer_name}},\n\nWe have processed refund #{{refund_id}}{{order_reference}}.\n\n{{items}}\nTotal refunded: {{total}}\nRefunded to: {{refund_method}}\n\n{{footer_text}}",
+        html_body: r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; color: #222;">
+  <p>Hello {{customer_name}},</p>
+  <p>We have processed refund #{{refund_id}}{{order_reference}}.</p>
+  <pre style="font-family: 'Courier New', monospace; font-size: 13px; background: #f7f7f7; padding: 16px; display: inline-block;">{{items}}</pre>
+  <p><strong>Total refunded:</strong> {{total}}<br><strong>Refunded to:</strong> {{refund_method}}</p>
+  <p>{{footer_text}}</p>
+</body>
+</html>"#,
+    },
+    BuiltinTemplate {
+        key: ACCOUNT_SETUP,
+        description: "Sent to a new employee or admin with a link to set their password.",
+        variables: &["first_name", "site_name", "set_password_url", "expires_in_hours"],
+        subject: "Set Your Account Password",
+        text_body: "Hello {{first_name}},\n\nWelcome! Your account has been created. Please set your password by clicking the link below:\n\n{{set_password_url}}\n\nThe link will expire in {{expires_in_hours}} hours.",
+        html_body: r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; color: #222;">
+  <p>Hello {{first_name}},</p>
+  <p>Welcome! Your {{site_name}} account has been created. Please set your password using the link below:</p>
+  <p><a href="{{set_password_url}}">Set your password</a></p>
+  <p>The link will expire in {{expires_in_hours}} hours.</p>
+</body>
+</html>"#,
+    },
+    BuiltinTemplate {
+        key: PASSWORD_RESET,
+        description: "Sent when an employee asks to reset a forgotten password.",
+        variables: &["first_name", "site_name", "set_password_url", "expires_in_hours"],
+        subject: "Reset Your Password",
+        text_body: "Hello {{first_name}},\n\nYou requested to reset your password. Please click the link below to set a new one:\n\n{{set_password_url}}\n\nThe link will expire in {{expires_in_hours}} hour(s).",
+        html_body: r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; color: #222;">
+  <p>Hello {{first_name}},</p>
+  <p>You requested to reset your {{site_name}} password. Please use the link below to set a new one:</p>
+  <p><a href="{{set_password_url}}">Reset your password</a></p>
+  <p>The link will expire in {{expires_in_hours}} hour(s). If you did not ask for this, you can ignore this email.</p>
+</body>
+</html>"#,
+    },
+];
+
+pub fn builtin_template(key: &str) -> Option<&'static BuiltinTemplate> {
+    TEMPLATES.iter().find(|t| t.key == key)
+}
+
+struct RenderedEmail {
+    subject: String,
+    text_body: String,
+    html_body: Option<String>,
+}
+
+/// Renders a template, preferring an admin override over the built-in default.
+async fn render_template<C: ConnectionTrait>(db: &C, key: &str, vars: &HashMap<&str, String>) -> Result<RenderedEmail, DbErr> {
+    let (subject, text_body, html_body) = match EmailTemplateRepository::find_by_key(db, key).await? {
+        Some(custom) => (custom.subject, custom.text_body, custom.html_body),
+        None => {
+            let builtin = builtin_template(key).ok_or_else(|| DbErr::Custom(format!("Unknown email template '{}'", key)))?;
+            (builtin.subject.to_string(), builtin.text_body.to_string(), Some(builtin.html_body.to_string()))
+        }
+    };
+
+    Ok(RenderedEmail {
+        subject: template::render(&subject, vars, false),
+        text_body: template::render(&text_body, vars, false),
+        html_body: html_body.map(|html| template::render(&html, vars, true)),
+    })
+}
+
+async fn enqueue_rendered<C: ConnectionTrait>(
+    db: &C,
+    key: &str,
+    vars: &HashMap<&str, String>,
+    recipient_email: String,
+    recipient_name: Option<String>,
+    reference_id: Option<i32>,
+) -> Result<email_outbox::Model, DbErr> {
+    let rendered = render_template(db, key, vars).await?;
+    let new_email = NewEmail {
+        recipient_email,
+        recipient_name,
+        subject: rendered.subject,
+        text_body: rendered.text_body,
+        html_body: rendered.html_body,
+        category: key.to_string(),
+        reference_id,
+    };
+    EmailOutboxRepository::enqueue(db, new_email).await
+}
+
+/// Queues an emailed receipt for the order's customer.
+/// Returns `None` when the customer has no email address.
+pub async fn queue_order_receipt<C: ConnectionTrait>(
+    db: &C,
+    order: orders::Model,
+    settings: &Settings,
+) -> Result<Option<email_outbox::Model>, DbErr> {
+    let customer = match CustomerRepository::find_by_id(db, order.customer_id).await? {
+        Some(customer) => customer,
+        None => return Ok(None),
+    };
+    let recipient_email = match customer.email.clone().filter(|e| !e.trim().is_empty()) {
+        Some(email) => email,
+        None => return Ok(None),
+    };
+
+    let order_id = order.id;
+    let receipt = receipt::build_receipt(db, order, settings).await?;
+    let vars = HashMap::from([
+        ("customer_name", customer.first_name.clone()),
+        ("store_name", receipt.store_name.clone()),
+        ("order_id", order_id.to_string()),
+        ("total", receipt::format_money(&receipt.currency_symbol, receipt.total)),
+        ("receipt", receipt::render_text(&receipt)),
+    ]);
+
+    let recipient_name = Some(format!("{} {}", customer.first_name, customer.last_name));
+    enqueue_rendered(db, ORDER_RECEIPT, &vars, recipient_email, recipient_name, Some(order_id)).await.map(Some)
+}
+
+/// Queues a refund confirmation for the refund's customer.
+/// Returns `None` when there is no customer or they have no email address.
+pub async fn queue_refund_confirmation<C: ConnectionTrait>(
+    db: &C,
+    refund: &refunds::Model,
+    items: &[refund_items::Model],
+    settings: &Settings,
+) -> Result<Option<email_outbox::Model>, DbErr> {
+    let customer = match refund.customer_id {
+        Some(customer_id) => CustomerRepository::find_by_id(db, customer_id).await?,
+        None => None,
+    };
+    let Some(customer) = customer else { return Ok(None) };
+    let recipient_email = match customer.email.clone().filter(|e| !e.trim().is_empty()) {
+        Some(email) => email,
+        None => return Ok(None),
+    };
+
+    let store_name = StoreRepository::find_by_id(db, refund.store_id)
+        .await?
+        .map(|s| s.name)
+        .unwrap_or_else(|| settings.general.site_name.clone());
+    let product_names: HashMap<i32, String> = ProductRepository::find_by_ids(db, items.iter().map(|i| i.product_id).collect())
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+
+    let currency_symbol = &settings.general.currency_symbol;
+    let item_lines: String = items
+        .iter()
+        .map(|item| {
+            let name = product_names.get(&item.product_id).cloned().unwrap_or_else(|| format!("Product #{}", item.product_id));
+            receipt::columns(&format!("{} x {}", item.quantity, name), &receipt::format_money(currency_symbol, item.amount)) + "\n"
+        })
+        .collect();
+    let refund_method = match refund.refund_method.as_str() {
+        "CASH" => "Cash",
+        "STORE_CREDIT" => "Store credit",
+        _ => "Original payment method",
+    };
+
+    let vars = HashMap::from([
+        ("customer_name", customer.first_name.clone()),
+        ("store_name", store_name),
+        ("refund_id", refund.id.to_string()),
+        ("order_reference", refund.order_id.map(|id| format!(" for order #{}", id)).unwrap_or_default()),
+        ("items", item_lines),
+        ("total", receipt::format_money(currency_symbol, refund.total_amount)),
+        ("refund_method", refund_method.to_string()),
+        ("footer_text", settings.receipt.footer_text.clone()),
+    ]);
+
+    let recipient_name = Some(format!("{} {}", customer.first_name, customer.last_name));
+    enqueue_rendered(db, REFUND_CONFIRMATION, &vars, recipient_email, recipient_name, Some(refund.id)).await.map(Some)
+}
+
+/// Queues a set-password (`ACCOUNT_SETUP`) or reset (`PASSWORD_RESET`) link for an employee.
+pub async fn queue_password_link<C: ConnectionTrait>(
+    db: &C,
+    template_key: &str,
+    employee: &employees::Model,
+    token: &str,
+    expires_in_hours: i64,
+    settings: &Settings,
+) -> Result<email_outbox::Model, DbErr> {
+    let frontend_url = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
+    let vars = HashMap::from([
+        ("first_name", employee.first_name.clone()),
+        ("site_name", settings.general.site_name.clone()),
+        ("set_password_url", format!("{}/set-password?token={}", frontend_url, token)),
+        ("expires_in_hours", expires_in_hours.to_string()),
+    ]);
+
+    enqueue_rendered(db, template_key, &vars, employee.email.clone(), Some(employee.first_name.clone()), Some(employee.id)).await
+}
+
+/// Backoff before the next attempt: 30s, 1m, 2m, 4m, ... capped at an hour.
+fn retry_delay(attempts: i32) -> Duration {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    Duration::seconds((BASE_RETRY_DELAY_SECS * 2i64.pow(exponent)).min(MAX_RETRY_DELAY_SECS))
+}
+
+/// Sends a claimed email and records the outcome, scheduling a retry on failure.
+async fn deliver(db: &DatabaseConnection, settings: &EmailSettings, email: email_outbox::Model) -> Result<email_outbox::Model, DbErr> {
+    let result = email::send_email_with_html(
+        settings,
+        &email.recipient_email,
+        email.recipient_name.as_deref(),
+        &email.subject,
+        &email.text_body,
+        email.html_body.as_deref(),
+    )
+    .await;
+
+    match result {
+        Ok(()) => EmailOutboxRepository::mark_sent(db, email).await,
+        Err(e) => {
+            let attempts = email.attempts + 1;
+            let retry_at = (attempts < MAX_ATTEMPTS).then(|| Utc::now() + retry_delay(attempts));
+            log::warn!("Email {} to {} failed (attempt {}): {}", email.id, email.recipient_email, attempts, e);
+            EmailOutboxRepository::record_failure(db, email, e, retry_at).await
+        }
+    }
+}
+
+/// Sends every email that is currently due. Returns how many were attempted.
+pub async fn process_due(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let now = Utc::now();
+    let due = EmailOutboxRepository::find_due(db, now, WORKER_BATCH_SIZE).await?;
+    if due.is_empty() {
+        return Ok(0);
+    }
+
+    let settings = settings_repository::get_settings(db).await?;
+    let lease_until = now + Duration::seconds(CLAIM_LEASE_SECS);
+    let mut attempted = 0;
+    for email in due {
+        if !EmailOutboxRepository::claim(db, &email, lease_until).await? {
+            continue;
+        }
+        deliver(db, &settings.email, email).await?;
+        attempted += 1;
+    }
+    Ok(attempted)
+}
+
+/// Starts the background worker that drains the email outbox.
+pub fn spawn_worker(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(WORKER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = process_due(&db).await {
+                log::error!("Email outbox worker failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::Utc;
+    use sea_orm::{DatabaseConnection, Value};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use super::{process_due, MAX_ATTEMPTS};
+    use crate::entities::settings_model::Settings;
+    use crate::entities::{email_outbox, settings};
+    use crate::helper::test_database::{self, Statements};
+
+    /// A minimal SMTP server on a free local port. Accepted messages are collected; with
+    /// `reject` set, every recipient is refused with a temporary error.
+    async fn smtp_server(reject: bool) -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let messages = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let messages = messages.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+                    let mut data: Option<String> = None;
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if let Some(body) = data.as_mut() {
+                            if line == "." {
+                                messages.lock().unwrap().push(data.take().unwrap());
+                                writer.write_all(b"250 OK queued\r\n").await.unwrap();
+                            } else {
+                                body.push_str(&line);
+                                body.push('\n');
+                            }
+                            continue;
+                        }
+                        let command = line.to_ascii_uppercase();
+                        let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                            b"250 localhost\r\n"
+                        } else if command.starts_with("RCPT") && reject {
+                            b"451 4.3.0 Try again later\r\n"
+                        } else if command.starts_with("DATA") {
+                            data = Some(String::new());
+                            b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                        } else if command.starts_with("QUIT") {
+                            writer.write_all(b"221 Bye\r\n").await.unwrap();
+                            break;
+                        } else {
+                            b"250 OK\r\n"
+                        };
+                        writer.write_all(reply).await.unwrap();
+                    }
+                });
+            }
+        });
+        (port, received)
+    }
+
+    fn settings_row(port: u16) -> settings::Model {
+        let mut settings = Settings::default();
+        settings.email.from_email = "shop@example.com".to_string();
+        settings.email.smtp_server = "127.0.0.1".to_string();
+        settings.email.smtp_port = port.into();
+        settings.email.smtp_username = String::new();
+        settings.email.smtp_password = String::new();
+        settings.email.smtp_security = "NONE".to_string();
+        settings::Model { id: 1, config: serde_json::to_value(settings).unwrap() }
+    }
+
+    fn pending_email(attempts: i32) -> email_outbox::Model {
+        let now = Utc::now();
+        email_outbox::Model {
+            id: 7,
+            recipient_email: "jane@example.com".to_string(),
+            recipient_name: Some("Jane".to_string()),
+            subject: "Your receipt".to_string(),
+            text_body: "Thanks for shopping with us".to_string(),
+            html_body: None,
+            category: super::ORDER_RECEIPT.to_string(),
+            reference_id: Some(42),
+            status: "PENDING".to_string(),
+            attempts,
+            last_error: None,
+            next_attempt_at: Some(now),
+            sent_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// A database holding one due email and SMTP settings pointing at `port`.
+    async fn database(email: email_outbox::Model, port: u16) -> (DatabaseConnection, Statements) {
+        let settings = settings_row(port);
+        test_database::connect(move |statement| {
+            if statement.sql.contains("FROM `settings`") {
+                vec![test_database::row(&settings)]
+            } else {
+                vec![test_database::row(&email)]
+            }
+        })
+        .await
+    }
+
+    /// The values of the last UPDATE the worker ran, the one recording the outcome.
+    fn outcome(statements: &Statements) -> Vec<Value> {
+        let statements = statements.lock().unwrap();
+        let update = statements
+            .iter()
+            .rfind(|statement| statement.sql.starts_with("UPDATE"))
+            .expect("the outcome is recorded");
+        update.values.clone().expect("the update has values").0
+    }
+
+    #[tokio::test]
+    async fn worker_delivers_a_due_email() {
+        let (port, received) = smtp_server(false).await;
+        let (db, statements) = database(pending_email(0), port).await;
+
+        assert_eq!(process_due(&db).await.unwrap(), 1);
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].contains("Subject: Your receipt"));
+        assert!(received[0].contains("Thanks for shopping with us"));
+        let values = outcome(&statements);
+        assert!(values.contains(&Value::from("SENT")));
+        assert!(values.contains(&Value::from(1)));
+    }
+
+    #[tokio::test]
+    async fn worker_retries_and_then_marks_the_email_failed() {
+        let (port, received) = smtp_server(true).await;
+
+        // A first failure leaves the email pending with a later attempt scheduled
+        let (db, statements) = database(pending_email(0), port).await;
+        assert_eq!(process_due(&db).await.unwrap(), 1);
+        let values = outcome(&statements);
+        assert!(values.contains(&Value::from("PENDING")));
+        assert!(values.contains(&Value::from(1)));
+        assert!(!values.iter().any(|value| matches!(value, Value::ChronoDateTimeUtc(None))));
+
+        // The last allowed attempt gives up and clears the schedule
+        let (db, statements) = database(pending_email(MAX_ATTEMPTS - 1), port).await;
+        assert_eq!(process_due(&db).await.unwrap(), 1);
+        let values = outcome(&statements);
+        assert!(values.contains(&Value::from("FAILED")));
+        assert!(values.contains(&Value::from(MAX_ATTEMPTS)));
+        assert!(values.iter().any(|value| matches!(value, Value::ChronoDateTimeUtc(None))));
+
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
@@ -1,2 +1,13 @@
 pub mod response;
-pub mod email;
\ No newline at end of file
+pub mod email;
+pub mod escpos;
+pub mod pdf;
+pub mod receipt;
+pub mod template;
+pub mod mailer;
+pub mod csv;
+pub mod secrets;
+pub mod uploads;
+pub mod pagination;
+#[cfg(test)]
+pub mod test_database;
@@ -0,0 +1,325 @@
+//! Paging, sorting and filtering for list endpoints.
+//!
+//! Every list takes `page` (from 1) and `size` (at most `MAX_PAGE_SIZE`), or instead of `page`
+//! the `cursor` a previous page returned; `sort`, a comma separated list of fields where a
+//! leading `-` sorts descending (`sort=-order_date,total_amount`); and filters as
+//! `field=value`. Each list declares the fields it can sort and filter on with `ListFields`,
+//! and anything else is rejected with 400 rather than silently ignored.
+//!
+//! Cursors page by id and skip the count, so they stay fast however deep a client scrolls;
+//! they can only be used when sorting by `id` or `-id`.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{Condition, ConnectionTrait, DbErr, IntoSimpleExpr, Order, PaginatorTrait, QueryFilter, QueryOrder, SelectorTrait};
+use serde::Serialize;
+
+use crate::extractor::list_query_extractor::ListQuery;
+
+pub const DEFAULT_PAGE_SIZE: u64 = 25;
+pub const MAX_PAGE_SIZE: u64 = 200;
+/// Keeps `page * size` and the offset well within `u64`.
+pub const MAX_PAGE: u64 = u32::MAX as u64;
+
+#[derive(Debug, Clone, Copy)]
+pub enum FilterKind {
+    /// Equal to an integer; `1,2,3` matches any of them.
+    Int,
+    /// Equal to a text; `a,b` matches any of them.
+    Text,
+    /// Contains the text, ignoring case.
+    Contains,
+    /// `true` or `false`, for boolean and 0/1 columns.
+    Bool,
+    /// On or after a date (`2025-10-01`, from the start of the day, UTC) or an RFC 3339 time.
+    From,
+    /// On or before a date (the whole day included) or an RFC 3339 time.
+    To,
+    /// On a date.
+    Date,
+}
+
+struct FilterField {
+    name: &'static str,
+    column: SimpleExpr,
+    kind: FilterKind,
+}
+
+enum TimeBound {
+    Day(NaiveDate),
+    Instant(DateTime<Utc>),
+}
+
+fn parse_time(value: &str) -> Option<TimeBound> {
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(TimeBound::Day(day));
+    }
+    DateTime::parse_from_rfc3339(value).ok().map(|time| TimeBound::Instant(time.with_timezone(&Utc)))
+}
+
+fn start_of(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).unwrap().and_utc()
+}
+
+fn start_of_next(day: NaiveDate) -> DateTime<Utc> {
+    start_of(day.checked_add_days(Days::new(1)).unwrap_or(day))
+}
+
+/// Escapes `LIKE` wildcards so they match literally.
+fn like_pattern(value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+impl FilterField {
+    fn condition(&self, value: &str) -> Result<SimpleExpr, String> {
+        let column = || Expr::expr(self.column.clone());
+        let invalid = |expected: &str| format!("Invalid value '{}' for filter '{}': expected {}", value, self.name, expected);
+
+        Ok(match self.kind {
+            FilterKind::Int => {
+                let values = value
+                    .split(',')
+                    .map(|v| v.trim().parse::<i64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| invalid("an integer or a comma separated list of integers"))?;
+                match values.as_slice() {
+                    [single] => column().eq(*single),
+                    _ => column().is_in(values),
+                }
+            }
+            FilterKind::Text => {
+                let values: Vec<String> = value.split(',').map(|v| v.trim().to_string()).collect();
+                match values.as_slice() {
+                    [single] => column().eq(single.clone()),
+                    _ => column().is_in(values),
+                }
+            }
+            FilterKind::Contains => column().like(like_pattern(value)),
+            FilterKind::Bool => match value {
+                "true" | "1" => column().eq(true),
+                "false" | "0" => column().eq(false),
+                _ => return Err(invalid("true or false")),
+            },
+            FilterKind::From => match parse_time(value).ok_or_else(|| invalid("a date or time"))? {
+                TimeBound::Day(day) => column().gte(start_of(day)),
+                TimeBound::Instant(time) => column().gte(time),
+            },
+            FilterKind::To => match parse_time(value).ok_or_else(|| invalid("a date or time"))? {
+                TimeBound::Day(day) => column().lt(start_of_next(day)),
+                TimeBound::Instant(time) => column().lte(time),
+            },
+            FilterKind::Date => match parse_time(value) {
+                Some(TimeBound::Day(day)) => column().gte(start_of(day)).and(column().lt(start_of_next(day))),
+                _ => return Err(invalid("a date")),
+            },
+        })
+    }
+}
+
+/// The fields a list can be sorted and filtered on.
+pub struct ListFields {
+    id: SimpleExpr,
+    sorts: Vec<(&'static str, SimpleExpr)>,
+    filters: Vec<FilterField>,
+    params: Vec<&'static str>,
+    default_sort: &'static str,
+}
+
+impl ListFields {
+    /// `id` is the list's unique key. It can always be sorted on, is added as the last sort
+    /// so rows with equal values keep their order from one page to the next, and is what
+    /// cursors go by. Lists are sorted by `-id` unless `default_sort` says otherwise.
+    pub fn new(id: impl IntoSimpleExpr) -> Self {
+        let id = id.into_simple_expr();
+        ListFields {
+            sorts: vec![("id", id.clone())],
+            id,
+            filters: vec![],
+            params: vec![],
+            default_sort: "-id",
+        }
+    }
+
+    pub fn sort(mut self, name: &'static str, column: impl IntoSimpleExpr) -> Self {
+        self.sorts.push((name, column.into_simple_expr()));
+        self
+    }
+
+    pub fn filter(mut self, name: &'static str, column: impl IntoSimpleExpr, kind: FilterKind) -> Self {
+        self.filters.push(FilterField { name, column: column.into_simple_expr(), kind });
+        self
+    }
+
+    /// A query parameter the handler reads itself, such as a `store_id` checked against the
+    /// caller's scope; it is accepted but does not become a filter.
+    pub fn param(mut self, name: &'static str) -> Self {
+        self.params.push(name);
+        self
+    }
+
+    pub fn default_sort(mut self, sort: &'static str) -> Self {
+        self.default_sort = sort;
+        self
+    }
+
+    fn names<T>(entries: &[T], name: impl Fn(&T) -> &'static str) -> String {
+        entries.iter().map(name).collect::<Vec<_>>().join(", ")
+    }
+
+    pub fn plan(&self, query: &ListQuery) -> Result<ListPlan, String> {
+        let mut sorts = Vec::new();
+        let sort = query.sort.as_deref().unwrap_or(self.default_sort);
+        for field in sort.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+            let (name, order) = match field.strip_prefix('-') {
+                Some(name) => (name, Order::Desc),
+                None => (field, Order::Asc),
+            };
+            let column = self
+                .sorts
+                .iter()
+                .find(|(sort, _)| *sort == name)
+                .map(|(_, column)| column.clone())
+                .ok_or_else(|| format!("Cannot sort by '{}'; use one of: {}", name, Self::names(&self.sorts, |(name, _)| name)))?;
+            sorts.push((name, column, order));
+        }
+        let id_order = match sorts.as_slice() {
+            [("id", _, order)] => Some(order.clone()),
+            _ => None,
+        };
+        if !sorts.iter().any(|(name, ..)| *name == "id") {
+            let order = sorts.last().map(|(.., order)| order.clone()).unwrap_or(Order::Desc);
+            sorts.push(("id", self.id.clone(), order));
+        }
+
+        let mut condition = Condition::all();
+        for (name, value) in &query.filters {
+            if self.params.contains(&name.as_str()) {
+                continue;
+            }
+            let filter = self.filters.iter().find(|filter| filter.name == name).ok_or_else(|| {
+                if self.filters.is_empty() {
+                    format!("Unknown filter '{}'; this list has no filters", name)
+                } else {
+                    format!("Unknown filter '{}'; use one of: {}", name, Self::names(&self.filters, |filter| filter.name))
+                }
+            })?;
+            condition = condition.add(filter.condition(value)?);
+        }
+
+        if let Some(cursor) = &query.cursor {
+            let order = id_order.clone().ok_or("A cursor can only be used when sorting by id or -id")?;
+            let after = decode_cursor(cursor).ok_or("Invalid cursor")?;
+            condition = condition.add(match order {
+                Order::Asc => Expr::expr(self.id.clone()).gt(after),
+                _ => Expr::expr(self.id.clone()).lt(after),
+            });
+        }
+
+        Ok(ListPlan {
+            condition,
+            order: sorts.into_iter().map(|(_, column, order)| (column, order)).collect(),
+            page: query.page,
+            size: query.size,
+            cursor: query.cursor.is_some(),
+            by_id: id_order.is_some(),
+        })
+    }
+}
+
+fn encode_cursor(id: i32) -> String {
+    URL_SAFE_NO_PAD.encode(id.to_string())
+}
+
+fn decode_cursor(cursor: &str) -> Option<i32> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    String::from_utf8(bytes).ok()?.parse().ok()
+}
+
+/// A validated list request, ready to apply to a query.
+pub struct ListPlan {
+    condition: Condition,
+    order: Vec<(SimpleExpr, Order)>,
+    page: u64,
+    size: u64,
+    cursor: bool,
+    by_id: bool,
+}
+
+impl ListPlan {
+    /// Adds the filters and sort order. Call it before `into_model`/`into_json`, which
+    /// return selectors that can no longer be filtered.
+    pub fn apply<Q: QueryFilter + QueryOrder>(&self, query: Q) -> Q {
+        let mut query = query.filter(self.condition.clone());
+        for (column, order) in &self.order {
+            query = query.order_by(column.clone(), order.clone());
+        }
+        query
+    }
+
+    /// Fetches the requested page of a query `apply` was called on. `id_of` reads the list's
+    /// id from a row, for the next cursor.
+    pub async fn fetch<'db, C, S>(
+        &self,
+        db: &'db C,
+        select: S,
+        id_of: impl Fn(&<S::Selector as SelectorTrait>::Item) -> i32,
+    ) -> Result<Page<<S::Selector as SelectorTrait>::Item>, DbErr>
+    where
+        C: ConnectionTrait,
+        S: PaginatorTrait<'db, C>,
+    {
+        let paginator = select.paginate(db, self.size);
+        let (items, total) = if self.cursor {
+            (paginator.fetch_page(0).await?, None)
+        } else {
+            let total = paginator.num_items().await?;
+            (paginator.fetch_page(self.page - 1).await?, Some(total))
+        };
+
+        let has_more = match total {
+            Some(total) => self.page * self.size < total,
+            None => items.len() as u64 == self.size,
+        };
+        let next_cursor = match items.last() {
+            Some(last) if self.by_id && has_more => Some(encode_cursor(id_of(last))),
+            _ => None,
+        };
+
+        Ok(Page {
+            items,
+            pagination: Pagination {
+                page: (!self.cursor).then_some(self.page),
+                size: self.size,
+                total,
+                total_pages: total.map(|total| total.div_ceil(self.size)),
+                next_cursor,
+            },
+        })
+    }
+}
+
+/// Where a page sits in the list. With a cursor there is no page number or total, since
+/// counting every row is exactly what cursors avoid.
+#[derive(Debug, Clone, Serialize)]
+pub struct Pagination {
+    pub page: Option<u64>,
+    pub size: u64,
+    pub total: Option<u64>,
+    pub total_pages: Option<u64>,
+    /// Set when sorted by id and there are more rows; pass it as `cursor` for the next page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub pagination: Pagination,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page { items: self.items.into_iter().map(f).collect(), pagination: self.pagination }
+    }
+}
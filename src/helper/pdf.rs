@@ -0,0 +1,69 @@
+/// Renders monospaced lines of text as a single-page PDF sized like a receipt roll.
+/// Each line is `(text, bold)`; text outside Latin-1 is replaced with `?`.
+pub fn text_document(lines: &[(String, bool)], chars_per_line: usize) -> Vec<u8> {
+    const FONT_SIZE: f32 = 9.0;
+    const LEADING: f32 = 11.0;
+    const MARGIN: f32 = 12.0;
+    // Courier glyphs are 600/1000 em wide
+    let width = chars_per_line as f32 * FONT_SIZE * 0.6 + 2.0 * MARGIN;
+    let height = lines.len().max(1) as f32 * LEADING + 2.0 * MARGIN;
+
+    let mut content = format!("BT\n{} TL\n{} {} Td\n", LEADING, MARGIN, height - MARGIN - FONT_SIZE).into_bytes();
+    let mut current_bold = None;
+    for (text, bold) in lines {
+        if current_bold != Some(*bold) {
+            let font = if *bold { "F2" } else { "F1" };
+            content.extend_from_slice(format!("/{} {} Tf\n", font, FONT_SIZE).as_bytes());
+            current_bold = Some(*bold);
+        }
+        content.push(b'(');
+        for c in text.chars() {
+            match c {
+                '(' | ')' | '\\' => {
+                    content.push(b'\\');
+                    content.push(c as u8);
+                }
+                c if (c as u32) < 0x20 => content.push(b' '),
+                c if (c as u32) < 0x100 => content.push(c as u32 as u8),
+                _ => content.push(b'?'),
+            }
+        }
+        content.extend_from_slice(b") Tj T*\n");
+    }
+    content.extend_from_slice(b"ET");
+
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.1} {:.1}] /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>",
+            width, height
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+    stream.extend_from_slice(&content);
+    stream.extend_from_slice(b"\nendstream");
+    objects.push(stream);
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_offset).as_bytes(),
+    );
+    pdf
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use image::DynamicImage;
+use sea_orm::{ConnectionTrait, DbErr, prelude::Decimal};
+
+use crate::entities::orders;
+use crate::entities::settings_model::{date_pattern, Settings};
+use crate::helper::escpos::{EscPos, Justification};
+use crate::helper::pdf;
+use crate::repository::{
+    customers_repository::CustomerRepository,
+    employees_repository::EmployeeRepository,
+    order_items_repository::OrderItemRepository,
+    payments_repository::PaymentRepository,
+    products_repository::ProductRepository,
+    promotions_repository::PromotionRepository,
+    stores_repository::StoreRepository,
+    terminals_repository::TerminalRepository,
+};
+
+/// Characters per line; fits 80mm paper in Font A and 58mm paper in Font B.
+pub const RECEIPT_WIDTH: usize = 42;
+
+pub struct ReceiptItem {
+    pub name: String,
+    pub quantity: i32,
+    /// Shelf price before any promotion.
+    pub unit_price: Decimal,
+    pub discount_per_unit: Decimal,
+    pub promotion_name: Option<String>,
+}
+
+impl ReceiptItem {
+    fn gross(&self) -> Decimal {
+        self.unit_price * Decimal::from(self.quantity)
+    }
+
+    fn discount(&self) -> Decimal {
+        self.discount_per_unit * Decimal::from(self.quantity)
+    }
+}
+
+pub struct ReceiptPayment {
+    pub method: String,
+    pub amount: Decimal,
+}
+
+pub struct Receipt {
+    pub order_id: i32,
+    pub order_date: DateTime<Utc>,
+    pub store_name: String,
+    pub store_address: Option<String>,
+    pub store_phone: Option<String>,
+    pub cashier_name: Option<String>,
+    pub terminal_name: Option<String>,
+    pub customer_name: Option<String>,
+    pub items: Vec<ReceiptItem>,
+    pub payments: Vec<ReceiptPayment>,
+    pub total: Decimal,
+    pub tax_rate: f64,
+    pub header_text: String,
+    pub footer_text: String,
+    pub currency_symbol: String,
+    pub date_format: String,
+    pub logo_url: String,
+}
+
+/// A single printed line; `text` is already laid out to `RECEIPT_WIDTH`.
+pub struct ReceiptLine {
+    pub text: String,
+    pub justification: Justification,
+    pub bold: bool,
+}
+
+pub async fn build_receipt<C: ConnectionTrait>(db: &C, order: orders::Model, settings: &Settings) -> Result<Receipt, DbErr> {
+    let store = StoreRepository::find_by_id(db, order.store_id).await?;
+    let cashier = EmployeeRepository::find_by_id(db, order.employee_id).await?;
+    let customer = CustomerRepository::find_by_id(db, order.customer_id).await?;
+    let terminal = match order.terminal_id {
+        Some(id) => TerminalRepository::find_by_id(db, id).await?,
+        None => None,
+    };
+
+    let order_items = OrderItemRepository::get_all_by_order_id(db, order.id).await?;
+    let product_ids = order_items.iter().map(|i| i.product_id).collect();
+    let promotion_ids = order_items.iter().filter_map(|i| i.promotion_id).collect();
+    let product_names: HashMap<i32, String> = ProductRepository::find_by_ids(db, product_ids)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    let promotion_names: HashMap<i32, String> = PromotionRepository::find_by_ids(db, promotion_ids)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+
+    let items = order_items
+        .into_iter()
+        .map(|item| ReceiptItem {
+            name: product_names.get(&item.product_id).cloned().unwrap_or_else(|| format!("Product #{}", item.product_id)),
+            quantity: item.quantity,
+            unit_price: item.unit_price + item.discount_amount,
+            discount_per_unit: item.discount_amount,
+            promotion_name: item.promotion_id.and_then(|id| promotion_names.get(&id).cloned()),
+        })
+        .collect();
+
+    let payments = PaymentRepository::find_by_order_id(db, order.id)
+        .await?
+        .into_iter()
+        .map(|p| ReceiptPayment { method: p.payment_method, amount: p.amount })
+        .collect();
+
+    let show_address = settings.receipt.show_store_address;
+    Ok(Receipt {
+        order_id: order.id,
+        order_date: order.order_date,
+        store_name: store.as_ref().map(|s| s.name.clone()).unwrap_or_else(|| settings.general.site_name.clone()),
+        store_address: store.as_ref().filter(|_| show_address).and_then(|s| s.address.clone()),
+        store_phone: store.as_ref().filter(|_| show_address).and_then(|s| s.phone.clone()),
+        cashier_name: cashier.map(|e| format!("{} {}", e.first_name, e.last_name)),
+        terminal_name: terminal.map(|t| t.name),
+        customer_name: customer.map(|c| format!("{} {}", c.first_name, c.last_name)),
+        items,
+        payments,
+        total: order.total_amount,
+        tax_rate: settings.general.default_tax_rate,
+        header_text: settings.receipt.header_text.clone(),
+        footer_text: settings.receipt.footer_text.clone(),
+        currency_symbol: settings.general.currency_symbol.clone(),
+        date_format: settings.general.default_date_format.clone(),
+        logo_url: settings.general.logo_url.clone(),
+    })
+}
+
+impl Receipt {
+    fn money(&self, amount: Decimal) -> String {
+        format_money(&self.currency_symbol, amount)
+    }
+
+    /// The order date in the settings date format, or ISO 8601 if that format is invalid
+    /// (it is validated on save, but older configs were not).
+    fn formatted_date(&self) -> String {
+        let pattern = date_pattern(&self.date_format).unwrap_or_else(|| "%Y-%m-%d".to_string());
+        format!("{} {}", self.order_date.format(&pattern), self.order_date.format("%H:%M"))
+    }
+
+    /// Prices are tax-inclusive, so the tax is the share of the total at the default rate.
+    fn tax_included(&self) -> Decimal {
+        let rate = Decimal::from_f64_retain(self.tax_rate).unwrap_or_default();
+        if rate > Decimal::ZERO {
+            (self.total * rate / (Decimal::ONE_HUNDRED + rate)).round_dp(2)
+        } else {
+            Decimal::ZERO
+        }
+    }
+
+    pub fn lines(&self) -> Vec<ReceiptLine> {
+        let mut lines = Vec::new();
+        let centered = |lines: &mut Vec<ReceiptLine>, text: &str, bold: bool| {
+            for chunk in wrap(text, RECEIPT_WIDTH) {
+                lines.push(ReceiptLine { text: chunk, justification: Justification::Center, bold });
+            }
+        };
+        let left = |lines: &mut Vec<ReceiptLine>, text: String, bold: bool| {
+            lines.push(ReceiptLine { text, justification: Justification::Left, bold });
+        };
+        let rule = "-".repeat(RECEIPT_WIDTH);
+
+        centered(&mut lines, &self.store_name, true);
+        if let Some(address) = &self.store_address {
+            centered(&mut lines, address, false);
+        }
+        if let Some(phone) = &self.store_phone {
+            centered(&mut lines, phone, false);
+        }
+        if !self.header_text.is_empty() {
+            centered(&mut lines, &self.header_text, false);
+        }
+        left(&mut lines, rule.clone(), false);
+
+        left(&mut lines, columns(&format!("Order #{}", self.order_id), &self.formatted_date()), false);
+        if let Some(cashier) = &self.cashier_name {
+            left(&mut lines, format!("Cashier: {}", cashier), false);
+        }
+        if let Some(terminal) = &self.terminal_name {
+            left(&mut lines, format!("Register: {}", terminal), false);
+        }
+        if let Some(customer) = &self.customer_name {
+            left(&mut lines, format!("Customer: {}", customer), false);
+        }
+        left(&mut lines, rule.clone(), false);
+
+        for item in &self.items {
+            for chunk in wrap(&item.name, RECEIPT_WIDTH) {
+                left(&mut lines, chunk, false);
+            }
+            left(&mut lines, columns(&format!("  {} x {}", item.quantity, self.money(item.unit_price)), &self.money(item.gross())), false);
+            if item.discount_per_unit > Decimal::ZERO {
+                let label = item.promotion_name.as_deref().unwrap_or("Discount");
+                left(&mut lines, columns(&format!("  {}", label), &self.money(-item.discount())), false);
+            }
+        }
+        left(&mut lines, rule.clone(), false);
+
+        let subtotal: Decimal = self.items.iter().map(|i| i.gross()).sum();
+        let discounts: Decimal = self.items.iter().map(|i| i.discount()).sum();
+        left(&mut lines, columns("Subtotal", &self.money(subtotal)), false);
+        if discounts > Decimal::ZERO {
+            left(&mut lines, columns("Promotions", &self.money(-discounts)), false);
+        }
+        left(&mut lines, columns("TOTAL", &self.money(self.total)), true);
+        if self.tax_rate > 0.0 {
+            left(&mut lines, columns(&format!("Incl. tax {}%", self.tax_rate), &self.money(self.tax_included())), false);
+        }
+        if !self.payments.is_empty() {
+            left(&mut lines, rule.clone(), false);
+            for payment in &self.payments {
+                left(&mut lines, columns(&payment.method, &self.money(payment.amount)), false);
+            }
+        }
+
+        if !self.footer_text.is_empty() {
+            left(&mut lines, rule, false);
+            centered(&mut lines, &self.footer_text, false);
+        }
+        lines
+    }
+}
+
+pub fn render_text(receipt: &Receipt) -> String {
+    receipt
+        .lines()
+        .into_iter()
+        .map(|line| match line.justification {
+            Justification::Center => center(&line.text, RECEIPT_WIDTH),
+            Justification::Left => line.text,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+pub fn render_pdf(receipt: &Receipt) -> Vec<u8> {
+    let lines: Vec<(String, bool)> = receipt
+        .lines()
+        .into_iter()
+        .map(|line| match line.justification {
+            Justification::Center => (center(&line.text, RECEIPT_WIDTH), line.bold),
+            Justification::Left => (line.text, line.bold),
+        })
+        .collect();
+    pdf::text_document(&lines, RECEIPT_WIDTH)
+}
+
+pub fn render_escpos(receipt: &Receipt) -> Vec<u8> {
+    let mut printer = EscPos::new();
+
+    if let Some(logo) = load_logo(&receipt.logo_url) {
+        printer.justify(Justification::Center).image(&logo);
+    }
+
+    let mut current = (Justification::Left, false);
+    printer.justify(current.0).bold(current.1);
+    for line in receipt.lines() {
+        if line.justification != current.0 {
+            printer.justify(line.justification);
+        }
+        if line.bold != current.1 {
+            printer.bold(line.bold);
+        }
+        current = (line.justification, line.bold);
+        printer.line(&line.text);
+    }
+
+    printer.bold(false).feed(3).cut();
+    printer.into_bytes()
+}
+
+/// Loads the configured logo when it points at a file under this server's `/uploads`.
+/// Remote logos are not fetched; the receipt is printed without one.
+fn load_logo(logo_url: &str) -> Option<DynamicImage> {
+    let path = match logo_url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|i| &rest[i..])?,
+        None => logo_url,
+    };
+    let relative = path.strip_prefix("/uploads/")?;
+    if relative.split('/').any(|segment| segment == "..") {
+        return None;
+    }
+    match image::open(format!("./uploads/{}", relative)) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            log::warn!("Failed to load receipt logo {}: {}", logo_url, e);
+            None
+        }
+    }
+}
+
+pub fn format_money(currency_symbol: &str, amount: Decimal) -> String {
+    let amount = amount.round_dp(2);
+    if amount.is_sign_negative() && !amount.is_zero() {
+        format!("-{}{:.2}", currency_symbol, amount.abs())
+    } else {
+        format!("{}{:.2}", currency_symbol, amount)
+    }
+}
+
+/// Puts `left` and `right` on one line, truncating `left` if they don't fit.
+pub fn columns(left: &str, right: &str) -> String {
+    let right_len = right.chars().count();
+    let max_left = RECEIPT_WIDTH.saturating_sub(right_len + 1);
+    let left: String = left.chars().take(max_left).collect();
+    let padding = RECEIPT_WIDTH.saturating_sub(left.chars().count() + right_len);
+    format!("{}{}{}", left, " ".repeat(padding), right)
+}
+
+fn center(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(text.chars().count()) / 2;
+    format!("{}{}", " ".repeat(padding), text)
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        while word.chars().count() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            let head: String = word.chars().take(width).collect();
+            word = word.chars().skip(width).collect();
+            lines.push(head);
+        }
+        if current.is_empty() {
+            current = word;
+        } else if current.chars().count() + 1 + word.chars().count() <= width {
+            current.push(' ');
+            current.push_str(&word);
+        } else {
+            lines.push(std::mem::replace(&mut current, word));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
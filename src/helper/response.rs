@@ -6,6 +6,8 @@ use actix_web::{
 use sea_orm::DbErr;
 use std::fmt;
 
+use crate::helper::pagination::{Page, Pagination};
+
 #[allow(dead_code)]
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T> {
@@ -22,6 +24,24 @@ impl<T: Serialize> ApiResponse<T> {
     }
 }
 
+/// A page of a list; `data` holds the rows and `pagination` where they sit in the list.
+#[derive(Debug, Serialize)]
+pub struct ApiPage<T> {
+    pub success: bool,
+    pub data: Vec<T>,
+    pub pagination: Pagination,
+}
+
+impl<T: Serialize> ApiPage<T> {
+    pub fn new(page: Page<T>) -> Self {
+        ApiPage {
+            success: true,
+            data: page.items,
+            pagination: page.pagination,
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct ApiError {
     pub success: bool,
@@ -37,6 +57,24 @@ impl ApiError {
     }
 }
 
+/// Returned with 422 when a payload is well formed but some of its fields are invalid.
+#[derive(Serialize)]
+pub struct ApiValidationError<E: Serialize> {
+    pub success: bool,
+    pub message: String,
+    pub errors: Vec<E>,
+}
+
+impl<E: Serialize> ApiValidationError<E> {
+    pub fn new(message: String, errors: Vec<E>) -> Self {
+        ApiValidationError {
+            success: false,
+            message,
+            errors,
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub enum AppError {
@@ -0,0 +1,76 @@
+//! AES-256-GCM encryption for secrets stored in the database, such as the SMTP password in
+//! the settings JSON. Keys are 32 random bytes, base64 encoded, read from
+//! `SETTINGS_ENCRYPTION_KEY`. While rotating, the old key goes in
+//! `SETTINGS_ENCRYPTION_KEY_PREVIOUS` so existing values can still be read until
+//! `rotate-settings-key` has re-encrypted them.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::RngCore;
+
+pub const KEY_ENV: &str = "SETTINGS_ENCRYPTION_KEY";
+pub const PREVIOUS_KEY_ENV: &str = "SETTINGS_ENCRYPTION_KEY_PREVIOUS";
+/// Marks an encrypted value; anything without it is a plaintext value from before encryption.
+const PREFIX: &str = "enc:v1:";
+const NONCE_BYTES: usize = 12;
+
+fn load_key(var: &str) -> Result<Option<Aes256Gcm>, String> {
+    let encoded = match std::env::var(var) {
+        Ok(value) if !value.trim().is_empty() => value,
+        _ => return Ok(None),
+    };
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| format!("{} is not valid base64", var))?;
+    Aes256Gcm::new_from_slice(&bytes)
+        .map(Some)
+        .map_err(|_| format!("{} must be 32 bytes", var))
+}
+
+/// The current key, if one is configured.
+pub fn current_key() -> Result<Option<Aes256Gcm>, String> {
+    load_key(KEY_ENV)
+}
+
+pub fn encrypt(key: &Aes256Gcm, plaintext: &str) -> Result<String, String> {
+    let mut nonce = [0u8; NONCE_BYTES];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = key
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", PREFIX, STANDARD.encode(payload)))
+}
+
+fn decrypt_with(key: &Aes256Gcm, payload: &[u8]) -> Option<String> {
+    let (nonce, ciphertext) = payload.split_at(NONCE_BYTES);
+    let plaintext = key.decrypt(Nonce::from_slice(nonce), ciphertext).ok()?;
+    String::from_utf8(plaintext).ok()
+}
+
+/// Decrypts a stored value with the current key, falling back to the previous one.
+/// Values stored before encryption was introduced are returned as they are.
+pub fn decrypt(value: &str) -> Result<String, String> {
+    let Some(encoded) = value.strip_prefix(PREFIX) else {
+        return Ok(value.to_string());
+    };
+    let payload = STANDARD
+        .decode(encoded)
+        .map_err(|_| "Encrypted secret is not valid base64".to_string())?;
+    if payload.len() <= NONCE_BYTES {
+        return Err("Encrypted secret is truncated".to_string());
+    }
+
+    for var in [KEY_ENV, PREVIOUS_KEY_ENV] {
+        if let Some(key) = load_key(var)? {
+            if let Some(plaintext) = decrypt_with(&key, &payload) {
+                return Ok(plaintext);
+            }
+        }
+    }
+    Err(format!("Secret cannot be decrypted with {} or {}", KEY_ENV, PREVIOUS_KEY_ENV))
+}
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+/// Replaces `{{name}}` placeholders with values from `vars`. Unknown placeholders
+/// render as empty. Values are HTML-escaped when `escape_html` is set.
+pub fn render(template: &str, vars: &HashMap<&str, String>, escape_html: bool) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        match after_open.find("}}") {
+            Some(end) => {
+                if let Some(value) = vars.get(after_open[..end].trim()) {
+                    if escape_html {
+                        output.push_str(&escape(value));
+                    } else {
+                        output.push_str(value);
+                    }
+                }
+                rest = &after_open[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,136 @@
+//! A stand-in database for tests: queries are answered by a closure and every statement is
+//! recorded, so tests can check what was written and how many round trips it took.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use sea_orm::{
+    Database, DatabaseBackend, DatabaseConnection, DbErr, EntityTrait, IdenStatic, Iterable, ModelTrait,
+    ProxyDatabaseTrait, ProxyExecResult, ProxyRow, Statement, Value,
+};
+
+use crate::auth::auth_service::Claims;
+use crate::entities::sessions;
+
+/// What `access_token` signs with; every test uses the same one since the app reads
+/// `JWT_SECRET` only once.
+const JWT_SECRET: &str = "test-jwt-secret";
+const EMPLOYEE_ID: i32 = 7;
+const SESSION_ID: i32 = 3;
+
+pub type Statements = Arc<Mutex<Vec<Statement>>>;
+
+type Respond = Box<dyn Fn(&Statement) -> Vec<ProxyRow> + Send + Sync>;
+
+struct TestDatabase {
+    respond: Respond,
+    statements: Statements,
+}
+
+impl std::fmt::Debug for TestDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestDatabase").finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl ProxyDatabaseTrait for TestDatabase {
+    async fn query(&self, statement: Statement) -> Result<Vec<ProxyRow>, DbErr> {
+        let rows = (self.respond)(&statement);
+        self.statements.lock().unwrap().push(statement);
+        Ok(rows)
+    }
+
+    /// Every write affects one row.
+    async fn execute(&self, statement: Statement) -> Result<ProxyExecResult, DbErr> {
+        self.statements.lock().unwrap().push(statement);
+        Ok(ProxyExecResult::new(0, 1))
+    }
+}
+
+/// A MySQL connection whose queries return what `respond` gives for them.
+pub async fn connect(respond: impl Fn(&Statement) -> Vec<ProxyRow> + Send + Sync + 'static) -> (DatabaseConnection, Statements) {
+    let statements = Statements::default();
+    let database: Box<dyn ProxyDatabaseTrait> = Box::new(TestDatabase { respond: Box::new(respond), statements: statements.clone() });
+    let db = Database::connect_proxy(DatabaseBackend::MySql, Arc::new(database)).await.unwrap();
+    (db, statements)
+}
+
+/// A result row holding every column of a model.
+pub fn row<M: ModelTrait>(model: &M) -> ProxyRow {
+    let values = <M::Entity as EntityTrait>::Column::iter()
+        .map(|column| (column.as_str().to_string(), model.get(column)))
+        .collect();
+    ProxyRow::new(values)
+}
+
+
+/// The row `PaginatorTrait::num_items` reads, which takes the count as an `i32` on MySQL.
+pub fn count_row(count: i32) -> ProxyRow {
+    ProxyRow::new(BTreeMap::from([("num_items".to_string(), Value::from(count))]))
+}
+
+/// The integer parameters of a statement, such as the ids of an `IN (...)` list.
+pub fn int_values(statement: &Statement) -> Vec<i32> {
+    let values = statement.values.as_ref().map(|values| values.0.as_slice()).unwrap_or_default();
+    values
+        .iter()
+        .filter_map(|value| match value {
+            Value::Int(Some(value)) => Some(*value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// An access token for an employee working in store 1, holding `permissions`. Answer
+/// `is_session_lookup` statements with `session()` for the app to accept it.
+pub fn access_token(permissions: &[&str]) -> String {
+    std::env::set_var("JWT_SECRET", JWT_SECRET);
+    let now = Utc::now();
+    let claims = Claims {
+        sub: EMPLOYEE_ID,
+        email: "jane@example.com".to_string(),
+        role: "Cashier".to_string(),
+        store_id: Some(1),
+        store_ids: vec![1],
+        permissions: permissions.iter().map(|p| p.to_string()).collect(),
+        sid: SESSION_ID,
+        scope: sessions::SCOPE_FULL.to_string(),
+        ver: 0,
+        iat: now.timestamp() as usize,
+        exp: (now + Duration::minutes(5)).timestamp() as usize,
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.as_bytes())).unwrap()
+}
+
+pub fn is_session_lookup(statement: &Statement) -> bool {
+    statement.sql.contains("FROM `sessions`")
+}
+
+/// The active session behind `access_token`.
+pub fn session() -> ProxyRow {
+    row(&session_model())
+}
+
+/// The model behind `session()`, for tests that need to change it first.
+pub fn session_model() -> sessions::Model {
+    let now = Utc::now();
+    sessions::Model {
+        id: SESSION_ID,
+        employee_id: EMPLOYEE_ID,
+        refresh_token_hash: String::new(),
+        previous_token_hash: None,
+        user_agent: None,
+        ip_address: None,
+        access_version: 0,
+        created_at: now,
+        last_used_at: now,
+        expires_at: now + Duration::hours(1),
+        revoked_at: None,
+        scope: sessions::SCOPE_FULL.to_string(),
+        terminal_id: None,
+        active_store_id: Some(1),
+    }
+}
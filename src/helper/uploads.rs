@@ -0,0 +1,253 @@
+//! Image uploads for product and employee photos. Every upload is checked against size,
+//! type and dimension limits, turned upright from its EXIF orientation and re-encoded as
+//! WebP in each of `SIZES`. Re-encoding drops EXIF and any other metadata, such as the GPS
+//! position a phone camera records.
+//!
+//! Objects are stored under `<subfolder>/<uuid>.webp` (the full size, whose URL is stored on
+//! the record) and `<subfolder>/<uuid>_<size>.webp` for the smaller sizes, in whichever
+//! `Storage` backend is configured.
+
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use image::io::{Limits, Reader as ImageReader};
+use image::{DynamicImage, ImageFormat};
+use sea_orm::{DatabaseConnection, DbErr};
+use uuid::Uuid;
+
+use crate::repository::employees_repository::EmployeeRepository;
+use crate::repository::products_repository::ProductRepository;
+use crate::storage::{Storage, StorageError};
+
+pub const PRODUCTS: &str = "products";
+pub const EMPLOYEES: &str = "employees";
+pub const MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+/// Largest width or height accepted, checked before decoding so a small file cannot expand
+/// into a huge bitmap.
+pub const MAX_DIMENSION: u32 = 6000;
+const ALLOWED_FORMATS: [ImageFormat; 4] = [ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::WebP, ImageFormat::Gif];
+const WEBP_QUALITY: f32 = 80.0;
+/// Generated sizes and the longest edge of each. Images smaller than a size are not enlarged.
+pub const SIZES: [(&str, u32); 3] = [("thumb", 200), ("medium", 800), ("full", 2000)];
+
+/// How long an unreferenced image is kept, so one uploaded for a form that has not been
+/// saved yet is not swept away.
+const ORPHAN_GRACE_HOURS: i64 = 24;
+const SWEEP_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+#[derive(Debug)]
+pub enum UploadError {
+    /// The file is larger than `MAX_UPLOAD_BYTES`.
+    TooLarge,
+    /// The client sent something that is not an acceptable image.
+    Rejected(String),
+    Failed(String),
+}
+
+/// URLs of every generated size, keyed by size name.
+pub type ImageUrls = Vec<(&'static str, String)>;
+
+fn decode(bytes: &[u8]) -> Result<DynamicImage, UploadError> {
+    let format = image::guess_format(bytes)
+        .ok()
+        .filter(|format| ALLOWED_FORMATS.contains(format))
+        .ok_or_else(|| UploadError::Rejected("Only JPEG, PNG, WebP and GIF images are accepted".to_string()))?;
+
+    let (width, height) = ImageReader::with_format(Cursor::new(bytes), format)
+        .into_dimensions()
+        .map_err(|e| UploadError::Rejected(format!("Failed to read image: {}", e)))?;
+    if width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(UploadError::Rejected(format!(
+            "Image is {}x{} pixels; the maximum is {}x{}",
+            width, height, MAX_DIMENSION, MAX_DIMENSION
+        )));
+    }
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    reader.limits(limits);
+    let img = reader
+        .decode()
+        .map_err(|e| UploadError::Rejected(format!("Failed to decode image: {}", e)))?;
+
+    Ok(apply_orientation(img, exif_orientation(bytes)))
+}
+
+/// The EXIF orientation tag (1 to 8), or 1 when there is none.
+fn exif_orientation(bytes: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .unwrap_or(1)
+}
+
+/// Rotates and flips the pixels the way a viewer would, since the tag itself is stripped.
+fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+fn encode_webp(img: &DynamicImage) -> Result<Vec<u8>, UploadError> {
+    // The encoder only takes 8-bit RGB and RGBA
+    let img = if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(img.to_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+    };
+    let encoder = webp::Encoder::from_image(&img)
+        .map_err(|e| UploadError::Failed(format!("Failed to create WebP encoder: {}", e)))?;
+    Ok(encoder.encode(WEBP_QUALITY).to_vec())
+}
+
+fn key(subfolder: &str, id: &Uuid, size: &str) -> String {
+    if size == "full" {
+        format!("{}/{}.webp", subfolder, id)
+    } else {
+        format!("{}/{}_{}.webp", subfolder, id, size)
+    }
+}
+
+/// The URL a record stores for an object; the app resolves it to the backend on each request.
+fn url_for_key(key: &str) -> String {
+    format!("/uploads/{}", key)
+}
+
+/// Decodes, checks and encodes every size. CPU bound, so `store_image` runs it off the
+/// async workers.
+fn render_sizes(bytes: &[u8]) -> Result<Vec<(&'static str, Vec<u8>)>, UploadError> {
+    if bytes.len() > MAX_UPLOAD_BYTES {
+        return Err(UploadError::TooLarge);
+    }
+    let img = decode(bytes)?;
+
+    let mut rendered = Vec::with_capacity(SIZES.len());
+    for (size, max_edge) in SIZES {
+        let resized = if img.width() > max_edge || img.height() > max_edge {
+            img.resize(max_edge, max_edge, image::imageops::FilterType::Lanczos3)
+        } else {
+            img.clone()
+        };
+        rendered.push((size, encode_webp(&resized)?));
+    }
+    Ok(rendered)
+}
+
+fn storage_failed(e: StorageError) -> UploadError {
+    UploadError::Failed(e.to_string())
+}
+
+/// Validates an uploaded image and stores every size under `subfolder`.
+pub async fn store_image(storage: &dyn Storage, subfolder: &str, bytes: Vec<u8>) -> Result<ImageUrls, UploadError> {
+    let rendered = tokio::task::spawn_blocking(move || render_sizes(&bytes))
+        .await
+        .map_err(|e| UploadError::Failed(e.to_string()))??;
+
+    let id = Uuid::new_v4();
+    let mut urls = Vec::with_capacity(rendered.len());
+    for (size, webp) in rendered {
+        let key = key(subfolder, &id, size);
+        storage.put(&key, webp, "image/webp").await.map_err(storage_failed)?;
+        urls.push((size, url_for_key(&key)));
+    }
+    Ok(urls)
+}
+
+/// The image id in a stored URL such as `/uploads/products/<uuid>.webp`, if it is one of ours.
+pub fn image_id(subfolder: &str, url: &str) -> Option<Uuid> {
+    let name = url.strip_prefix(&format!("/uploads/{}/", subfolder))?;
+    parse_file_name(name)
+}
+
+/// The image id of a stored file name, for any of its sizes.
+fn parse_file_name(name: &str) -> Option<Uuid> {
+    let stem = name.strip_suffix(".webp")?;
+    let id = stem.split_once('_').map(|(id, _)| id).unwrap_or(stem);
+    Uuid::parse_str(id).ok()
+}
+
+/// Deletes every size of an image. Returns false if there was nothing to delete.
+pub async fn delete_image(storage: &dyn Storage, subfolder: &str, id: &Uuid) -> Result<bool, StorageError> {
+    let mut deleted = false;
+    for (size, _) in SIZES {
+        deleted |= storage.delete(&key(subfolder, id, size)).await?;
+    }
+    Ok(deleted)
+}
+
+/// Whether `key` names an uploaded image, as opposed to anything else a client might ask for.
+pub fn is_image_key(key: &str) -> bool {
+    match key.split_once('/') {
+        Some((subfolder, name)) => [PRODUCTS, EMPLOYEES].contains(&subfolder) && parse_file_name(name).is_some(),
+        None => false,
+    }
+}
+
+/// Whether any product or employee still uses the image.
+pub async fn is_referenced(db: &DatabaseConnection, subfolder: &str, id: &Uuid) -> Result<bool, DbErr> {
+    Ok(referenced_ids(db, subfolder).await?.contains(id))
+}
+
+async fn referenced_ids(db: &DatabaseConnection, subfolder: &str) -> Result<HashSet<Uuid>, DbErr> {
+    let urls = match subfolder {
+        PRODUCTS => ProductRepository::get_photo_urls(db).await?,
+        EMPLOYEES => EmployeeRepository::get_photo_urls(db).await?,
+        _ => vec![],
+    };
+    Ok(urls.iter().filter_map(|url| image_id(subfolder, url)).collect())
+}
+
+/// Deletes images that no product or employee references any more, once they are older
+/// than the grace period. Returns how many objects were deleted.
+pub async fn sweep_orphans(db: &DatabaseConnection, storage: &dyn Storage) -> Result<usize, String> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(ORPHAN_GRACE_HOURS);
+    let mut deleted = 0;
+    for subfolder in [PRODUCTS, EMPLOYEES] {
+        let referenced = referenced_ids(db, subfolder).await.map_err(|e| e.to_string())?;
+        let objects = storage.list(&format!("{}/", subfolder)).await.map_err(|e| e.to_string())?;
+
+        for object in objects {
+            let Some(id) = object.key.split_once('/').and_then(|(_, name)| parse_file_name(name)) else {
+                continue;
+            };
+            if referenced.contains(&id) || object.last_modified > cutoff {
+                continue;
+            }
+            match storage.delete(&object.key).await {
+                Ok(true) => deleted += 1,
+                Ok(false) => {}
+                Err(e) => log::error!("Failed to delete orphaned image {}: {}", object.key, e),
+            }
+        }
+    }
+    Ok(deleted)
+}
+
+/// Sweeps orphaned images in the background.
+pub fn spawn_sweeper(db: DatabaseConnection, storage: Arc<dyn Storage>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match sweep_orphans(&db, storage.as_ref()).await {
+                Ok(0) => {}
+                Ok(deleted) => log::info!("Deleted {} orphaned image file(s)", deleted),
+                Err(e) => log::error!("Orphaned image sweep failed: {}", e),
+            }
+        }
+    });
+}
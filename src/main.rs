@@ -9,21 +9,21 @@ mod middleware;
 mod extractor;
 mod guard; // New module
 mod websocket; // <-- MODUL BARU
+mod storage;
+mod search;
 
 use std::env;
+use std::sync::Arc;
 use actix::Actor;
 use sea_orm::{Database, DatabaseConnection};
 use sea_orm_migration::MigratorTrait; // Import MigratorTrait
 use actix_cors::Cors;
 use actix_web::{http, web, App, HttpRequest, HttpServer, Result};
 use actix_files::{Files, NamedFile};
-use env_logger;
 
 use crate::websocket::broadcaster::Broadcaster;
 use crate::websocket::start_ws_connection;
 
-use crate::auth::auth_handler::login;
-
 pub struct AppState {
     pub db: DatabaseConnection,
     pub broadcaster: actix::Addr<Broadcaster>,
@@ -43,41 +43,96 @@ async fn main() -> std::io::Result<()> {
 
     migration::Migrator::up(&db, None).await.expect("Failed to run migrations");
 
-    // Ensure upload directories exist
-    std::fs::create_dir_all("./uploads/products")
-        .expect("Failed to create uploads/products directory");
-    std::fs::create_dir_all("./uploads/employees")
-        .expect("Failed to create uploads/employees directory");
+    // `webservice rotate-settings-key` re-encrypts stored secrets with SETTINGS_ENCRYPTION_KEY,
+    // reading them with SETTINGS_ENCRYPTION_KEY_PREVIOUS where needed, then exits
+    if env::args().nth(1).as_deref() == Some("rotate-settings-key") {
+        match repository::settings_repository::rotate_secrets(&db).await {
+            Ok(count) => {
+                println!("Re-encrypted {} secret(s) with the current key.", count);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Failed to rotate the settings key: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let media_storage = storage::from_env().unwrap_or_else(|e| panic!("{}", e));
+
+    // `webservice migrate-uploads <from> <to>` copies uploaded media between storage backends
+    // (`local` or `s3`, each configured from the environment), then exits
+    if env::args().nth(1).as_deref() == Some("migrate-uploads") {
+        let (Some(from), Some(to)) = (env::args().nth(2), env::args().nth(3)) else {
+            eprintln!("Usage: webservice migrate-uploads <local|s3> <local|s3>");
+            std::process::exit(2);
+        };
+        let result = async {
+            let (from, to) = (storage::from_name(&from)?, storage::from_name(&to)?);
+            storage::migrate(from.as_ref(), to.as_ref(), &["products/", "employees/"]).await
+        }
+        .await;
+        match result {
+            Ok(count) => {
+                println!("Copied {} file(s).", count);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Failed to migrate uploads: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    match helper::secrets::current_key() {
+        Ok(Some(_)) => {}
+        Ok(None) => log::warn!("{} is not set; secrets cannot be saved in the settings", helper::secrets::KEY_ENV),
+        Err(e) => panic!("{}", e),
+    }
+
+    // Drain the email outbox in the background
+    helper::mailer::spawn_worker(db.clone());
+    // Delete uploaded images nothing refers to any more
+    helper::uploads::spawn_sweeper(db.clone(), media_storage.clone());
+    // Build the product and customer search index and keep it in step with the database
+    let search_index = Arc::new(search::SearchIndex::default());
+    search::spawn_refresher(db.clone(), search_index.clone());
 
-    // Mulai Broadcaster Actor
-    let broadcaster = Broadcaster::default().start();
+    // Sends live events to WebSocket clients and keeps them for replay
+    let broadcaster = Broadcaster::new(db.clone()).start();
 
     let app_state = web::Data::new(AppState { db: db.clone(), broadcaster: broadcaster.clone() });
+    let db_data = web::Data::new(db.clone());
+    let broadcaster_data = web::Data::new(broadcaster.clone());
+    let storage_data: web::Data<dyn storage::Storage> = web::Data::from(media_storage);
+    let search_data = web::Data::from(search_index);
 
     HttpServer::new(move || {
         let cors = Cors::default()
             .allowed_origin("http://localhost:5173")
-            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
+            .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
             .allowed_headers(vec![http::header::AUTHORIZATION, http::header::ACCEPT])
             .allowed_header(http::header::CONTENT_TYPE)
+            .allowed_header(crate::extractor::terminal_extractor::TERMINAL_TOKEN_HEADER)
             .max_age(3600);
 
         App::new()
             .wrap(cors)
             .app_data(app_state.clone())
-            .service(web::scope("/api/auth").route("/login", web::post().to(login)))
+            .app_data(db_data.clone())
+            .app_data(broadcaster_data.clone())
+            .app_data(storage_data.clone())
+            .app_data(search_data.clone())
             .service(
                 web::scope("/api")
                     .configure(routes::configure_routes)
             )
             
-            // WebSocket route
-            .service(start_ws_connection) // <-- Tambahkan rute WebSocket
+            // Live events for authenticated clients
+            .service(start_ws_connection)
 
             // Static assets
             .service(Files::new("/assets", "./frontend/dist/assets"))
-            .service(Files::new("/uploads/products", "./uploads/products")) 
-            .service(Files::new("/uploads/employees", "./uploads/employees"))
+            .route("/uploads/{key:.*}", web::get().to(handler::upload_handler::serve_upload))
             .route("/vite.svg", web::get().to(|| async { NamedFile::open_async("./frontend/dist/vite.svg").await }))
 
             // SPA routes (should be last)
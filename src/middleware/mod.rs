@@ -1,2 +1 @@
-pub mod role;
 pub mod permission;
\ No newline at end of file
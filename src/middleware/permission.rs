@@ -3,12 +3,11 @@ use std::future::{ready, Ready};
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
     Error,
-    error::ErrorForbidden,
+    error::{ErrorForbidden, ErrorUnauthorized},
     HttpMessage,
 };
 use futures_util::future::LocalBoxFuture;
-use crate::auth::auth_service::{decode_jwt};
-use actix_web::http::header::AUTHORIZATION;
+use crate::auth::auth_service::authenticate;
 
 pub struct PermissionMiddlewareFactory {
     pub required_permissions: Vec<String>,
@@ -53,46 +52,28 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = self.service.clone();
-        let _required_permissions = self.required_permissions.clone();
+        let required_permissions = self.required_permissions.clone();
 
         Box::pin(async move {
-            let claims = if let Some(auth_header) = req.headers().get(AUTHORIZATION) {
-                if let Ok(auth_str) = auth_header.to_str() {
-                    if auth_str.starts_with("Bearer ") {
-                        let token = &auth_str["Bearer ".len()..];
-                        decode_jwt(token).ok().map(|token_data| token_data.claims)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            // Expired, revoked or superseded tokens are treated as missing
+            let claims = authenticate(req.request()).await.ok();
 
             if let Some(claims) = claims {
-                // TEMPORARY BYPASS: Always allow if token is valid
-                req.extensions_mut().insert(claims);
-                service.call(req).await
-                /* Original logic:
-                let user_permissions = &claims.permissions;
-                log::debug!("Required permissions: {:?}", required_permissions);
-                log::debug!("User permissions: {:?}", user_permissions);
-                let has_all_permissions = required_permissions.iter().all(|p| user_permissions.contains(p));
-
-                if has_all_permissions {
+                let missing: Vec<&String> = required_permissions.iter().filter(|p| !claims.permissions.contains(p)).collect();
+                if missing.is_empty() {
                     req.extensions_mut().insert(claims);
                     service.call(req).await
+                } else if claims.is_pos_session() {
+                    // PIN sessions carry only the POS permissions
+                    log::warn!("Forbidden: POS session of {:?} used outside POS actions", claims.email);
+                    Err(ErrorForbidden("Forbidden: Not available from a POS session"))
                 } else {
-                    log::warn!("Forbidden: Insufficient permissions for user {:?}. Missing: {:?}", claims.email, required_permissions.iter().filter(|p| !user_permissions.contains(p)).collect::<Vec<_>>());
+                    log::warn!("Forbidden: Insufficient permissions for user {:?}. Missing: {:?}", claims.email, missing);
                     Err(ErrorForbidden("Forbidden: Insufficient permissions"))
                 }
-                */
             } else {
-                println!("DEBUG: PermissionMiddleware - Claims is None. Returning 403."); // Added debug print
-                log::warn!("Forbidden: Invalid or missing token");
-                Err(ErrorForbidden("Forbidden: Invalid or missing token"))
+                log::warn!("Unauthorized: Invalid, expired or revoked token");
+                Err(ErrorUnauthorized("Unauthorized: Invalid, expired or revoked token"))
             }
         })
     }
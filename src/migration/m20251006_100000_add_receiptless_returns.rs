@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Receipt-less refunds have no originating order or order item
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Refunds::Table)
+                    .modify_column(ColumnDef::new(Refunds::OrderId).integer().null())
+                    .add_column(ColumnDef::new(Refunds::CustomerId).integer().null())
+                    .add_column(ColumnDef::new(Refunds::RefundMethod).string().not_null().default("ORIGINAL_TENDER"))
+                    .add_column(ColumnDef::new(Refunds::IsReceiptless).boolean().not_null().default(false))
+                    .add_column(ColumnDef::new(Refunds::ApprovedBy).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RefundItems::Table)
+                    .modify_column(ColumnDef::new(RefundItems::OrderItemId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Customers::Table)
+                    .add_column(ColumnDef::new(Customers::StoreCreditBalance).decimal_len(10, 2).not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager.create_foreign_key(ForeignKey::create().name("fk-refunds-customer_id").from(Refunds::Table, Refunds::CustomerId).to(Customers::Table, Customers::Id).to_owned()).await?;
+        manager.create_foreign_key(ForeignKey::create().name("fk-refunds-approved_by").from(Refunds::Table, Refunds::ApprovedBy).to(Employees::Table, Employees::Id).to_owned()).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_foreign_key(ForeignKey::drop().name("fk-refunds-customer_id").table(Refunds::Table).to_owned()).await?;
+        manager.drop_foreign_key(ForeignKey::drop().name("fk-refunds-approved_by").table(Refunds::Table).to_owned()).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Customers::Table)
+                    .drop_column(Customers::StoreCreditBalance)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RefundItems::Table)
+                    .modify_column(ColumnDef::new(RefundItems::OrderItemId).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Refunds::Table)
+                    .drop_column(Refunds::CustomerId)
+                    .drop_column(Refunds::RefundMethod)
+                    .drop_column(Refunds::IsReceiptless)
+                    .drop_column(Refunds::ApprovedBy)
+                    .modify_column(ColumnDef::new(Refunds::OrderId).integer().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Refunds {
+    Table,
+    OrderId,
+    CustomerId,
+    RefundMethod,
+    IsReceiptless,
+    ApprovedBy,
+}
+
+#[derive(DeriveIden)]
+enum RefundItems {
+    Table,
+    OrderItemId,
+}
+
+#[derive(DeriveIden)]
+enum Customers {
+    Table,
+    Id,
+    StoreCreditBalance,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+use sea_orm::{ConnectionTrait, Statement, DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute(Statement::from_string(
+            DbBackend::MySql,
+            "INSERT IGNORE INTO permissions (name, description) VALUES ('refunds:override', 'Can approve refunds above return limits');",
+        )).await?;
+
+        for role_name in ["Owner", "Admin", "StoreManager"] {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!(
+                    "INSERT IGNORE INTO role_permissions (role_id, permission_id) \
+                     SELECT r.id, p.id FROM roles r, permissions p WHERE r.name = '{}' AND p.name = 'refunds:override';",
+                    role_name
+                ),
+            )).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute(Statement::from_string(
+            DbBackend::MySql,
+            "DELETE rp FROM role_permissions rp JOIN permissions p ON p.id = rp.permission_id WHERE p.name = 'refunds:override';",
+        )).await?;
+        db.execute(Statement::from_string(DbBackend::MySql, "DELETE FROM permissions WHERE name = 'refunds:override';")).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Shifts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Shifts::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Shifts::EmployeeId).integer().not_null())
+                    .col(ColumnDef::new(Shifts::StoreId).integer().not_null())
+                    .col(ColumnDef::new(Shifts::TerminalName).string().not_null())
+                    .col(ColumnDef::new(Shifts::OpeningFloat).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(Shifts::ExpectedCash).decimal_len(10, 2).null())
+                    .col(ColumnDef::new(Shifts::CountedCash).decimal_len(10, 2).null())
+                    .col(ColumnDef::new(Shifts::OverShort).decimal_len(10, 2).null())
+                    .col(ColumnDef::new(Shifts::Status).string().not_null())
+                    .col(ColumnDef::new(Shifts::Notes).text().null())
+                    .col(ColumnDef::new(Shifts::OpenedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Shifts::ClosedAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(Shifts::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Shifts::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-shifts-employee_id")
+                            .from(Shifts::Table, Shifts::EmployeeId)
+                            .to(Employees::Table, Employees::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-shifts-store_id")
+                            .from(Shifts::Table, Shifts::StoreId)
+                            .to(Stores::Table, Stores::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Shifts::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Shifts {
+    Table,
+    Id,
+    EmployeeId,
+    StoreId,
+    TerminalName,
+    OpeningFloat,
+    ExpectedCash,
+    CountedCash,
+    OverShort,
+    Status,
+    Notes,
+    OpenedAt,
+    ClosedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Stores {
+    Table,
+    Id,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CashMovements::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CashMovements::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CashMovements::ShiftId).integer().not_null())
+                    .col(ColumnDef::new(CashMovements::EmployeeId).integer().not_null())
+                    .col(ColumnDef::new(CashMovements::MovementType).string().not_null())
+                    .col(ColumnDef::new(CashMovements::Amount).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(CashMovements::Reason).string().not_null())
+                    .col(ColumnDef::new(CashMovements::CreatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-cash_movements-shift_id")
+                            .from(CashMovements::Table, CashMovements::ShiftId)
+                            .to(Shifts::Table, Shifts::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-cash_movements-employee_id")
+                            .from(CashMovements::Table, CashMovements::EmployeeId)
+                            .to(Employees::Table, Employees::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CashMovements::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CashMovements {
+    Table,
+    Id,
+    ShiftId,
+    EmployeeId,
+    MovementType,
+    Amount,
+    Reason,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Shifts {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
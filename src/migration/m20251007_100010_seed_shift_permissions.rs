@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+use sea_orm::{ConnectionTrait, Statement, DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const PERMISSIONS: [(&str, &str); 2] = [
+    ("shifts:manage", "Can open and close own register shifts"),
+    ("shifts:read", "Can read register shifts and drawer reports"),
+];
+
+const ROLE_PERMISSIONS: [(&str, &[&str]); 4] = [
+    ("Owner", &["shifts:manage", "shifts:read"]),
+    ("Admin", &["shifts:manage", "shifts:read"]),
+    ("StoreManager", &["shifts:manage", "shifts:read"]),
+    ("Cashier", &["shifts:manage"]),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (name, description) in PERMISSIONS {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!("INSERT IGNORE INTO permissions (name, description) VALUES ('{}', '{}');", name, description),
+            )).await?;
+        }
+
+        for (role_name, permissions) in ROLE_PERMISSIONS {
+            for permission in permissions {
+                db.execute(Statement::from_string(
+                    DbBackend::MySql,
+                    format!(
+                        "INSERT IGNORE INTO role_permissions (role_id, permission_id) \
+                         SELECT r.id, p.id FROM roles r, permissions p WHERE r.name = '{}' AND p.name = '{}';",
+                        role_name, permission
+                    ),
+                )).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (name, _) in PERMISSIONS {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!("DELETE rp FROM role_permissions rp JOIN permissions p ON p.id = rp.permission_id WHERE p.name = '{}';", name),
+            )).await?;
+            db.execute(Statement::from_string(DbBackend::MySql, format!("DELETE FROM permissions WHERE name = '{}';", name))).await?;
+        }
+        Ok(())
+    }
+}
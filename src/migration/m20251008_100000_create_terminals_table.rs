@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Terminals::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Terminals::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Terminals::StoreId).integer().not_null())
+                    .col(ColumnDef::new(Terminals::Name).string().not_null())
+                    .col(ColumnDef::new(Terminals::Status).string().not_null())
+                    .col(ColumnDef::new(Terminals::CredentialHash).string().null())
+                    .col(ColumnDef::new(Terminals::PairingCode).string().unique_key().null())
+                    .col(ColumnDef::new(Terminals::PairingCodeExpiresAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(Terminals::PairedAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(Terminals::LastSeenAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(Terminals::DisabledAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(Terminals::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Terminals::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-terminals-store_id")
+                            .from(Terminals::Table, Terminals::StoreId)
+                            .to(Stores::Table, Stores::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Terminals::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Terminals {
+    Table,
+    Id,
+    StoreId,
+    Name,
+    Status,
+    CredentialHash,
+    PairingCode,
+    PairingCodeExpiresAt,
+    PairedAt,
+    LastSeenAt,
+    DisabledAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Stores {
+    Table,
+    Id,
+}
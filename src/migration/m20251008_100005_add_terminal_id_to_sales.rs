@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Orders::Table)
+                    .add_column(ColumnDef::new(Orders::TerminalId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Payments::Table)
+                    .add_column(ColumnDef::new(Payments::TerminalId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shifts::Table)
+                    .add_column(ColumnDef::new(Shifts::TerminalId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager.create_foreign_key(ForeignKey::create().name("fk-orders-terminal_id").from(Orders::Table, Orders::TerminalId).to(Terminals::Table, Terminals::Id).on_delete(ForeignKeyAction::SetNull).to_owned()).await?;
+        manager.create_foreign_key(ForeignKey::create().name("fk-payments-terminal_id").from(Payments::Table, Payments::TerminalId).to(Terminals::Table, Terminals::Id).on_delete(ForeignKeyAction::SetNull).to_owned()).await?;
+        manager.create_foreign_key(ForeignKey::create().name("fk-shifts-terminal_id").from(Shifts::Table, Shifts::TerminalId).to(Terminals::Table, Terminals::Id).on_delete(ForeignKeyAction::SetNull).to_owned()).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_foreign_key(ForeignKey::drop().name("fk-orders-terminal_id").table(Orders::Table).to_owned()).await?;
+        manager.drop_foreign_key(ForeignKey::drop().name("fk-payments-terminal_id").table(Payments::Table).to_owned()).await?;
+        manager.drop_foreign_key(ForeignKey::drop().name("fk-shifts-terminal_id").table(Shifts::Table).to_owned()).await?;
+
+        manager
+            .alter_table(Table::alter().table(Orders::Table).drop_column(Orders::TerminalId).to_owned())
+            .await?;
+        manager
+            .alter_table(Table::alter().table(Payments::Table).drop_column(Payments::TerminalId).to_owned())
+            .await?;
+        manager
+            .alter_table(Table::alter().table(Shifts::Table).drop_column(Shifts::TerminalId).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Orders {
+    Table,
+    TerminalId,
+}
+
+#[derive(DeriveIden)]
+enum Payments {
+    Table,
+    TerminalId,
+}
+
+#[derive(DeriveIden)]
+enum Shifts {
+    Table,
+    TerminalId,
+}
+
+#[derive(DeriveIden)]
+enum Terminals {
+    Table,
+    Id,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+use sea_orm::{ConnectionTrait, Statement, DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute(Statement::from_string(
+            DbBackend::MySql,
+            "INSERT IGNORE INTO permissions (name, description) VALUES ('terminals:manage', 'Can register, pair and disable store terminals');",
+        )).await?;
+
+        for role_name in ["Owner", "Admin", "StoreManager"] {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!(
+                    "INSERT IGNORE INTO role_permissions (role_id, permission_id) \
+                     SELECT r.id, p.id FROM roles r, permissions p WHERE r.name = '{}' AND p.name = 'terminals:manage';",
+                    role_name
+                ),
+            )).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute(Statement::from_string(
+            DbBackend::MySql,
+            "DELETE rp FROM role_permissions rp JOIN permissions p ON p.id = rp.permission_id WHERE p.name = 'terminals:manage';",
+        )).await?;
+        db.execute(Statement::from_string(DbBackend::MySql, "DELETE FROM permissions WHERE name = 'terminals:manage';")).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Remember which promotion produced a line's discount so receipts can show it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItems::Table)
+                    .add_column(ColumnDef::new(OrderItems::PromotionId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager.create_foreign_key(ForeignKey::create().name("fk-order_items-promotion_id").from(OrderItems::Table, OrderItems::PromotionId).to(Promotions::Table, Promotions::Id).on_delete(ForeignKeyAction::SetNull).to_owned()).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_foreign_key(ForeignKey::drop().name("fk-order_items-promotion_id").table(OrderItems::Table).to_owned()).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItems::Table)
+                    .drop_column(OrderItems::PromotionId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrderItems {
+    Table,
+    PromotionId,
+}
+
+#[derive(DeriveIden)]
+enum Promotions {
+    Table,
+    Id,
+}
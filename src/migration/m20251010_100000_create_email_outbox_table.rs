@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailOutbox::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EmailOutbox::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EmailOutbox::RecipientEmail).string().not_null())
+                    .col(ColumnDef::new(EmailOutbox::RecipientName).string().null())
+                    .col(ColumnDef::new(EmailOutbox::Subject).string().not_null())
+                    .col(ColumnDef::new(EmailOutbox::TextBody).text().not_null())
+                    .col(ColumnDef::new(EmailOutbox::HtmlBody).text().null())
+                    .col(ColumnDef::new(EmailOutbox::Category).string().not_null())
+                    .col(ColumnDef::new(EmailOutbox::ReferenceId).integer().null())
+                    .col(ColumnDef::new(EmailOutbox::Status).string().not_null())
+                    .col(ColumnDef::new(EmailOutbox::Attempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(EmailOutbox::LastError).text().null())
+                    .col(ColumnDef::new(EmailOutbox::SentAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(EmailOutbox::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(EmailOutbox::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .index(
+                        Index::create()
+                            .name("idx-email_outbox-status")
+                            .col(EmailOutbox::Status),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmailOutbox::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EmailOutbox {
+    Table,
+    Id,
+    RecipientEmail,
+    RecipientName,
+    Subject,
+    TextBody,
+    HtmlBody,
+    Category,
+    ReferenceId,
+    Status,
+    Attempts,
+    LastError,
+    SentAt,
+    CreatedAt,
+    UpdatedAt,
+}
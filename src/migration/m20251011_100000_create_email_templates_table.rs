@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailTemplates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EmailTemplates::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EmailTemplates::TemplateKey).string().not_null().unique_key())
+                    .col(ColumnDef::new(EmailTemplates::Subject).string().not_null())
+                    .col(ColumnDef::new(EmailTemplates::TextBody).text().not_null())
+                    .col(ColumnDef::new(EmailTemplates::HtmlBody).text().null())
+                    .col(ColumnDef::new(EmailTemplates::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(EmailTemplates::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmailTemplates::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EmailTemplates {
+    Table,
+    Id,
+    TemplateKey,
+    Subject,
+    TextBody,
+    HtmlBody,
+    CreatedAt,
+    UpdatedAt,
+}
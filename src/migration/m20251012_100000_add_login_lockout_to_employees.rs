@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Track failed logins per account so the lockout policy can be enforced
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .add_column(ColumnDef::new(Employees::FailedLoginAttempts).integer().not_null().default(0))
+                    .add_column(ColumnDef::new(Employees::LockedUntil).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .drop_column(Employees::FailedLoginAttempts)
+                    .drop_column(Employees::LockedUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    FailedLoginAttempts,
+    LockedUntil,
+}
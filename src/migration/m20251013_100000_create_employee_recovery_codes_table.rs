@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmployeeRecoveryCodes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EmployeeRecoveryCodes::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EmployeeRecoveryCodes::EmployeeId).integer().not_null())
+                    .col(ColumnDef::new(EmployeeRecoveryCodes::CodeHash).string().not_null())
+                    .col(ColumnDef::new(EmployeeRecoveryCodes::UsedAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(EmployeeRecoveryCodes::CreatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-employee_recovery_codes-employee_id")
+                            .from(EmployeeRecoveryCodes::Table, EmployeeRecoveryCodes::EmployeeId)
+                            .to(Employees::Table, Employees::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-employee_recovery_codes-employee_id-code_hash")
+                            .col(EmployeeRecoveryCodes::EmployeeId)
+                            .col(EmployeeRecoveryCodes::CodeHash),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmployeeRecoveryCodes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EmployeeRecoveryCodes {
+    Table,
+    Id,
+    EmployeeId,
+    CodeHash,
+    UsedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .add_column(ColumnDef::new(Employees::TotpSecret).string().null())
+                    .add_column(ColumnDef::new(Employees::TotpEnabled).boolean().not_null().default(false))
+                    .add_column(ColumnDef::new(Employees::TotpLastStep).big_integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .drop_column(Employees::TotpSecret)
+                    .drop_column(Employees::TotpEnabled)
+                    .drop_column(Employees::TotpLastStep)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    TotpSecret,
+    TotpEnabled,
+    TotpLastStep,
+}
@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Sessions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Sessions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Sessions::EmployeeId).integer().not_null())
+                    .col(ColumnDef::new(Sessions::RefreshTokenHash).string().not_null().unique_key())
+                    .col(ColumnDef::new(Sessions::PreviousTokenHash).string().null())
+                    .col(ColumnDef::new(Sessions::UserAgent).string().null())
+                    .col(ColumnDef::new(Sessions::IpAddress).string().null())
+                    .col(ColumnDef::new(Sessions::AccessValidAfter).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(Sessions::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Sessions::LastUsedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Sessions::ExpiresAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Sessions::RevokedAt).timestamp_with_time_zone().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-sessions-employee_id")
+                            .from(Sessions::Table, Sessions::EmployeeId)
+                            .to(Employees::Table, Employees::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-sessions-previous_token_hash")
+                            .col(Sessions::PreviousTokenHash),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Sessions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    Id,
+    EmployeeId,
+    RefreshTokenHash,
+    PreviousTokenHash,
+    UserAgent,
+    IpAddress,
+    AccessValidAfter,
+    CreatedAt,
+    LastUsedAt,
+    ExpiresAt,
+    RevokedAt,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
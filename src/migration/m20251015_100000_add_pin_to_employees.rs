@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // PIN attempts are counted separately from password attempts
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .add_column(ColumnDef::new(Employees::PinHash).string().null())
+                    .add_column(ColumnDef::new(Employees::FailedPinAttempts).integer().not_null().default(0))
+                    .add_column(ColumnDef::new(Employees::PinLockedUntil).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .drop_column(Employees::PinHash)
+                    .drop_column(Employees::FailedPinAttempts)
+                    .drop_column(Employees::PinLockedUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    PinHash,
+    FailedPinAttempts,
+    PinLockedUntil,
+}
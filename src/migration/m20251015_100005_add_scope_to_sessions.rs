@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // PIN logins open POS-only sessions bound to the terminal they were made on
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .add_column(ColumnDef::new(Sessions::Scope).string().not_null().default("FULL"))
+                    .add_column(ColumnDef::new(Sessions::TerminalId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-sessions-terminal_id")
+                            .from_tbl(Sessions::Table)
+                            .from_col(Sessions::TerminalId)
+                            .to_tbl(Terminals::Table)
+                            .to_col(Terminals::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .drop_foreign_key(Alias::new("fk-sessions-terminal_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .drop_column(Sessions::Scope)
+                    .drop_column(Sessions::TerminalId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    Scope,
+    TerminalId,
+}
+
+#[derive(DeriveIden)]
+enum Terminals {
+    Table,
+    Id,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+use sea_orm::{ConnectionTrait, Statement, DbBackend};
+
+/// Permissions replacing the role-name checks that decided how much store data an employee sees.
+const PERMISSIONS: &[(&str, &str, &[&str])] = &[
+    ("stores:access_all", "Can access the data of every store", &["Owner", "Admin"]),
+    ("stores:access_own", "Can access all data of their own store", &["StoreManager"]),
+    ("orders:delete", "Can delete orders, order items and payments", &["Owner", "Admin", "StoreManager"]),
+    ("employees:manage_security", "Can reset other employees' two-factor authentication", &["Owner", "Admin"]),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (name, description, roles) in PERMISSIONS {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!("INSERT IGNORE INTO permissions (name, description) VALUES ('{}', '{}');", name, description.replace('\'', "''")),
+            )).await?;
+
+            for role_name in *roles {
+                db.execute(Statement::from_string(
+                    DbBackend::MySql,
+                    format!(
+                        "INSERT IGNORE INTO role_permissions (role_id, permission_id) \
+                         SELECT r.id, p.id FROM roles r, permissions p WHERE r.name = '{}' AND p.name = '{}';",
+                        role_name, name
+                    ),
+                )).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (name, _, _) in PERMISSIONS {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!("DELETE rp FROM role_permissions rp JOIN permissions p ON p.id = rp.permission_id WHERE p.name = '{}';", name),
+            )).await?;
+            db.execute(Statement::from_string(DbBackend::MySql, format!("DELETE FROM permissions WHERE name = '{}';", name))).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+use sea_orm::{ConnectionTrait, Statement, DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // role_id may lag behind role for rows edited before it existed; bring it in line
+        // so the drop loses nothing, and stop if a role name has no matching role
+        db.execute(Statement::from_string(
+            DbBackend::MySql,
+            "UPDATE employees e JOIN roles r ON r.name = e.role SET e.role_id = r.id;",
+        )).await?;
+        let unmatched: i64 = db
+            .query_one(Statement::from_string(
+                DbBackend::MySql,
+                "SELECT COUNT(*) AS count FROM employees e LEFT JOIN roles r ON r.name = e.role WHERE r.id IS NULL;",
+            ))
+            .await?
+            .map(|row| row.try_get("", "count"))
+            .transpose()?
+            .unwrap_or(0);
+        if unmatched > 0 {
+            return Err(DbErr::Migration(format!(
+                "{} employee(s) have a role that does not exist in roles; fix them before dropping employees.role",
+                unmatched
+            )));
+        }
+
+        // The role name is read through role_id -> roles instead
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .drop_column(Employees::Role)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .add_column(ColumnDef::new(Employees::Role).string().not_null().default(""))
+                    .to_owned(),
+            )
+            .await?;
+        manager.get_connection().execute(Statement::from_string(
+            DbBackend::MySql,
+            "UPDATE employees e JOIN roles r ON r.id = e.role_id SET e.role = r.name;",
+        )).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Role,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Stores an employee works at, in addition to their primary `employees.store_id`
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmployeeStores::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(EmployeeStores::EmployeeId).integer().not_null())
+                    .col(ColumnDef::new(EmployeeStores::StoreId).integer().not_null())
+                    .col(ColumnDef::new(EmployeeStores::CreatedAt).timestamp_with_time_zone().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(EmployeeStores::EmployeeId)
+                            .col(EmployeeStores::StoreId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-employee_stores-employee_id")
+                            .from(EmployeeStores::Table, EmployeeStores::EmployeeId)
+                            .to(Employees::Table, Employees::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-employee_stores-store_id")
+                            .from(EmployeeStores::Table, EmployeeStores::StoreId)
+                            .to(Stores::Table, Stores::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmployeeStores::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EmployeeStores {
+    Table,
+    EmployeeId,
+    StoreId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Stores {
+    Table,
+    Id,
+}
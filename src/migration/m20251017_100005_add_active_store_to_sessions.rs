@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The store a session works in, chosen at login and switchable without logging out
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .add_column(ColumnDef::new(Sessions::ActiveStoreId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-sessions-active_store_id")
+                            .from_tbl(Sessions::Table)
+                            .from_col(Sessions::ActiveStoreId)
+                            .to_tbl(Stores::Table)
+                            .to_col(Stores::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .drop_foreign_key(Alias::new("fk-sessions-active_store_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .drop_column(Sessions::ActiveStoreId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    ActiveStoreId,
+}
+
+#[derive(DeriveIden)]
+enum Stores {
+    Table,
+    Id,
+}
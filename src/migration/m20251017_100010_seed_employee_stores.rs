@@ -0,0 +1,22 @@
+use sea_orm_migration::prelude::*;
+use sea_orm::{ConnectionTrait, Statement, DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Every employee is assigned to their primary store
+        manager.get_connection().execute(Statement::from_string(
+            DbBackend::MySql,
+            "INSERT IGNORE INTO employee_stores (employee_id, store_id, created_at) SELECT id, store_id, NOW() FROM employees WHERE store_id IS NOT NULL;",
+        )).await?;
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // The rows go with the table when its create migration is rolled back
+        Ok(())
+    }
+}
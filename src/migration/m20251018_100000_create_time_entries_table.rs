@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TimeEntries::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TimeEntries::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TimeEntries::EmployeeId).integer().not_null())
+                    .col(ColumnDef::new(TimeEntries::StoreId).integer().not_null())
+                    .col(ColumnDef::new(TimeEntries::TerminalId).integer().null())
+                    .col(ColumnDef::new(TimeEntries::ClockInAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(TimeEntries::ClockOutAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(TimeEntries::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(TimeEntries::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-time_entries-employee_id")
+                            .from(TimeEntries::Table, TimeEntries::EmployeeId)
+                            .to(Employees::Table, Employees::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-time_entries-store_id")
+                            .from(TimeEntries::Table, TimeEntries::StoreId)
+                            .to(Stores::Table, Stores::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-time_entries-terminal_id")
+                            .from(TimeEntries::Table, TimeEntries::TerminalId)
+                            .to(Terminals::Table, Terminals::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-time_entries-employee_id-clock_in_at")
+                            .col(TimeEntries::EmployeeId)
+                            .col(TimeEntries::ClockInAt),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TimeEntries::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TimeEntries {
+    Table,
+    Id,
+    EmployeeId,
+    StoreId,
+    TerminalId,
+    ClockInAt,
+    ClockOutAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Stores {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Terminals {
+    Table,
+    Id,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TimeEntryBreaks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TimeEntryBreaks::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TimeEntryBreaks::TimeEntryId).integer().not_null())
+                    .col(ColumnDef::new(TimeEntryBreaks::StartedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(TimeEntryBreaks::EndedAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(TimeEntryBreaks::Paid).boolean().not_null().default(false))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-time_entry_breaks-time_entry_id")
+                            .from(TimeEntryBreaks::Table, TimeEntryBreaks::TimeEntryId)
+                            .to(TimeEntries::Table, TimeEntries::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TimeEntryBreaks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TimeEntryBreaks {
+    Table,
+    Id,
+    TimeEntryId,
+    StartedAt,
+    EndedAt,
+    Paid,
+}
+
+#[derive(DeriveIden)]
+enum TimeEntries {
+    Table,
+    Id,
+}
@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Audit trail of manager corrections to time entries
+        manager
+            .create_table(
+                Table::create()
+                    .table(TimeEntryEdits::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TimeEntryEdits::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TimeEntryEdits::TimeEntryId).integer().not_null())
+                    .col(ColumnDef::new(TimeEntryEdits::EditedBy).integer().not_null())
+                    .col(ColumnDef::new(TimeEntryEdits::Reason).text().not_null())
+                    .col(ColumnDef::new(TimeEntryEdits::PreviousValues).text().not_null())
+                    .col(ColumnDef::new(TimeEntryEdits::NewValues).text().not_null())
+                    .col(ColumnDef::new(TimeEntryEdits::CreatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-time_entry_edits-time_entry_id")
+                            .from(TimeEntryEdits::Table, TimeEntryEdits::TimeEntryId)
+                            .to(TimeEntries::Table, TimeEntries::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-time_entry_edits-edited_by")
+                            .from(TimeEntryEdits::Table, TimeEntryEdits::EditedBy)
+                            .to(Employees::Table, Employees::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TimeEntryEdits::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TimeEntryEdits {
+    Table,
+    Id,
+    TimeEntryId,
+    EditedBy,
+    Reason,
+    PreviousValues,
+    NewValues,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum TimeEntries {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+use sea_orm::{ConnectionTrait, Statement, DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const PERMISSIONS: [(&str, &str); 3] = [
+    ("timeclock:use", "Can clock in and out and take breaks"),
+    ("timesheets:read", "Can read timesheets and export payroll reports"),
+    ("timesheets:manage", "Can correct time entries"),
+];
+
+const ROLE_PERMISSIONS: [(&str, &[&str]); 4] = [
+    ("Owner", &["timeclock:use", "timesheets:read", "timesheets:manage"]),
+    ("Admin", &["timeclock:use", "timesheets:read", "timesheets:manage"]),
+    ("StoreManager", &["timeclock:use", "timesheets:read", "timesheets:manage"]),
+    ("Cashier", &["timeclock:use"]),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (name, description) in PERMISSIONS {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!("INSERT IGNORE INTO permissions (name, description) VALUES ('{}', '{}');", name, description),
+            )).await?;
+        }
+
+        for (role_name, permissions) in ROLE_PERMISSIONS {
+            for permission in permissions {
+                db.execute(Statement::from_string(
+                    DbBackend::MySql,
+                    format!(
+                        "INSERT IGNORE INTO role_permissions (role_id, permission_id) \
+                         SELECT r.id, p.id FROM roles r, permissions p WHERE r.name = '{}' AND p.name = '{}';",
+                        role_name, permission
+                    ),
+                )).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (name, _) in PERMISSIONS {
+            db.execute(Statement::from_string(
+                DbBackend::MySql,
+                format!("DELETE rp FROM role_permissions rp JOIN permissions p ON p.id = rp.permission_id WHERE p.name = '{}';", name),
+            )).await?;
+            db.execute(Statement::from_string(DbBackend::MySql, format!("DELETE FROM permissions WHERE name = '{}';", name))).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CommissionPlans::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CommissionPlans::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CommissionPlans::Name).string().not_null().unique_key())
+                    .col(ColumnDef::new(CommissionPlans::PlanType).string_len(20).not_null())
+                    .col(ColumnDef::new(CommissionPlans::Rate).decimal_len(5, 2).not_null().default(0))
+                    .col(ColumnDef::new(CommissionPlans::IsActive).boolean().not_null().default(true))
+                    .col(ColumnDef::new(CommissionPlans::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(CommissionPlans::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CommissionPlans::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CommissionPlans {
+    Table,
+    Id,
+    Name,
+    PlanType,
+    Rate,
+    IsActive,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Category rates for per-category plans and sales bands for tiered plans
+        manager
+            .create_table(
+                Table::create()
+                    .table(CommissionPlanRules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CommissionPlanRules::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CommissionPlanRules::CommissionPlanId).integer().not_null())
+                    .col(ColumnDef::new(CommissionPlanRules::CategoryId).integer().null())
+                    .col(ColumnDef::new(CommissionPlanRules::MinSales).decimal_len(12, 2).null())
+                    .col(ColumnDef::new(CommissionPlanRules::Rate).decimal_len(5, 2).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-commission_plan_rules-commission_plan_id")
+                            .from(CommissionPlanRules::Table, CommissionPlanRules::CommissionPlanId)
+                            .to(CommissionPlans::Table, CommissionPlans::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-commission_plan_rules-category_id")
+                            .from(CommissionPlanRules::Table, CommissionPlanRules::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CommissionPlanRules::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CommissionPlanRules {
+    Table,
+    Id,
+    CommissionPlanId,
+    CategoryId,
+    MinSales,
+    Rate,
+}
+
+#[derive(DeriveIden)]
+enum CommissionPlans {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The commission plan an employee earns under; none means no commission
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .add_column(ColumnDef::new(Employees::CommissionPlanId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-employees-commission_plan_id")
+                            .from_tbl(Employees::Table)
+                            .from_col(Employees::CommissionPlanId)
+                            .to_tbl(CommissionPlans::Table)
+                            .to_col(CommissionPlans::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .drop_foreign_key(Alias::new("fk-employees-commission_plan_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Employees::Table)
+                    .drop_column(Employees::CommissionPlanId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    CommissionPlanId,
+}
+
+#[derive(DeriveIden)]
+enum CommissionPlans {
+    Table,
+    Id,
+}
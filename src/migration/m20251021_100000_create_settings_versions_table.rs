@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SettingsVersions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SettingsVersions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SettingsVersions::Version).integer().not_null().unique_key())
+                    .col(ColumnDef::new(SettingsVersions::Config).json().not_null())
+                    .col(ColumnDef::new(SettingsVersions::ChangedBy).integer().null())
+                    .col(ColumnDef::new(SettingsVersions::Summary).string().not_null())
+                    .col(ColumnDef::new(SettingsVersions::CreatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-settings_versions-changed_by")
+                            .from(SettingsVersions::Table, SettingsVersions::ChangedBy)
+                            .to(Employees::Table, Employees::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SettingsVersions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SettingsVersions {
+    Table,
+    Id,
+    Version,
+    Config,
+    ChangedBy,
+    Summary,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Employees {
+    Table,
+    Id,
+}
@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+
+use crate::entities::settings_model::{upgrade_config, Settings as SettingsData};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Fills in fields added to the settings since they were saved and records the result as the
+/// first version in the settings history. Later schema changes can rerun `upgrade_config`
+/// the same way.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let select = Query::select()
+            .columns([Settings::Id, Settings::Config])
+            .from(Settings::Table)
+            .to_owned();
+        let rows = db.query_all(db.get_database_backend().build(&select)).await?;
+
+        for row in rows {
+            let id: i32 = row.try_get("", "id")?;
+            let config: serde_json::Value = row.try_get("", "config")?;
+            let upgraded = upgrade_config(config);
+
+            let update = Query::update()
+                .table(Settings::Table)
+                .value(Settings::Config, upgraded.clone())
+                .and_where(Expr::col(Settings::Id).eq(id))
+                .to_owned();
+            manager.exec_stmt(update).await?;
+
+            // An invalid config is left for an admin to fix rather than replaced with defaults
+            let settings = match SettingsData::from_stored(upgraded) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    log::error!("Stored settings are invalid and were not versioned: {}", e);
+                    continue;
+                }
+            };
+            let masked = serde_json::to_value(settings.masked())
+                .map_err(|e| DbErr::Migration(e.to_string()))?;
+            let insert = Query::insert()
+                .into_table(SettingsVersions::Table)
+                .columns([
+                    SettingsVersions::Version,
+                    SettingsVersions::Config,
+                    SettingsVersions::Summary,
+                    SettingsVersions::CreatedAt,
+                ])
+                .values_panic([
+                    1.into(),
+                    masked.into(),
+                    "Initial settings".into(),
+                    chrono::Utc::now().into(),
+                ])
+                .to_owned();
+            manager.exec_stmt(insert).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Added fields are harmless to older code, so the upgrade is kept
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Settings {
+    Table,
+    Id,
+    Config,
+}
+
+#[derive(DeriveIden)]
+enum SettingsVersions {
+    Table,
+    Version,
+    Config,
+    Summary,
+    CreatedAt,
+}
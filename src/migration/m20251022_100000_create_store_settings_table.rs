@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StoreSettings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StoreSettings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(StoreSettings::StoreId).integer().not_null().unique_key())
+                    .col(ColumnDef::new(StoreSettings::Overrides).json().not_null())
+                    .col(ColumnDef::new(StoreSettings::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-store_settings-store_id")
+                            .from(StoreSettings::Table, StoreSettings::StoreId)
+                            .to(Stores::Table, Stores::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StoreSettings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StoreSettings {
+    Table,
+    Id,
+    StoreId,
+    Overrides,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Stores {
+    Table,
+    Id,
+}
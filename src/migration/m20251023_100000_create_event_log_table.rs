@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EventLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EventLog::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EventLog::Topic).string().not_null())
+                    .col(ColumnDef::new(EventLog::Event).json().not_null())
+                    .col(ColumnDef::new(EventLog::CreatedAt).timestamp_with_time_zone().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-event_log-created_at")
+                    .table(EventLog::Table)
+                    .col(EventLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EventLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EventLog {
+    Table,
+    Id,
+    Topic,
+    Event,
+    CreatedAt,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+/// Indexes for the filters and sorts list endpoints offer, so paging through large tables
+/// does not scan and sort them on every request. Foreign key columns are indexed already.
+const INDEXES: &[(&str, &str, &[&str])] = &[
+    ("idx-orders-store_id-order_date", "orders", &["store_id", "order_date"]),
+    ("idx-orders-order_date", "orders", &["order_date"]),
+    ("idx-orders-status", "orders", &["status"]),
+    ("idx-refunds-store_id-created_at", "refunds", &["store_id", "created_at"]),
+    ("idx-refunds-created_at", "refunds", &["created_at"]),
+    ("idx-payments-payment_date", "payments", &["payment_date"]),
+    ("idx-shifts-store_id-opened_at", "shifts", &["store_id", "opened_at"]),
+    ("idx-shifts-status", "shifts", &["status"]),
+    ("idx-time_entries-store_id-clock_in_at", "time_entries", &["store_id", "clock_in_at"]),
+    ("idx-email_outbox-created_at", "email_outbox", &["created_at"]),
+    ("idx-purchase_orders-status-order_date", "purchase_orders", &["status", "order_date"]),
+    ("idx-purchase_orders-order_date", "purchase_orders", &["order_date"]),
+    ("idx-customers-last_name-first_name", "customers", &["last_name", "first_name"]),
+    ("idx-products-name", "products", &["name"]),
+    ("idx-employees-last_name", "employees", &["last_name"]),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table, columns) in INDEXES {
+            let mut index = Index::create();
+            index.name(*name).table(Alias::new(*table));
+            for column in *columns {
+                index.col(Alias::new(*column));
+            }
+            manager.create_index(index.to_owned()).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table, _) in INDEXES.iter().rev() {
+            manager
+                .drop_index(Index::drop().name(*name).table(Alias::new(*table)).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
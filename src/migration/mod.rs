@@ -21,6 +21,22 @@ mod m20250927_130105_create_refunds_table;
 mod m20250927_130110_create_refund_items_table;
 mod m20251001_100000_create_settings_table;
 mod m20251001_150000_create_password_reset_tokens_table;
+mod m20251007_100000_create_shifts_table;
+mod m20251007_100005_create_cash_movements_table;
+mod m20251008_100000_create_terminals_table;
+mod m20251010_100000_create_email_outbox_table;
+mod m20251011_100000_create_email_templates_table;
+mod m20251013_100000_create_employee_recovery_codes_table;
+mod m20251014_100000_create_sessions_table;
+mod m20251017_100000_create_employee_stores_table;
+mod m20251018_100000_create_time_entries_table;
+mod m20251018_100005_create_time_entry_breaks_table;
+mod m20251018_100010_create_time_entry_edits_table;
+mod m20251019_100000_create_commission_plans_table;
+mod m20251019_100005_create_commission_plan_rules_table;
+mod m20251021_100000_create_settings_versions_table;
+mod m20251022_100000_create_store_settings_table;
+mod m20251023_100000_create_event_log_table;
 
 // Alterations and Foreign Keys
 mod m20250927_120015_alter_employees_add_role_id;
@@ -28,12 +44,40 @@ mod m20250927_130115_create_foreign_keys;
 mod m20250929_100000_add_photo_url_to_products;
 mod m20250929_100005_add_photo_url_to_employees;
 pub mod m20250930_100000_add_expires_at_to_products;
+mod m20251006_100000_add_receiptless_returns;
+mod m20251008_100005_add_terminal_id_to_sales;
+mod m20251009_100000_add_promotion_id_to_order_items;
+mod m20251011_100005_add_next_attempt_at_to_email_outbox;
+mod m20251012_100000_add_login_lockout_to_employees;
+mod m20251013_100005_add_totp_to_employees;
+mod m20251015_100000_add_pin_to_employees;
+mod m20251015_100005_add_scope_to_sessions;
+mod m20251017_100005_add_active_store_to_sessions;
+mod m20251019_100010_add_commission_plan_to_employees;
+mod m20251024_100000_add_list_indexes;
+mod m20251025_100005_add_access_version_to_sessions;
 
 
 // Seeding
 mod m20250927_120020_seed_default_roles_permissions;
 mod m20250927_130120_seed_main_store;
+// Applied migrations are left as they are, lints included
+#[allow(clippy::needless_borrows_for_generic_args)]
 mod m20250927_140000_seed_owner_admin_accounts;
+mod m20251006_100005_seed_refund_override_permission;
+mod m20251007_100010_seed_shift_permissions;
+mod m20251008_100010_seed_terminal_permissions;
+mod m20251010_100005_seed_email_permissions;
+mod m20251016_100000_seed_store_access_permissions;
+mod m20251017_100010_seed_employee_stores;
+mod m20251018_100015_seed_time_clock_permissions;
+mod m20251019_100015_seed_commission_permissions;
+mod m20251020_100000_seed_settings_permissions;
+mod m20251021_100005_upgrade_settings_config;
+mod m20251025_100000_seed_owner_management_permission;
+
+// Cleanup (runs after the seeds, which still write the dropped columns)
+mod m20251016_100005_drop_role_from_employees;
 
 pub struct Migrator;
 
@@ -62,6 +106,22 @@ impl MigratorTrait for Migrator {
             Box::new(m20250927_130110_create_refund_items_table::Migration),
             Box::new(m20251001_100000_create_settings_table::Migration),
             Box::new(m20251001_150000_create_password_reset_tokens_table::Migration),
+            Box::new(m20251007_100000_create_shifts_table::Migration),
+            Box::new(m20251007_100005_create_cash_movements_table::Migration),
+            Box::new(m20251008_100000_create_terminals_table::Migration),
+            Box::new(m20251010_100000_create_email_outbox_table::Migration),
+            Box::new(m20251011_100000_create_email_templates_table::Migration),
+            Box::new(m20251013_100000_create_employee_recovery_codes_table::Migration),
+            Box::new(m20251014_100000_create_sessions_table::Migration),
+            Box::new(m20251017_100000_create_employee_stores_table::Migration),
+            Box::new(m20251018_100000_create_time_entries_table::Migration),
+            Box::new(m20251018_100005_create_time_entry_breaks_table::Migration),
+            Box::new(m20251018_100010_create_time_entry_edits_table::Migration),
+            Box::new(m20251019_100000_create_commission_plans_table::Migration),
+            Box::new(m20251019_100005_create_commission_plan_rules_table::Migration),
+            Box::new(m20251021_100000_create_settings_versions_table::Migration),
+            Box::new(m20251022_100000_create_store_settings_table::Migration),
+            Box::new(m20251023_100000_create_event_log_table::Migration),
 
             // Alterations and Foreign Keys
             Box::new(m20250927_120015_alter_employees_add_role_id::Migration),
@@ -69,11 +129,37 @@ impl MigratorTrait for Migrator {
             Box::new(m20250929_100000_add_photo_url_to_products::Migration),
             Box::new(m20250929_100005_add_photo_url_to_employees::Migration),
             Box::new(m20250930_100000_add_expires_at_to_products::Migration),
+            Box::new(m20251006_100000_add_receiptless_returns::Migration),
+            Box::new(m20251008_100005_add_terminal_id_to_sales::Migration),
+            Box::new(m20251009_100000_add_promotion_id_to_order_items::Migration),
+            Box::new(m20251011_100005_add_next_attempt_at_to_email_outbox::Migration),
+            Box::new(m20251012_100000_add_login_lockout_to_employees::Migration),
+            Box::new(m20251013_100005_add_totp_to_employees::Migration),
+            Box::new(m20251015_100000_add_pin_to_employees::Migration),
+            Box::new(m20251015_100005_add_scope_to_sessions::Migration),
+            Box::new(m20251017_100005_add_active_store_to_sessions::Migration),
+            Box::new(m20251019_100010_add_commission_plan_to_employees::Migration),
+            Box::new(m20251024_100000_add_list_indexes::Migration),
+            Box::new(m20251025_100005_add_access_version_to_sessions::Migration),
 
             // Seed Data
             Box::new(m20250927_120020_seed_default_roles_permissions::Migration),
             Box::new(m20250927_130120_seed_main_store::Migration),
             Box::new(m20250927_140000_seed_owner_admin_accounts::Migration),
+            Box::new(m20251006_100005_seed_refund_override_permission::Migration),
+            Box::new(m20251007_100010_seed_shift_permissions::Migration),
+            Box::new(m20251008_100010_seed_terminal_permissions::Migration),
+            Box::new(m20251010_100005_seed_email_permissions::Migration),
+            Box::new(m20251016_100000_seed_store_access_permissions::Migration),
+            Box::new(m20251017_100010_seed_employee_stores::Migration),
+            Box::new(m20251018_100015_seed_time_clock_permissions::Migration),
+            Box::new(m20251019_100015_seed_commission_permissions::Migration),
+            Box::new(m20251020_100000_seed_settings_permissions::Migration),
+            Box::new(m20251021_100005_upgrade_settings_config::Migration),
+            Box::new(m20251025_100000_seed_owner_management_permission::Migration),
+
+            // Cleanup
+            Box::new(m20251016_100005_drop_role_from_employees::Migration),
         ]
     }
 }
\ No newline at end of file
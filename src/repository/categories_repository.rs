@@ -1,12 +1,20 @@
 use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue};
 use crate::entities::categories;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 use chrono::{Utc, DateTime};
 
 pub struct CategoryRepository;
 
 impl CategoryRepository {
-    pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<categories::Model>, DbErr> {
-        categories::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(categories::Column::Id)
+            .sort("name", categories::Column::Name)
+            .filter("name", categories::Column::Name, FilterKind::Contains)
+            .default_sort("id")
+    }
+
+    pub async fn get_all(db: &DatabaseConnection, plan: &ListPlan) -> Result<Page<categories::Model>, DbErr> {
+        plan.fetch(db, plan.apply(categories::Entity::find()), |category| category.id).await
     }
 
     pub async fn create(db: &DatabaseConnection, new_category: categories::CreateCategory) -> Result<categories::Model, DbErr> {
@@ -0,0 +1,190 @@
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
+use sea_orm::prelude::Decimal;
+use sea_orm::sea_query::Expr;
+use chrono::{DateTime, Utc};
+
+use crate::auth::store_scope::StoreScope;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
+use crate::entities::{
+    commission_plan_rules, commission_plans, employees, order_items, orders, products, refund_items, refunds,
+};
+use crate::entities::commission_plan_rules::CommissionRuleInput;
+
+pub struct CommissionRepository;
+
+impl CommissionRepository {
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        name: String,
+        plan_type: String,
+        rate: Decimal,
+        is_active: bool,
+    ) -> Result<commission_plans::Model, DbErr> {
+        let now = Utc::now();
+        let plan = commission_plans::ActiveModel {
+            name: ActiveValue::Set(name),
+            plan_type: ActiveValue::Set(plan_type),
+            rate: ActiveValue::Set(rate),
+            is_active: ActiveValue::Set(is_active),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            ..Default::default()
+        };
+        plan.insert(db).await
+    }
+
+    pub fn list_fields() -> ListFields {
+        ListFields::new(commission_plans::Column::Id)
+            .sort("name", commission_plans::Column::Name)
+            .sort("rate", commission_plans::Column::Rate)
+            .filter("plan_type", commission_plans::Column::PlanType, FilterKind::Text)
+            .filter("is_active", commission_plans::Column::IsActive, FilterKind::Bool)
+            .default_sort("name")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, plan: &ListPlan) -> Result<Page<commission_plans::Model>, DbErr> {
+        plan.fetch(db, plan.apply(commission_plans::Entity::find()), |plan| plan.id).await
+    }
+
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<commission_plans::Model>, DbErr> {
+        commission_plans::Entity::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_by_name<C: ConnectionTrait>(db: &C, name: &str) -> Result<Option<commission_plans::Model>, DbErr> {
+        commission_plans::Entity::find()
+            .filter(commission_plans::Column::Name.eq(name))
+            .one(db)
+            .await
+    }
+
+    pub async fn update<C: ConnectionTrait>(
+        db: &C,
+        plan: commission_plans::Model,
+        name: String,
+        plan_type: String,
+        rate: Decimal,
+        is_active: bool,
+    ) -> Result<commission_plans::Model, DbErr> {
+        let mut active_model: commission_plans::ActiveModel = plan.into();
+        active_model.name = ActiveValue::Set(name);
+        active_model.plan_type = ActiveValue::Set(plan_type);
+        active_model.rate = ActiveValue::Set(rate);
+        active_model.is_active = ActiveValue::Set(is_active);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    pub async fn delete<C: ConnectionTrait>(db: &C, id: i32) -> Result<u64, DbErr> {
+        let result = commission_plans::Entity::delete_by_id(id).exec(db).await?;
+        Ok(result.rows_affected)
+    }
+
+    pub async fn get_rules<C: ConnectionTrait>(db: &C, plan_ids: Vec<i32>) -> Result<Vec<commission_plan_rules::Model>, DbErr> {
+        if plan_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        commission_plan_rules::Entity::find()
+            .filter(commission_plan_rules::Column::CommissionPlanId.is_in(plan_ids))
+            .order_by_asc(commission_plan_rules::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn replace_rules<C: ConnectionTrait>(
+        db: &C,
+        plan_id: i32,
+        rules: Vec<CommissionRuleInput>,
+    ) -> Result<Vec<commission_plan_rules::Model>, DbErr> {
+        commission_plan_rules::Entity::delete_many()
+            .filter(commission_plan_rules::Column::CommissionPlanId.eq(plan_id))
+            .exec(db)
+            .await?;
+
+        let mut inserted = Vec::with_capacity(rules.len());
+        for rule in rules {
+            let model = commission_plan_rules::ActiveModel {
+                commission_plan_id: ActiveValue::Set(plan_id),
+                category_id: ActiveValue::Set(rule.category_id),
+                min_sales: ActiveValue::Set(rule.min_sales),
+                rate: ActiveValue::Set(rule.rate),
+                ..Default::default()
+            };
+            inserted.push(model.insert(db).await?);
+        }
+        Ok(inserted)
+    }
+
+    pub async fn set_employee_plan<C: ConnectionTrait>(
+        db: &C,
+        employee: employees::Model,
+        commission_plan_id: Option<i32>,
+    ) -> Result<employees::Model, DbErr> {
+        let mut active_model: employees::ActiveModel = employee.into();
+        active_model.commission_plan_id = ActiveValue::Set(commission_plan_id);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    /// Net item sales (after line discounts) per seller and product category, for orders in
+    /// scope placed between `from` and `to` that went through checkout.
+    pub async fn get_sales_by_employee_and_category<C: ConnectionTrait>(
+        db: &C,
+        scope: &StoreScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(i32, i32, Decimal)>, DbErr> {
+        let rows: Vec<(i32, i32, Option<Decimal>)> = order_items::Entity::find()
+            .join(JoinType::InnerJoin, order_items::Relation::Orders.def())
+            .join(JoinType::InnerJoin, order_items::Relation::Products.def())
+            .filter(scope.record_condition(orders::Column::StoreId, orders::Column::EmployeeId))
+            .filter(orders::Column::OrderDate.between(from, to))
+            .filter(orders::Column::Status.ne("Pending"))
+            .select_only()
+            .column(orders::Column::EmployeeId)
+            .column(products::Column::CategoryId)
+            .column_as(
+                Expr::expr(
+                    Expr::col((order_items::Entity, order_items::Column::UnitPrice))
+                        .sub(Expr::col((order_items::Entity, order_items::Column::DiscountAmount)))
+                        .mul(Expr::col((order_items::Entity, order_items::Column::Quantity))),
+                )
+                .sum(),
+                "total",
+            )
+            .group_by(orders::Column::EmployeeId)
+            .group_by(products::Column::CategoryId)
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(rows.into_iter().map(|(employee_id, category_id, total)| (employee_id, category_id, total.unwrap_or_default())).collect())
+    }
+
+    /// Refunded amounts between `from` and `to` per product category, charged back to the
+    /// employee who made the original sale. Receiptless returns have no seller and are left out.
+    pub async fn get_refunds_by_employee_and_category<C: ConnectionTrait>(
+        db: &C,
+        scope: &StoreScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(i32, i32, Decimal)>, DbErr> {
+        let rows: Vec<(i32, i32, Option<Decimal>)> = refund_items::Entity::find()
+            .join(JoinType::InnerJoin, refund_items::Relation::Refunds.def())
+            .join(JoinType::InnerJoin, refunds::Relation::Orders.def())
+            .join(JoinType::InnerJoin, refund_items::Relation::Products.def())
+            .filter(scope.record_condition(orders::Column::StoreId, orders::Column::EmployeeId))
+            .filter(refunds::Column::CreatedAt.between(from, to))
+            .select_only()
+            .column(orders::Column::EmployeeId)
+            .column(products::Column::CategoryId)
+            .column_as(refund_items::Column::Amount.sum(), "total")
+            .group_by(orders::Column::EmployeeId)
+            .group_by(products::Column::CategoryId)
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(rows.into_iter().map(|(employee_id, category_id, total)| (employee_id, category_id, total.unwrap_or_default())).collect())
+    }
+}
@@ -1,12 +1,25 @@
-use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, QueryFilter, QuerySelect, prelude::Decimal};
 use crate::entities::customers;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 use chrono::{Utc, DateTime};
 
 pub struct CustomerRepository;
 
 impl CustomerRepository {
-    pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<customers::Model>, DbErr> {
-        customers::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(customers::Column::Id)
+            .sort("last_name", customers::Column::LastName)
+            .sort("first_name", customers::Column::FirstName)
+            .sort("created_at", customers::Column::CreatedAt)
+            .filter("first_name", customers::Column::FirstName, FilterKind::Contains)
+            .filter("last_name", customers::Column::LastName, FilterKind::Contains)
+            .filter("email", customers::Column::Email, FilterKind::Contains)
+            .filter("phone", customers::Column::Phone, FilterKind::Contains)
+            .default_sort("id")
+    }
+
+    pub async fn get_all(db: &DatabaseConnection, plan: &ListPlan) -> Result<Page<customers::Model>, DbErr> {
+        plan.fetch(db, plan.apply(customers::Entity::find()), |customer| customer.id).await
     }
 
     pub async fn create(db: &DatabaseConnection, new_customer: customers::CreateCustomer) -> Result<customers::Model, DbErr> {
@@ -24,10 +37,29 @@ impl CustomerRepository {
         customer.insert(db).await
     }
 
-    pub async fn find_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<customers::Model>, DbErr> {
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<customers::Model>, DbErr> {
         customers::Entity::find_by_id(id).one(db).await
     }
 
+    pub async fn find_by_ids<C: ConnectionTrait>(db: &C, ids: Vec<i32>) -> Result<Vec<customers::Model>, DbErr> {
+        customers::Entity::find().filter(customers::Column::Id.is_in(ids)).all(db).await
+    }
+
+    pub async fn find_all<C: ConnectionTrait>(db: &C) -> Result<Vec<customers::Model>, DbErr> {
+        customers::Entity::find().all(db).await
+    }
+
+    /// The id and last change of every customer, to tell which ones changed since a given time.
+    pub async fn get_update_stamps<C: ConnectionTrait>(db: &C) -> Result<Vec<(i32, DateTime<Utc>)>, DbErr> {
+        customers::Entity::find()
+            .select_only()
+            .column(customers::Column::Id)
+            .column(customers::Column::UpdatedAt)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
     pub async fn update(db: &DatabaseConnection, id: i32, update_data: customers::UpdateCustomer) -> Result<Option<customers::Model>, DbErr> {
         let customer: Option<customers::Model> = customers::Entity::find_by_id(id).one(db).await?;
         if let Some(customer) = customer {
@@ -58,4 +90,16 @@ impl CustomerRepository {
         let res = customers::Entity::delete_by_id(id).exec(db).await?;
         Ok(res.rows_affected)
     }
+
+    pub async fn add_store_credit<C: ConnectionTrait>(db: &C, id: i32, amount: Decimal) -> Result<customers::Model, DbErr> {
+        let customer = customers::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::Custom(format!("Customer with ID {} not found", id)))?;
+
+        let mut active_model: customers::ActiveModel = customer.into();
+        active_model.store_credit_balance = ActiveValue::Set(active_model.store_credit_balance.as_ref() + amount);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
 }
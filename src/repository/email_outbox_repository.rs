@@ -0,0 +1,119 @@
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use sea_orm::sea_query::Expr;
+use chrono::{DateTime, Utc};
+
+use crate::entities::email_outbox::{self, NewEmail};
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
+
+pub struct EmailOutboxRepository;
+
+impl EmailOutboxRepository {
+    pub async fn enqueue<C: ConnectionTrait>(db: &C, email: NewEmail) -> Result<email_outbox::Model, DbErr> {
+        let now = Utc::now();
+        let model = email_outbox::ActiveModel {
+            recipient_email: ActiveValue::Set(email.recipient_email),
+            recipient_name: ActiveValue::Set(email.recipient_name),
+            subject: ActiveValue::Set(email.subject),
+            text_body: ActiveValue::Set(email.text_body),
+            html_body: ActiveValue::Set(email.html_body),
+            category: ActiveValue::Set(email.category),
+            reference_id: ActiveValue::Set(email.reference_id),
+            status: ActiveValue::Set("PENDING".to_string()),
+            attempts: ActiveValue::Set(0),
+            next_attempt_at: ActiveValue::Set(Some(now)),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<email_outbox::Model>, DbErr> {
+        email_outbox::Entity::find_by_id(id).one(db).await
+    }
+
+    pub fn list_fields() -> ListFields {
+        ListFields::new(email_outbox::Column::Id)
+            .sort("created_at", email_outbox::Column::CreatedAt)
+            .sort("sent_at", email_outbox::Column::SentAt)
+            .sort("next_attempt_at", email_outbox::Column::NextAttemptAt)
+            .filter("status", email_outbox::Column::Status, FilterKind::Text)
+            .filter("category", email_outbox::Column::Category, FilterKind::Text)
+            .filter("recipient_email", email_outbox::Column::RecipientEmail, FilterKind::Contains)
+            .filter("reference_id", email_outbox::Column::ReferenceId, FilterKind::Int)
+            .filter("from", email_outbox::Column::CreatedAt, FilterKind::From)
+            .filter("to", email_outbox::Column::CreatedAt, FilterKind::To)
+            .default_sort("-created_at")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, plan: &ListPlan) -> Result<Page<email_outbox::Model>, DbErr> {
+        plan.fetch(db, plan.apply(email_outbox::Entity::find()), |email| email.id).await
+    }
+
+    /// Pending emails whose next attempt is due, oldest first.
+    pub async fn find_due<C: ConnectionTrait>(db: &C, now: DateTime<Utc>, limit: u64) -> Result<Vec<email_outbox::Model>, DbErr> {
+        email_outbox::Entity::find()
+            .filter(email_outbox::Column::Status.eq("PENDING"))
+            .filter(email_outbox::Column::NextAttemptAt.lte(now))
+            .order_by_asc(email_outbox::Column::NextAttemptAt)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Pushes a due email's next attempt out to `lease_until` so no other worker picks it
+    /// up while it is being sent. Returns false if another worker claimed it first.
+    pub async fn claim<C: ConnectionTrait>(db: &C, email: &email_outbox::Model, lease_until: DateTime<Utc>) -> Result<bool, DbErr> {
+        let res = email_outbox::Entity::update_many()
+            .col_expr(email_outbox::Column::NextAttemptAt, Expr::value(lease_until))
+            .filter(email_outbox::Column::Id.eq(email.id))
+            .filter(email_outbox::Column::Status.eq("PENDING"))
+            .filter(email_outbox::Column::NextAttemptAt.eq(email.next_attempt_at))
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected == 1)
+    }
+
+    pub async fn mark_sent<C: ConnectionTrait>(db: &C, email: email_outbox::Model) -> Result<email_outbox::Model, DbErr> {
+        let now = Utc::now();
+        let attempts = email.attempts + 1;
+        let mut active_model: email_outbox::ActiveModel = email.into();
+        active_model.status = ActiveValue::Set("SENT".to_string());
+        active_model.attempts = ActiveValue::Set(attempts);
+        active_model.last_error = ActiveValue::Set(None);
+        active_model.next_attempt_at = ActiveValue::Set(None);
+        active_model.sent_at = ActiveValue::Set(Some(now));
+        active_model.updated_at = ActiveValue::Set(now);
+        active_model.update(db).await
+    }
+
+    /// Records a failed attempt. The email stays pending when `retry_at` is given,
+    /// otherwise it is marked as failed for good.
+    pub async fn record_failure<C: ConnectionTrait>(
+        db: &C,
+        email: email_outbox::Model,
+        error: String,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<email_outbox::Model, DbErr> {
+        let attempts = email.attempts + 1;
+        let status = if retry_at.is_some() { "PENDING" } else { "FAILED" };
+        let mut active_model: email_outbox::ActiveModel = email.into();
+        active_model.status = ActiveValue::Set(status.to_string());
+        active_model.attempts = ActiveValue::Set(attempts);
+        active_model.last_error = ActiveValue::Set(Some(error));
+        active_model.next_attempt_at = ActiveValue::Set(retry_at);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    /// Puts a failed email back in the queue with a fresh set of attempts.
+    pub async fn requeue<C: ConnectionTrait>(db: &C, email: email_outbox::Model) -> Result<email_outbox::Model, DbErr> {
+        let now = Utc::now();
+        let mut active_model: email_outbox::ActiveModel = email.into();
+        active_model.status = ActiveValue::Set("PENDING".to_string());
+        active_model.attempts = ActiveValue::Set(0);
+        active_model.next_attempt_at = ActiveValue::Set(Some(now));
+        active_model.updated_at = ActiveValue::Set(now);
+        active_model.update(db).await
+    }
+}
@@ -0,0 +1,53 @@
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
+use chrono::Utc;
+
+use crate::entities::email_templates::{self, UpdateEmailTemplate};
+
+pub struct EmailTemplateRepository;
+
+impl EmailTemplateRepository {
+    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<email_templates::Model>, DbErr> {
+        email_templates::Entity::find().all(db).await
+    }
+
+    pub async fn find_by_key<C: ConnectionTrait>(db: &C, template_key: &str) -> Result<Option<email_templates::Model>, DbErr> {
+        email_templates::Entity::find()
+            .filter(email_templates::Column::TemplateKey.eq(template_key))
+            .one(db)
+            .await
+    }
+
+    pub async fn upsert<C: ConnectionTrait>(db: &C, template_key: &str, data: UpdateEmailTemplate) -> Result<email_templates::Model, DbErr> {
+        let now = Utc::now();
+        match Self::find_by_key(db, template_key).await? {
+            Some(existing) => {
+                let mut active_model: email_templates::ActiveModel = existing.into();
+                active_model.subject = ActiveValue::Set(data.subject);
+                active_model.text_body = ActiveValue::Set(data.text_body);
+                active_model.html_body = ActiveValue::Set(data.html_body);
+                active_model.updated_at = ActiveValue::Set(now);
+                active_model.update(db).await
+            }
+            None => {
+                let model = email_templates::ActiveModel {
+                    template_key: ActiveValue::Set(template_key.to_string()),
+                    subject: ActiveValue::Set(data.subject),
+                    text_body: ActiveValue::Set(data.text_body),
+                    html_body: ActiveValue::Set(data.html_body),
+                    created_at: ActiveValue::Set(now),
+                    updated_at: ActiveValue::Set(now),
+                    ..Default::default()
+                };
+                model.insert(db).await
+            }
+        }
+    }
+
+    pub async fn delete_by_key<C: ConnectionTrait>(db: &C, template_key: &str) -> Result<u64, DbErr> {
+        let res = email_templates::Entity::delete_many()
+            .filter(email_templates::Column::TemplateKey.eq(template_key))
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+}
@@ -0,0 +1,46 @@
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
+use sea_orm::sea_query::Expr;
+use chrono::Utc;
+
+use crate::entities::employee_recovery_codes;
+
+pub struct RecoveryCodeRepository;
+
+impl RecoveryCodeRepository {
+    /// Replaces all of an employee's recovery codes with a fresh set.
+    pub async fn replace_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32, code_hashes: Vec<String>) -> Result<(), DbErr> {
+        Self::delete_for_employee(db, employee_id).await?;
+        let now = Utc::now();
+        for code_hash in code_hashes {
+            employee_recovery_codes::ActiveModel {
+                employee_id: ActiveValue::Set(employee_id),
+                code_hash: ActiveValue::Set(code_hash),
+                created_at: ActiveValue::Set(now),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Marks an unused code as used. Returns false if it does not exist or was already used.
+    pub async fn consume<C: ConnectionTrait>(db: &C, employee_id: i32, code_hash: &str) -> Result<bool, DbErr> {
+        let res = employee_recovery_codes::Entity::update_many()
+            .col_expr(employee_recovery_codes::Column::UsedAt, Expr::value(Utc::now()))
+            .filter(employee_recovery_codes::Column::EmployeeId.eq(employee_id))
+            .filter(employee_recovery_codes::Column::CodeHash.eq(code_hash))
+            .filter(employee_recovery_codes::Column::UsedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected > 0)
+    }
+
+    pub async fn delete_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<u64, DbErr> {
+        let res = employee_recovery_codes::Entity::delete_many()
+            .filter(employee_recovery_codes::Column::EmployeeId.eq(employee_id))
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+}
@@ -0,0 +1,59 @@
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait};
+use chrono::Utc;
+
+use crate::entities::{employee_stores, stores};
+
+pub struct EmployeeStoreRepository;
+
+impl EmployeeStoreRepository {
+    pub async fn get_store_ids<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<Vec<i32>, DbErr> {
+        employee_stores::Entity::find()
+            .select_only()
+            .column(employee_stores::Column::StoreId)
+            .filter(employee_stores::Column::EmployeeId.eq(employee_id))
+            .order_by_asc(employee_stores::Column::StoreId)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    pub async fn get_stores<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<Vec<stores::Model>, DbErr> {
+        stores::Entity::find()
+            .join(JoinType::InnerJoin, employee_stores::Relation::Stores.def().rev())
+            .filter(employee_stores::Column::EmployeeId.eq(employee_id))
+            .order_by_asc(stores::Column::Name)
+            .all(db)
+            .await
+    }
+
+    pub async fn is_assigned<C: ConnectionTrait>(db: &C, employee_id: i32, store_id: i32) -> Result<bool, DbErr> {
+        Ok(employee_stores::Entity::find_by_id((employee_id, store_id)).one(db).await?.is_some())
+    }
+
+    /// Assigns the employee to a store unless they already are.
+    pub async fn assign<C: ConnectionTrait>(db: &C, employee_id: i32, store_id: i32) -> Result<(), DbErr> {
+        if Self::is_assigned(db, employee_id, store_id).await? {
+            return Ok(());
+        }
+        employee_stores::ActiveModel {
+            employee_id: ActiveValue::Set(employee_id),
+            store_id: ActiveValue::Set(store_id),
+            created_at: ActiveValue::Set(Utc::now()),
+        }
+        .insert(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Replaces all of an employee's store assignments.
+    pub async fn replace_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32, store_ids: &[i32]) -> Result<(), DbErr> {
+        employee_stores::Entity::delete_many()
+            .filter(employee_stores::Column::EmployeeId.eq(employee_id))
+            .exec(db)
+            .await?;
+        for store_id in store_ids {
+            Self::assign(db, employee_id, *store_id).await?;
+        }
+        Ok(())
+    }
+}
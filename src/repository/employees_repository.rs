@@ -1,39 +1,83 @@
-use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue, QueryFilter, ColumnTrait};
-use crate::entities::employees;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue, QueryFilter, ColumnTrait, ConnectionTrait, JoinType, QuerySelect, RelationTrait, SelectTwo};
+use sea_orm::sea_query::{Expr, Query, SimpleExpr};
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{employee_stores, employees, roles};
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
+use crate::repository::employee_stores_repository::EmployeeStoreRepository;
 use crate::repository::roles_repository::RoleRepository;
 use chrono::{Utc, DateTime};
 
 pub struct EmployeeRepository;
 
 impl EmployeeRepository {
-    pub async fn get_all(db: &DatabaseConnection, role: Option<String>, store_id: Option<i32>, roles_to_exclude: Option<Vec<String>>) -> Result<Vec<employees::Model>, DbErr> {
-        dbg!(&role, &store_id, &roles_to_exclude);
-        let mut query = employees::Entity::find();
-        if let Some(role) = role {
-            query = query.filter(employees::Column::Role.eq(role));
-        }
-        if let Some(store_id) = store_id {
-            query = query.filter(employees::Column::StoreId.eq(store_id));
-        }
+    /// `store_id` is checked against the caller's scope and `roles_to_exclude` is a comma separated
+    /// list of role names, both read by the handler.
+    pub fn list_fields() -> ListFields {
+        ListFields::new(employees::Column::Id)
+            .sort("last_name", employees::Column::LastName)
+            .sort("first_name", employees::Column::FirstName)
+            .sort("email", employees::Column::Email)
+            .sort("created_at", employees::Column::CreatedAt)
+            .filter("role", roles::Column::Name, FilterKind::Text)
+            .filter("role_id", employees::Column::RoleId, FilterKind::Int)
+            .filter("first_name", employees::Column::FirstName, FilterKind::Contains)
+            .filter("last_name", employees::Column::LastName, FilterKind::Contains)
+            .filter("email", employees::Column::Email, FilterKind::Contains)
+            .param("store_id")
+            .param("roles_to_exclude")
+            .default_sort("id")
+    }
+
+    /// Employees assigned to any store in scope, with their role; `roles_to_exclude` matches role names.
+    pub async fn get_all(db: &DatabaseConnection, scope: &StoreScope, roles_to_exclude: Option<Vec<String>>, plan: &ListPlan) -> Result<Page<(employees::Model, Option<roles::Model>)>, DbErr> {
+        let mut query = Self::in_scope(scope);
         if let Some(roles_to_exclude) = roles_to_exclude {
-            query = query.filter(employees::Column::Role.is_not_in(roles_to_exclude));
+            query = query.filter(roles::Column::Name.is_not_in(roles_to_exclude));
+        }
+        plan.fetch(db, plan.apply(query), |(employee, _)| employee.id).await
+    }
+
+    /// Every employee in scope with their role, for reports.
+    pub async fn get_all_in_scope(db: &DatabaseConnection, scope: &StoreScope) -> Result<Vec<(employees::Model, Option<roles::Model>)>, DbErr> {
+        Self::in_scope(scope).all(db).await
+    }
+
+    fn in_scope(scope: &StoreScope) -> SelectTwo<employees::Entity, roles::Entity> {
+        let mut query = employees::Entity::find().find_also_related(roles::Entity);
+        if !scope.is_all_stores() {
+            query = query.filter(
+                employees::Column::Id.in_subquery(
+                    Query::select()
+                        .column(employee_stores::Column::EmployeeId)
+                        .from(employee_stores::Entity)
+                        .cond_where(scope.store_condition(employee_stores::Column::StoreId))
+                        .to_owned(),
+                ),
+            );
         }
-        query.all(db).await
+        query
     }
 
+    /// Employees assigned to the store.
     pub async fn get_all_by_store(db: &DatabaseConnection, store_id: i32) -> Result<Vec<employees::Model>, DbErr> {
-        employees::Entity::find().filter(employees::Column::StoreId.eq(store_id)).all(db).await
+        employees::Entity::find()
+            .join(JoinType::InnerJoin, employee_stores::Relation::Employees.def().rev())
+            .filter(employee_stores::Column::StoreId.eq(store_id))
+            .all(db)
+            .await
     }
 
-    pub async fn create(db: &DatabaseConnection, new_employee: employees::CreateEmployee) -> Result<employees::Model, DbErr> {
-        // Find the role by ID to get its name
-        let role_model = RoleRepository::find_by_id(db, new_employee.role_id).await?;
+    /// Pairs an employee with their role, e.g. to build an `EmployeeResponse`.
+    pub async fn with_role<C: ConnectionTrait>(db: &C, employee: employees::Model) -> Result<(employees::Model, Option<roles::Model>), DbErr> {
+        let role = RoleRepository::find_by_id(db, employee.role_id).await?;
+        Ok((employee, role))
+    }
 
-        let role_name = match role_model {
-            Some(model) => model.name,
-            // If the role doesn't exist for the given ID, return an error.
-            None => return Err(DbErr::Custom(format!("Role with ID '{}' not found", new_employee.role_id)))
-        };
+    pub async fn create<C: ConnectionTrait>(db: &C, new_employee: employees::CreateEmployee) -> Result<employees::Model, DbErr> {
+        // If the role doesn't exist for the given ID, return an error.
+        if RoleRepository::find_by_id(db, new_employee.role_id).await?.is_none() {
+            return Err(DbErr::Custom(format!("Role with ID '{}' not found", new_employee.role_id)));
+        }
 
         let now: DateTime<Utc> = Utc::now();
         let employee = employees::ActiveModel {
@@ -42,7 +86,6 @@ impl EmployeeRepository {
             email: ActiveValue::Set(new_employee.email),
             phone: ActiveValue::Set(new_employee.phone),
             store_id: ActiveValue::Set(new_employee.store_id),
-            role: ActiveValue::Set(role_name), // Set the role name string
             role_id: ActiveValue::Set(new_employee.role_id), // Set the foreign key
             password_hash: ActiveValue::Set("".to_string()), // Set empty password hash
             photo_url: ActiveValue::Set(new_employee.photo_url),
@@ -50,10 +93,27 @@ impl EmployeeRepository {
             updated_at: ActiveValue::Set(now),
             ..Default::default()
         };
-        employee.insert(db).await
+        let employee = employee.insert(db).await?;
+        // The primary store is always one of the employee's stores
+        if let Some(store_id) = employee.store_id {
+            EmployeeStoreRepository::assign(db, employee.id, store_id).await?;
+        }
+        Ok(employee)
+    }
+
+    /// Every photo URL in use, for the orphaned image sweep.
+    pub async fn get_photo_urls<C: ConnectionTrait>(db: &C) -> Result<Vec<String>, DbErr> {
+        employees::Entity::find()
+            .select_only()
+            .column(employees::Column::PhotoUrl)
+            .filter(employees::Column::PhotoUrl.is_not_null())
+            .into_tuple::<Option<String>>()
+            .all(db)
+            .await
+            .map(|urls| urls.into_iter().flatten().collect())
     }
 
-    pub async fn find_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<employees::Model>, DbErr> {
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<employees::Model>, DbErr> {
         employees::Entity::find_by_id(id).one(db).await
     }
 
@@ -79,7 +139,6 @@ impl EmployeeRepository {
             if let Some(role_id) = update_data.role_id {
                 let role_model = RoleRepository::find_by_id(db, role_id).await?;
                 if let Some(role) = role_model {
-                    active_model.role = ActiveValue::Set(role.name);
                     active_model.role_id = ActiveValue::Set(role.id);
                 } else {
                     return Err(DbErr::Custom(format!("Role with ID '{}' not found", role_id)));
@@ -92,7 +151,11 @@ impl EmployeeRepository {
                 active_model.photo_url = ActiveValue::Set(Some(photo_url));
             }
             active_model.updated_at = ActiveValue::Set(Utc::now());
-            Ok(Some(active_model.update(db).await?))
+            let employee = active_model.update(db).await?;
+            if let Some(store_id) = employee.store_id {
+                EmployeeStoreRepository::assign(db, employee.id, store_id).await?;
+            }
+            Ok(Some(employee))
         } else {
             Ok(None)
         }
@@ -103,6 +166,128 @@ impl EmployeeRepository {
         Ok(res.rows_affected)
     }
 
+    /// Counts a failed login and locks the account for `lockout_minutes` once
+    /// `max_attempts` is reached. A limit of zero disables the lockout.
+    pub async fn record_failed_login(db: &DatabaseConnection, employee: employees::Model, max_attempts: i32, lockout_minutes: i32) -> Result<employees::Model, DbErr> {
+        let locked_until = Utc::now() + chrono::Duration::minutes(lockout_minutes.max(0) as i64);
+        Self::count_failure(
+            db,
+            employee.id,
+            employees::Column::FailedLoginAttempts,
+            employees::Column::LockedUntil,
+            (max_attempts > 0).then_some(max_attempts),
+            locked_until,
+        )
+        .await
+    }
+
+    /// Adds one to a failure counter in a single statement, so concurrent failures are all
+    /// counted. When the count reaches `max_attempts` it starts over and `lock_column` is set
+    /// to `locked_until`. Returns the employee as stored afterwards.
+    async fn count_failure(
+        db: &DatabaseConnection,
+        id: i32,
+        counter: employees::Column,
+        lock_column: employees::Column,
+        max_attempts: Option<i32>,
+        locked_until: DateTime<Utc>,
+    ) -> Result<employees::Model, DbErr> {
+        let reaches_limit: SimpleExpr = match max_attempts {
+            Some(max_attempts) => Expr::col(counter).gte(max_attempts - 1),
+            None => Expr::val(false).into(),
+        };
+        // MySQL assigns left to right, so the lock is decided on the count before this failure
+        employees::Entity::update_many()
+            .col_expr(
+                lock_column,
+                Expr::cust_with_exprs("IF(?, ?, ?)", [reaches_limit.clone(), Expr::val(locked_until).into(), Expr::col(lock_column).into()]),
+            )
+            .col_expr(
+                counter,
+                Expr::cust_with_exprs("IF(?, 0, ?)", [reaches_limit, Expr::col(counter).add(1)]),
+            )
+            .filter(employees::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        employees::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("Employee {} not found", id)))
+    }
+
+    /// Resets the failed login counter and lifts any lockout.
+    pub async fn clear_failed_logins(db: &DatabaseConnection, employee: employees::Model) -> Result<employees::Model, DbErr> {
+        if employee.failed_login_attempts == 0 && employee.locked_until.is_none() {
+            return Ok(employee);
+        }
+        let mut active_model: employees::ActiveModel = employee.into();
+        active_model.failed_login_attempts = ActiveValue::Set(0);
+        active_model.locked_until = ActiveValue::Set(None);
+        active_model.update(db).await
+    }
+
+    /// Stores a new, not yet confirmed TOTP secret.
+    pub async fn set_pending_totp_secret(db: &DatabaseConnection, employee: employees::Model, secret: String) -> Result<employees::Model, DbErr> {
+        let mut active_model: employees::ActiveModel = employee.into();
+        active_model.totp_secret = ActiveValue::Set(Some(secret));
+        active_model.totp_enabled = ActiveValue::Set(false);
+        active_model.totp_last_step = ActiveValue::Set(None);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    /// Remembers the time step of an accepted code, turning 2FA on if it was pending.
+    pub async fn accept_totp_step<C: ConnectionTrait>(db: &C, employee: employees::Model, step: i64) -> Result<employees::Model, DbErr> {
+        let mut active_model: employees::ActiveModel = employee.into();
+        active_model.totp_enabled = ActiveValue::Set(true);
+        active_model.totp_last_step = ActiveValue::Set(Some(step));
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    pub async fn clear_totp<C: ConnectionTrait>(db: &C, employee: employees::Model) -> Result<employees::Model, DbErr> {
+        let mut active_model: employees::ActiveModel = employee.into();
+        active_model.totp_secret = ActiveValue::Set(None);
+        active_model.totp_enabled = ActiveValue::Set(false);
+        active_model.totp_last_step = ActiveValue::Set(None);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    /// Sets or (with `None`) removes the quick-login PIN, lifting any PIN lockout.
+    pub async fn set_pin_hash(db: &DatabaseConnection, employee: employees::Model, pin_hash: Option<String>) -> Result<employees::Model, DbErr> {
+        let mut active_model: employees::ActiveModel = employee.into();
+        active_model.pin_hash = ActiveValue::Set(pin_hash);
+        active_model.failed_pin_attempts = ActiveValue::Set(0);
+        active_model.pin_locked_until = ActiveValue::Set(None);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    /// Counts a wrong PIN; reaching `max_attempts` locks PIN login for `lockout_minutes`.
+    pub async fn record_failed_pin(db: &DatabaseConnection, employee: employees::Model, max_attempts: i32, lockout_minutes: i64) -> Result<employees::Model, DbErr> {
+        let locked_until = Utc::now() + chrono::Duration::minutes(lockout_minutes);
+        Self::count_failure(
+            db,
+            employee.id,
+            employees::Column::FailedPinAttempts,
+            employees::Column::PinLockedUntil,
+            Some(max_attempts),
+            locked_until,
+        )
+        .await
+    }
+
+    pub async fn clear_failed_pins(db: &DatabaseConnection, employee: employees::Model) -> Result<employees::Model, DbErr> {
+        if employee.failed_pin_attempts == 0 && employee.pin_locked_until.is_none() {
+            return Ok(employee);
+        }
+        let mut active_model: employees::ActiveModel = employee.into();
+        active_model.failed_pin_attempts = ActiveValue::Set(0);
+        active_model.pin_locked_until = ActiveValue::Set(None);
+        active_model.update(db).await
+    }
+
     pub async fn find_by_email(db: &DatabaseConnection, email: String) -> Result<Option<employees::Model>, DbErr> {
         employees::Entity::find().filter(employees::Column::Email.eq(email)).one(db).await
     }
@@ -0,0 +1,54 @@
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use chrono::{DateTime, Utc};
+
+use crate::entities::event_log;
+
+pub struct EventLogRepository;
+
+impl EventLogRepository {
+    pub async fn append<C: ConnectionTrait>(db: &C, topic: String, event: serde_json::Value) -> Result<event_log::Model, DbErr> {
+        let model = event_log::ActiveModel {
+            topic: ActiveValue::Set(topic),
+            event: ActiveValue::Set(event),
+            created_at: ActiveValue::Set(Utc::now()),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn latest_id<C: ConnectionTrait>(db: &C) -> Result<Option<i64>, DbErr> {
+        Ok(event_log::Entity::find()
+            .order_by_desc(event_log::Column::Id)
+            .one(db)
+            .await?
+            .map(|event| event.id))
+    }
+
+    pub async fn oldest_id<C: ConnectionTrait>(db: &C) -> Result<Option<i64>, DbErr> {
+        Ok(event_log::Entity::find()
+            .order_by_asc(event_log::Column::Id)
+            .one(db)
+            .await?
+            .map(|event| event.id))
+    }
+
+    /// Events after sequence number `since`, oldest first.
+    pub async fn find_after<C: ConnectionTrait>(db: &C, since: i64, limit: u64) -> Result<Vec<event_log::Model>, DbErr> {
+        event_log::Entity::find()
+            .filter(event_log::Column::Id.gt(since))
+            .order_by_asc(event_log::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Deletes events older than `before`, and all but the newest `keep` events.
+    pub async fn prune<C: ConnectionTrait>(db: &C, before: DateTime<Utc>, keep: i64) -> Result<u64, DbErr> {
+        let mut condition = Condition::any().add(event_log::Column::CreatedAt.lt(before));
+        if let Some(latest) = Self::latest_id(db).await? {
+            condition = condition.add(event_log::Column::Id.lte(latest - keep));
+        }
+        let res = event_log::Entity::delete_many().filter(condition).exec(db).await?;
+        Ok(res.rows_affected)
+    }
+}
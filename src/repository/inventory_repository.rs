@@ -1,20 +1,26 @@
-use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ColumnTrait, QueryFilter, ConnectionTrait};
+use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ColumnTrait, QueryFilter, QuerySelect, ConnectionTrait};
+use std::collections::HashMap;
 use crate::entities::inventory;
-use actix::Addr;
-use crate::websocket::broadcaster::{Broadcaster, BroadcastMessage};
 use chrono::{Utc, DateTime};
+use crate::auth::store_scope::StoreScope;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub struct InventoryRepository;
 
 impl InventoryRepository {
-    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<inventory::Model>, DbErr> {
-        inventory::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(inventory::Column::Id)
+            .sort("quantity", inventory::Column::Quantity)
+            .sort("last_restocked", inventory::Column::LastRestocked)
+            .sort("updated_at", inventory::Column::UpdatedAt)
+            .filter("store_id", inventory::Column::StoreId, FilterKind::Int)
+            .filter("product_id", inventory::Column::ProductId, FilterKind::Int)
+            .default_sort("id")
     }
 
-    pub async fn get_all_by_store<C: ConnectionTrait>(db: &C, store_id: i32) -> Result<Vec<inventory::Model>, DbErr> {
-        inventory::Entity::find()
-            .filter(inventory::Column::StoreId.eq(store_id))
-            .all(db).await
+    pub async fn get_all<C: ConnectionTrait>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<inventory::Model>, DbErr> {
+        let query = plan.apply(inventory::Entity::find().filter(scope.store_condition(inventory::Column::StoreId)));
+        plan.fetch(db, query, |item| item.id).await
     }
 
     pub async fn find_by_product_and_store<C: ConnectionTrait>(db: &C, product_id: i32, store_id: i32) -> Result<Option<inventory::Model>, DbErr> {
@@ -25,6 +31,20 @@ impl InventoryRepository {
             .await
     }
 
+    /// Quantities in a store by product; products the store does not stock are left out.
+    pub async fn quantities_in_store<C: ConnectionTrait>(db: &C, store_id: i32, product_ids: Vec<i32>) -> Result<HashMap<i32, i32>, DbErr> {
+        let rows: Vec<(i32, i32)> = inventory::Entity::find()
+            .select_only()
+            .column(inventory::Column::ProductId)
+            .column(inventory::Column::Quantity)
+            .filter(inventory::Column::StoreId.eq(store_id))
+            .filter(inventory::Column::ProductId.is_in(product_ids))
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
     pub async fn increase_quantity<C: ConnectionTrait>(db: &C, product_id: i32, store_id: i32, quantity_to_add: i32) -> Result<inventory::Model, DbErr> {
         let inventory_item = Self::find_by_product_and_store(db, product_id, store_id).await?;
         let now: DateTime<Utc> = Utc::now();
@@ -39,7 +59,7 @@ impl InventoryRepository {
                 product_id: ActiveValue::Set(product_id),
                 store_id: ActiveValue::Set(store_id),
                 quantity: ActiveValue::Set(quantity_to_add),
-                last_restocked: ActiveValue::Set(Some(now.into())),
+                last_restocked: ActiveValue::Set(Some(now)),
                 created_at: ActiveValue::Set(now),
                 updated_at: ActiveValue::Set(now),
                 ..Default::default()
@@ -85,7 +105,6 @@ impl InventoryRepository {
         db: &C, 
         id: i32, 
         update_data: inventory::UpdateInventory,
-        broadcaster: &Addr<Broadcaster>,
     ) -> Result<Option<inventory::Model>, DbErr> {
         let inventory: Option<inventory::Model> = inventory::Entity::find_by_id(id).one(db).await?;
         if let Some(inventory) = inventory {
@@ -104,9 +123,6 @@ impl InventoryRepository {
             }
             active_model.updated_at = ActiveValue::Set(Utc::now());
             let result = active_model.update(db).await?;
-
-            broadcaster.do_send(BroadcastMessage("inventory_updated".to_string()));
-
             Ok(Some(result))
         } else {
             Ok(None)
@@ -16,3 +16,13 @@ pub mod permissions_repository;
 pub mod roles_repository;
 pub mod settings_repository;
 pub mod password_reset_tokens_repository;
+pub mod shifts_repository;
+pub mod terminals_repository;
+pub mod email_outbox_repository;
+pub mod email_templates_repository;
+pub mod employee_recovery_codes_repository;
+pub mod sessions_repository;
+pub mod employee_stores_repository;
+pub mod time_entries_repository;
+pub mod commissions_repository;
+pub mod event_log_repository;
@@ -1,12 +1,28 @@
-use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ColumnTrait, QueryFilter, JoinType, QuerySelect, RelationTrait, ConnectionTrait};
-use crate::entities::{order_items, orders};
-use chrono::Utc;
+use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ColumnTrait, QueryFilter, QueryOrder, JoinType, QuerySelect, RelationTrait, ConnectionTrait, prelude::Decimal};
+use crate::entities::{order_items, orders, products};
+use chrono::{Utc, DateTime};
+use crate::auth::store_scope::StoreScope;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub struct OrderItemRepository;
 
 impl OrderItemRepository {
-    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<order_items::Model>, DbErr> {
-        order_items::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(order_items::Column::Id)
+            .sort("created_at", order_items::Column::CreatedAt)
+            .filter("order_id", order_items::Column::OrderId, FilterKind::Int)
+            .filter("product_id", order_items::Column::ProductId, FilterKind::Int)
+            .filter("promotion_id", order_items::Column::PromotionId, FilterKind::Int)
+            .filter("store_id", orders::Column::StoreId, FilterKind::Int)
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<order_items::Model>, DbErr> {
+        let query = plan.apply(
+            order_items::Entity::find()
+                .join(JoinType::InnerJoin, order_items::Relation::Orders.def())
+                .filter(scope.record_condition(orders::Column::StoreId, orders::Column::EmployeeId)),
+        );
+        plan.fetch(db, query, |item| item.id).await
     }
 
     pub async fn get_all_by_order_id<C: ConnectionTrait>(db: &C, order_id: i32) -> Result<Vec<order_items::Model>, DbErr> {
@@ -16,23 +32,38 @@ impl OrderItemRepository {
             .await
     }
 
-    pub async fn get_all_by_store<C: ConnectionTrait>(db: &C, store_id: i32) -> Result<Vec<order_items::Model>, DbErr> {
+    /// The lines of many bills in one query, in order of the bills and then of the lines.
+    pub async fn find_bill_items<C: ConnectionTrait>(db: &C, order_ids: Vec<i32>) -> Result<Vec<order_items::BillItemRow>, DbErr> {
         order_items::Entity::find()
-            .join(JoinType::InnerJoin, order_items::Relation::Orders.def())
-            .filter(orders::Column::StoreId.eq(store_id))
+            .join(JoinType::LeftJoin, order_items::Relation::Products.def())
+            .filter(order_items::Column::OrderId.is_in(order_ids))
+            .select_only()
+            .column(order_items::Column::OrderId)
+            .column_as(products::Column::Name, "product_name")
+            .column(order_items::Column::Quantity)
+            .column(order_items::Column::UnitPrice)
+            .column(order_items::Column::DiscountAmount)
+            .order_by_asc(order_items::Column::OrderId)
+            .order_by_asc(order_items::Column::Id)
+            .into_model::<order_items::BillItemRow>()
             .all(db)
             .await
     }
 
-    pub async fn get_all_by_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<Vec<order_items::Model>, DbErr> {
-        order_items::Entity::find()
+    /// Lowest price the product was actually sold at since `since`, after promotions.
+    pub async fn find_lowest_unit_price_since<C: ConnectionTrait>(db: &C, product_id: i32, since: DateTime<Utc>) -> Result<Option<Decimal>, DbErr> {
+        let lowest: Option<Option<Decimal>> = order_items::Entity::find()
             .join(JoinType::InnerJoin, order_items::Relation::Orders.def())
-            .filter(orders::Column::EmployeeId.eq(employee_id))
-            .all(db)
-            .await
-    }
+            .filter(order_items::Column::ProductId.eq(product_id))
+            .filter(orders::Column::OrderDate.gte(since))
+            .select_only()
+            .column_as(order_items::Column::UnitPrice.min(), "lowest_unit_price")
+            .into_tuple()
+            .one(db)
+            .await?;
 
-    
+        Ok(lowest.flatten())
+    }
 
     pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<order_items::Model>, DbErr> {
         order_items::Entity::find_by_id(id).one(db).await
@@ -1,37 +1,80 @@
-use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ColumnTrait, QueryFilter, ConnectionTrait, prelude::Decimal};
-use crate::entities::{orders, order_items};
+use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, QueryFilter, QuerySelect, RelationTrait, JoinType, ConnectionTrait, prelude::Decimal};
+use sea_orm::sea_query::Expr;
+use crate::entities::{orders, order_items, customers, employees, stores};
 use chrono::{Utc, DateTime};
+use crate::auth::store_scope::StoreScope;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub struct OrderRepository;
 
 impl OrderRepository {
-    pub async fn get_all<C>(db: &C) -> Result<Vec<orders::Model>, DbErr> where C: ConnectionTrait {
-        orders::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(orders::Column::Id)
+            .sort("order_date", orders::Column::OrderDate)
+            .sort("total_amount", orders::Column::TotalAmount)
+            .filter("store_id", orders::Column::StoreId, FilterKind::Int)
+            .filter("employee_id", orders::Column::EmployeeId, FilterKind::Int)
+            .filter("customer_id", orders::Column::CustomerId, FilterKind::Int)
+            .filter("terminal_id", orders::Column::TerminalId, FilterKind::Int)
+            .filter("status", orders::Column::Status, FilterKind::Text)
+            .filter("from", orders::Column::OrderDate, FilterKind::From)
+            .filter("to", orders::Column::OrderDate, FilterKind::To)
+            .default_sort("-order_date")
     }
 
-    pub async fn get_all_by_store<C>(db: &C, store_id: i32) -> Result<Vec<orders::Model>, DbErr> where C: ConnectionTrait {
-        orders::Entity::find()
-            .filter(orders::Column::StoreId.eq(store_id))
-            .all(db)
-            .await
+    pub async fn get_all<C>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<orders::Model>, DbErr> where C: ConnectionTrait {
+        let query = plan.apply(orders::Entity::find().filter(scope.record_condition(orders::Column::StoreId, orders::Column::EmployeeId)));
+        plan.fetch(db, query, |order| order.id).await
     }
 
-    
+    pub fn bill_list_fields() -> ListFields {
+        ListFields::new(orders::Column::Id)
+            .sort("order_date", orders::Column::OrderDate)
+            .sort("total_amount", orders::Column::TotalAmount)
+            .filter("date", orders::Column::OrderDate, FilterKind::Date)
+            .filter("from", orders::Column::OrderDate, FilterKind::From)
+            .filter("to", orders::Column::OrderDate, FilterKind::To)
+            .filter("status", orders::Column::Status, FilterKind::Text)
+            .filter("store_id", orders::Column::StoreId, FilterKind::Int)
+            .filter("employee_id", orders::Column::EmployeeId, FilterKind::Int)
+            .filter("customer_id", orders::Column::CustomerId, FilterKind::Int)
+            .default_sort("-order_date")
+    }
 
-    pub async fn get_all_by_employee_and_store<C>(db: &C, employee_id: i32, store_id: i32) -> Result<Vec<orders::Model>, DbErr> where C: ConnectionTrait {
-        orders::Entity::find()
-            .filter(orders::Column::EmployeeId.eq(employee_id))
-            .filter(orders::Column::StoreId.eq(store_id))
-            .all(db)
-            .await
+    /// A page of orders for the bills list, with customer, cashier and store names joined in.
+    pub async fn get_bills<C: ConnectionTrait>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<orders::BillRow>, DbErr> {
+        let full_name = |first_name: Expr, last_name: Expr| Expr::cust_with_exprs("CONCAT(?, ' ', ?)", [first_name.into(), last_name.into()]);
+        let query = plan
+            .apply(orders::Entity::find().filter(scope.record_condition(orders::Column::StoreId, orders::Column::EmployeeId)))
+            .join(JoinType::LeftJoin, orders::Relation::Customers.def())
+            .join(JoinType::LeftJoin, orders::Relation::Employees.def())
+            .join(JoinType::LeftJoin, orders::Relation::Stores.def())
+            .select_only()
+            .column(orders::Column::Id)
+            .column(orders::Column::TotalAmount)
+            .column(orders::Column::Status)
+            .column(orders::Column::OrderDate)
+            .column_as(
+                full_name(Expr::col((customers::Entity, customers::Column::FirstName)), Expr::col((customers::Entity, customers::Column::LastName))),
+                "customer_name",
+            )
+            .column_as(
+                full_name(Expr::col((employees::Entity, employees::Column::FirstName)), Expr::col((employees::Entity, employees::Column::LastName))),
+                "employee_name",
+            )
+            .column_as(stores::Column::Name, "store_name")
+            .into_model::<orders::BillRow>();
+        plan.fetch(db, query, |bill| bill.id).await
     }
 
-    pub async fn create<C>(db: &C, customer_id: i32, employee_id: i32, store_id: i32, total_amount: Decimal, status: String, items: Vec<order_items::ActiveModel>) -> Result<orders::Model, DbErr> where C: ConnectionTrait {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create<C>(db: &C, customer_id: i32, employee_id: i32, store_id: i32, terminal_id: Option<i32>, total_amount: Decimal, status: String, items: Vec<order_items::ActiveModel>) -> Result<orders::Model, DbErr> where C: ConnectionTrait {
         let now: DateTime<Utc> = Utc::now();
         let order = orders::ActiveModel {
             customer_id: ActiveValue::Set(customer_id),
             employee_id: ActiveValue::Set(employee_id),
             store_id: ActiveValue::Set(store_id),
+            terminal_id: ActiveValue::Set(terminal_id),
             order_date: ActiveValue::Set(now),
             total_amount: ActiveValue::Set(total_amount),
             status: ActiveValue::Set(status),
@@ -1,10 +1,10 @@
-use sea_orm::{DbConn, DbErr, Set, ActiveModelTrait, EntityTrait, QueryFilter, ColumnTrait, DeleteResult};
+use sea_orm::{DbConn, DbErr, Set, ActiveModelTrait, EntityTrait, QueryFilter, ColumnTrait, ConnectionTrait, DeleteResult};
 use crate::entities::password_reset_tokens;
 
 pub struct PasswordResetTokenRepository;
 
 impl PasswordResetTokenRepository {
-    pub async fn create(db: &DbConn, employee_id: i32, token: String, expires_at: chrono::NaiveDateTime) -> Result<password_reset_tokens::Model, DbErr> {
+    pub async fn create<C: ConnectionTrait>(db: &C, employee_id: i32, token: String, expires_at: chrono::NaiveDateTime) -> Result<password_reset_tokens::Model, DbErr> {
         let new_token = password_reset_tokens::ActiveModel {
             employee_id: Set(employee_id),
             token: Set(token),
@@ -1,34 +1,40 @@
 use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ColumnTrait, QueryFilter, JoinType, QuerySelect, RelationTrait, ConnectionTrait, DatabaseTransaction};
 use crate::entities::{payments, orders};
 use chrono::{Utc, DateTime};
+use crate::auth::store_scope::StoreScope;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub struct PaymentRepository;
 
 impl PaymentRepository {
-    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<payments::Model>, DbErr> {
-        payments::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(payments::Column::Id)
+            .sort("payment_date", payments::Column::PaymentDate)
+            .sort("amount", payments::Column::Amount)
+            .filter("order_id", payments::Column::OrderId, FilterKind::Int)
+            .filter("terminal_id", payments::Column::TerminalId, FilterKind::Int)
+            .filter("store_id", orders::Column::StoreId, FilterKind::Int)
+            .filter("payment_method", payments::Column::PaymentMethod, FilterKind::Text)
+            .filter("status", payments::Column::Status, FilterKind::Text)
+            .filter("from", payments::Column::PaymentDate, FilterKind::From)
+            .filter("to", payments::Column::PaymentDate, FilterKind::To)
+            .default_sort("-payment_date")
     }
 
-    pub async fn get_all_by_store<C: ConnectionTrait>(db: &C, store_id: i32) -> Result<Vec<payments::Model>, DbErr> {
-        payments::Entity::find()
-            .join(JoinType::InnerJoin, payments::Relation::Orders.def())
-            .filter(orders::Column::StoreId.eq(store_id))
-            .all(db)
-            .await
-    }
-
-    pub async fn get_all_by_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<Vec<payments::Model>, DbErr> {
-        payments::Entity::find()
-            .join(JoinType::InnerJoin, payments::Relation::Orders.def())
-            .filter(orders::Column::EmployeeId.eq(employee_id))
-            .all(db)
-            .await
+    pub async fn get_all<C: ConnectionTrait>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<payments::Model>, DbErr> {
+        let query = plan.apply(
+            payments::Entity::find()
+                .join(JoinType::InnerJoin, payments::Relation::Orders.def())
+                .filter(scope.record_condition(orders::Column::StoreId, orders::Column::EmployeeId)),
+        );
+        plan.fetch(db, query, |payment| payment.id).await
     }
 
     pub async fn create<C: ConnectionTrait>(db: &C, new_payment: payments::CreatePayment) -> Result<payments::Model, DbErr> {
         let now: DateTime<Utc> = Utc::now();
         let payment = payments::ActiveModel {
             order_id: ActiveValue::Set(new_payment.order_id),
+            terminal_id: ActiveValue::Set(new_payment.terminal_id),
             payment_method: ActiveValue::Set(new_payment.payment_method),
             amount: ActiveValue::Set(new_payment.amount),
             payment_date: ActiveValue::Set(new_payment.payment_date),
@@ -44,6 +50,7 @@ impl PaymentRepository {
         let now: DateTime<Utc> = Utc::now();
         let payment = payments::ActiveModel {
             order_id: ActiveValue::Set(new_payment.order_id),
+            terminal_id: ActiveValue::Set(new_payment.terminal_id),
             payment_method: ActiveValue::Set(new_payment.payment_method),
             amount: ActiveValue::Set(new_payment.amount),
             payment_date: ActiveValue::Set(new_payment.payment_date),
@@ -59,6 +66,13 @@ impl PaymentRepository {
         payments::Entity::find_by_id(id).one(db).await
     }
 
+    pub async fn find_by_order_id<C: ConnectionTrait>(db: &C, order_id: i32) -> Result<Vec<payments::Model>, DbErr> {
+        payments::Entity::find()
+            .filter(payments::Column::OrderId.eq(order_id))
+            .all(db)
+            .await
+    }
+
     pub async fn update<C: ConnectionTrait>(db: &C, id: i32, update_data: payments::UpdatePayment) -> Result<Option<payments::Model>, DbErr> {
         let payment: Option<payments::Model> = payments::Entity::find_by_id(id).one(db).await?;
         if let Some(payment) = payment {
@@ -1,11 +1,19 @@
 use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ColumnTrait, QueryFilter, ActiveModelTrait, ActiveValue, ConnectionTrait};
 use crate::entities::{permissions, role_permissions};
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub struct PermissionsRepository;
 
 impl PermissionsRepository {
-    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<permissions::Model>, DbErr> {
-        permissions::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(permissions::Column::Id)
+            .sort("name", permissions::Column::Name)
+            .filter("name", permissions::Column::Name, FilterKind::Contains)
+            .default_sort("id")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, plan: &ListPlan) -> Result<Page<permissions::Model>, DbErr> {
+        plan.fetch(db, plan.apply(permissions::Entity::find()), |permission| permission.id).await
     }
 
     pub async fn create<C: ConnectionTrait>(db: &C, new_permission: permissions::CreatePermission) -> Result<permissions::Model, DbErr> {
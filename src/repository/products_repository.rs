@@ -1,13 +1,28 @@
-use sea_orm::{ConnectionTrait, DbErr, EntityTrait, ActiveModelTrait, ActiveValue, QueryFilter, ColumnTrait, JoinType, QuerySelect, RelationTrait};
+use sea_orm::{ConnectionTrait, DbErr, EntityTrait, ActiveModelTrait, ActiveValue, QueryFilter, ColumnTrait, Condition, JoinType, QuerySelect, RelationTrait, Select};
 use crate::entities::products::{self, ProductWithDetails};
 use crate::entities::categories;
 use crate::entities::suppliers;
 use chrono::{Utc, DateTime};
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub struct ProductRepository;
 
 impl ProductRepository {
-    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<ProductWithDetails>, DbErr> {
+    pub fn list_fields() -> ListFields {
+        ListFields::new(products::Column::Id)
+            .sort("name", products::Column::Name)
+            .sort("price", products::Column::Price)
+            .sort("sku", products::Column::Sku)
+            .sort("created_at", products::Column::CreatedAt)
+            .filter("name", products::Column::Name, FilterKind::Contains)
+            .filter("sku", products::Column::Sku, FilterKind::Text)
+            .filter("category_id", products::Column::CategoryId, FilterKind::Int)
+            .filter("supplier_id", products::Column::SupplierId, FilterKind::Int)
+            .default_sort("id")
+    }
+
+    /// Products with their category and supplier names.
+    fn with_details() -> Select<products::Entity> {
         products::Entity::find()
             .join(JoinType::InnerJoin, products::Relation::Categories.def())
             .join(JoinType::InnerJoin, products::Relation::Suppliers.def())
@@ -22,11 +37,42 @@ impl ProductRepository {
             .column(products::Column::PhotoUrl)
             .column(products::Column::CreatedAt)
             .column(products::Column::UpdatedAt)
+            .column(products::Column::ExpiresAt)
             .column_as(categories::Column::Name, "category_name")
             .column_as(suppliers::Column::Name, "supplier_name")
-            .into_model::<ProductWithDetails>()
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, plan: &ListPlan) -> Result<Page<ProductWithDetails>, DbErr> {
+        let query = plan.apply(Self::with_details()).into_model::<ProductWithDetails>();
+        plan.fetch(db, query, |product| product.id).await
+    }
+
+    /// Products matching a condition, with their category and supplier names.
+    pub async fn find_with_details<C: ConnectionTrait>(db: &C, condition: Condition) -> Result<Vec<ProductWithDetails>, DbErr> {
+        Self::with_details().filter(condition).into_model::<ProductWithDetails>().all(db).await
+    }
+
+    /// The id and last change of every product, to tell which ones changed since a given time.
+    pub async fn get_update_stamps<C: ConnectionTrait>(db: &C) -> Result<Vec<(i32, DateTime<Utc>)>, DbErr> {
+        products::Entity::find()
+            .select_only()
+            .column(products::Column::Id)
+            .column(products::Column::UpdatedAt)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    /// Every photo URL in use, for the orphaned image sweep.
+    pub async fn get_photo_urls<C: ConnectionTrait>(db: &C) -> Result<Vec<String>, DbErr> {
+        products::Entity::find()
+            .select_only()
+            .column(products::Column::PhotoUrl)
+            .filter(products::Column::PhotoUrl.is_not_null())
+            .into_tuple::<Option<String>>()
             .all(db)
             .await
+            .map(|urls| urls.into_iter().flatten().collect())
     }
 
     pub async fn find_by_ids<C: ConnectionTrait>(db: &C, ids: Vec<i32>) -> Result<Vec<products::Model>, DbErr> {
@@ -50,6 +96,10 @@ impl ProductRepository {
         product.insert(db).await
     }
 
+    pub async fn find_by_sku<C: ConnectionTrait>(db: &C, sku: String) -> Result<Option<products::Model>, DbErr> {
+        products::Entity::find().filter(products::Column::Sku.eq(sku)).one(db).await
+    }
+
     pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<products::Model>, DbErr> {
         products::Entity::find_by_id(id).one(db).await
     }
@@ -1,5 +1,6 @@
 use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ConnectionTrait, ColumnTrait, QueryFilter};
 use crate::entities::promotions;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 use chrono::{Utc, DateTime};
 
 pub struct PromotionRepository;
@@ -23,8 +24,22 @@ impl PromotionRepository {
         promo.insert(db).await
     }
 
-    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<promotions::Model>, DbErr> {
-        promotions::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(promotions::Column::Id)
+            .sort("name", promotions::Column::Name)
+            .sort("start_date", promotions::Column::StartDate)
+            .sort("end_date", promotions::Column::EndDate)
+            .filter("name", promotions::Column::Name, FilterKind::Contains)
+            .filter("promotion_type", promotions::Column::PromotionType, FilterKind::Text)
+            .filter("product_id", promotions::Column::ProductId, FilterKind::Int)
+            .filter("is_active", promotions::Column::IsActive, FilterKind::Bool)
+            .filter("starts_from", promotions::Column::StartDate, FilterKind::From)
+            .filter("ends_to", promotions::Column::EndDate, FilterKind::To)
+            .default_sort("id")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, plan: &ListPlan) -> Result<Page<promotions::Model>, DbErr> {
+        plan.fetch(db, plan.apply(promotions::Entity::find()), |promotion| promotion.id).await
     }
 
     pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<promotions::Model>, DbErr> {
@@ -65,7 +80,7 @@ impl PromotionRepository {
             promo.product_id = ActiveValue::Set(Some(product_id));
         }
         
-        promo.updated_at = ActiveValue::Set(chrono::Utc::now().into());
+        promo.updated_at = ActiveValue::Set(chrono::Utc::now());
 
         promo.update(db).await
     }
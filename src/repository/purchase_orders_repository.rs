@@ -1,6 +1,7 @@
-use sea_orm::{DbErr, Set, EntityTrait, ActiveModelTrait, DatabaseConnection, QueryFilter, ColumnTrait, ModelTrait, TransactionTrait, ConnectionTrait};
-use crate::entities::{purchase_orders, purchase_order_items, suppliers, stores, products};
+use sea_orm::{DbErr, Set, EntityTrait, ActiveModelTrait, DatabaseConnection, QueryFilter, ColumnTrait, ModelTrait, TransactionTrait, ConnectionTrait, LoaderTrait};
+use crate::entities::{purchase_orders, purchase_order_items, suppliers, stores, products, inventory};
 use crate::repository::inventory_repository::InventoryRepository;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 use crate::handler::purchase_orders_handler::ReceiveItem;
 use chrono::{Utc, DateTime};
 
@@ -18,7 +19,7 @@ impl PurchaseOrderRepository {
             supplier_id: Set(supplier_id),
             store_id: Set(store_id),
             employee_id: Set(employee_id),
-            order_date: Set(now.into()),
+            order_date: Set(now),
             status: Set("draft".to_owned()),
             created_at: Set(now),
             updated_at: Set(now),
@@ -65,17 +66,35 @@ impl PurchaseOrderRepository {
         }
     }
 
+    pub fn list_fields() -> ListFields {
+        ListFields::new(purchase_orders::Column::Id)
+            .sort("order_date", purchase_orders::Column::OrderDate)
+            .sort("expected_delivery_date", purchase_orders::Column::ExpectedDeliveryDate)
+            .sort("total_amount", purchase_orders::Column::TotalAmount)
+            .filter("status", purchase_orders::Column::Status, FilterKind::Text)
+            .filter("store_id", purchase_orders::Column::StoreId, FilterKind::Int)
+            .filter("supplier_id", purchase_orders::Column::SupplierId, FilterKind::Int)
+            .filter("from", purchase_orders::Column::OrderDate, FilterKind::From)
+            .filter("to", purchase_orders::Column::OrderDate, FilterKind::To)
+            .default_sort("-order_date")
+    }
+
     pub async fn find_all_pos_with_relations<C: ConnectionTrait>(
         db: &C,
-    ) -> Result<Vec<(purchase_orders::Model, Option<suppliers::Model>, Option<stores::Model>)>, DbErr> {
-        let pos = purchase_orders::Entity::find().all(db).await?;
-        let mut result = Vec::new();
-        for po in pos {
-            let supplier = po.find_related(suppliers::Entity).one(db).await?;
-            let store = po.find_related(stores::Entity).one(db).await?;
-            result.push((po, supplier, store));
-        }
-        Ok(result)
+        plan: &ListPlan,
+    ) -> Result<Page<(purchase_orders::Model, Option<suppliers::Model>, Option<stores::Model>)>, DbErr> {
+        let page = plan.fetch(db, plan.apply(purchase_orders::Entity::find()), |po| po.id).await?;
+        // One query per relation for the whole page rather than two per purchase order
+        let suppliers = page.items.load_one(suppliers::Entity, db).await?;
+        let stores = page.items.load_one(stores::Entity, db).await?;
+        let items = page
+            .items
+            .into_iter()
+            .zip(suppliers)
+            .zip(stores)
+            .map(|((po, supplier), store)| (po, supplier, store))
+            .collect();
+        Ok(Page { items, pagination: page.pagination })
     }
 
     pub async fn find_items_for_po<C: ConnectionTrait>(
@@ -105,11 +124,13 @@ impl PurchaseOrderRepository {
         }
     }
 
+    /// Books received items into the store's stock. Returns the updated purchase order and
+    /// each stock level it changed, with the quantity it had before.
     pub async fn receive_purchase_order_items(
         db: &DatabaseConnection,
         po_id: i32,
         items: Vec<ReceiveItem>,
-    ) -> Result<(), DbErr> {
+    ) -> Result<(purchase_orders::Model, Vec<(inventory::Model, i32)>), DbErr> {
         let txn = db.begin().await?;
 
         let po = purchase_orders::Entity::find_by_id(po_id)
@@ -117,6 +138,7 @@ impl PurchaseOrderRepository {
             .await?
             .ok_or_else(|| DbErr::Custom("Purchase Order not found".to_owned()))?;
 
+        let mut stock_changes = Vec::new();
         for item in items {
             let po_item = purchase_order_items::Entity::find_by_id(item.purchase_order_item_id)
                 .one(&txn)
@@ -129,12 +151,14 @@ impl PurchaseOrderRepository {
             }
 
             // Update inventory
-            InventoryRepository::increase_quantity(
+            let stock = InventoryRepository::increase_quantity(
                 &txn,
                 po_item.product_id,
                 po.store_id,
                 item.quantity_received,
             ).await?;
+            let previous_quantity = stock.quantity - item.quantity_received;
+            stock_changes.push((stock, previous_quantity));
 
             // Update the received quantity on the PO item
             let mut po_item_active: purchase_order_items::ActiveModel = po_item.into();
@@ -154,8 +178,9 @@ impl PurchaseOrderRepository {
             "partially_received".to_string()
         };
 
-        Self::update_po_status(&txn, po_id, new_status).await?;
+        let po = Self::update_po_status(&txn, po_id, new_status).await?;
 
-        txn.commit().await
+        txn.commit().await?;
+        Ok((po, stock_changes))
     }
 }
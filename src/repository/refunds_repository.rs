@@ -1,6 +1,12 @@
-use sea_orm::{DbErr, Set, ActiveModelTrait, ConnectionTrait, EntityTrait, QueryFilter, ColumnTrait};
+use sea_orm::{DbErr, Set, ActiveModelTrait, ConnectionTrait, EntityTrait, QueryFilter, ColumnTrait, QuerySelect, QueryOrder, PaginatorTrait, Order, JoinType, RelationTrait};
+use sea_orm::prelude::Decimal;
+use sea_orm::sea_query::Expr;
+use chrono::{DateTime, Utc};
 
-use crate::entities::{refunds, refund_items};
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{refunds, refund_items, customers, employees};
+use crate::entities::refunds::{ReturnsAbuseReportQueryParams, ReturnsAbuseReportRow};
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub async fn create_refund<C>(
     db: &C,
@@ -18,17 +24,114 @@ pub async fn create_refund<C>(
     Ok((refund, created_items))
 }
 
-pub async fn get_all<C>(db: &C) -> Result<Vec<refunds::Model>, DbErr> where C: ConnectionTrait {
-    refunds::Entity::find().all(db).await
+pub fn list_fields() -> ListFields {
+    ListFields::new(refunds::Column::Id)
+        .sort("created_at", refunds::Column::CreatedAt)
+        .sort("total_amount", refunds::Column::TotalAmount)
+        .filter("store_id", refunds::Column::StoreId, FilterKind::Int)
+        .filter("employee_id", refunds::Column::EmployeeId, FilterKind::Int)
+        .filter("customer_id", refunds::Column::CustomerId, FilterKind::Int)
+        .filter("order_id", refunds::Column::OrderId, FilterKind::Int)
+        .filter("refund_method", refunds::Column::RefundMethod, FilterKind::Text)
+        .filter("is_receiptless", refunds::Column::IsReceiptless, FilterKind::Bool)
+        .filter("from", refunds::Column::CreatedAt, FilterKind::From)
+        .filter("to", refunds::Column::CreatedAt, FilterKind::To)
+        .default_sort("-created_at")
 }
 
-pub async fn get_all_by_store<C>(db: &C, store_id: i32) -> Result<Vec<refunds::Model>, DbErr> where C: ConnectionTrait {
+pub async fn get_all<C>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<refunds::Model>, DbErr> where C: ConnectionTrait {
+    let query = plan.apply(refunds::Entity::find().filter(scope.record_condition(refunds::Column::StoreId, refunds::Column::EmployeeId)));
+    plan.fetch(db, query, |refund| refund.id).await
+}
+
+pub async fn find_by_id<C>(db: &C, id: i32) -> Result<Option<refunds::Model>, DbErr> where C: ConnectionTrait {
+    refunds::Entity::find_by_id(id).one(db).await
+}
+
+pub async fn count_receiptless_by_customer_since<C>(db: &C, customer_id: i32, since: DateTime<Utc>) -> Result<u64, DbErr> where C: ConnectionTrait {
     refunds::Entity::find()
-        .filter(refunds::Column::StoreId.eq(store_id))
+        .filter(refunds::Column::CustomerId.eq(customer_id))
+        .filter(refunds::Column::IsReceiptless.eq(true))
+        .filter(refunds::Column::CreatedAt.gte(since))
+        .count(db)
+        .await
+}
+
+pub async fn get_receiptless_returns_by_customer<C>(db: &C, scope: &StoreScope, params: &ReturnsAbuseReportQueryParams) -> Result<Vec<ReturnsAbuseReportRow>, DbErr> where C: ConnectionTrait {
+    let mut query = refunds::Entity::find()
+        .left_join(customers::Entity)
+        .filter(refunds::Column::IsReceiptless.eq(true))
+        .filter(scope.store_condition(refunds::Column::StoreId));
+
+    if let Some(start_date) = params.start_date {
+        query = query.filter(refunds::Column::CreatedAt.gte(start_date));
+    }
+    if let Some(end_date) = params.end_date {
+        query = query.filter(refunds::Column::CreatedAt.lte(end_date));
+    }
+
+    let return_count = refunds::Column::Id.count();
+
+    query
+        .select_only()
+        .column(refunds::Column::CustomerId)
+        .column_as(
+            Expr::cust_with_exprs(
+                "CONCAT(?, ' ', ?)",
+                [
+                    Expr::col((customers::Entity, customers::Column::FirstName)).into(),
+                    Expr::col((customers::Entity, customers::Column::LastName)).into(),
+                ],
+            ),
+            "customer_name",
+        )
+        .column_as(return_count.clone(), "receiptless_returns")
+        .column_as(
+            Expr::cust("CAST(SUM(CASE WHEN `refunds`.`approved_by` IS NOT NULL THEN 1 ELSE 0 END) AS SIGNED)"),
+            "overridden_returns",
+        )
+        .column_as(refunds::Column::TotalAmount.sum(), "total_refunded")
+        .group_by(refunds::Column::CustomerId)
+        .group_by(customers::Column::FirstName)
+        .group_by(customers::Column::LastName)
+        .having(Expr::expr(return_count.clone()).gte(params.min_returns.unwrap_or(1)))
+        .order_by(return_count, Order::Desc)
+        .into_model::<ReturnsAbuseReportRow>()
         .all(db)
         .await
 }
 
-pub async fn find_by_id<C>(db: &C, id: i32) -> Result<Option<refunds::Model>, DbErr> where C: ConnectionTrait {
-    refunds::Entity::find_by_id(id).one(db).await
-}
\ No newline at end of file
+/// Refunds processed per employee as `(employee_id, first_name, last_name, count, total)`.
+pub async fn get_totals_by_employee<C>(
+    db: &C,
+    scope: &StoreScope,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    employee_id: Option<i32>,
+) -> Result<Vec<(i32, String, String, i64, Option<Decimal>)>, DbErr> where C: ConnectionTrait {
+    let mut query = refunds::Entity::find()
+        .join(JoinType::InnerJoin, refunds::Relation::Employees.def())
+        .filter(scope.store_condition(refunds::Column::StoreId));
+    if let Some(from) = from {
+        query = query.filter(refunds::Column::CreatedAt.gte(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(refunds::Column::CreatedAt.lte(to));
+    }
+    if let Some(employee_id) = employee_id {
+        query = query.filter(refunds::Column::EmployeeId.eq(employee_id));
+    }
+    query
+        .select_only()
+        .column(refunds::Column::EmployeeId)
+        .column(employees::Column::FirstName)
+        .column(employees::Column::LastName)
+        .column_as(refunds::Column::Id.count(), "refunds_count")
+        .column_as(refunds::Column::TotalAmount.sum(), "refunds_total")
+        .group_by(refunds::Column::EmployeeId)
+        .group_by(employees::Column::FirstName)
+        .group_by(employees::Column::LastName)
+        .into_tuple()
+        .all(db)
+        .await
+}
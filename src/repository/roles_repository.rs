@@ -1,12 +1,20 @@
 use sea_orm::{DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ConnectionTrait, QueryFilter, ColumnTrait};
 use crate::entities::roles::{self, CreateRole, UpdateRole};
 use crate::entities::role_permissions;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 
 pub struct RoleRepository;
 
 impl RoleRepository {
-    pub async fn get_all<C: ConnectionTrait>(db: &C) -> Result<Vec<roles::Model>, DbErr> {
-        roles::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(roles::Column::Id)
+            .sort("name", roles::Column::Name)
+            .filter("name", roles::Column::Name, FilterKind::Contains)
+            .default_sort("id")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, plan: &ListPlan) -> Result<Page<roles::Model>, DbErr> {
+        plan.fetch(db, plan.apply(roles::Entity::find()), |role| role.id).await
     }
 
     pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<roles::Model>, DbErr> {
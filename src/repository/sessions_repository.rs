@@ -0,0 +1,154 @@
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use sea_orm::sea_query::{Expr, Query};
+use chrono::{DateTime, Utc};
+
+use crate::entities::{employees, sessions};
+use crate::entities::sessions::CreateSession;
+
+pub struct SessionRepository;
+
+impl SessionRepository {
+    pub async fn create<C: ConnectionTrait>(db: &C, data: CreateSession) -> Result<sessions::Model, DbErr> {
+        let now = Utc::now();
+        let model = sessions::ActiveModel {
+            employee_id: ActiveValue::Set(data.employee_id),
+            refresh_token_hash: ActiveValue::Set(data.refresh_token_hash),
+            user_agent: ActiveValue::Set(data.user_agent),
+            ip_address: ActiveValue::Set(data.ip_address),
+            scope: ActiveValue::Set(data.scope),
+            terminal_id: ActiveValue::Set(data.terminal_id),
+            active_store_id: ActiveValue::Set(data.active_store_id),
+            created_at: ActiveValue::Set(now),
+            last_used_at: ActiveValue::Set(now),
+            expires_at: ActiveValue::Set(data.expires_at),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<sessions::Model>, DbErr> {
+        sessions::Entity::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_by_refresh_token_hash<C: ConnectionTrait>(db: &C, hash: &str) -> Result<Option<sessions::Model>, DbErr> {
+        sessions::Entity::find()
+            .filter(sessions::Column::RefreshTokenHash.eq(hash))
+            .one(db)
+            .await
+    }
+
+    pub async fn find_by_previous_token_hash<C: ConnectionTrait>(db: &C, hash: &str) -> Result<Option<sessions::Model>, DbErr> {
+        sessions::Entity::find()
+            .filter(sessions::Column::PreviousTokenHash.eq(hash))
+            .one(db)
+            .await
+    }
+
+    pub async fn get_active_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<Vec<sessions::Model>, DbErr> {
+        sessions::Entity::find()
+            .filter(sessions::Column::EmployeeId.eq(employee_id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .filter(sessions::Column::ExpiresAt.gt(Utc::now()))
+            .order_by_desc(sessions::Column::LastUsedAt)
+            .all(db)
+            .await
+    }
+
+    /// Swaps in a new refresh token, keeping the old hash to detect replays. The swap only
+    /// happens if the session still holds the token it was read with; `None` means another
+    /// request rotated it first.
+    pub async fn rotate<C: ConnectionTrait>(db: &C, session: sessions::Model, new_hash: String, expires_at: DateTime<Utc>) -> Result<Option<sessions::Model>, DbErr> {
+        let res = sessions::Entity::update_many()
+            .col_expr(sessions::Column::PreviousTokenHash, Expr::value(session.refresh_token_hash.clone()))
+            .col_expr(sessions::Column::RefreshTokenHash, Expr::value(new_hash))
+            .col_expr(sessions::Column::LastUsedAt, Expr::value(Utc::now()))
+            .col_expr(sessions::Column::ExpiresAt, Expr::value(expires_at))
+            .filter(sessions::Column::Id.eq(session.id))
+            .filter(sessions::Column::RefreshTokenHash.eq(session.refresh_token_hash))
+            .exec(db)
+            .await?;
+        if res.rows_affected == 0 {
+            return Ok(None);
+        }
+        sessions::Entity::find_by_id(session.id).one(db).await
+    }
+
+    pub async fn set_active_store<C: ConnectionTrait>(db: &C, session: sessions::Model, store_id: i32) -> Result<sessions::Model, DbErr> {
+        let mut active_model: sessions::ActiveModel = session.into();
+        active_model.active_store_id = ActiveValue::Set(Some(store_id));
+        active_model.last_used_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    pub async fn revoke<C: ConnectionTrait>(db: &C, id: i32) -> Result<u64, DbErr> {
+        let res = sessions::Entity::update_many()
+            .col_expr(sessions::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(sessions::Column::Id.eq(id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+
+    pub async fn revoke_all_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<u64, DbErr> {
+        let res = sessions::Entity::update_many()
+            .col_expr(sessions::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(sessions::Column::EmployeeId.eq(employee_id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+
+    /// Ends every session opened on a terminal, e.g. when the next cashier signs in with
+    /// their PIN or the terminal is disabled.
+    pub async fn revoke_all_for_terminal<C: ConnectionTrait>(db: &C, terminal_id: i32) -> Result<u64, DbErr> {
+        let res = sessions::Entity::update_many()
+            .col_expr(sessions::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(sessions::Column::TerminalId.eq(terminal_id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+
+    /// Forces an employee's devices to refresh their access tokens, picking up new claims.
+    pub async fn invalidate_access_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<u64, DbErr> {
+        let res = sessions::Entity::update_many()
+            .col_expr(sessions::Column::AccessVersion, Expr::col(sessions::Column::AccessVersion).add(1))
+            .filter(sessions::Column::EmployeeId.eq(employee_id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+
+    /// Same as `invalidate_access_for_employee`, for everyone holding a role.
+    pub async fn invalidate_access_for_role<C: ConnectionTrait>(db: &C, role_id: i32) -> Result<u64, DbErr> {
+        let res = sessions::Entity::update_many()
+            .col_expr(sessions::Column::AccessVersion, Expr::col(sessions::Column::AccessVersion).add(1))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .filter(
+                sessions::Column::EmployeeId.in_subquery(
+                    Query::select()
+                        .column(employees::Column::Id)
+                        .from(employees::Entity)
+                        .and_where(employees::Column::RoleId.eq(role_id))
+                        .to_owned(),
+                ),
+            )
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+
+    /// Same as `invalidate_access_for_employee`, for every session.
+    pub async fn invalidate_access_for_all<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+        let res = sessions::Entity::update_many()
+            .col_expr(sessions::Column::AccessVersion, Expr::col(sessions::Column::AccessVersion).add(1))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+}
@@ -1,17 +1,43 @@
-use sea_orm::{DbConn, DbErr, EntityTrait, ActiveModelTrait, IntoActiveModel};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbConn, DbErr, EntityTrait, IntoActiveModel,
+    QueryFilter, QueryOrder, TransactionTrait,
+};
+use chrono::Utc;
 use std::result::Result;
 
-use crate::entities::settings;
+use crate::entities::{settings, settings_versions, store_settings};
 use crate::entities::settings_model::Settings as SettingsModel;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
+use crate::helper::secrets;
 
+fn parse_stored(config: serde_json::Value) -> Result<SettingsModel, DbErr> {
+    SettingsModel::from_stored(config).map_err(|e| {
+        log::error!("Stored settings are invalid: {}", e);
+        DbErr::Custom(format!("Stored settings are invalid: {}", e))
+    })
+}
 
+/// Settings with their secrets decrypted. A secret that cannot be decrypted (for example
+/// because the key changed without a rotation) is logged and treated as unset, though saving
+/// keeps it stored. A config that cannot be read is an error rather than a silent reset to
+/// the defaults.
 pub async fn get_settings(db: &DbConn) -> Result<SettingsModel, DbErr> {
     let setting_entity = settings::Entity::find()
         .one(db)
         .await?;
 
     if let Some(entity) = setting_entity {
-        Ok(SettingsModel::from(entity.config))
+        let mut settings = parse_stored(entity.config)?;
+        for secret in settings.secrets_mut() {
+            match secrets::decrypt(secret) {
+                Ok(plaintext) => *secret = plaintext,
+                Err(e) => {
+                    log::error!("Failed to decrypt a stored setting: {}", e);
+                    secret.clear();
+                }
+            }
+        }
+        Ok(settings)
     } else {
         // If no settings found, return default and maybe log a warning
         println!("Warning: No settings found in the database, returning default settings.");
@@ -19,23 +45,201 @@ pub async fn get_settings(db: &DbConn) -> Result<SettingsModel, DbErr> {
     }
 }
 
-pub async fn update_settings(db: &DbConn, settings_data: SettingsModel) -> Result<SettingsModel, DbErr> {
-    let settings_json = serde_json::to_value(&settings_data).map_err(|e| DbErr::Custom(e.to_string()))?;
+/// The settings that apply in a store: the global settings with the store's overrides, if any.
+pub async fn get_settings_for_store(db: &DbConn, store_id: i32) -> Result<SettingsModel, DbErr> {
+    let settings = get_settings(db).await?;
+    match get_store_overrides(db, store_id).await? {
+        Some(overrides) => settings.with_store_overrides(&overrides.overrides).map_err(|errors| {
+            let fields: Vec<String> = errors.into_iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+            log::error!("Settings overrides for store {} are invalid: {}", store_id, fields.join("; "));
+            DbErr::Custom(format!("Settings overrides for store {} are invalid", store_id))
+        }),
+        None => Ok(settings),
+    }
+}
+
+pub async fn get_store_overrides<C: ConnectionTrait>(db: &C, store_id: i32) -> Result<Option<store_settings::Model>, DbErr> {
+    store_settings::Entity::find()
+        .filter(store_settings::Column::StoreId.eq(store_id))
+        .one(db)
+        .await
+}
+
+pub async fn set_store_overrides<C: ConnectionTrait>(
+    db: &C,
+    store_id: i32,
+    overrides: serde_json::Value,
+) -> Result<store_settings::Model, DbErr> {
+    match get_store_overrides(db, store_id).await? {
+        Some(existing) => {
+            let mut active_model = existing.into_active_model();
+            active_model.overrides = ActiveValue::Set(overrides);
+            active_model.updated_at = ActiveValue::Set(Utc::now());
+            active_model.update(db).await
+        }
+        None => {
+            store_settings::ActiveModel {
+                store_id: ActiveValue::Set(store_id),
+                overrides: ActiveValue::Set(overrides),
+                updated_at: ActiveValue::Set(Utc::now()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+        }
+    }
+}
+
+pub async fn delete_store_overrides<C: ConnectionTrait>(db: &C, store_id: i32) -> Result<u64, DbErr> {
+    let result = store_settings::Entity::delete_many()
+        .filter(store_settings::Column::StoreId.eq(store_id))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+/// Encrypts every secret that is set with the current key.
+fn encrypt_secrets(settings: &mut SettingsModel) -> Result<(), DbErr> {
+    let key = secrets::current_key().map_err(DbErr::Custom)?;
+    for secret in settings.secrets_mut() {
+        if secret.is_empty() {
+            continue;
+        }
+        let key = key.as_ref().ok_or_else(|| {
+            DbErr::Custom(format!("{} must be set to store secrets in the settings", secrets::KEY_ENV))
+        })?;
+        *secret = secrets::encrypt(key, secret).map_err(DbErr::Custom)?;
+    }
+    Ok(())
+}
+
+/// `get_settings` reads a secret it cannot decrypt as unset, so a save would otherwise wipe
+/// it. Unless a new value was given, the stored ciphertext is kept for when the right key is
+/// back.
+async fn keep_undecryptable_secrets<C: ConnectionTrait>(db: &C, settings: &mut SettingsModel) -> Result<(), DbErr> {
+    let Some(entity) = settings::Entity::find().one(db).await? else {
+        return Ok(());
+    };
+    let mut current = parse_stored(entity.config)?;
+    for (secret, current) in settings.secrets_mut().into_iter().zip(current.secrets_mut()) {
+        if secret.is_empty() && secrets::decrypt(current).is_err() {
+            *secret = std::mem::take(current);
+        }
+    }
+    Ok(())
+}
 
+async fn save_config<C: ConnectionTrait>(db: &C, settings_json: serde_json::Value) -> Result<(), DbErr> {
     let current_settings = settings::Entity::find().one(db).await?;
 
     if let Some(current) = current_settings {
         let mut active_model = current.into_active_model();
         active_model.config = sea_orm::Set(settings_json);
-        let updated = active_model.update(db).await?;
-        Ok(SettingsModel::from(updated.config))
+        active_model.update(db).await?;
     } else {
         // If no settings exist, create a new record
         let active_model = settings::ActiveModel {
             config: sea_orm::Set(settings_json),
             ..Default::default()
         };
-        let new_settings = active_model.insert(db).await?;
-        Ok(SettingsModel::from(new_settings.config))
+        active_model.insert(db).await?;
+    }
+    Ok(())
+}
+
+/// Saves the settings, encrypting secrets on the way in, and records them as a new version
+/// with secrets masked. Returns them as given (decrypted) with the new version number.
+pub async fn update_settings(
+    db: &DbConn,
+    settings_data: SettingsModel,
+    changed_by: Option<i32>,
+    summary: String,
+) -> Result<(SettingsModel, i32), DbErr> {
+    let mut stored = settings_data.clone();
+    encrypt_secrets(&mut stored)?;
+    let masked_json = serde_json::to_value(settings_data.clone().masked()).map_err(|e| DbErr::Custom(e.to_string()))?;
+
+    let txn = db.begin().await?;
+    keep_undecryptable_secrets(&txn, &mut stored).await?;
+    let settings_json = serde_json::to_value(&stored).map_err(|e| DbErr::Custom(e.to_string()))?;
+    save_config(&txn, settings_json).await?;
+    let version = current_version(&txn).await? + 1;
+    settings_versions::ActiveModel {
+        version: ActiveValue::Set(version),
+        config: ActiveValue::Set(masked_json),
+        changed_by: ActiveValue::Set(changed_by),
+        summary: ActiveValue::Set(summary),
+        created_at: ActiveValue::Set(Utc::now()),
+        ..Default::default()
     }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+
+    Ok((settings_data, version))
+}
+
+/// The latest version number, or 0 before the first save.
+pub async fn current_version<C: ConnectionTrait>(db: &C) -> Result<i32, DbErr> {
+    let latest = settings_versions::Entity::find()
+        .order_by_desc(settings_versions::Column::Version)
+        .one(db)
+        .await?;
+    Ok(latest.map(|v| v.version).unwrap_or(0))
+}
+
+pub fn version_list_fields() -> ListFields {
+    ListFields::new(settings_versions::Column::Id)
+        .sort("version", settings_versions::Column::Version)
+        .sort("created_at", settings_versions::Column::CreatedAt)
+        .filter("changed_by", settings_versions::Column::ChangedBy, FilterKind::Int)
+        .filter("from", settings_versions::Column::CreatedAt, FilterKind::From)
+        .filter("to", settings_versions::Column::CreatedAt, FilterKind::To)
+        .default_sort("-version")
+}
+
+pub async fn get_versions<C: ConnectionTrait>(db: &C, plan: &ListPlan) -> Result<Page<settings_versions::Model>, DbErr> {
+    plan.fetch(db, plan.apply(settings_versions::Entity::find()), |version| version.id).await
+}
+
+pub async fn find_version<C: ConnectionTrait>(db: &C, version: i32) -> Result<Option<settings_versions::Model>, DbErr> {
+    settings_versions::Entity::find()
+        .filter(settings_versions::Column::Version.eq(version))
+        .one(db)
+        .await
+}
+
+/// The version saved before `version`, if any.
+pub async fn find_previous_version<C: ConnectionTrait>(
+    db: &C,
+    version: i32,
+) -> Result<Option<settings_versions::Model>, DbErr> {
+    settings_versions::Entity::find()
+        .filter(settings_versions::Column::Version.lt(version))
+        .order_by_desc(settings_versions::Column::Version)
+        .one(db)
+        .await
+}
+
+/// Re-encrypts every stored secret with the current key. Secrets encrypted with the previous
+/// key, or stored in plaintext before encryption was introduced, are read first. Returns how
+/// many secrets were re-encrypted.
+pub async fn rotate_secrets(db: &DbConn) -> Result<usize, DbErr> {
+    if secrets::current_key().map_err(DbErr::Custom)?.is_none() {
+        return Err(DbErr::Custom(format!("{} is not set", secrets::KEY_ENV)));
+    }
+    let Some(entity) = settings::Entity::find().one(db).await? else {
+        return Ok(0);
+    };
+
+    let mut settings = parse_stored(entity.config)?;
+    for secret in settings.secrets_mut() {
+        *secret = secrets::decrypt(secret).map_err(DbErr::Custom)?;
+    }
+    let rotated = settings.secrets_mut().iter().filter(|s| !s.is_empty()).count();
+    encrypt_secrets(&mut settings)?;
+
+    let settings_json = serde_json::to_value(&settings).map_err(|e| DbErr::Custom(e.to_string()))?;
+    save_config(db, settings_json).await?;
+    Ok(rotated)
 }
@@ -0,0 +1,301 @@
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
+use sea_orm::prelude::Decimal;
+use sea_orm::sea_query::Expr;
+use chrono::{DateTime, Utc};
+
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{cash_movements, order_items, orders, payments, refunds, shifts};
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
+use crate::entities::shifts::{SalesSummary, TenderTotal};
+
+pub struct ShiftRepository;
+
+impl ShiftRepository {
+    pub async fn open<C: ConnectionTrait>(
+        db: &C,
+        employee_id: i32,
+        store_id: i32,
+        terminal_id: Option<i32>,
+        terminal_name: String,
+        opening_float: Decimal,
+    ) -> Result<shifts::Model, DbErr> {
+        let now = Utc::now();
+        let shift = shifts::ActiveModel {
+            employee_id: ActiveValue::Set(employee_id),
+            store_id: ActiveValue::Set(store_id),
+            terminal_id: ActiveValue::Set(terminal_id),
+            terminal_name: ActiveValue::Set(terminal_name),
+            opening_float: ActiveValue::Set(opening_float),
+            status: ActiveValue::Set("OPEN".to_string()),
+            opened_at: ActiveValue::Set(now),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            ..Default::default()
+        };
+        shift.insert(db).await
+    }
+
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<shifts::Model>, DbErr> {
+        shifts::Entity::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_open_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<Option<shifts::Model>, DbErr> {
+        shifts::Entity::find()
+            .filter(shifts::Column::EmployeeId.eq(employee_id))
+            .filter(shifts::Column::Status.eq("OPEN"))
+            .one(db)
+            .await
+    }
+
+    pub async fn find_open_for_terminal<C: ConnectionTrait>(db: &C, terminal_id: i32) -> Result<Option<shifts::Model>, DbErr> {
+        shifts::Entity::find()
+            .filter(shifts::Column::TerminalId.eq(terminal_id))
+            .filter(shifts::Column::Status.eq("OPEN"))
+            .one(db)
+            .await
+    }
+
+    /// `store_id` is checked against the caller's scope by the handler.
+    pub fn list_fields() -> ListFields {
+        ListFields::new(shifts::Column::Id)
+            .sort("opened_at", shifts::Column::OpenedAt)
+            .sort("closed_at", shifts::Column::ClosedAt)
+            .filter("status", shifts::Column::Status, FilterKind::Text)
+            .filter("employee_id", shifts::Column::EmployeeId, FilterKind::Int)
+            .filter("terminal_id", shifts::Column::TerminalId, FilterKind::Int)
+            .filter("from", shifts::Column::OpenedAt, FilterKind::From)
+            .filter("to", shifts::Column::OpenedAt, FilterKind::To)
+            .param("store_id")
+            .default_sort("-opened_at")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<shifts::Model>, DbErr> {
+        let query = plan.apply(
+            shifts::Entity::find()
+                .filter(scope.record_condition(shifts::Column::StoreId, shifts::Column::EmployeeId)),
+        );
+        plan.fetch(db, query, |shift| shift.id).await
+    }
+
+    /// Every open shift in scope, newest first.
+    pub async fn get_open<C: ConnectionTrait>(db: &C, scope: &StoreScope) -> Result<Vec<shifts::Model>, DbErr> {
+        shifts::Entity::find()
+            .filter(scope.record_condition(shifts::Column::StoreId, shifts::Column::EmployeeId))
+            .filter(shifts::Column::Status.eq("OPEN"))
+            .order_by_desc(shifts::Column::OpenedAt)
+            .all(db)
+            .await
+    }
+
+    /// Shifts at a store that were open at any point between `from` and `to`.
+    pub async fn get_for_store_between<C: ConnectionTrait>(
+        db: &C,
+        store_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<shifts::Model>, DbErr> {
+        shifts::Entity::find()
+            .filter(shifts::Column::StoreId.eq(store_id))
+            .filter(shifts::Column::OpenedAt.lte(to))
+            .filter(
+                shifts::Column::ClosedAt.is_null()
+                    .or(shifts::Column::ClosedAt.gte(from)),
+            )
+            .order_by_asc(shifts::Column::OpenedAt)
+            .all(db)
+            .await
+    }
+
+    pub async fn close<C: ConnectionTrait>(
+        db: &C,
+        shift: shifts::Model,
+        expected_cash: Decimal,
+        counted_cash: Decimal,
+        notes: Option<String>,
+    ) -> Result<shifts::Model, DbErr> {
+        let now = Utc::now();
+        let mut active_model: shifts::ActiveModel = shift.into();
+        active_model.expected_cash = ActiveValue::Set(Some(expected_cash));
+        active_model.counted_cash = ActiveValue::Set(Some(counted_cash));
+        active_model.over_short = ActiveValue::Set(Some(counted_cash - expected_cash));
+        active_model.status = ActiveValue::Set("CLOSED".to_string());
+        active_model.notes = ActiveValue::Set(notes);
+        active_model.closed_at = ActiveValue::Set(Some(now));
+        active_model.updated_at = ActiveValue::Set(now);
+        active_model.update(db).await
+    }
+
+    pub async fn add_cash_movement<C: ConnectionTrait>(
+        db: &C,
+        shift_id: i32,
+        employee_id: i32,
+        payload: cash_movements::CreateCashMovement,
+    ) -> Result<cash_movements::Model, DbErr> {
+        let movement = cash_movements::ActiveModel {
+            shift_id: ActiveValue::Set(shift_id),
+            employee_id: ActiveValue::Set(employee_id),
+            movement_type: ActiveValue::Set(payload.movement_type),
+            amount: ActiveValue::Set(payload.amount),
+            reason: ActiveValue::Set(payload.reason),
+            created_at: ActiveValue::Set(Utc::now()),
+            ..Default::default()
+        };
+        movement.insert(db).await
+    }
+
+    pub async fn get_movements_for_shift<C: ConnectionTrait>(db: &C, shift_id: i32) -> Result<Vec<cash_movements::Model>, DbErr> {
+        cash_movements::Entity::find()
+            .filter(cash_movements::Column::ShiftId.eq(shift_id))
+            .order_by_asc(cash_movements::Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Opening float plus cash taken by the shift's employee, plus cash-ins,
+    /// minus paid-outs and cash refunds, up to the close (or now, if still open).
+    pub async fn compute_expected_cash<C: ConnectionTrait>(
+        db: &C,
+        shift: &shifts::Model,
+        movements: &[cash_movements::Model],
+    ) -> Result<Decimal, DbErr> {
+        let until = shift.closed_at.unwrap_or_else(Utc::now);
+
+        let cash_sales: Option<Decimal> = payments::Entity::find()
+            .join(JoinType::InnerJoin, payments::Relation::Orders.def())
+            .filter(payments::Column::PaymentMethod.eq("Cash"))
+            .filter(payments::Column::Amount.gt(Decimal::ZERO))
+            .filter(payments::Column::PaymentDate.between(shift.opened_at, until))
+            .filter(orders::Column::EmployeeId.eq(shift.employee_id))
+            .filter(orders::Column::StoreId.eq(shift.store_id))
+            .select_only()
+            .column_as(payments::Column::Amount.sum(), "total")
+            .into_tuple()
+            .one(db)
+            .await?
+            .flatten();
+
+        let cash_refunds: Option<Decimal> = refunds::Entity::find()
+            .filter(refunds::Column::RefundMethod.eq("CASH"))
+            .filter(refunds::Column::CreatedAt.between(shift.opened_at, until))
+            .filter(refunds::Column::EmployeeId.eq(shift.employee_id))
+            .filter(refunds::Column::StoreId.eq(shift.store_id))
+            .select_only()
+            .column_as(refunds::Column::TotalAmount.sum(), "total")
+            .into_tuple()
+            .one(db)
+            .await?
+            .flatten();
+
+        let movements_total: Decimal = movements
+            .iter()
+            .map(|m| if m.movement_type == "PAY_OUT" { -m.amount } else { m.amount })
+            .sum();
+
+        Ok(shift.opening_float
+            + cash_sales.unwrap_or_default()
+            + movements_total
+            - cash_refunds.unwrap_or_default())
+    }
+
+    /// Sales, refunds, discounts and tax for a store in a period, optionally
+    /// narrowed to one employee. Order prices are tax-inclusive, so tax is
+    /// backed out of net takings using the configured default tax rate.
+    pub async fn summarize<C: ConnectionTrait>(
+        db: &C,
+        store_id: i32,
+        employee_id: Option<i32>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        tax_rate: f64,
+    ) -> Result<SalesSummary, DbErr> {
+        let mut tender_query = payments::Entity::find()
+            .join(JoinType::InnerJoin, payments::Relation::Orders.def())
+            .filter(orders::Column::StoreId.eq(store_id))
+            .filter(payments::Column::Amount.gt(Decimal::ZERO))
+            .filter(payments::Column::PaymentDate.between(from, to));
+        if let Some(employee_id) = employee_id {
+            tender_query = tender_query.filter(orders::Column::EmployeeId.eq(employee_id));
+        }
+        let tenders: Vec<(String, i64, Option<Decimal>)> = tender_query
+            .select_only()
+            .column(payments::Column::PaymentMethod)
+            .column_as(payments::Column::Id.count(), "transactions")
+            .column_as(payments::Column::Amount.sum(), "total")
+            .group_by(payments::Column::PaymentMethod)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let sales_by_tender: Vec<TenderTotal> = tenders
+            .into_iter()
+            .map(|(payment_method, transactions, total)| TenderTotal {
+                payment_method,
+                transactions,
+                total: total.unwrap_or_default(),
+            })
+            .collect();
+
+        let mut refund_query = refunds::Entity::find()
+            .filter(refunds::Column::StoreId.eq(store_id))
+            .filter(refunds::Column::CreatedAt.between(from, to));
+        if let Some(employee_id) = employee_id {
+            refund_query = refund_query.filter(refunds::Column::EmployeeId.eq(employee_id));
+        }
+        let (refunds_count, refunds_total): (i64, Option<Decimal>) = refund_query
+            .select_only()
+            .column_as(refunds::Column::Id.count(), "refunds_count")
+            .column_as(refunds::Column::TotalAmount.sum(), "refunds_total")
+            .into_tuple()
+            .one(db)
+            .await?
+            .unwrap_or((0, None));
+
+        let mut discount_query = order_items::Entity::find()
+            .join(JoinType::InnerJoin, order_items::Relation::Orders.def())
+            .filter(orders::Column::StoreId.eq(store_id))
+            .filter(orders::Column::OrderDate.between(from, to));
+        if let Some(employee_id) = employee_id {
+            discount_query = discount_query.filter(orders::Column::EmployeeId.eq(employee_id));
+        }
+        let discounts_total: Option<Decimal> = discount_query
+            .select_only()
+            .column_as(
+                Expr::expr(
+                    Expr::col((order_items::Entity, order_items::Column::DiscountAmount))
+                        .mul(Expr::col((order_items::Entity, order_items::Column::Quantity))),
+                )
+                .sum(),
+                "discounts_total",
+            )
+            .into_tuple()
+            .one(db)
+            .await?
+            .flatten();
+
+        let transactions = sales_by_tender.iter().map(|t| t.transactions).sum();
+        let gross_sales: Decimal = sales_by_tender.iter().map(|t| t.total).sum();
+        let refunds_total = refunds_total.unwrap_or_default();
+        let taxable = gross_sales - refunds_total;
+        let rate = Decimal::from_f64_retain(tax_rate).unwrap_or_default();
+        let tax_total = if rate > Decimal::ZERO {
+            (taxable * rate / (Decimal::ONE_HUNDRED + rate)).round_dp(2)
+        } else {
+            Decimal::ZERO
+        };
+
+        Ok(SalesSummary {
+            transactions,
+            gross_sales,
+            sales_by_tender,
+            refunds_count,
+            refunds_total,
+            discounts_total: discounts_total.unwrap_or_default().round_dp(2),
+            tax_rate,
+            tax_total,
+            net_sales: taxable - tax_total,
+        })
+    }
+}
@@ -1,12 +1,22 @@
-use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue, ConnectionTrait, QueryFilter};
+use crate::auth::store_scope::StoreScope;
 use crate::entities::stores;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 use chrono::{Utc, DateTime};
 
 pub struct StoreRepository;
 
 impl StoreRepository {
-    pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<stores::Model>, DbErr> {
-        stores::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(stores::Column::Id)
+            .sort("name", stores::Column::Name)
+            .filter("name", stores::Column::Name, FilterKind::Contains)
+            .default_sort("id")
+    }
+
+    pub async fn get_all(db: &DatabaseConnection, scope: &StoreScope, plan: &ListPlan) -> Result<Page<stores::Model>, DbErr> {
+        let query = plan.apply(stores::Entity::find().filter(scope.store_condition(stores::Column::Id)));
+        plan.fetch(db, query, |store| store.id).await
     }
 
     pub async fn create(db: &DatabaseConnection, new_store: stores::CreateStore) -> Result<stores::Model, DbErr> {
@@ -22,7 +32,7 @@ impl StoreRepository {
         store.insert(db).await
     }
 
-    pub async fn find_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<stores::Model>, DbErr> {
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<stores::Model>, DbErr> {
         stores::Entity::find_by_id(id).one(db).await
     }
 
@@ -1,12 +1,21 @@
 use sea_orm::{DatabaseConnection, DbErr, EntityTrait, ActiveModelTrait, ActiveValue};
 use crate::entities::suppliers;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
 use chrono::{Utc, DateTime};
 
 pub struct SupplierRepository;
 
 impl SupplierRepository {
-    pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<suppliers::Model>, DbErr> {
-        suppliers::Entity::find().all(db).await
+    pub fn list_fields() -> ListFields {
+        ListFields::new(suppliers::Column::Id)
+            .sort("name", suppliers::Column::Name)
+            .filter("name", suppliers::Column::Name, FilterKind::Contains)
+            .filter("email", suppliers::Column::Email, FilterKind::Contains)
+            .default_sort("id")
+    }
+
+    pub async fn get_all(db: &DatabaseConnection, plan: &ListPlan) -> Result<Page<suppliers::Model>, DbErr> {
+        plan.fetch(db, plan.apply(suppliers::Entity::find()), |supplier| supplier.id).await
     }
 
     pub async fn create(db: &DatabaseConnection, new_supplier: suppliers::CreateSupplier) -> Result<suppliers::Model, DbErr> {
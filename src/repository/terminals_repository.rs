@@ -0,0 +1,114 @@
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
+use chrono::{DateTime, Utc};
+
+use crate::auth::store_scope::StoreScope;
+use crate::entities::terminals;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
+
+pub struct TerminalRepository;
+
+impl TerminalRepository {
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        store_id: i32,
+        name: String,
+        pairing_code: String,
+        pairing_code_expires_at: DateTime<Utc>,
+    ) -> Result<terminals::Model, DbErr> {
+        let now = Utc::now();
+        let terminal = terminals::ActiveModel {
+            store_id: ActiveValue::Set(store_id),
+            name: ActiveValue::Set(name),
+            status: ActiveValue::Set("PENDING".to_string()),
+            pairing_code: ActiveValue::Set(Some(pairing_code)),
+            pairing_code_expires_at: ActiveValue::Set(Some(pairing_code_expires_at)),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            ..Default::default()
+        };
+        terminal.insert(db).await
+    }
+
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<terminals::Model>, DbErr> {
+        terminals::Entity::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_by_pairing_code<C: ConnectionTrait>(db: &C, pairing_code: &str) -> Result<Option<terminals::Model>, DbErr> {
+        terminals::Entity::find()
+            .filter(terminals::Column::PairingCode.eq(pairing_code))
+            .one(db)
+            .await
+    }
+
+    /// `store_id` is checked against the caller's scope by the handler.
+    pub fn list_fields() -> ListFields {
+        ListFields::new(terminals::Column::Id)
+            .sort("name", terminals::Column::Name)
+            .sort("last_seen_at", terminals::Column::LastSeenAt)
+            .filter("status", terminals::Column::Status, FilterKind::Text)
+            .param("store_id")
+            .default_sort("name")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<terminals::Model>, DbErr> {
+        let query = plan.apply(terminals::Entity::find().filter(scope.store_condition(terminals::Column::StoreId)));
+        plan.fetch(db, query, |terminal| terminal.id).await
+    }
+
+    pub async fn rename<C: ConnectionTrait>(db: &C, terminal: terminals::Model, name: String) -> Result<terminals::Model, DbErr> {
+        let mut active_model: terminals::ActiveModel = terminal.into();
+        active_model.name = ActiveValue::Set(name);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    /// Puts the terminal back into pairing mode. Any device that was paired
+    /// before loses its credential.
+    pub async fn reset_pairing<C: ConnectionTrait>(
+        db: &C,
+        terminal: terminals::Model,
+        pairing_code: String,
+        pairing_code_expires_at: DateTime<Utc>,
+    ) -> Result<terminals::Model, DbErr> {
+        let mut active_model: terminals::ActiveModel = terminal.into();
+        active_model.status = ActiveValue::Set("PENDING".to_string());
+        active_model.credential_hash = ActiveValue::Set(None);
+        active_model.pairing_code = ActiveValue::Set(Some(pairing_code));
+        active_model.pairing_code_expires_at = ActiveValue::Set(Some(pairing_code_expires_at));
+        active_model.paired_at = ActiveValue::Set(None);
+        active_model.disabled_at = ActiveValue::Set(None);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    pub async fn complete_pairing<C: ConnectionTrait>(db: &C, terminal: terminals::Model, credential_hash: String) -> Result<terminals::Model, DbErr> {
+        let now = Utc::now();
+        let mut active_model: terminals::ActiveModel = terminal.into();
+        active_model.status = ActiveValue::Set("ACTIVE".to_string());
+        active_model.credential_hash = ActiveValue::Set(Some(credential_hash));
+        active_model.pairing_code = ActiveValue::Set(None);
+        active_model.pairing_code_expires_at = ActiveValue::Set(None);
+        active_model.paired_at = ActiveValue::Set(Some(now));
+        active_model.last_seen_at = ActiveValue::Set(Some(now));
+        active_model.updated_at = ActiveValue::Set(now);
+        active_model.update(db).await
+    }
+
+    pub async fn disable<C: ConnectionTrait>(db: &C, terminal: terminals::Model) -> Result<terminals::Model, DbErr> {
+        let now = Utc::now();
+        let mut active_model: terminals::ActiveModel = terminal.into();
+        active_model.status = ActiveValue::Set("DISABLED".to_string());
+        active_model.credential_hash = ActiveValue::Set(None);
+        active_model.pairing_code = ActiveValue::Set(None);
+        active_model.pairing_code_expires_at = ActiveValue::Set(None);
+        active_model.disabled_at = ActiveValue::Set(Some(now));
+        active_model.updated_at = ActiveValue::Set(now);
+        active_model.update(db).await
+    }
+
+    pub async fn touch_last_seen<C: ConnectionTrait>(db: &C, terminal: terminals::Model) -> Result<terminals::Model, DbErr> {
+        let mut active_model: terminals::ActiveModel = terminal.into();
+        active_model.last_seen_at = ActiveValue::Set(Some(Utc::now()));
+        active_model.update(db).await
+    }
+}
@@ -0,0 +1,233 @@
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter,
+    QueryOrder,
+};
+use chrono::{DateTime, Utc};
+
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{employees, time_entries, time_entry_breaks, time_entry_edits};
+use crate::entities::time_entries::BreakInput;
+use crate::helper::pagination::{FilterKind, ListFields, ListPlan, Page};
+
+pub struct TimeEntryRepository;
+
+impl TimeEntryRepository {
+    pub async fn clock_in<C: ConnectionTrait>(
+        db: &C,
+        employee_id: i32,
+        store_id: i32,
+        terminal_id: Option<i32>,
+    ) -> Result<time_entries::Model, DbErr> {
+        let now = Utc::now();
+        let entry = time_entries::ActiveModel {
+            employee_id: ActiveValue::Set(employee_id),
+            store_id: ActiveValue::Set(store_id),
+            terminal_id: ActiveValue::Set(terminal_id),
+            clock_in_at: ActiveValue::Set(now),
+            clock_out_at: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            ..Default::default()
+        };
+        entry.insert(db).await
+    }
+
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> Result<Option<time_entries::Model>, DbErr> {
+        time_entries::Entity::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_open_for_employee<C: ConnectionTrait>(db: &C, employee_id: i32) -> Result<Option<time_entries::Model>, DbErr> {
+        time_entries::Entity::find()
+            .filter(time_entries::Column::EmployeeId.eq(employee_id))
+            .filter(time_entries::Column::ClockOutAt.is_null())
+            .one(db)
+            .await
+    }
+
+    /// Clocks the employee out, ending any break that is still running.
+    pub async fn clock_out<C: ConnectionTrait>(db: &C, entry: time_entries::Model) -> Result<time_entries::Model, DbErr> {
+        let now = Utc::now();
+        if let Some(open_break) = Self::find_open_break(db, entry.id).await? {
+            Self::end_break(db, open_break).await?;
+        }
+        let mut active_model: time_entries::ActiveModel = entry.into();
+        active_model.clock_out_at = ActiveValue::Set(Some(now));
+        active_model.updated_at = ActiveValue::Set(now);
+        active_model.update(db).await
+    }
+
+    /// `from` and `to` match the clock-in time; `store_id` is checked against the caller's scope
+    /// by the handler.
+    pub fn list_fields() -> ListFields {
+        ListFields::new(time_entries::Column::Id)
+            .sort("clock_in_at", time_entries::Column::ClockInAt)
+            .sort("clock_out_at", time_entries::Column::ClockOutAt)
+            .filter("employee_id", time_entries::Column::EmployeeId, FilterKind::Int)
+            .filter("terminal_id", time_entries::Column::TerminalId, FilterKind::Int)
+            .filter("from", time_entries::Column::ClockInAt, FilterKind::From)
+            .filter("to", time_entries::Column::ClockInAt, FilterKind::To)
+            .param("store_id")
+            .default_sort("-clock_in_at")
+    }
+
+    pub async fn get_all<C: ConnectionTrait>(db: &C, scope: &StoreScope, plan: &ListPlan) -> Result<Page<time_entries::Model>, DbErr> {
+        let query = plan.apply(
+            time_entries::Entity::find()
+                .filter(scope.record_condition(time_entries::Column::StoreId, time_entries::Column::EmployeeId)),
+        );
+        plan.fetch(db, query, |entry| entry.id).await
+    }
+
+    /// Entries in scope that were clocked in at any point between `from` and `to`, with their employee.
+    pub async fn get_between<C: ConnectionTrait>(
+        db: &C,
+        scope: &StoreScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(time_entries::Model, Option<employees::Model>)>, DbErr> {
+        time_entries::Entity::find()
+            .find_also_related(employees::Entity)
+            .filter(scope.record_condition(time_entries::Column::StoreId, time_entries::Column::EmployeeId))
+            .filter(time_entries::Column::ClockInAt.lt(to))
+            .filter(
+                time_entries::Column::ClockOutAt.is_null()
+                    .or(time_entries::Column::ClockOutAt.gt(from)),
+            )
+            .order_by_asc(time_entries::Column::EmployeeId)
+            .order_by_asc(time_entries::Column::ClockInAt)
+            .all(db)
+            .await
+    }
+
+    /// Entries of the given employees at any store that were clocked in at any point between
+    /// `from` and `to`.
+    pub async fn get_for_employees_between<C: ConnectionTrait>(
+        db: &C,
+        employee_ids: Vec<i32>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<time_entries::Model>, DbErr> {
+        if employee_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        time_entries::Entity::find()
+            .filter(time_entries::Column::EmployeeId.is_in(employee_ids))
+            .filter(time_entries::Column::ClockInAt.lt(to))
+            .filter(
+                time_entries::Column::ClockOutAt.is_null()
+                    .or(time_entries::Column::ClockOutAt.gt(from)),
+            )
+            .order_by_asc(time_entries::Column::EmployeeId)
+            .order_by_asc(time_entries::Column::ClockInAt)
+            .all(db)
+            .await
+    }
+
+    pub async fn get_breaks<C: ConnectionTrait>(db: &C, time_entry_id: i32) -> Result<Vec<time_entry_breaks::Model>, DbErr> {
+        Self::get_breaks_for_entries(db, vec![time_entry_id]).await
+    }
+
+    pub async fn get_breaks_for_entries<C: ConnectionTrait>(
+        db: &C,
+        time_entry_ids: Vec<i32>,
+    ) -> Result<Vec<time_entry_breaks::Model>, DbErr> {
+        if time_entry_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        time_entry_breaks::Entity::find()
+            .filter(time_entry_breaks::Column::TimeEntryId.is_in(time_entry_ids))
+            .order_by_asc(time_entry_breaks::Column::StartedAt)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_open_break<C: ConnectionTrait>(db: &C, time_entry_id: i32) -> Result<Option<time_entry_breaks::Model>, DbErr> {
+        time_entry_breaks::Entity::find()
+            .filter(time_entry_breaks::Column::TimeEntryId.eq(time_entry_id))
+            .filter(time_entry_breaks::Column::EndedAt.is_null())
+            .one(db)
+            .await
+    }
+
+    pub async fn start_break<C: ConnectionTrait>(db: &C, time_entry_id: i32, paid: bool) -> Result<time_entry_breaks::Model, DbErr> {
+        let entry_break = time_entry_breaks::ActiveModel {
+            time_entry_id: ActiveValue::Set(time_entry_id),
+            started_at: ActiveValue::Set(Utc::now()),
+            ended_at: ActiveValue::Set(None),
+            paid: ActiveValue::Set(paid),
+            ..Default::default()
+        };
+        entry_break.insert(db).await
+    }
+
+    pub async fn end_break<C: ConnectionTrait>(db: &C, entry_break: time_entry_breaks::Model) -> Result<time_entry_breaks::Model, DbErr> {
+        let mut active_model: time_entry_breaks::ActiveModel = entry_break.into();
+        active_model.ended_at = ActiveValue::Set(Some(Utc::now()));
+        active_model.update(db).await
+    }
+
+    pub async fn update_times<C: ConnectionTrait>(
+        db: &C,
+        entry: time_entries::Model,
+        clock_in_at: DateTime<Utc>,
+        clock_out_at: Option<DateTime<Utc>>,
+    ) -> Result<time_entries::Model, DbErr> {
+        let mut active_model: time_entries::ActiveModel = entry.into();
+        active_model.clock_in_at = ActiveValue::Set(clock_in_at);
+        active_model.clock_out_at = ActiveValue::Set(clock_out_at);
+        active_model.updated_at = ActiveValue::Set(Utc::now());
+        active_model.update(db).await
+    }
+
+    pub async fn replace_breaks<C: ConnectionTrait>(
+        db: &C,
+        time_entry_id: i32,
+        breaks: Vec<BreakInput>,
+    ) -> Result<Vec<time_entry_breaks::Model>, DbErr> {
+        time_entry_breaks::Entity::delete_many()
+            .filter(time_entry_breaks::Column::TimeEntryId.eq(time_entry_id))
+            .exec(db)
+            .await?;
+
+        let mut inserted = Vec::with_capacity(breaks.len());
+        for entry_break in breaks {
+            let model = time_entry_breaks::ActiveModel {
+                time_entry_id: ActiveValue::Set(time_entry_id),
+                started_at: ActiveValue::Set(entry_break.started_at),
+                ended_at: ActiveValue::Set(Some(entry_break.ended_at)),
+                paid: ActiveValue::Set(entry_break.paid),
+                ..Default::default()
+            };
+            inserted.push(model.insert(db).await?);
+        }
+        Ok(inserted)
+    }
+
+    pub async fn record_edit<C: ConnectionTrait>(
+        db: &C,
+        time_entry_id: i32,
+        edited_by: i32,
+        reason: String,
+        previous_values: String,
+        new_values: String,
+    ) -> Result<time_entry_edits::Model, DbErr> {
+        let edit = time_entry_edits::ActiveModel {
+            time_entry_id: ActiveValue::Set(time_entry_id),
+            edited_by: ActiveValue::Set(edited_by),
+            reason: ActiveValue::Set(reason),
+            previous_values: ActiveValue::Set(previous_values),
+            new_values: ActiveValue::Set(new_values),
+            created_at: ActiveValue::Set(Utc::now()),
+            ..Default::default()
+        };
+        edit.insert(db).await
+    }
+
+    pub async fn get_edits<C: ConnectionTrait>(db: &C, time_entry_id: i32) -> Result<Vec<time_entry_edits::Model>, DbErr> {
+        time_entry_edits::Entity::find()
+            .filter(time_entry_edits::Column::TimeEntryId.eq(time_entry_id))
+            .order_by_desc(time_entry_edits::Column::CreatedAt)
+            .all(db)
+            .await
+    }
+}
@@ -1,5 +1,6 @@
 use actix_web::web;
 use crate::auth::auth_handler::{login, set_password, forgot_password};
+use crate::auth::{pin_handler, session_handler, two_factor_handler};
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -7,5 +8,32 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .route("/login", web::post().to(login))
             .route("/set-password", web::post().to(set_password))
             .route("/forgot-password", web::post().to(forgot_password))
+            .route("/refresh", web::post().to(session_handler::refresh))
+            .route("/logout", web::post().to(session_handler::logout))
+            .route("/logout-all", web::post().to(session_handler::logout_all))
+            .route("/sessions", web::get().to(session_handler::get_my_sessions))
+            .route("/sessions/{id}", web::delete().to(session_handler::revoke_my_session))
+            .route("/switch-store", web::post().to(session_handler::switch_store))
+            .service(
+                web::scope("/pin")
+                    // Called from a registered terminal (X-Terminal-Token)
+                    .route("/users", web::get().to(pin_handler::get_pin_users))
+                    .route("/login", web::post().to(pin_handler::pin_login))
+                    // Self-service for logged-in employees
+                    .route("", web::put().to(pin_handler::set_my_pin))
+                    .route("", web::delete().to(pin_handler::remove_my_pin)),
+            )
+            .service(
+                web::scope("/2fa")
+                    // Second login step, authenticated by the challenge token from /login
+                    .route("/verify", web::post().to(two_factor_handler::verify_login))
+                    .route("/setup", web::post().to(two_factor_handler::setup_from_challenge))
+                    .route("/setup/confirm", web::post().to(two_factor_handler::confirm_setup_from_challenge))
+                    // Self-service for logged-in employees
+                    .route("/enroll", web::post().to(two_factor_handler::enroll))
+                    .route("/enroll/confirm", web::post().to(two_factor_handler::confirm_enrollment))
+                    .route("/recovery-codes", web::post().to(two_factor_handler::regenerate_recovery_codes))
+                    .route("/disable", web::post().to(two_factor_handler::disable)),
+            )
     );
 }
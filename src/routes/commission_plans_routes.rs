@@ -0,0 +1,49 @@
+use actix_web::web;
+use crate::handler::commissions_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/commission-plans")
+            .route(
+                "",
+                web::get()
+                    .to(commissions_handler::get_all_commission_plans)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["commissions:read".to_string()],
+                    }),
+            )
+            .route(
+                "",
+                web::post()
+                    .to(commissions_handler::create_commission_plan)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["commissions:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(commissions_handler::get_commission_plan_by_id)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["commissions:read".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::put()
+                    .to(commissions_handler::update_commission_plan)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["commissions:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::delete()
+                    .to(commissions_handler::delete_commission_plan)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["commissions:manage".to_string()],
+                    }),
+            ),
+    );
+}
@@ -0,0 +1,33 @@
+use actix_web::web;
+use crate::handler::email_outbox_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/email-outbox")
+            .route(
+                "",
+                web::get()
+                    .to(email_outbox_handler::get_all_emails)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["emails:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(email_outbox_handler::get_email_by_id)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["emails:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}/retry",
+                web::post()
+                    .to(email_outbox_handler::retry_email)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["emails:manage".to_string()],
+                    }),
+            ),
+    );
+}
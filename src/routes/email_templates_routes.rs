@@ -0,0 +1,41 @@
+use actix_web::web;
+use crate::handler::email_templates_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/email-templates")
+            .route(
+                "",
+                web::get()
+                    .to(email_templates_handler::get_all_templates)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["emails:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{key}",
+                web::get()
+                    .to(email_templates_handler::get_template)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["emails:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{key}",
+                web::put()
+                    .to(email_templates_handler::update_template)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["emails:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{key}",
+                web::delete()
+                    .to(email_templates_handler::reset_template)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["emails:manage".to_string()],
+                    }),
+            ),
+    );
+}
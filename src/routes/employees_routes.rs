@@ -1,5 +1,5 @@
 use actix_web::web;
-use crate::handler::employees_handler;
+use crate::handler::{commissions_handler, employees_handler};
 use crate::middleware::permission::PermissionMiddlewareFactory;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
@@ -70,6 +70,62 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                             .wrap(PermissionMiddlewareFactory {
                                 required_permissions: vec!["employees:delete".to_string()],
                             }),
+                    )
+                    .route(
+                        "/two-factor",
+                        web::delete()
+                            .to(employees_handler::reset_two_factor)
+                            .wrap(PermissionMiddlewareFactory {
+                                required_permissions: vec!["employees:update".to_string()],
+                            }),
+                    )
+                    .route(
+                        "/pin",
+                        web::delete()
+                            .to(employees_handler::reset_pin)
+                            .wrap(PermissionMiddlewareFactory {
+                                required_permissions: vec!["employees:update".to_string()],
+                            }),
+                    )
+                    .route(
+                        "/sessions",
+                        web::get()
+                            .to(employees_handler::get_employee_sessions)
+                            .wrap(PermissionMiddlewareFactory {
+                                required_permissions: vec!["employees:read".to_string()],
+                            }),
+                    )
+                    .route(
+                        "/sessions",
+                        web::delete()
+                            .to(employees_handler::revoke_employee_sessions)
+                            .wrap(PermissionMiddlewareFactory {
+                                required_permissions: vec!["employees:update".to_string()],
+                            }),
+                    )
+                    .route(
+                        "/stores",
+                        web::get()
+                            .to(employees_handler::get_employee_stores)
+                            .wrap(PermissionMiddlewareFactory {
+                                required_permissions: vec!["employees:read".to_string()],
+                            }),
+                    )
+                    .route(
+                        "/stores",
+                        web::put()
+                            .to(employees_handler::set_employee_stores)
+                            .wrap(PermissionMiddlewareFactory {
+                                required_permissions: vec!["employees:update".to_string()],
+                            }),
+                    )
+                    .route(
+                        "/commission-plan",
+                        web::put()
+                            .to(commissions_handler::set_employee_commission_plan)
+                            .wrap(PermissionMiddlewareFactory {
+                                required_permissions: vec!["commissions:manage".to_string()],
+                            }),
                     ),
             ),
     );
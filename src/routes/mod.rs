@@ -19,6 +19,13 @@ pub mod roles_routes;
 pub mod upload_routes;
 pub mod settings_routes;
 pub mod auth_routes;
+pub mod shifts_routes;
+pub mod terminals_routes;
+pub mod email_outbox_routes;
+pub mod email_templates_routes;
+pub mod timesheets_routes;
+pub mod commission_plans_routes;
+pub mod search_routes;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.configure(categories_routes::configure_routes)
@@ -39,5 +46,12 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
        .configure(roles_routes::configure_routes) // Configure new routes
        .configure(upload_routes::init_routes)
        .configure(settings_routes::configure_routes)
+       .configure(shifts_routes::configure_routes)
+       .configure(terminals_routes::configure_routes)
+       .configure(email_outbox_routes::configure_routes)
+       .configure(email_templates_routes::configure_routes)
+       .configure(timesheets_routes::configure_routes)
+       .configure(commission_plans_routes::configure_routes)
+       .configure(search_routes::configure_routes)
        .configure(auth_routes::configure_routes);
 }
\ No newline at end of file
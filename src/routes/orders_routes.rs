@@ -21,6 +21,14 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                         required_permissions: vec!["orders:read".to_string()],
                     }),
             )
+            .route(
+                "/{id}/receipt",
+                web::get()
+                    .to(orders_handler::get_order_receipt)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["orders:read".to_string()],
+                    }),
+            )
             .route(
                 "/{id}",
                 web::put()
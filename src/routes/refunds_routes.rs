@@ -21,6 +21,22 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                         required_permissions: vec!["refunds:read".to_string()],
                     }),
             )
+            .route(
+                "/receiptless",
+                web::post()
+                    .to(refunds_handler::create_receiptless_refund)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["refunds:create".to_string()],
+                    }),
+            )
+            .route(
+                "/abuse-report",
+                web::get()
+                    .to(refunds_handler::get_returns_abuse_report)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["reports:read".to_string()],
+                    }),
+            )
             .route(
                 "/{id}",
                 web::get()
@@ -1,5 +1,5 @@
 use actix_web::web;
-use crate::handler::reports_handler;
+use crate::handler::{commissions_handler, reports_handler};
 use crate::middleware::permission::PermissionMiddlewareFactory;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
@@ -20,6 +20,14 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .wrap(PermissionMiddlewareFactory {
                         required_permissions: vec!["reports:read".to_string()],
                     }),
+            )
+            .route(
+                "/commissions",
+                web::get()
+                    .to(commissions_handler::get_commission_report)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["commissions:read".to_string()],
+                    }),
             ),
     );
 }
@@ -0,0 +1,25 @@
+use actix_web::web;
+use crate::handler::search_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/search")
+            .route(
+                "/products",
+                web::get()
+                    .to(search_handler::search_products)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["products:read".to_string()],
+                    }),
+            )
+            .route(
+                "/customers",
+                web::get()
+                    .to(search_handler::search_customers)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["customers:read".to_string()],
+                    }),
+            ),
+    );
+}
@@ -1,11 +1,58 @@
 use actix_web::web;
 
-use crate::handler::settings_handler::{get_settings, save_settings};
+use crate::handler::settings_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/admin")
-            .service(get_settings)
-            .service(save_settings)
+            .route(
+                "/settings",
+                web::get()
+                    .to(settings_handler::get_settings)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["settings:read".to_string()],
+                    }),
+            )
+            .route(
+                "/settings",
+                web::post()
+                    .to(settings_handler::save_settings)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["settings:update".to_string()],
+                    }),
+            )
+            .route(
+                "/settings/versions",
+                web::get()
+                    .to(settings_handler::get_settings_versions)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["settings:read".to_string()],
+                    }),
+            )
+            .route(
+                "/settings/versions/{version}",
+                web::get()
+                    .to(settings_handler::get_settings_version)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["settings:read".to_string()],
+                    }),
+            )
+            .route(
+                "/settings/versions/{version}/rollback",
+                web::post()
+                    .to(settings_handler::rollback_settings)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["settings:update".to_string()],
+                    }),
+            )
+            .route(
+                "/settings/{section}",
+                web::patch()
+                    .to(settings_handler::update_settings_section)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["settings:update".to_string()],
+                    }),
+            ),
     );
-}
\ No newline at end of file
+}
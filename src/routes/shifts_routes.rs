@@ -0,0 +1,73 @@
+use actix_web::web;
+use crate::handler::shifts_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/shifts")
+            .route(
+                "",
+                web::get()
+                    .to(shifts_handler::get_all_shifts)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/open",
+                web::post()
+                    .to(shifts_handler::open_shift)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/current",
+                web::get()
+                    .to(shifts_handler::get_current_shift)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/reports/x",
+                web::get()
+                    .to(shifts_handler::get_x_report)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:read".to_string()],
+                    }),
+            )
+            .route(
+                "/reports/z",
+                web::get()
+                    .to(shifts_handler::get_z_report)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:read".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(shifts_handler::get_shift_by_id)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}/cash-movements",
+                web::post()
+                    .to(shifts_handler::add_cash_movement)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}/close",
+                web::post()
+                    .to(shifts_handler::close_shift)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["shifts:manage".to_string()],
+                    }),
+            ),
+    );
+}
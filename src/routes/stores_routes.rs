@@ -1,5 +1,5 @@
 use actix_web::web;
-use crate::handler::stores_handler;
+use crate::handler::{settings_handler, stores_handler};
 use crate::middleware::permission::PermissionMiddlewareFactory;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
@@ -36,6 +36,24 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                         web::delete().to(stores_handler::delete_store).wrap(PermissionMiddlewareFactory {
                             required_permissions: vec!["stores:delete".to_string()],
                         }),
+                    )
+                    .route(
+                        "/settings",
+                        web::get().to(settings_handler::get_store_settings).wrap(PermissionMiddlewareFactory {
+                            required_permissions: vec!["stores:read".to_string()],
+                        }),
+                    )
+                    .route(
+                        "/settings",
+                        web::put().to(settings_handler::update_store_settings).wrap(PermissionMiddlewareFactory {
+                            required_permissions: vec!["settings:update".to_string()],
+                        }),
+                    )
+                    .route(
+                        "/settings",
+                        web::delete().to(settings_handler::delete_store_settings).wrap(PermissionMiddlewareFactory {
+                            required_permissions: vec!["settings:update".to_string()],
+                        }),
                     ),
             ),
     );
@@ -0,0 +1,60 @@
+use actix_web::web;
+use crate::handler::terminals_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/terminals")
+            .route(
+                "",
+                web::post()
+                    .to(terminals_handler::create_terminal)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["terminals:manage".to_string()],
+                    }),
+            )
+            .route(
+                "",
+                web::get()
+                    .to(terminals_handler::get_all_terminals)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["terminals:manage".to_string()],
+                    }),
+            )
+            // Pairing and self-checks are done from the device by any signed-in employee
+            .route("/pair", web::post().to(terminals_handler::pair_terminal))
+            .route("/me", web::get().to(terminals_handler::get_current_terminal))
+            .route(
+                "/{id}",
+                web::get()
+                    .to(terminals_handler::get_terminal_by_id)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["terminals:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::put()
+                    .to(terminals_handler::update_terminal)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["terminals:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}/pairing-code",
+                web::post()
+                    .to(terminals_handler::reset_terminal_pairing)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["terminals:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}/disable",
+                web::post()
+                    .to(terminals_handler::disable_terminal)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["terminals:manage".to_string()],
+                    }),
+            ),
+    );
+}
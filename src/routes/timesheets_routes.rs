@@ -0,0 +1,100 @@
+use actix_web::web;
+use crate::handler::timesheets_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/time-clock")
+            .route(
+                "/clock-in",
+                web::post()
+                    .to(timesheets_handler::clock_in)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timeclock:use".to_string()],
+                    }),
+            )
+            .route(
+                "/clock-out",
+                web::post()
+                    .to(timesheets_handler::clock_out)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timeclock:use".to_string()],
+                    }),
+            )
+            .route(
+                "/breaks/start",
+                web::post()
+                    .to(timesheets_handler::start_break)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timeclock:use".to_string()],
+                    }),
+            )
+            .route(
+                "/breaks/end",
+                web::post()
+                    .to(timesheets_handler::end_break)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timeclock:use".to_string()],
+                    }),
+            )
+            .route(
+                "/current",
+                web::get()
+                    .to(timesheets_handler::get_current_entry)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timeclock:use".to_string()],
+                    }),
+            ),
+    );
+    cfg.service(
+        web::scope("/timesheets")
+            .route(
+                "",
+                web::get()
+                    .to(timesheets_handler::get_all_time_entries)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timesheets:read".to_string()],
+                    }),
+            )
+            .route(
+                "/reports/weekly",
+                web::get()
+                    .to(timesheets_handler::get_weekly_timesheet)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timesheets:read".to_string()],
+                    }),
+            )
+            .route(
+                "/reports/weekly/export",
+                web::get()
+                    .to(timesheets_handler::export_weekly_timesheet)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timesheets:read".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(timesheets_handler::get_time_entry_by_id)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timesheets:read".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}",
+                web::put()
+                    .to(timesheets_handler::update_time_entry)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timesheets:manage".to_string()],
+                    }),
+            )
+            .route(
+                "/{id}/edits",
+                web::get()
+                    .to(timesheets_handler::get_time_entry_edits)
+                    .wrap(PermissionMiddlewareFactory {
+                        required_permissions: vec!["timesheets:read".to_string()],
+                    }),
+            ),
+    );
+}
@@ -1,15 +1,57 @@
 use actix_web::web;
 use crate::handler::upload_handler;
+use crate::middleware::permission::PermissionMiddlewareFactory;
 
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
-        web::resource("/upload/product") 
+        web::resource("/upload/product")
             .route(web::post().to(upload_handler::upload_product_image))
-            .route(web::delete().to(upload_handler::delete_file)) 
+            .route(web::delete().to(upload_handler::delete_product_image))
+            .wrap(PermissionMiddlewareFactory {
+                required_permissions: vec!["products:update".to_string()],
+            }),
     );
     cfg.service(
-        web::resource("/upload/employee") 
+        web::resource("/upload/employee")
             .route(web::post().to(upload_handler::upload_employee_photo))
-            .route(web::delete().to(upload_handler::delete_file)) 
+            .route(web::delete().to(upload_handler::delete_employee_photo))
+            .wrap(PermissionMiddlewareFactory {
+                required_permissions: vec!["employees:update".to_string()],
+            }),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, web, App};
+
+    use super::init_routes;
+    use crate::helper::test_database;
+
+    #[actix_web::test]
+    async fn uploads_require_the_route_permission() {
+        let (db, _) = test_database::connect(|_| vec![test_database::session()]).await;
+        let app = test::init_service(App::new().app_data(web::Data::new(db)).configure(init_routes)).await;
+
+        let status = |token: Option<String>| {
+            let mut request = test::TestRequest::post().uri("/upload/product");
+            if let Some(token) = token {
+                request = request.insert_header(("Authorization", format!("Bearer {}", token)));
+            }
+            let request = request.to_request();
+            let app = &app;
+            async move {
+                match test::try_call_service(app, request).await {
+                    Ok(response) => response.status(),
+                    Err(e) => e.as_response_error().status_code(),
+                }
+            }
+        };
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some(test_database::access_token(&["products:read"]))).await, StatusCode::FORBIDDEN);
+        let allowed = status(Some(test_database::access_token(&["products:update"]))).await;
+        assert_ne!(allowed, StatusCode::UNAUTHORIZED);
+        assert_ne!(allowed, StatusCode::FORBIDDEN);
+    }
+}
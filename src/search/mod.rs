@@ -0,0 +1,173 @@
+//! Full-text search over products (name, SKU, description, category and supplier) and
+//! customers (name, phone and email), with prefix matching and typo tolerance.
+//!
+//! The indexes live in memory and are built from the database at startup. Handlers that
+//! change products or customers, or the category and supplier names products are found by,
+//! update them right after the write. A background task also reconciles them with the
+//! database every `RECONCILE_INTERVAL_SECS`, which picks up writes made by other app
+//! instances, and rebuilds them from scratch every `REBUILD_EVERY` rounds, which also picks
+//! up category and supplier renames made elsewhere.
+
+pub mod text;
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use sea_orm::{ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr};
+
+use crate::entities::customers;
+use crate::entities::products::{self, ProductWithDetails};
+use crate::repository::customers_repository::CustomerRepository;
+use crate::repository::products_repository::ProductRepository;
+use text::{Field, Hit, TextIndex};
+
+pub const DEFAULT_RESULTS: usize = 20;
+pub const MAX_RESULTS: usize = 100;
+
+const RECONCILE_INTERVAL_SECS: u64 = 60;
+const REBUILD_EVERY: u64 = 15;
+/// Rows re-read per query when many changed at once.
+const SYNC_BATCH: usize = 500;
+
+fn index_product(index: &mut TextIndex, product: &ProductWithDetails) {
+    index.insert(product.id, product.updated_at, &[
+        Field::code(&product.sku, 4.0),
+        Field::text(&product.name, 3.0),
+        Field::text(&product.category_name, 1.5),
+        Field::text(&product.supplier_name, 1.0),
+        Field::text(product.description.as_deref().unwrap_or_default(), 0.5),
+    ]);
+}
+
+fn index_customer(index: &mut TextIndex, customer: &customers::Model) {
+    index.insert(customer.id, customer.updated_at, &[
+        Field::text(&customer.first_name, 3.0),
+        Field::text(&customer.last_name, 3.0),
+        Field::code(customer.phone.as_deref().unwrap_or_default(), 2.0),
+        Field::code(customer.email.as_deref().unwrap_or_default(), 2.0),
+    ]);
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    products: RwLock<TextIndex>,
+    customers: RwLock<TextIndex>,
+}
+
+impl SearchIndex {
+    /// Product ids best matching the query, best first.
+    pub fn search_products(&self, query: &str, limit: usize) -> Vec<Hit> {
+        self.products.read().unwrap_or_else(PoisonError::into_inner).search(query, limit)
+    }
+
+    /// Customer ids best matching the query, best first.
+    pub fn search_customers(&self, query: &str, limit: usize) -> Vec<Hit> {
+        self.customers.read().unwrap_or_else(PoisonError::into_inner).search(query, limit)
+    }
+
+    /// Re-reads the products matching a condition after they were created or changed.
+    /// Failures are only logged; the next reconcile catches up.
+    pub async fn products_changed<C: ConnectionTrait>(&self, db: &C, condition: Condition) {
+        if let Err(e) = self.sync_products(db, condition).await {
+            log::warn!("Failed to update the product search index: {}", e);
+        }
+    }
+
+    pub async fn product_changed<C: ConnectionTrait>(&self, db: &C, id: i32) {
+        self.products_changed(db, Condition::all().add(products::Column::Id.eq(id))).await
+    }
+
+    pub fn product_removed(&self, id: i32) {
+        self.products.write().unwrap_or_else(PoisonError::into_inner).remove(id);
+    }
+
+    /// Re-reads a customer after it was created or changed. Failures are only logged; the
+    /// next reconcile catches up.
+    pub async fn customer_changed<C: ConnectionTrait>(&self, db: &C, id: i32) {
+        if let Err(e) = self.sync_customers(db, vec![id]).await {
+            log::warn!("Failed to update the customer search index: {}", e);
+        }
+    }
+
+    pub fn customer_removed(&self, id: i32) {
+        self.customers.write().unwrap_or_else(PoisonError::into_inner).remove(id);
+    }
+
+    async fn sync_products<C: ConnectionTrait>(&self, db: &C, condition: Condition) -> Result<(), DbErr> {
+        let rows = ProductRepository::find_with_details(db, condition).await?;
+        let mut index = self.products.write().unwrap_or_else(PoisonError::into_inner);
+        // A slower reader may bring an older version than one already indexed
+        for product in &rows {
+            if index.updated_at(product.id).is_none_or(|indexed| indexed <= product.updated_at) {
+                index_product(&mut index, product);
+            }
+        }
+        Ok(())
+    }
+
+    async fn sync_customers<C: ConnectionTrait>(&self, db: &C, ids: Vec<i32>) -> Result<(), DbErr> {
+        let rows = CustomerRepository::find_by_ids(db, ids).await?;
+        let mut index = self.customers.write().unwrap_or_else(PoisonError::into_inner);
+        for customer in &rows {
+            if index.updated_at(customer.id).is_none_or(|indexed| indexed <= customer.updated_at) {
+                index_customer(&mut index, customer);
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds both indexes from scratch and swaps them in.
+    pub async fn rebuild<C: ConnectionTrait>(&self, db: &C) -> Result<(), DbErr> {
+        let mut products = TextIndex::default();
+        for product in ProductRepository::find_with_details(db, Condition::all()).await? {
+            index_product(&mut products, &product);
+        }
+        let mut customers = TextIndex::default();
+        for customer in CustomerRepository::find_all(db).await? {
+            index_customer(&mut customers, &customer);
+        }
+        log::info!("Search index built: {} product(s), {} customer(s)", products.len(), customers.len());
+
+        *self.products.write().unwrap_or_else(PoisonError::into_inner) = products;
+        *self.customers.write().unwrap_or_else(PoisonError::into_inner) = customers;
+        Ok(())
+    }
+
+    /// Re-reads rows whose `updated_at` differs from the indexed version and drops deleted ones.
+    pub async fn reconcile<C: ConnectionTrait>(&self, db: &C) -> Result<(), DbErr> {
+        let stamps = ProductRepository::get_update_stamps(db).await?;
+        let (changed, removed) = self.products.read().unwrap_or_else(PoisonError::into_inner).compare(stamps);
+        for ids in changed.chunks(SYNC_BATCH) {
+            self.sync_products(db, Condition::all().add(products::Column::Id.is_in(ids.to_vec()))).await?;
+        }
+        removed.into_iter().for_each(|id| self.product_removed(id));
+
+        let stamps = CustomerRepository::get_update_stamps(db).await?;
+        let (changed, removed) = self.customers.read().unwrap_or_else(PoisonError::into_inner).compare(stamps);
+        for ids in changed.chunks(SYNC_BATCH) {
+            self.sync_customers(db, ids.to_vec()).await?;
+        }
+        removed.into_iter().for_each(|id| self.customer_removed(id));
+        Ok(())
+    }
+}
+
+/// Builds the indexes, then keeps them in step with the database in the background.
+pub fn spawn_refresher(db: DatabaseConnection, index: Arc<SearchIndex>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(RECONCILE_INTERVAL_SECS));
+        let mut round: u64 = 0;
+        let mut built = false;
+        loop {
+            interval.tick().await;
+            let result = if !built || round.is_multiple_of(REBUILD_EVERY) {
+                index.rebuild(&db).await.map(|_| built = true)
+            } else {
+                index.reconcile(&db).await
+            };
+            if let Err(e) = result {
+                log::error!("Search index refresh failed: {}", e);
+            }
+            round += 1;
+        }
+    });
+}
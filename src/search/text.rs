@@ -0,0 +1,292 @@
+//! A small inverted index: documents are sets of weighted fields, split into lowercase terms.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+
+use chrono::{DateTime, Utc};
+
+/// A piece of a document's text and how much a match in it counts.
+pub struct Field<'a> {
+    text: &'a str,
+    weight: f32,
+    code: bool,
+}
+
+impl<'a> Field<'a> {
+    /// Prose such as a name or a description, matched word by word.
+    pub fn text(text: &'a str, weight: f32) -> Self {
+        Field { text, weight, code: false }
+    }
+
+    /// An identifier such as a SKU, phone number or email address. Besides its parts it is
+    /// indexed as a whole with the punctuation removed, so `ABC-123`, `abc123` and `abc 123`
+    /// all find it.
+    pub fn code(text: &'a str, weight: f32) -> Self {
+        Field { text, weight, code: true }
+    }
+}
+
+/// How closely a query term has to match: exactly, as the start of a word, or with typos.
+const EXACT: f32 = 1.0;
+const PREFIX: f32 = 0.8;
+const ONE_TYPO: f32 = 0.6;
+const TWO_TYPOS: f32 = 0.4;
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Typos allowed in a query term: none in short ones, where almost everything is one edit
+/// away from something else.
+fn allowed_typos(term_len: usize) -> usize {
+    match term_len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Edit distance counting a swap of neighbouring characters as one edit, or `None` if it is
+/// more than `max`.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut before_previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_min = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(before_previous[j - 2] + 1);
+            }
+            current[j] = distance;
+            row_min = row_min.min(distance);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[b.len()]).filter(|distance| *distance <= max)
+}
+
+struct Document {
+    terms: Vec<String>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Hit {
+    pub id: i32,
+    pub score: f32,
+}
+
+#[derive(Default)]
+pub struct TextIndex {
+    documents: HashMap<i32, Document>,
+    /// Term -> document -> weight of the heaviest field it appears in.
+    postings: BTreeMap<String, HashMap<i32, f32>>,
+}
+
+impl TextIndex {
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Adds a document, replacing any previous version of it. `updated_at` is kept to tell
+    /// later whether the source row has changed since.
+    pub fn insert(&mut self, id: i32, updated_at: DateTime<Utc>, fields: &[Field]) {
+        self.remove(id);
+        let mut weights: HashMap<String, f32> = HashMap::new();
+        for field in fields {
+            let mut terms = tokenize(field.text);
+            if field.code && terms.len() > 1 {
+                terms.push(terms.concat());
+            }
+            for term in terms {
+                let weight = weights.entry(term).or_insert(0.0);
+                *weight = weight.max(field.weight);
+            }
+        }
+        for (term, weight) in &weights {
+            self.postings.entry(term.clone()).or_default().insert(id, *weight);
+        }
+        self.documents.insert(id, Document { terms: weights.into_keys().collect(), updated_at });
+    }
+
+    pub fn remove(&mut self, id: i32) -> bool {
+        let Some(document) = self.documents.remove(&id) else {
+            return false;
+        };
+        for term in document.terms {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.remove(&id);
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        true
+    }
+
+    pub fn updated_at(&self, id: i32) -> Option<DateTime<Utc>> {
+        self.documents.get(&id).map(|document| document.updated_at)
+    }
+
+    /// Compares the index with the `(id, updated_at)` of every source row: returns the ids
+    /// that are new or changed, and the indexed ids that no longer exist.
+    pub fn compare(&self, stamps: Vec<(i32, DateTime<Utc>)>) -> (Vec<i32>, Vec<i32>) {
+        let current: HashSet<i32> = stamps.iter().map(|(id, _)| *id).collect();
+        let changed = stamps
+            .into_iter()
+            .filter(|(id, updated_at)| self.updated_at(*id) != Some(*updated_at))
+            .map(|(id, _)| id)
+            .collect();
+        let removed = self.documents.keys().copied().filter(|id| !current.contains(id)).collect();
+        (changed, removed)
+    }
+
+    /// Indexed terms matching a query term, with how closely each matches.
+    fn matching_terms(&self, query_term: &str) -> Vec<(&String, f32)> {
+        let mut matches: Vec<(&String, f32)> = self
+            .postings
+            .range::<str, _>((Bound::Included(query_term), Bound::Unbounded))
+            .take_while(|(term, _)| term.starts_with(query_term))
+            .map(|(term, _)| (term, if term.as_str() == query_term { EXACT } else { PREFIX }))
+            .collect();
+
+        let query: Vec<char> = query_term.chars().collect();
+        let max = allowed_typos(query.len());
+        if max == 0 {
+            return matches;
+        }
+        for term in self.postings.keys().filter(|term| !term.starts_with(query_term)) {
+            let chars: Vec<char> = term.chars().collect();
+            // Against the whole term, and against its start so that typing still finds longer words
+            let whole = edit_distance(&query, &chars, max);
+            let start = (chars.len() > query.len())
+                .then(|| edit_distance(&query, &chars[..query.len()], max))
+                .flatten();
+            let quality = match (whole, start) {
+                (Some(distance), _) => if distance == 1 { ONE_TYPO } else { TWO_TYPOS },
+                (None, Some(distance)) => if distance == 1 { ONE_TYPO * PREFIX } else { TWO_TYPOS * PREFIX },
+                (None, None) => continue,
+            };
+            matches.push((term, quality));
+        }
+        matches
+    }
+
+    /// Documents matching every term of the query, best first. A document scores, for each
+    /// query term, its closest match weighted by the field it is in.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Hit> {
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() {
+            return vec![];
+        }
+
+        let mut scores: Option<HashMap<i32, f32>> = None;
+        for query_term in &query_terms {
+            let mut term_scores: HashMap<i32, f32> = HashMap::new();
+            for (term, quality) in self.matching_terms(query_term) {
+                for (id, weight) in &self.postings[term] {
+                    let score = term_scores.entry(*id).or_insert(0.0);
+                    *score = score.max(quality * weight);
+                }
+            }
+            scores = Some(match scores {
+                None => term_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(id, score)| term_scores.get(&id).map(|term_score| (id, score + term_score)))
+                    .collect(),
+            });
+        }
+
+        let mut hits: Vec<Hit> = scores.unwrap_or_default().into_iter().map(|(id, score)| Hit { id, score }).collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::{edit_distance, Field, TextIndex};
+
+    /// Products indexed the way `SearchIndex` does: SKU, name, category, supplier, description.
+    fn products() -> TextIndex {
+        let mut index = TextIndex::default();
+        for (id, sku, name, description) in [
+            (1, "CHOC-001", "Dark Chocolate Bar", "Rich cocoa"),
+            (2, "MILK-002", "Whole Milk", "Pairs well with choc chip cookies"),
+            (3, "TEA-003", "Green Tea", "Loose leaf"),
+        ] {
+            index.insert(id, Utc::now(), &[
+                Field::code(sku, 4.0),
+                Field::text(name, 3.0),
+                Field::text("Groceries", 1.5),
+                Field::text("Acme", 1.0),
+                Field::text(description, 0.5),
+            ]);
+        }
+        index
+    }
+
+    fn ids(index: &TextIndex, query: &str) -> Vec<i32> {
+        index.search(query, 10).into_iter().map(|hit| hit.id).collect()
+    }
+
+    #[test]
+    fn finds_misspelled_names() {
+        let index = products();
+        assert_eq!(ids(&index, "chcolate"), [1]);
+        assert_eq!(ids(&index, "chocolte bar"), [1]);
+        assert_eq!(ids(&index, "gren tea"), [3]);
+        // Short terms must match exactly or as a prefix
+        assert!(ids(&index, "tae").is_empty());
+    }
+
+    #[test]
+    fn finds_sku_prefixes_with_or_without_punctuation() {
+        let index = products();
+        assert_eq!(ids(&index, "choc00"), [1]);
+        assert_eq!(ids(&index, "CHOC-0").first(), Some(&1));
+        assert_eq!(ids(&index, "milk002"), [2]);
+        assert_eq!(ids(&index, "tea-003"), [3]);
+    }
+
+    #[test]
+    fn ranks_sku_matches_above_description_matches() {
+        let index = products();
+        let hits = index.search("choc", 10);
+        assert_eq!(hits.iter().map(|hit| hit.id).collect::<Vec<_>>(), [1, 2]);
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn matches_every_query_term() {
+        let index = products();
+        assert_eq!(ids(&index, "milk cookies"), [2]);
+        assert!(ids(&index, "milk tea").is_empty());
+    }
+
+    #[test]
+    fn counts_swapped_letters_as_one_edit() {
+        let chars = |text: &str| text.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("tea"), &chars("tae"), 2), Some(1));
+        assert_eq!(edit_distance(&chars("milk"), &chars("silk"), 1), Some(1));
+        assert_eq!(edit_distance(&chars("milk"), &chars("mild"), 0), None);
+    }
+}
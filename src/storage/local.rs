@@ -0,0 +1,134 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{Storage, StorageError, StoredObject};
+
+/// Files under a directory on this machine, `./uploads` unless `UPLOAD_DIR` says otherwise.
+/// Only suitable for a single app instance.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn from_env() -> Result<Self, StorageError> {
+        Ok(Self::new(std::env::var("UPLOAD_DIR").unwrap_or_else(|_| "./uploads".to_string())))
+    }
+
+    /// Keys are relative paths of plain segments; anything that could leave the root is refused.
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        if key.is_empty() || key.split('/').any(|segment| segment.is_empty() || segment == "." || segment == "..") {
+            return Err(StorageError(format!("Invalid storage key '{}'", key)));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> StorageError {
+    StorageError(format!("Failed to {} {}: {}", action, path.display(), e))
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(|e| io_error("create", dir, e))?;
+        }
+        // Write to a temporary file first so readers never see a partial image
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, bytes).await.map_err(|e| io_error("write", &partial, e))?;
+        tokio::fs::rename(&partial, &path).await.map_err(|e| io_error("write", &path, e))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("read", &path, e)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StorageError> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(io_error("delete", &path, e)),
+        }
+    }
+
+    /// Lists the files in the folder `prefix` names, e.g. `products/`.
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, StorageError> {
+        let dir = self.path(prefix.trim_end_matches('/'))?;
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(io_error("read", &dir, e)),
+        };
+
+        let mut objects = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| io_error("read", &dir, e))? {
+            let metadata = entry.metadata().await.map_err(|e| io_error("read", &entry.path(), e))?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if !metadata.is_file() || name.ends_with(".partial") {
+                continue;
+            }
+            let last_modified = metadata.modified().map(DateTime::<Utc>::from).unwrap_or_else(|_| Utc::now());
+            objects.push(StoredObject { key: format!("{}/{}", prefix.trim_end_matches('/'), name), last_modified });
+        }
+        Ok(objects)
+    }
+
+    fn signed_url(&self, _key: &str, _expires_in: Duration) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LocalStorage;
+    use crate::storage::Storage;
+
+    #[tokio::test]
+    async fn round_trip() {
+        let root = std::env::temp_dir().join(format!("local-storage-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+
+        storage.put("products/a.webp", b"first".to_vec(), "image/webp").await.unwrap();
+        storage.put("products/b.webp", b"second".to_vec(), "image/webp").await.unwrap();
+        storage.put("employees/c.webp", b"third".to_vec(), "image/webp").await.unwrap();
+        assert_eq!(storage.get("products/a.webp").await.unwrap().as_deref(), Some(&b"first"[..]));
+        assert_eq!(storage.get("products/missing.webp").await.unwrap(), None);
+
+        let mut keys: Vec<String> = storage.list("products/").await.unwrap().into_iter().map(|object| object.key).collect();
+        keys.sort();
+        assert_eq!(keys, ["products/a.webp", "products/b.webp"]);
+        assert!(storage.list("suppliers/").await.unwrap().is_empty());
+
+        // The app serves local files itself
+        assert_eq!(storage.signed_url("products/a.webp", Duration::from_secs(60)), None);
+
+        assert!(storage.delete("products/a.webp").await.unwrap());
+        assert!(!storage.delete("products/a.webp").await.unwrap());
+        assert_eq!(storage.get("products/a.webp").await.unwrap(), None);
+        assert_eq!(storage.list("products/").await.unwrap().len(), 1);
+
+        assert!(storage.get("../secret").await.is_err());
+        assert!(storage.put("products//a.webp", vec![], "image/webp").await.is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+//! Where uploaded media lives. Objects are addressed by keys such as `products/<uuid>.webp`
+//! and records store them as `/uploads/<key>`, which the app resolves per request, so
+//! switching backends does not touch the database.
+//!
+//! The backend is chosen with `STORAGE_BACKEND`: `local` (the default) keeps files under
+//! `UPLOAD_DIR`, `s3` uses any S3-compatible service such as AWS S3 or MinIO. Several app
+//! instances behind a load balancer must share an `s3` backend.
+
+pub mod local;
+pub mod s3;
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+#[derive(Debug)]
+pub struct StorageError(pub String);
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub key: String,
+    pub last_modified: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), StorageError>;
+
+    /// The object's bytes, or `None` if there is no such object.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Returns false if there was no such object.
+    async fn delete(&self, key: &str) -> Result<bool, StorageError>;
+
+    /// Every object whose key starts with `prefix`.
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, StorageError>;
+
+    /// A URL clients can fetch the object from directly, valid for at least `expires_in`.
+    /// `None` means the app serves the object itself.
+    fn signed_url(&self, key: &str, expires_in: Duration) -> Option<String>;
+}
+
+/// Builds a backend by name (`local` or `s3`) from its environment variables.
+pub fn from_name(name: &str) -> Result<Arc<dyn Storage>, StorageError> {
+    match name {
+        "local" => Ok(Arc::new(LocalStorage::from_env()?)),
+        "s3" => Ok(Arc::new(S3Storage::from_env()?)),
+        other => Err(StorageError(format!("Unknown storage backend '{}'; use local or s3", other))),
+    }
+}
+
+/// The backend selected by `STORAGE_BACKEND`.
+pub fn from_env() -> Result<Arc<dyn Storage>, StorageError> {
+    let name = std::env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string());
+    from_name(name.trim())
+}
+
+/// Copies every object under `prefixes` from one backend to another, skipping objects the
+/// target already has. Returns how many objects were copied. The source is left as it is.
+pub async fn migrate(from: &dyn Storage, to: &dyn Storage, prefixes: &[&str]) -> Result<usize, StorageError> {
+    let mut copied = 0;
+    for prefix in prefixes {
+        let existing: std::collections::HashSet<String> =
+            to.list(prefix).await?.into_iter().map(|object| object.key).collect();
+        for object in from.list(prefix).await? {
+            if existing.contains(&object.key) {
+                continue;
+            }
+            let Some(bytes) = from.get(&object.key).await? else {
+                continue;
+            };
+            to.put(&object.key, bytes, content_type(&object.key)).await?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+pub fn content_type(key: &str) -> &'static str {
+    match key.rsplit('.').next() {
+        Some("webp") => "image/webp",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
@@ -1,42 +1,268 @@
 use actix::prelude::*;
-use std::collections::HashMap;
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
-/// Pesan broadcast yang akan dikirim ke semua klien.
+use crate::auth::store_scope::StoreScope;
+use crate::entities::event_log;
+use crate::repository::event_log_repository::EventLogRepository;
+use crate::websocket::events::{Entity, Envelope, Event, Topic};
+
+/// How long events are kept for clients to catch up on.
+const EVENT_RETENTION_HOURS: i64 = 24;
+/// The most events kept, however recent.
+const MAX_EVENTS: i64 = 10_000;
+/// The longest gap replayed to a reconnecting client; past that it has to reload everything.
+const MAX_REPLAY: u64 = 1_000;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// How often the event log is checked for events published by other instances.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// The most events delivered per poll.
+const POLL_BATCH: u64 = 500;
+/// How long delivery waits at a gap in the sequence for an earlier insert to commit. Ids of
+/// failed inserts are never filled, so after this the gap is skipped.
+const GAP_GRACE: Duration = Duration::from_secs(2);
+
+/// Text sent to a single client.
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct BroadcastMessage(pub String);
 
-/// Pesan untuk terhubung ke broadcaster.
+/// Registers an authenticated session with what it is allowed to see.
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct Connect {
     pub addr: Recipient<BroadcastMessage>,
     pub id: usize,
+    pub scope: StoreScope,
+    pub entities: Vec<Entity>,
 }
 
-/// Pesan untuk memutus koneksi dari broadcaster.
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct Disconnect {
     pub id: usize,
 }
 
-/// Aktor Broadcaster
-#[derive(Debug, Default)]
+/// Topics have already been checked against the session's scope. With `since`, the events
+/// after that sequence number are replayed first, or the client is told to resync.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Subscribe {
+    pub id: usize,
+    pub topics: Vec<Topic>,
+    pub since: Option<i64>,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Unsubscribe {
+    pub id: usize,
+    pub topics: Vec<Topic>,
+}
+
+/// Records an event, to be sent to every session subscribed to its topic that may see it.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Publish(pub Event);
+
+struct Session {
+    addr: Recipient<BroadcastMessage>,
+    scope: StoreScope,
+    entities: Vec<Entity>,
+    topics: HashSet<Topic>,
+}
+
+impl Session {
+    fn wants(&self, event: &Event) -> bool {
+        self.entities.contains(&event.entity())
+            && event.is_visible_to(&self.scope)
+            && self.topics.iter().any(|topic| topic.matches(event))
+    }
+
+    fn send(&self, message: serde_json::Value) {
+        self.addr.do_send(BroadcastMessage(message.to_string()));
+    }
+}
+
+fn read_event(stored: &event_log::Model) -> Option<Event> {
+    serde_json::from_value::<Event>(stored.event.clone())
+        .map_err(|e| log::error!("Skipping unreadable event {}: {}", stored.id, e))
+        .ok()
+}
+
+/// Whether a session that last saw `since` can be caught up with `events`, the stored events
+/// after it (up to `last_seq`), given the oldest event still stored. If not, it has to resync.
+fn can_replay(since: i64, last_seq: i64, oldest: Option<i64>, events: &[event_log::Model]) -> bool {
+    // Nothing was pruned between `since` and what is left, and the gap is not too long
+    since <= last_seq
+        && oldest.map_or(since == last_seq, |oldest| since + 1 >= oldest)
+        && (events.len() as u64) <= MAX_REPLAY
+}
+
+/// How many of `ids`, the sequence numbers stored after `last_seq` in order, can be delivered.
+/// Delivery stops at the first gap, since an earlier insert may not have committed yet,
+/// unless `skip_gap` says that gap has been waited on long enough.
+fn deliverable(last_seq: i64, ids: &[i64], skip_gap: bool) -> usize {
+    let mut expected = last_seq + 1;
+    let mut skip_gap = skip_gap;
+    for (count, id) in ids.iter().enumerate() {
+        if *id != expected {
+            if !skip_gap {
+                return count;
+            }
+            skip_gap = false;
+        }
+        expected = id + 1;
+    }
+    ids.len()
+}
+
+fn serialize(event: &Event, seq: Option<i64>) -> Option<String> {
+    serde_json::to_string(&Envelope { seq, topic: event.topic(), event })
+        .map_err(|e| log::error!("Failed to serialize WebSocket event: {}", e))
+        .ok()
+}
+
+/// Keeps track of connected sessions and their subscriptions. Events are numbered by the
+/// shared event log and delivered from it, in order, so every instance of the service sends
+/// its clients the events published on any of them. Publishing, polling and replaying wait
+/// for the database, so a client never gets events out of order, not even while catching up.
 pub struct Broadcaster {
-    sessions: HashMap<usize, Recipient<BroadcastMessage>>,
+    db: DatabaseConnection,
+    sessions: HashMap<usize, Session>,
+    /// Sequence number of the newest event delivered.
+    last_seq: i64,
+    /// Since when delivery has been waiting at a gap after `last_seq`, and at which `last_seq`.
+    gap: Option<(i64, Instant)>,
+}
+
+impl Broadcaster {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, sessions: HashMap::new(), last_seq: 0, gap: None }
+    }
+
+    /// Delivers the events stored since `last_seq`.
+    fn poll(&mut self, ctx: &mut Context<Self>) {
+        let db = self.db.clone();
+        let since = self.last_seq;
+        ctx.wait(
+            async move { EventLogRepository::find_after(&db, since, POLL_BATCH).await }
+                .into_actor(self)
+                .map(|res, act, _| match res {
+                    Ok(events) => act.deliver_stored(events),
+                    Err(e) => log::error!("Failed to poll the event log: {}", e),
+                }),
+        );
+    }
+
+    fn deliver_stored(&mut self, events: Vec<event_log::Model>) {
+        let events: Vec<event_log::Model> = events.into_iter().filter(|stored| stored.id > self.last_seq).collect();
+        let ids: Vec<i64> = events.iter().map(|stored| stored.id).collect();
+        let skip_gap = self.gap.is_some_and(|(seq, since)| seq == self.last_seq && since.elapsed() >= GAP_GRACE);
+        let count = deliverable(self.last_seq, &ids, skip_gap);
+
+        for stored in &events[..count] {
+            if let Some(event) = read_event(stored) {
+                self.deliver(&event, Some(stored.id));
+            }
+            self.last_seq = stored.id;
+        }
+        if count == events.len() {
+            self.gap = None;
+        } else if self.gap.is_none_or(|(seq, _)| seq != self.last_seq) {
+            self.gap = Some((self.last_seq, Instant::now()));
+        }
+    }
+
+    fn deliver(&self, event: &Event, seq: Option<i64>) {
+        let mut recipients = self.sessions.values().filter(|session| session.wants(event)).peekable();
+        if recipients.peek().is_none() {
+            return;
+        }
+        if let Some(text) = serialize(event, seq) {
+            for session in recipients {
+                session.addr.do_send(BroadcastMessage(text.clone()));
+            }
+        }
+    }
+
+    /// Sends a reconnecting session what it missed, once its new topics are in place.
+    fn replay(&self, id: usize, since: i64, stored: Result<(Option<i64>, Vec<event_log::Model>), String>) {
+        let Some(session) = self.sessions.get(&id) else {
+            return;
+        };
+        let events = match stored {
+            Ok((oldest, mut events)) => {
+                // Newer events are still to be delivered by the poll
+                events.retain(|stored| stored.id <= self.last_seq);
+                if !can_replay(since, self.last_seq, oldest, &events) {
+                    return session.send(json!({ "type": "resync_required", "seq": self.last_seq }));
+                }
+                events
+            }
+            Err(e) => {
+                log::error!("Failed to load events to replay: {}", e);
+                return session.send(json!({ "type": "resync_required", "seq": self.last_seq }));
+            }
+        };
+
+        for stored in events {
+            let Some(event) = read_event(&stored) else {
+                continue;
+            };
+            if session.wants(&event) {
+                if let Some(text) = serialize(&event, Some(stored.id)) {
+                    session.addr.do_send(BroadcastMessage(text));
+                }
+            }
+        }
+    }
 }
 
 impl Actor for Broadcaster {
     type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let db = self.db.clone();
+        ctx.wait(
+            async move { EventLogRepository::latest_id(&db).await }
+                .into_actor(self)
+                .map(|res, act, _| match res {
+                    Ok(latest) => act.last_seq = latest.unwrap_or(0),
+                    Err(e) => log::error!("Failed to load the latest event sequence number: {}", e),
+                }),
+        );
+
+        ctx.run_interval(POLL_INTERVAL, |act, ctx| act.poll(ctx));
+
+        ctx.run_interval(PRUNE_INTERVAL, |act, ctx| {
+            let db = act.db.clone();
+            ctx.spawn(
+                async move {
+                    let before = chrono::Utc::now() - chrono::Duration::hours(EVENT_RETENTION_HOURS);
+                    if let Err(e) = EventLogRepository::prune(&db, before, MAX_EVENTS).await {
+                        log::error!("Failed to prune the event log: {}", e);
+                    }
+                }
+                .into_actor(act),
+            );
+        });
+    }
 }
 
 impl Handler<Connect> for Broadcaster {
     type Result = ();
 
     fn handle(&mut self, msg: Connect, _: &mut Self::Context) {
-        println!("Broadcaster: New client connected with id {}", msg.id);
-        self.sessions.insert(msg.id, msg.addr);
+        log::debug!("WebSocket session {} connected for employee {}", msg.id, msg.scope.employee_id);
+        self.sessions.insert(msg.id, Session {
+            addr: msg.addr,
+            scope: msg.scope,
+            entities: msg.entities,
+            topics: HashSet::new(),
+        });
     }
 }
 
@@ -44,18 +270,144 @@ impl Handler<Disconnect> for Broadcaster {
     type Result = ();
 
     fn handle(&mut self, msg: Disconnect, _: &mut Self::Context) {
-        println!("Broadcaster: Client {} disconnected", msg.id);
+        log::debug!("WebSocket session {} disconnected", msg.id);
         self.sessions.remove(&msg.id);
     }
 }
 
-impl Handler<BroadcastMessage> for Broadcaster {
+impl Handler<Subscribe> for Broadcaster {
+    type Result = ();
+
+    fn handle(&mut self, msg: Subscribe, ctx: &mut Self::Context) {
+        let Some(session) = self.sessions.get_mut(&msg.id) else {
+            return;
+        };
+        let names: Vec<String> = msg.topics.iter().map(Topic::to_string).collect();
+        session.topics.extend(msg.topics);
+
+        let id = msg.id;
+        let Some(since) = msg.since else {
+            return session.send(json!({ "type": "subscribed", "topics": names, "seq": self.last_seq }));
+        };
+
+        let db = self.db.clone();
+        ctx.wait(
+            async move {
+                let oldest = EventLogRepository::oldest_id(&db).await?;
+                let events = EventLogRepository::find_after(&db, since, MAX_REPLAY + 1).await?;
+                Ok((oldest, events))
+            }
+            .into_actor(self)
+            .map(move |res: Result<_, sea_orm::DbErr>, act, _| {
+                act.replay(id, since, res.map_err(|e| e.to_string()));
+                if let Some(session) = act.sessions.get(&id) {
+                    session.send(json!({ "type": "subscribed", "topics": names, "seq": act.last_seq }));
+                }
+            }),
+        );
+    }
+}
+
+impl Handler<Unsubscribe> for Broadcaster {
     type Result = ();
 
-    fn handle(&mut self, msg: BroadcastMessage, _: &mut Self::Context) {
-        println!("Broadcaster: Broadcasting message to all clients");
-        for addr in self.sessions.values() {
-            let _ = addr.do_send(BroadcastMessage(msg.0.clone()));
+    fn handle(&mut self, msg: Unsubscribe, _: &mut Self::Context) {
+        if let Some(session) = self.sessions.get_mut(&msg.id) {
+            for topic in &msg.topics {
+                session.topics.remove(topic);
+            }
+            let names: Vec<String> = msg.topics.iter().map(Topic::to_string).collect();
+            session.send(json!({ "type": "unsubscribed", "topics": names }));
         }
     }
 }
+
+impl Handler<Publish> for Broadcaster {
+    type Result = ();
+
+    fn handle(&mut self, msg: Publish, ctx: &mut Self::Context) {
+        let event = msg.0;
+        let db = self.db.clone();
+        let topic = event.topic();
+        let stored = serde_json::to_value(&event);
+        ctx.wait(
+            async move {
+                let stored = stored.map_err(|e| e.to_string())?;
+                EventLogRepository::append(&db, topic, stored).await.map_err(|e| e.to_string())
+            }
+            .into_actor(self)
+            .map(move |res, act, ctx| match res {
+                // Delivered from the log like the events of other instances, without waiting for the next poll
+                Ok(_) => act.poll(ctx),
+                // An event that could not be recorded is still sent live, just without a number
+                Err(e) => {
+                    log::error!("Failed to record event {}: {}", event.topic(), e);
+                    act.deliver(&event, None);
+                }
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(ids: std::ops::RangeInclusive<i64>) -> Vec<event_log::Model> {
+        ids.map(|id| event_log::Model {
+            id,
+            topic: "orders".to_string(),
+            event: json!({}),
+            created_at: chrono::Utc::now(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn replays_what_a_client_missed() {
+        assert!(can_replay(10, 15, Some(1), &stored(11..=15)));
+        // Everything up to `since` may have been pruned, as long as nothing after it was
+        assert!(can_replay(10, 15, Some(11), &stored(11..=15)));
+    }
+
+    #[test]
+    fn a_client_that_is_up_to_date_gets_nothing() {
+        assert!(can_replay(15, 15, Some(1), &[]));
+        // All events pruned, but none happened since
+        assert!(can_replay(15, 15, None, &[]));
+    }
+
+    #[test]
+    fn resyncs_when_missed_events_were_pruned() {
+        assert!(!can_replay(10, 15, Some(12), &stored(12..=15)));
+        assert!(!can_replay(10, 15, None, &[]));
+    }
+
+    #[test]
+    fn resyncs_when_the_gap_is_too_long() {
+        let last_seq = 10 + MAX_REPLAY as i64 + 1;
+        assert!(!can_replay(10, last_seq, Some(1), &stored(11..=last_seq)));
+        assert!(can_replay(11, last_seq, Some(1), &stored(12..=last_seq)));
+    }
+
+    #[test]
+    fn resyncs_a_client_ahead_of_the_log() {
+        // e.g. after the database was restored from a backup
+        assert!(!can_replay(20, 15, Some(1), &[]));
+    }
+
+    #[test]
+    fn delivers_consecutive_events() {
+        assert_eq!(deliverable(10, &[11, 12, 13], false), 3);
+        assert_eq!(deliverable(10, &[], false), 0);
+    }
+
+    #[test]
+    fn waits_at_a_gap_until_it_has_been_skipped() {
+        assert_eq!(deliverable(10, &[11, 13, 14], false), 1);
+        assert_eq!(deliverable(10, &[12, 13], false), 0);
+        assert_eq!(deliverable(10, &[12, 13], true), 2);
+        // Only the gap that was waited on is skipped
+        assert_eq!(deliverable(10, &[12, 13, 15], true), 2);
+    }
+}
@@ -0,0 +1,278 @@
+//! Events pushed to WebSocket clients. Every event belongs to one store and one entity, and is
+//! published on the topic `store:<store_id>:<entity>`, e.g. `store:3:inventory`.
+//!
+//! Clients subscribe with `{"action": "subscribe", "topics": [...]}`, where either part of a
+//! topic may be `*`: `store:3:*` is everything in store 3, `store:*:orders` orders in every store
+//! the caller can see. Events arrive as `{"type": "order_created", "seq": 812, "topic":
+//! "store:3:orders", "data": {...}}`.
+//!
+//! `seq` numbers every event in the order it was sent. A client that reconnects subscribes
+//! with `"since": <last seq it saw>` and first gets the events it missed, or a
+//! `{"type": "resync_required"}` when they are no longer kept and it has to reload its data.
+
+use sea_orm::prelude::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::auth_service::Claims;
+use crate::auth::store_scope::StoreScope;
+use crate::entities::{inventory, orders, purchase_orders, refunds};
+
+/// Stock at or below this is low, the same cut-off the inventory dashboard uses.
+pub const LOW_STOCK_THRESHOLD: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Entity {
+    Inventory,
+    Orders,
+    Refunds,
+    PurchaseOrders,
+}
+
+impl Entity {
+    const ALL: [Entity; 4] = [Entity::Inventory, Entity::Orders, Entity::Refunds, Entity::PurchaseOrders];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Entity::Inventory => "inventory",
+            Entity::Orders => "orders",
+            Entity::Refunds => "refunds",
+            Entity::PurchaseOrders => "purchase_orders",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Entity> {
+        Self::ALL.into_iter().find(|entity| entity.as_str() == value)
+    }
+
+    /// The permission needed to receive the entity's events, the same one its list endpoint needs.
+    pub fn read_permission(&self) -> &'static str {
+        match self {
+            Entity::Inventory => "inventory:read",
+            Entity::Orders => "orders:read",
+            Entity::Refunds => "refunds:read",
+            Entity::PurchaseOrders => "purchase_orders:read",
+        }
+    }
+
+    /// The entities whose events the caller may receive.
+    pub fn readable_by(claims: &Claims) -> Vec<Entity> {
+        Self::ALL.into_iter().filter(|entity| claims.has_permission(entity.read_permission())).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    /// A stock level was created, changed or deleted. `previous_quantity` is `None` for a new
+    /// inventory record; a deleted one has `deleted` set and a quantity of 0.
+    InventoryChanged {
+        inventory_id: i32,
+        store_id: i32,
+        product_id: i32,
+        quantity: i32,
+        previous_quantity: Option<i32>,
+        deleted: bool,
+    },
+    /// Stock dropped to `LOW_STOCK_THRESHOLD` or below. Sent once when it crosses the threshold,
+    /// not on every sale after that.
+    LowStock {
+        inventory_id: i32,
+        store_id: i32,
+        product_id: i32,
+        quantity: i32,
+        threshold: i32,
+    },
+    OrderCreated {
+        order_id: i32,
+        store_id: i32,
+        employee_id: i32,
+        customer_id: i32,
+        total_amount: Decimal,
+        status: String,
+    },
+    RefundIssued {
+        refund_id: i32,
+        order_id: Option<i32>,
+        store_id: i32,
+        employee_id: i32,
+        total_amount: Decimal,
+        refund_method: String,
+        is_receiptless: bool,
+    },
+    PurchaseOrderReceived {
+        purchase_order_id: i32,
+        store_id: i32,
+        supplier_id: i32,
+        status: String,
+    },
+}
+
+impl Event {
+    /// The events for a stock level that changed from `previous_quantity`: the change itself,
+    /// and a low stock warning if it just went below the threshold.
+    pub fn stock_changed(item: &inventory::Model, previous_quantity: Option<i32>) -> Vec<Event> {
+        let mut events = vec![Event::InventoryChanged {
+            inventory_id: item.id,
+            store_id: item.store_id,
+            product_id: item.product_id,
+            quantity: item.quantity,
+            previous_quantity,
+            deleted: false,
+        }];
+        let was_low = previous_quantity.is_some_and(|quantity| quantity <= LOW_STOCK_THRESHOLD);
+        if item.quantity <= LOW_STOCK_THRESHOLD && !was_low {
+            events.push(Event::LowStock {
+                inventory_id: item.id,
+                store_id: item.store_id,
+                product_id: item.product_id,
+                quantity: item.quantity,
+                threshold: LOW_STOCK_THRESHOLD,
+            });
+        }
+        events
+    }
+
+    pub fn stock_deleted(item: &inventory::Model) -> Event {
+        Event::InventoryChanged {
+            inventory_id: item.id,
+            store_id: item.store_id,
+            product_id: item.product_id,
+            quantity: 0,
+            previous_quantity: Some(item.quantity),
+            deleted: true,
+        }
+    }
+
+    pub fn order_created(order: &orders::Model) -> Event {
+        Event::OrderCreated {
+            order_id: order.id,
+            store_id: order.store_id,
+            employee_id: order.employee_id,
+            customer_id: order.customer_id,
+            total_amount: order.total_amount,
+            status: order.status.clone(),
+        }
+    }
+
+    pub fn refund_issued(refund: &refunds::Model) -> Event {
+        Event::RefundIssued {
+            refund_id: refund.id,
+            order_id: refund.order_id,
+            store_id: refund.store_id,
+            employee_id: refund.employee_id,
+            total_amount: refund.total_amount,
+            refund_method: refund.refund_method.clone(),
+            is_receiptless: refund.is_receiptless,
+        }
+    }
+
+    pub fn purchase_order_received(purchase_order: &purchase_orders::Model) -> Event {
+        Event::PurchaseOrderReceived {
+            purchase_order_id: purchase_order.id,
+            store_id: purchase_order.store_id,
+            supplier_id: purchase_order.supplier_id,
+            status: purchase_order.status.clone(),
+        }
+    }
+
+    pub fn store_id(&self) -> i32 {
+        match self {
+            Event::InventoryChanged { store_id, .. }
+            | Event::LowStock { store_id, .. }
+            | Event::OrderCreated { store_id, .. }
+            | Event::RefundIssued { store_id, .. }
+            | Event::PurchaseOrderReceived { store_id, .. } => *store_id,
+        }
+    }
+
+    pub fn entity(&self) -> Entity {
+        match self {
+            Event::InventoryChanged { .. } | Event::LowStock { .. } => Entity::Inventory,
+            Event::OrderCreated { .. } => Entity::Orders,
+            Event::RefundIssued { .. } => Entity::Refunds,
+            Event::PurchaseOrderReceived { .. } => Entity::PurchaseOrders,
+        }
+    }
+
+    pub fn topic(&self) -> String {
+        format!("store:{}:{}", self.store_id(), self.entity().as_str())
+    }
+
+    /// Whether a caller with `scope` may see the event. Sales and refunds are records of the
+    /// employee who made them, so callers limited to their own records only get their own.
+    pub fn is_visible_to(&self, scope: &StoreScope) -> bool {
+        match self {
+            Event::OrderCreated { store_id, employee_id, .. } | Event::RefundIssued { store_id, employee_id, .. } => {
+                scope.includes_record(*store_id, *employee_id)
+            }
+            _ => scope.includes_store(self.store_id()),
+        }
+    }
+}
+
+/// An event as it goes over the wire.
+#[derive(Serialize)]
+pub struct Envelope<'a> {
+    /// Missing only if the event could not be recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<i64>,
+    pub topic: String,
+    #[serde(flatten)]
+    pub event: &'a Event,
+}
+
+/// A topic a client subscribes to; `None` stands for `*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Topic {
+    pub store_id: Option<i32>,
+    pub entity: Option<Entity>,
+}
+
+impl Topic {
+    pub fn parse(value: &str) -> Result<Topic, String> {
+        let invalid = || format!("Invalid topic '{}'; expected store:<id or *>:<entity or *>", value);
+        let mut parts = value.split(':');
+        if parts.next() != Some("store") {
+            return Err(invalid());
+        }
+        let store_id = match parts.next() {
+            Some("*") => None,
+            Some(id) => Some(id.parse::<i32>().map_err(|_| invalid())?),
+            None => return Err(invalid()),
+        };
+        let entity = match parts.next() {
+            None | Some("*") => None,
+            Some(entity) => Some(Entity::parse(entity).ok_or_else(|| format!("Unknown entity '{}' in topic '{}'", entity, value))?),
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Topic { store_id, entity })
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        self.store_id.is_none_or(|id| id == event.store_id()) && self.entity.is_none_or(|entity| entity == event.entity())
+    }
+}
+
+impl std::fmt::Display for Topic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let store = self.store_id.map(|id| id.to_string()).unwrap_or_else(|| "*".to_string());
+        let entity = self.entity.map(|entity| entity.as_str()).unwrap_or("*");
+        write!(f, "store:{}:{}", store, entity)
+    }
+}
+
+/// What a client may send.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClientCommand {
+    Subscribe {
+        topics: Vec<String>,
+        /// The last sequence number the client saw, to replay what it missed.
+        #[serde(default)]
+        since: Option<i64>,
+    },
+    Unsubscribe { topics: Vec<String> },
+}
@@ -1,19 +1,51 @@
 pub mod broadcaster;
+pub mod events;
 pub mod session;
 
 use actix::Addr;
-use actix_web::{get, web, Error, HttpRequest, HttpResponse};
+use actix_web::{error::ErrorUnauthorized, get, web, Error, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
+use serde::Deserialize;
+use std::time::Duration;
 
-use crate::websocket::broadcaster::Broadcaster;
+use crate::auth::auth_service;
+use crate::auth::store_scope::StoreScope;
+use crate::websocket::broadcaster::{Broadcaster, Publish};
+use crate::websocket::events::{Entity, Event};
 use crate::websocket::session::WsConn;
 
+#[derive(Deserialize)]
+pub struct WsQuery {
+    /// Browsers cannot set headers on a WebSocket handshake, so the access token may be passed
+    /// as `?token=` instead of in the Authorization header.
+    pub token: Option<String>,
+}
+
 #[get("/ws")]
 pub async fn start_ws_connection(
     req: HttpRequest,
     stream: web::Payload,
+    query: web::Query<WsQuery>,
     broadcaster: web::Data<Addr<Broadcaster>>,
 ) -> Result<HttpResponse, Error> {
-    let broadcaster_addr = broadcaster.get_ref().clone();
-    ws::start(WsConn::new(broadcaster_addr), &req, stream)
+    let token = auth_service::bearer_token(&req)
+        .or(query.token.as_deref())
+        .ok_or_else(|| ErrorUnauthorized("Missing or invalid token"))?;
+    let claims = auth_service::authenticate_token(&req, token).await?;
+
+    let token_lifetime = Duration::from_secs((claims.exp as i64 - chrono::Utc::now().timestamp()).max(0) as u64);
+    let session = WsConn::new(
+        broadcaster.get_ref().clone(),
+        StoreScope::from_claims(&claims),
+        Entity::readable_by(&claims),
+        token_lifetime,
+    );
+    ws::start(session, &req, stream)
+}
+
+/// Sends events to subscribed clients. Call it once the change is committed.
+pub fn publish(broadcaster: &Addr<Broadcaster>, events: impl IntoIterator<Item = Event>) {
+    for event in events {
+        broadcaster.do_send(Publish(event));
+    }
 }
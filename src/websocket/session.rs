@@ -1,32 +1,42 @@
 use actix::prelude::*;
 use actix_web_actors::ws;
+use serde_json::json;
 use std::time::{Duration, Instant};
 
-use crate::websocket::broadcaster::{BroadcastMessage, Broadcaster, Connect, Disconnect};
+use crate::auth::store_scope::StoreScope;
+use crate::websocket::broadcaster::{BroadcastMessage, Broadcaster, Connect, Disconnect, Subscribe, Unsubscribe};
+use crate::websocket::events::{ClientCommand, Entity, Topic};
 
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
 
-#[derive(Debug)]
 pub struct WsConn {
     id: usize,
     hb: Instant,
     broadcaster_addr: Addr<Broadcaster>,
+    scope: StoreScope,
+    entities: Vec<Entity>,
+    /// Time left on the access token; the connection is closed when it runs out, so the client
+    /// reconnects with a fresh token.
+    token_lifetime: Duration,
 }
 
 impl WsConn {
-    pub fn new(broadcaster_addr: Addr<Broadcaster>) -> Self {
+    pub fn new(broadcaster_addr: Addr<Broadcaster>, scope: StoreScope, entities: Vec<Entity>, token_lifetime: Duration) -> Self {
         Self {
             id: rand::random::<usize>(),
             hb: Instant::now(),
             broadcaster_addr,
+            scope,
+            entities,
+            token_lifetime,
         }
     }
 
     fn hb(&self, ctx: &mut <Self as Actor>::Context) {
         ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
             if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
-                println!("WsConn: Client heartbeat failed, disconnecting!");
+                log::debug!("WebSocket session {} missed its heartbeat, disconnecting", act.id);
                 act.broadcaster_addr.do_send(Disconnect { id: act.id });
                 ctx.stop();
                 return;
@@ -34,6 +44,50 @@ impl WsConn {
             ctx.ping(b"");
         });
     }
+
+    /// Parses topics and checks them against what the caller may see. A topic naming a store
+    /// or entity the caller has no access to is refused rather than silently never delivered.
+    fn check_topics(&self, topics: &[String]) -> Result<Vec<Topic>, String> {
+        topics
+            .iter()
+            .map(|value| {
+                let topic = Topic::parse(value)?;
+                if let Some(store_id) = topic.store_id {
+                    if !self.scope.includes_store(store_id) {
+                        return Err(format!("You do not have access to store {}", store_id));
+                    }
+                }
+                if let Some(entity) = topic.entity {
+                    if !self.entities.contains(&entity) {
+                        return Err(format!("Missing permission: {}", entity.read_permission()));
+                    }
+                }
+                Ok(topic)
+            })
+            .collect()
+    }
+
+    fn handle_command(&mut self, text: &str, ctx: &mut <Self as Actor>::Context) {
+        let command = match serde_json::from_str::<ClientCommand>(text) {
+            Ok(command) => command,
+            Err(e) => return send_error(ctx, format!("Invalid message: {}", e)),
+        };
+        // The broadcaster confirms, after replaying anything the client missed
+        match command {
+            ClientCommand::Subscribe { topics, since } => match self.check_topics(&topics) {
+                Ok(topics) => self.broadcaster_addr.do_send(Subscribe { id: self.id, topics, since }),
+                Err(message) => send_error(ctx, message),
+            },
+            ClientCommand::Unsubscribe { topics } => match self.check_topics(&topics) {
+                Ok(topics) => self.broadcaster_addr.do_send(Unsubscribe { id: self.id, topics }),
+                Err(message) => send_error(ctx, message),
+            },
+        }
+    }
+}
+
+fn send_error(ctx: &mut <WsConn as Actor>::Context, message: String) {
+    ctx.text(json!({ "type": "error", "message": message }).to_string());
 }
 
 impl Actor for WsConn {
@@ -41,12 +95,21 @@ impl Actor for WsConn {
 
     fn started(&mut self, ctx: &mut Self::Context) {
         self.hb(ctx);
+        ctx.run_later(self.token_lifetime, |_, ctx| {
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Policy,
+                description: Some("Token expired".to_string()),
+            }));
+            ctx.stop();
+        });
 
         let addr = ctx.address();
         self.broadcaster_addr
             .send(Connect {
                 addr: addr.recipient(),
                 id: self.id,
+                scope: self.scope.clone(),
+                entities: self.entities.clone(),
             })
             .into_actor(self)
             .then(|res, _, ctx| {
@@ -82,10 +145,8 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsConn {
             Ok(ws::Message::Pong(_)) => {
                 self.hb = Instant::now();
             }
-            Ok(ws::Message::Text(text)) => {
-                self.broadcaster_addr.do_send(BroadcastMessage(text.to_string()));
-            }
-            Ok(ws::Message::Binary(bin)) => ctx.binary(bin),
+            Ok(ws::Message::Text(text)) => self.handle_command(&text, ctx),
+            Ok(ws::Message::Binary(_)) => send_error(ctx, "Binary messages are not supported".to_string()),
             Ok(ws::Message::Close(reason)) => {
                 ctx.close(reason);
                 ctx.stop();
@@ -93,4 +154,4 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsConn {
             _ => ctx.stop(),
         }
     }
-}
\ No newline at end of file
+}